@@ -57,7 +57,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Get session information
-    match agent.get_sessions(address).await {
+    match agent.get_sessions(address, false, None).await {
         Ok(sessions) => {
             info!("✅ Active sessions: {}", sessions.len());
             for session in sessions.iter() {
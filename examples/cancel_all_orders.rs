@@ -30,7 +30,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Cancel all orders
     info!("🚫 Canceling all open orders...");
     match agent.cancel_all(None).await {
-        Ok(result) => info!("✅ All orders canceled successfully, result: {}", result),
+        Ok(result) => info!(
+            "✅ All orders canceled successfully ({} canceled), tx: {}",
+            result
+                .canceled_count
+                .map_or("count not reported".to_string(), |n| n.to_string()),
+            result.tx_hash
+        ),
         Err(e) => {
             error!("❌ Failed to cancel all orders: {}", e);
             return Err(e.into());
@@ -23,15 +23,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("✅ AlphaSec Agent initialized successfully");
 
     info!("Depositing 1.0 KAIA to AlphaSec...");
-    // match agent.deposit_token("KAIA", 1.0).await {
-    match agent.deposit_token("KAIA", 1.0).await {
+    match agent.deposit_token("KAIA", "1.0").await {
         Ok(result) => info!("✅ Deposit successful: 1.0 KAIA, result: {}", result),
         Err(e) => error!("❌ Failed to deposit KAIA: {}", e),
     };
 
     info!("Withdrawing 1.0 KAIA from AlphaSec...");
-    // match agent.withdraw_token("KAIA", 1.0).await {
-    match agent.withdraw_token("KAIA", 1.0, None).await {
+    match agent.withdraw_token("KAIA", "1.0", None).await {
         Ok(result) => info!("✅ Withdrawal successful: 1.0 KAIA, result: {}", result),
         Err(e) => error!("❌ Failed to withdraw KAIA: {}", e),
     };
@@ -2,7 +2,7 @@
 //!
 //! This example demonstrates how to place a single buy order only
 
-use alphasec_rs::{Agent, Config, OrderMode, OrderSide, OrderType};
+use alphasec_rs::{agent::OrderBuilder, Agent, Config, OrderMode, OrderSide, OrderType};
 use rust_decimal::Decimal;
 use std::str::FromStr;
 use tracing::{error, info};
@@ -31,19 +31,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Place a BUY limit order
     info!("📈 Placing a BUY limit order for KAIA/USDT...");
-    match agent
-        .order(
-            "KAIA/USDT",                       // market
-            OrderSide::Buy,                    // side
-            Decimal::from_str("1.1").unwrap(), // price: $1.1
-            Decimal::from_str("1").unwrap(),   // quantity: 1 KAIA
-            OrderType::Limit,                  // order type
-            OrderMode::Base,                   // base token mode
-            None,                              // tp_limit
-            None,                              // sl_trigger
-            None,                              // sl_limit
-            None,                              // timestamp_ms
-        )
+    match OrderBuilder::new()
+        .market("KAIA/USDT")
+        .side(OrderSide::Buy)
+        .order_type(OrderType::Limit)
+        .price(Decimal::from_str("1.1").unwrap()) // price: $1.1
+        .quantity(Decimal::from_str("1").unwrap()) // quantity: 1 KAIA
+        .order_mode(OrderMode::Base)
+        .submit(&agent)
         .await
     {
         Ok(result) => info!(
@@ -32,7 +32,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .create_session(session_id, None, timestamp_ms, expires_at, metadata)
         .await
         .unwrap();
-    info!("Create session: {}", result);
+    info!("Create session: {:?}", result);
 
     // Update session
     let timestamp_ms = chrono::Utc::now().timestamp_millis() as u64;
@@ -42,15 +42,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .update_session(session_id, None, timestamp_ms, expires_at, metadata)
         .await
         .unwrap();
-    info!("Update session: {}", result);
+    info!("Update session: {:?}", result);
 
     // Delete session
     let timestamp_ms = chrono::Utc::now().timestamp_millis() as u64;
     let result = agent.delete_session(None, timestamp_ms).await.unwrap();
-    info!("Delete session: {}", result);
+    info!("Delete session: {:?}", result);
 
     // Get session
-    let result = agent.get_sessions(agent.l1_address()).await.unwrap();
+    let result = agent
+        .get_sessions(agent.l1_address(), false, None)
+        .await
+        .unwrap();
     info!("Get sessions: {:?}", result);
 
     Ok(())
@@ -44,7 +44,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Example 1: Value transfer (native KAIA)
     // info!("💰 Transferring native KAIA...");
-    // match agent.native_transfer(recipient, 1f64).await { // 1 KAIA
+    // match agent.native_transfer(recipient, "1", None).await { // 1 KAIA
     //     Ok(result) => {
     //             info!("✅ Value transfer successful: 1 KAIA sent to {}", recipient);
     //             info!("  Result: {}", result);
@@ -57,7 +57,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Example 2: Token transfer (USDT)
     info!("🪙 Transferring USDT tokens...");
-    match agent.token_transfer(recipient, 1.55f64, "USDT", None).await {
+    match agent.token_transfer(recipient, "1.55", "USDT", None).await {
         // 1.55 USDT
         Ok(result) => {
             info!(
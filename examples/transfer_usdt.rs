@@ -29,7 +29,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Recipient address (replace with actual address)
     let recipient = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"; // Example address
-    let amount = 100f64; // 100 USDT
+    let amount = "100"; // 100 USDT
     let token = "USDT";
 
     // Token transfer (USDT)
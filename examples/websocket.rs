@@ -3,7 +3,7 @@
 use alphasec_rs::{Agent, Config};
 use tokio::time::{interval, sleep, Duration, Instant};
 use tokio_tungstenite::tungstenite::Message;
-use tracing::{error, info, Level};
+use tracing::{error, info, warn, Level};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -82,6 +82,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 alphasec_rs::types::WebSocketMessage::Disconnected => {
                     info!("🔌 Disconnected");
                 }
+                alphasec_rs::types::WebSocketMessage::Closed { code, reason } => {
+                    info!("🔌 Closed by server: code={:?}, reason={}", code, reason);
+                }
+                alphasec_rs::types::WebSocketMessage::Lagged { dropped } => {
+                    warn!("⚠️ Fell behind, {} market data messages dropped", dropped);
+                }
                 alphasec_rs::types::WebSocketMessage::TradeMsg { params, .. } => {
                     for trade in &params.result {
                         info!("💱 Trade update #{}: channel={}, trade_id={}, market_id={}, price={}, quantity={}, buy_order_id={}, sell_order_id={}, created_at={}, is_buyer_maker={}",
@@ -123,6 +129,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         );
                     }
                 }
+                alphasec_rs::types::WebSocketMessage::KlineMsg { params, .. } => {
+                    let candle = &params.result;
+                    info!(
+                        "🕯️ Kline update #{}: channel={}, interval={}, open={}, high={}, low={}, close={}, volume={}, closed={}",
+                        message_count, params.channel, candle.interval, candle.open,
+                        candle.high, candle.low, candle.close, candle.volume, candle.is_closed
+                    );
+                }
                 alphasec_rs::types::WebSocketMessage::UserEventMsg { params, .. } => {
                     match &params.result {
                         alphasec_rs::types::UserEventResult::Order { base, order } => {
@@ -137,8 +151,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         }
                     }
                 }
-                alphasec_rs::types::WebSocketMessage::Generic(value) => {
-                    info!("🔧 Generic message #{}: {:?}", message_count, value);
+                generic @ alphasec_rs::types::WebSocketMessage::Generic(_) => {
+                    // Spot frames that failed strict parsing may still be recoverable
+                    match generic.try_typed() {
+                        Some(frame) => info!(
+                            "🔧 Recovered frame #{} on {}: {:?}",
+                            message_count,
+                            frame.channel(),
+                            frame
+                        ),
+                        None => info!("🔧 Generic message #{}: {:?}", message_count, generic),
+                    }
                 }
                 alphasec_rs::types::WebSocketMessage::Pong(items) => {
                     info!("🔧 Pong message #{}: {:?}", message_count, items);
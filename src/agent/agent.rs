@@ -3,33 +3,49 @@
 //! Provides a unified interface for all AlphaSec operations including
 //! market data, trading, and WebSocket.
 
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 pub use crate::types::account::{Transfer, TransferHistoryQuery};
 use crate::{
-    api::ApiClient,
-    endpoints,
+    api::{split_market, ApiClient},
     error::{AlphaSecError, Result},
     perp::{
         agent::{MarketCache, PerpAgent},
         client::PerpApiClient,
     },
     session_commands::{SESSION_COMMAND_DELETE, SESSION_COMMAND_UPDATE},
-    signer::{AlphaSecSigner, Config},
-    types::{account::*, market::*, orders::*, session_commands::SESSION_COMMAND_CREATE},
+    signer::{normalize_price_quantity_with, AlphaSecSigner, Config, OrderParams, OrderSigning},
+    telemetry,
+    types::{
+        account::*,
+        amount::IntoAmount,
+        api::{ApiResponse, Capabilities},
+        market::*,
+        orders::*,
+        session_commands::SESSION_COMMAND_CREATE,
+    },
 };
 
 #[cfg(feature = "websocket")]
-use crate::websocket::{WsConfig, WsManager};
+use crate::types::Channel;
+#[cfg(feature = "websocket")]
+use crate::websocket::{AuthFrameBuilder, WsConfig, WsManager};
 
 #[cfg(feature = "websocket")]
 use crate::websocket::trade::TradeWebSocket;
 
-use ethers::{providers::Middleware, signers::LocalWallet, types::U64};
+use ethers::{
+    providers::Middleware,
+    signers::{LocalWallet, Signer},
+    types::U64,
+};
 use rust_decimal::Decimal;
 #[cfg(feature = "websocket")]
 use tokio::sync::mpsc;
-
+use tokio::sync::RwLock;
 use tokio::time::{sleep, Duration};
 use tracing::{info, warn};
 
@@ -55,6 +71,20 @@ pub struct Agent {
     perp_client: PerpApiClient,
     /// Lazy symbol → market_id cache for perp operations (shared across clone)
     market_cache: Arc<MarketCache>,
+    /// Spot market definitions by market ID, refreshed by every market-list fetch and
+    /// used for order validation (shared across clone)
+    spot_markets: Arc<RwLock<HashMap<String, Market>>>,
+    /// Order submission limits from [`Config::order_throttle`] (shared across clone)
+    order_throttle: Option<Arc<super::order_throttle::OrderThrottle>>,
+    /// Account fee tiers fetched by [`Self::get_account_fee_tier`] (shared across clone)
+    fee_tiers: Arc<super::fee_tiers::FeeTierCache>,
+    /// Named session wallets added by [`Self::add_session_wallet`] (shared across clone)
+    session_wallets: Arc<std::sync::RwLock<HashMap<String, LocalWallet>>>,
+    /// Server features fetched by [`Self::server_capabilities`] (shared across clone)
+    capabilities: Arc<tokio::sync::OnceCell<Capabilities>>,
+    /// When and how the last [`Self::server_capabilities`] fetch failed (shared across
+    /// clone)
+    capabilities_failure: Arc<std::sync::Mutex<Option<(std::time::Instant, String)>>>,
 }
 
 impl Agent {
@@ -85,12 +115,42 @@ impl Agent {
     ///     Ok(())
     /// }
     /// ```
+    ///
+    /// The token metadata is fetched up to [`METADATA_FETCH_ATTEMPTS`] times. If every
+    /// attempt fails the agent is still returned, in a degraded state: methods taking raw
+    /// market or token IDs work, while symbol-based ones retry the fetch on use and fail
+    /// with a configuration error while it keeps failing.
     pub async fn new(config: Config) -> Result<Self> {
-        let signer = AlphaSecSigner::new(config.clone());
-        let mut api = ApiClient::new(&config, Some(signer.clone()))?;
+        let mut agent = Self::new_lazy(config)?;
+
+        let mut delay = METADATA_RETRY_DELAY;
+        for attempt in 1..=METADATA_FETCH_ATTEMPTS {
+            match agent.api.initialize_metadata().await {
+                Ok(()) => break,
+                Err(e) if attempt < METADATA_FETCH_ATTEMPTS => {
+                    warn!(
+                        "Token metadata fetch failed (attempt {}/{}), retrying in {:?}: {}",
+                        attempt, METADATA_FETCH_ATTEMPTS, delay, e
+                    );
+                    sleep(delay).await;
+                    delay *= 2;
+                }
+                Err(e) => warn!(
+                    "Token metadata unavailable, symbol-based methods will fail until it loads: {}",
+                    e
+                ),
+            }
+        }
+        Ok(agent)
+    }
 
-        // Initialize token metadata
-        api.initialize_metadata().await?;
+    /// Create a new Agent without fetching token metadata
+    ///
+    /// No request is made; the metadata is fetched by the first method that needs it
+    /// (or by [`Self::load_metadata`]).
+    pub fn new_lazy(config: Config) -> Result<Self> {
+        let signer = AlphaSecSigner::new(config.clone());
+        let api = ApiClient::new(&config, Some(signer.clone()))?;
 
         #[cfg(feature = "websocket")]
         let ws = {
@@ -102,6 +162,10 @@ impl Agent {
                 ping_interval: std::time::Duration::from_secs(10),
                 pong_timeout: std::time::Duration::from_secs(10),
                 message_queue_size: 1000,
+                backoff_reset_after: std::time::Duration::from_secs(60),
+                proxy: config.proxy.clone(),
+                tls: config.tls.clone(),
+                ..WsConfig::default()
             };
             Some(WsManager::new(ws_config))
         };
@@ -121,12 +185,92 @@ impl Agent {
             ws,
             #[cfg(feature = "websocket")]
             trade_ws: None,
+            order_throttle: config
+                .order_throttle
+                .map(super::order_throttle::OrderThrottle::new),
             config,
             perp_client,
             market_cache,
+            spot_markets: Arc::default(),
+            fee_tiers: Arc::default(),
+            session_wallets: Arc::default(),
+            capabilities: Arc::default(),
+            capabilities_failure: Arc::default(),
         })
     }
 
+    // === Server Capabilities ===
+
+    /// Protocol version and optional features of the server
+    ///
+    /// Fetched from `/api/v1/meta` on first use and cached for the life of the agent
+    /// (shared across clones). A failed fetch is not retried for
+    /// [`CAPABILITIES_FAILURE_BACKOFF`]; until then its message comes back as a `Network`
+    /// error. A server without the endpoint reports nothing, in which case every feature
+    /// is assumed available.
+    ///
+    /// [`Self::place_oco`] and reduce-only orders check this first and fail with
+    /// [`AlphaSecError::Unsupported`] on a server that does not offer them.
+    pub async fn server_capabilities(&self) -> Result<Capabilities> {
+        if let Some(capabilities) = self.capabilities.get() {
+            return Ok(capabilities.clone());
+        }
+        if let Some((failed_at, message)) = &*self
+            .capabilities_failure
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+        {
+            if failed_at.elapsed() < CAPABILITIES_FAILURE_BACKOFF {
+                return Err(AlphaSecError::network(format!(
+                    "Server capabilities lookup failed recently: {}",
+                    message
+                )));
+            }
+        }
+        let result = self
+            .capabilities
+            .get_or_try_init(|| self.api.get_capabilities())
+            .await
+            .cloned();
+        if let Err(e) = &result {
+            *self
+                .capabilities_failure
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) =
+                Some((std::time::Instant::now(), e.to_string()));
+        }
+        result
+    }
+
+    /// Fail with `Unsupported` if the server reports its features and `feature` is not
+    /// among them
+    ///
+    /// A failed capability lookup is logged and lets the call through, so the request
+    /// itself reports the problem.
+    async fn require_capability(&self, feature: &str, what: &str) -> Result<()> {
+        match self.server_capabilities().await {
+            Ok(capabilities) if !capabilities.supports(feature) => {
+                Err(AlphaSecError::unsupported(format!(
+                    "{} (server{} does not offer `{}`)",
+                    what,
+                    capabilities
+                        .version
+                        .map(|version| format!(" version {}", version))
+                        .unwrap_or_default(),
+                    feature
+                )))
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                warn!(
+                    "Could not fetch server capabilities, assuming `{}`: {}",
+                    feature, e
+                );
+                Ok(())
+            }
+        }
+    }
+
     // === WebSocket Lifecycle ===
 
     /// Start WebSocket connection
@@ -140,6 +284,9 @@ impl Agent {
     }
 
     /// Stop WebSocket connection
+    ///
+    /// Frames queued before the call, such as a just-sent unsubscribe, are flushed
+    /// before the connection is closed (see [`WsManager::stop`]).
     #[cfg(feature = "websocket")]
     pub async fn stop(&mut self) {
         if let Some(ref mut ws) = self.ws {
@@ -153,6 +300,8 @@ impl Agent {
     /// * `channel` - Channel in format 'type@target':
     ///   - 'trade@KAIA/USDT' for trade data
     ///   - 'ticker@KAIA/USDT' for ticker data
+    ///   - 'ticker@all' (or 'ticker@*') for the tickers of every market; `ticker` is the
+    ///     only channel type with a wildcard form
     ///   - 'depth@KAIA/USDT' for order book
     ///   - 'userEvent@0x123...' for user events
     ///
@@ -194,70 +343,128 @@ impl Agent {
     /// ```
     #[cfg(feature = "websocket")]
     pub async fn subscribe(&self, channel: &str) -> Result<i32> {
-        if !channel.contains('@') {
-            return Err(AlphaSecError::invalid_parameter(format!(
-                "Channel format should be 'type@target', got: {}",
-                channel
-            )));
+        if is_perp_channel(channel) {
+            return self.subscribe_wire(channel, channel.to_string()).await;
         }
+        self.subscribe_channel(channel.parse()?).await
+    }
+
+    /// Subscribe to a spot WebSocket channel
+    ///
+    /// Typed form of [`Self::subscribe`]: `Channel::Trade("KAIA/USDT".into())` is the
+    /// same subscription as `"trade@KAIA/USDT"`.
+    #[cfg(feature = "websocket")]
+    pub async fn subscribe_channel(&self, channel: Channel) -> Result<i32> {
+        let actual_channel = self.resolve(&channel).await?;
+        self.subscribe_wire(&channel.to_string(), actual_channel)
+            .await
+    }
 
-        let parts: Vec<&str> = channel.split('@').collect();
-        let channel_type = parts[0];
-        let target = parts[1];
+    #[cfg(feature = "websocket")]
+    async fn subscribe_wire(&self, channel: &str, actual_channel: String) -> Result<i32> {
+        let ws = self.connected_ws().await?;
+        let id = ws.subscribe(actual_channel).await?;
+        info!("📡 Subscribed to channel: {} (ID: {})", channel, id);
+        Ok(id)
+    }
 
-        let actual_channel = match channel_type {
-            "trade" | "ticker" | "depth" => {
-                // Convert market name to market_id
-                let market_id = if let Some(metadata) = self.api.token_metadata() {
-                    metadata.market_to_market_id(target)?
-                } else {
-                    target.to_string()
-                };
-                format!("{}@{}", channel_type, market_id)
-            }
-            "userEvent" => {
-                // Use address directly
-                format!("userEvent@{}", target)
-            }
-            // Perp channels (perp_markPrice / perp_ticker / perp_aggTrade / perp_aggDepth /
-            // perp_candle) already use a numeric marketId (candle: `marketId:resolution`), so
-            // they bypass the spot market-name→id conversion and pass through verbatim.
-            t if t.starts_with("perp_") => {
-                format!("{}@{}", t, target)
+    /// Subscribe to a WebSocket channel and wait for the server to confirm it
+    ///
+    /// Same channel format as [`Self::subscribe`], but only returns once the server's ack
+    /// for this subscription arrives. Errors if it does not arrive within `timeout` or the
+    /// server rejects the channel.
+    #[cfg(feature = "websocket")]
+    pub async fn subscribe_confirmed(&self, channel: &str, timeout: Duration) -> Result<i32> {
+        let actual_channel = self.resolve_channel(channel).await?;
+        let ws = self.connected_ws().await?;
+        let id = ws.subscribe_confirmed(actual_channel, timeout).await?;
+        info!("📡 Subscription confirmed: {} (ID: {})", channel, id);
+        Ok(id)
+    }
+
+    /// Authenticate the WebSocket with the frame `auth` builds, then subscribe to this
+    /// account's `userEvent@{l1_address}`
+    ///
+    /// AlphaSec has not published a WebSocket authentication protocol, so the SDK does
+    /// not build or sign the frame: `auth` must produce whatever the server expects. It
+    /// is sent ahead of the subscribe and rebuilt after every reconnect, see
+    /// [`WsManager::set_auth_frame`]. The server's answer to it is not checked, so a
+    /// frame it does not accept goes unnoticed here; the subscription may still be
+    /// acked, or private events may simply not arrive. An error building the frame is
+    /// returned before anything is subscribed.
+    #[cfg(feature = "websocket")]
+    pub async fn subscribe_user_events(&self, auth: AuthFrameBuilder) -> Result<i32> {
+        let ws = self.connected_ws().await?;
+        ws.set_auth_frame(auth)?;
+        self.subscribe(&format!("userEvent@{}", self.l1_address()))
+            .await
+    }
+
+    /// Convert a user-facing `type@target` channel into the wire channel name.
+    #[cfg(feature = "websocket")]
+    async fn resolve_channel(&self, channel: &str) -> Result<String> {
+        // Perp channels (perp_markPrice / perp_ticker / perp_aggTrade / perp_aggDepth /
+        // perp_candle) already use a numeric marketId (candle: `marketId:resolution`), so
+        // they bypass the spot market-name→id conversion and pass through verbatim.
+        if is_perp_channel(channel) {
+            return Ok(channel.to_string());
+        }
+        self.resolve(&channel.parse()?).await
+    }
+
+    /// Wire channel name of a spot channel, with its market symbol replaced by the ID
+    #[cfg(feature = "websocket")]
+    async fn resolve(&self, channel: &Channel) -> Result<String> {
+        let target = channel.target();
+        let actual_channel = match channel {
+            // Wildcards name no market, so there is nothing to resolve
+            Channel::Ticker(_) if ALL_MARKETS_TARGETS.contains(&target) => {
+                format!("ticker@{}", ALL_MARKETS_TARGETS[0])
             }
-            _ => {
+            Channel::Trade(_) | Channel::Depth(_) | Channel::Kline(..)
+                if ALL_MARKETS_TARGETS.contains(&target) =>
+            {
                 return Err(AlphaSecError::invalid_parameter(format!(
-                    "Unsupported channel type: {}. Use 'trade', 'ticker', 'depth', 'userEvent', or 'perp_*'",
-                    channel_type
+                    "Channel '{}' does not support the all-markets wildcard; only 'ticker@all' does",
+                    channel.kind()
                 )));
             }
+            Channel::Kline(_, interval) => {
+                let market_id = self.api.market_id(target).await?;
+                format!("kline@{}@{}", market_id, interval)
+            }
+            Channel::Trade(_) | Channel::Ticker(_) | Channel::Depth(_) => {
+                // Convert market name to market_id
+                let market_id = self.api.market_id(target).await?;
+                format!("{}@{}", channel.kind(), market_id)
+            }
+            // Use address directly
+            Channel::UserEvent(_) => channel.to_string(),
         };
+        Ok(actual_channel)
+    }
 
-        // Wait for WebSocket connection to be established
+    /// Wait for the WebSocket connection to be established.
+    #[cfg(feature = "websocket")]
+    async fn connected_ws(&self) -> Result<&WsManager> {
+        let ws = self
+            .ws
+            .as_ref()
+            .ok_or_else(|| AlphaSecError::network("WebSocket not initialized"))?;
         info!("Waiting for WebSocket connection to be established");
-        while let Some(ref ws) = self.ws {
-            if ws.is_connected().await {
-                break;
-            }
+        while !ws.is_connected().await {
             sleep(Duration::from_secs(1)).await;
         }
-
-        if let Some(ref ws) = self.ws {
-            let id = ws.subscribe(actual_channel).await?;
-            info!("📡 Subscribed to channel: {} (ID: {})", channel, id);
-            Ok(id)
-        } else {
-            Err(AlphaSecError::network("WebSocket not initialized"))
-        }
+        Ok(ws)
     }
 
     /// Get the message receiver for processing WebSocket messages
     /// This can only be called once. After calling this, all WebSocket messages
-    /// will be sent to the returned receiver.
+    /// will be sent to the returned receiver, and [`Self::message_receiver`] and the
+    /// streams built on it (`trade_stream`, `open_orders_stream`, `start_price_cache`,
+    /// ...) fail.
     #[cfg(feature = "websocket")]
-    pub async fn take_message_receiver(
-        &self,
-    ) -> Option<mpsc::UnboundedReceiver<crate::types::WebSocketMessage>> {
+    pub async fn take_message_receiver(&self) -> Option<crate::websocket::MessageReceiver> {
         if let Some(ref ws) = self.ws {
             ws.take_message_receiver().await
         } else {
@@ -265,6 +472,164 @@ impl Agent {
         }
     }
 
+    /// Get the message receiver with each message tagged by its connection epoch
+    ///
+    /// Shares its slot with [`Self::take_message_receiver`]: only one of them can be taken.
+    /// Pass the envelopes through an [`EpochFilter`](crate::types::EpochFilter) to drop
+    /// frames from a connection that has since been replaced.
+    #[cfg(feature = "websocket")]
+    pub async fn take_envelope_receiver(&self) -> Option<crate::websocket::EnvelopeReceiver> {
+        match self.ws {
+            Some(ref ws) => ws.take_envelope_receiver().await,
+            None => None,
+        }
+    }
+
+    /// Get a new receiver of every WebSocket message arriving from now on
+    ///
+    /// Can be called any number of times, so several consumers (and the streams below)
+    /// can run side by side; `None` if the WebSocket is not initialized or its receiver
+    /// was taken with [`Self::take_message_receiver`]. See [`WsManager::message_receiver`].
+    #[cfg(feature = "websocket")]
+    pub async fn message_receiver(&self) -> Option<crate::websocket::MessageReceiver> {
+        match self.ws {
+            Some(ref ws) => ws.message_receiver().await,
+            None => None,
+        }
+    }
+
+    /// [`Self::message_receiver`] as a [`Stream`](futures_util::Stream), for use with
+    /// combinators such as `filter`, `map` or `timeout`
+    #[cfg(feature = "websocket")]
+    pub async fn message_stream(
+        &self,
+    ) -> Option<impl futures_util::Stream<Item = crate::types::WebSocketMessage> + Send + Unpin>
+    {
+        self.message_receiver()
+            .await
+            .map(super::streams::message_stream)
+    }
+
+    /// [`Self::message_receiver`], failing if there is none
+    #[cfg(feature = "websocket")]
+    async fn feature_receiver(&self) -> Result<crate::websocket::MessageReceiver> {
+        self.message_receiver().await.ok_or_else(|| {
+            AlphaSecError::generic("WebSocket message receiver already taken or not initialized")
+        })
+    }
+
+    /// Subscribe to `trade@{market}` (e.g. "KAIA/USDT") and stream that market's trades
+    ///
+    /// Reads from its own [`Self::message_receiver`]; the WebSocket must already be
+    /// started.
+    #[cfg(feature = "websocket")]
+    pub async fn trade_stream(
+        &self,
+        market: &str,
+    ) -> Result<impl futures_util::Stream<Item = crate::types::TradeResult> + Send + Unpin> {
+        let market_id = self.api.metadata().await?.market_to_market_id(market)?;
+        let ws_rx = self.feature_receiver().await?;
+        self.subscribe(&format!("trade@{}", market)).await?;
+        Ok(super::streams::trade_stream(ws_rx, market_id))
+    }
+
+    /// Check that the `depth@{market}` stream agrees with the REST depth endpoint
+    ///
+    /// Subscribes to the market's depth, takes a REST snapshot, captures
+    /// [`DEPTH_CHECK_FRAMES`](super::DEPTH_CHECK_FRAMES) frames, takes a second (reference)
+    /// snapshot and keeps capturing until the stream catches up with it, then applies the
+    /// frames to the first snapshot and compares top-of-book with the reference (see
+    /// [`ConsistencyReport`](super::ConsistencyReport)). Gives up waiting for frames after
+    /// [`DEPTH_CHECK_TIMEOUT`](super::DEPTH_CHECK_TIMEOUT); the report then shows the
+    /// books as unaligned.
+    ///
+    /// Diagnostic tooling, not for the hot path: reads from its own
+    /// [`Self::message_receiver`] and unsubscribes when done. The WebSocket must already
+    /// be started.
+    #[cfg(feature = "websocket")]
+    pub async fn verify_depth_consistency(&self, market: &str) -> Result<super::ConsistencyReport> {
+        use super::depth_check::collect_depth_frames;
+
+        let market_id = self.api.metadata().await?.market_to_market_id(market)?;
+        let mut ws_rx = self.feature_receiver().await?;
+        let sub_id = self.subscribe(&format!("depth@{}", market)).await?;
+
+        let report = async {
+            let deadline = tokio::time::Instant::now() + super::DEPTH_CHECK_TIMEOUT;
+            let snapshot = self.get_depth(market, None).await?;
+            let mut frames = Vec::new();
+            collect_depth_frames(&mut ws_rx, &market_id, &mut frames, deadline, |frames| {
+                frames.len() >= super::DEPTH_CHECK_FRAMES
+            })
+            .await;
+            let reference = self.get_depth(market, None).await?;
+            collect_depth_frames(&mut ws_rx, &market_id, &mut frames, deadline, |frames| {
+                reference.last_updated_id <= snapshot.last_updated_id
+                    || frames
+                        .last()
+                        .is_some_and(|frame| frame.final_id >= reference.last_updated_id)
+            })
+            .await;
+            super::ConsistencyReport::compare(&market_id, &snapshot, &frames, &reference)
+        }
+        .await;
+
+        if let Err(e) = self.unsubscribe(sub_id).await {
+            warn!("Failed to unsubscribe depth check {}: {}", sub_id, e);
+        }
+        report
+    }
+
+    /// Register callbacks for WebSocket connection state changes, errors and reconnects
+    ///
+    /// See [`WsManager::set_observer`]; does nothing if the agent has no WebSocket.
+    #[cfg(feature = "websocket")]
+    pub fn set_ws_observer(
+        &self,
+        observer: Arc<dyn crate::websocket::ConnectionObserver + Send + Sync>,
+    ) {
+        if let Some(ref ws) = self.ws {
+            ws.set_observer(observer);
+        }
+    }
+
+    /// Round-trip time of a WebSocket ping
+    ///
+    /// Fails right away if the WebSocket is not connected, or if no pong arrives in time,
+    /// so a supervisor can restart an agent whose connection has silently stalled. See
+    /// [`WsManager::ping`].
+    #[cfg(feature = "websocket")]
+    pub async fn ws_ping(&self) -> Result<Duration> {
+        self.ws
+            .as_ref()
+            .ok_or_else(|| AlphaSecError::network("WebSocket not initialized"))?
+            .ping()
+            .await
+    }
+
+    /// Feed health of the WebSocket, `None` if the agent has no WebSocket
+    ///
+    /// See [`WsManager::health`].
+    #[cfg(feature = "websocket")]
+    pub async fn ws_health(&self) -> Option<crate::websocket::WsHealth> {
+        match self.ws {
+            Some(ref ws) => Some(ws.health().await),
+            None => None,
+        }
+    }
+
+    /// Drop the WebSocket connection and reconnect right away, keeping the
+    /// subscriptions, e.g. once [`Self::ws_health`] shows the feed has gone stale
+    ///
+    /// See [`WsManager::reconnect`].
+    #[cfg(feature = "websocket")]
+    pub fn ws_reconnect(&self) -> Result<()> {
+        self.ws
+            .as_ref()
+            .ok_or_else(|| AlphaSecError::network("WebSocket not initialized"))?
+            .reconnect()
+    }
+
     /// Get a clone of the underlying WebSocket sender for direct frame sending.
     #[cfg(feature = "websocket")]
     pub async fn get_ws_sender(
@@ -291,6 +656,107 @@ impl Agent {
         }
     }
 
+    /// Active WebSocket subscriptions as `(id, channel)` pairs, ordered by id
+    ///
+    /// Channels are wire names (`trade@KAIA/USDT` is listed as `trade@5_2`). Empty when
+    /// the WebSocket was never started. See [`WsManager::active_subscriptions`].
+    #[cfg(feature = "websocket")]
+    pub async fn subscriptions(&self) -> Vec<(i32, String)> {
+        match self.ws {
+            Some(ref ws) => ws.active_subscriptions().await,
+            None => Vec::new(),
+        }
+    }
+
+    /// Open orders for `addr` plus a stream of order updates that keeps them current.
+    ///
+    /// Subscribes to `userEvent@{addr}`, fetches the REST snapshot (every page of it),
+    /// and reconciles any ORDER events received while the snapshot was in flight before
+    /// returning it. Subsequent updates are forwarded only when they change the open set:
+    /// fills and status changes of resting orders, newly placed orders, and terminal
+    /// (filled/canceled/etc.) events that remove an order.
+    ///
+    /// Reads from its own [`Self::message_receiver`]; the WebSocket must already be
+    /// started.
+    #[cfg(feature = "websocket")]
+    pub async fn open_orders_stream(
+        &self,
+        addr: &str,
+    ) -> Result<(Vec<Order>, mpsc::Receiver<crate::types::OrderUpdate>)> {
+        use super::open_orders::{order_update_for, OpenOrders, ORDER_STREAM_BUFFER};
+
+        let mut ws_rx = self.feature_receiver().await?;
+        let sub_id = self.subscribe(&format!("userEvent@{}", addr)).await?;
+        // Events arriving while the snapshot request is in flight queue up in ws_rx and
+        // are buffered before the snapshot is applied.
+        let snapshot = match self.all_open_orders(addr, None).await {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                if let Err(e) = self.unsubscribe(sub_id).await {
+                    warn!("Failed to unsubscribe open orders stream {}: {}", sub_id, e);
+                }
+                return Err(e);
+            }
+        };
+        let mut open = OpenOrders::new();
+        while let Ok(msg) = ws_rx.try_recv() {
+            if let Some(update) = order_update_for(msg, addr) {
+                open.apply(update);
+            }
+        }
+        open.apply_snapshot(snapshot);
+        let orders = open.snapshot();
+
+        let (tx, rx) = mpsc::channel(ORDER_STREAM_BUFFER);
+        let addr = addr.to_string();
+        tokio::spawn(async move {
+            while let Some(msg) = ws_rx.recv().await {
+                let Some(update) = order_update_for(msg, &addr).and_then(|u| open.apply(u)) else {
+                    continue;
+                };
+                if tx.send(update).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok((orders, rx))
+    }
+
+    /// Start a [`PriceCache`](super::PriceCache) tracking the latest price of `markets`.
+    ///
+    /// Subscribes to `ticker@{market}` for each market, seeds the cache from the REST
+    /// ticker, then keeps it current from the stream. Prices are keyed by the market names
+    /// passed here.
+    ///
+    /// Reads from its own [`Self::message_receiver`]; the WebSocket must already be
+    /// started.
+    #[cfg(feature = "websocket")]
+    pub async fn start_price_cache(&self, markets: &[&str]) -> Result<super::PriceCache> {
+        use crate::types::TickerData;
+
+        let ws_rx = self.feature_receiver().await?;
+
+        let mut market_ids = std::collections::HashMap::new();
+        let mut initial = std::collections::HashMap::new();
+        for &market in markets {
+            let market_id = self.api.market_id(market).await?;
+            market_ids.insert(market_id, market.to_string());
+            self.subscribe(&format!("ticker@{}", market)).await?;
+
+            let ticker = self.get_ticker(market).await?;
+            let price = ticker.price_decimal().map_err(|e| {
+                AlphaSecError::invalid_parameter(format!(
+                    "Invalid ticker price for {}: {}",
+                    market, e
+                ))
+            })?;
+            initial.insert(market.to_string(), price);
+        }
+
+        Ok(super::PriceCache::spawn(market_ids, initial, ws_rx))
+    }
+
     // === Trade WebSocket ===
 
     /// Enable the Trade WebSocket for low-latency order operations.
@@ -298,7 +764,11 @@ impl Agent {
     /// attempt to use the WebSocket first, falling back to REST on connection errors.
     #[cfg(feature = "websocket")]
     pub fn enable_trade_ws(&mut self) {
-        let trade_ws = TradeWebSocket::new(self.config.ws_api_url.as_str());
+        let mut trade_ws =
+            TradeWebSocket::new(self.config.ws_api_url.as_str()).with_tls(self.config.tls.clone());
+        if let Some(proxy) = &self.config.proxy {
+            trade_ws = trade_ws.with_proxy(proxy);
+        }
         self.trade_ws = Some(trade_ws);
         info!("🚀 Trade WebSocket enabled: {}", self.config.ws_api_url);
     }
@@ -325,14 +795,14 @@ impl Agent {
         }
     }
 
-    /// Try to submit a signed transaction via Trade WebSocket.
-    /// Returns `Some(Ok(result))` on success, `Some(Err(e))` on API error,
-    /// or `None` on connection error (caller should fallback to REST).
+    /// Try to submit a signed transaction via Trade WebSocket, see [`TradeWsOutcome`]
     #[cfg(feature = "websocket")]
-    async fn try_trade_ws(&self, method: &str, signed_tx: &str) -> Option<Result<String>> {
-        let trade_ws = self.trade_ws.as_ref()?;
+    async fn try_trade_ws(&self, method: &str, signed_tx: &str) -> TradeWsOutcome {
+        let Some(trade_ws) = self.trade_ws.as_ref() else {
+            return TradeWsOutcome::NotSent;
+        };
         if !trade_ws.is_connected().await {
-            return None;
+            return TradeWsOutcome::NotSent;
         }
 
         let req_id = uuid::Uuid::new_v4().to_string();
@@ -347,23 +817,228 @@ impl Agent {
             Ok(response) => {
                 if let Some(err) = response.error {
                     // API-level error: don't fallback, return error directly
-                    Some(Err(AlphaSecError::api(err.code, err.message)))
+                    TradeWsOutcome::Answered(Err(AlphaSecError::from_api(err.code, err.message)))
                 } else if let Some(result) = response.result {
-                    Some(Ok(result))
+                    TradeWsOutcome::Answered(Ok(result))
                 } else {
-                    Some(Ok(String::new()))
+                    TradeWsOutcome::Answered(Ok(String::new()))
                 }
             }
             Err(e) => {
                 // Connection-level error: fallback to REST
                 warn!("Trade WS {} failed, falling back to REST: {}", method, e);
-                None
+                TradeWsOutcome::Failed
+            }
+        }
+    }
+
+    /// Admit `n` orders under [`Config::order_throttle`] together, or none of them; the
+    /// permit counts them as in flight until dropped
+    fn order_permit(&self, n: usize) -> Result<Option<super::order_throttle::OrderPermit>> {
+        self.order_throttle
+            .as_ref()
+            .map(|throttle| throttle.try_acquire(std::time::Instant::now(), n))
+            .transpose()
+    }
+
+    /// Sign `data` with `wallet` (the trading wallet if `None`) and submit it, via Trade
+    /// WebSocket `ws_method` when connected and `rest` otherwise (always `rest` when
+    /// `ws_method` is `None`).
+    ///
+    /// With [`Config::auto_retry_nonce`] set, a nonce conflict (see
+    /// [`AlphaSecError::is_nonce_conflict`]) re-signs the same data with a fresh nonce
+    /// and resubmits it, up to [`NONCE_RETRY_ATTEMPTS`] submissions in total. Only a
+    /// conflict answering the sole send of a transaction is retried: after a Trade
+    /// WebSocket failure the REST fallback may be resubmitting a transaction the server
+    /// already accepted, so its conflict is returned as is rather than placing the
+    /// order twice.
+    async fn submit_signed<'a, F, Fut>(
+        &'a self,
+        ws_method: Option<&str>,
+        data: &[u8],
+        timestamp_ms: Option<u64>,
+        wallet: Option<&LocalWallet>,
+        rest: F,
+    ) -> Result<String>
+    where
+        F: Fn(&'a ApiClient, String) -> Fut,
+        Fut: std::future::Future<Output = Result<ApiResponse<serde_json::Value>>>,
+    {
+        let result = self
+            .submit_signed_value(ws_method, data, timestamp_ms, wallet, rest)
+            .await?;
+        Ok(match result {
+            serde_json::Value::String(s) => s,
+            serde_json::Value::Null => "No result".to_string(),
+            result => result.to_string(),
+        })
+    }
+
+    /// [`Self::submit_signed`] returning the result as the server sent it; a Trade
+    /// WebSocket result is always a string
+    async fn submit_signed_value<'a, F, Fut>(
+        &'a self,
+        ws_method: Option<&str>,
+        data: &[u8],
+        timestamp_ms: Option<u64>,
+        wallet: Option<&LocalWallet>,
+        rest: F,
+    ) -> Result<serde_json::Value>
+    where
+        F: Fn(&'a ApiClient, String) -> Fut,
+        Fut: std::future::Future<Output = Result<ApiResponse<serde_json::Value>>>,
+    {
+        let attempts = if self.config.auto_retry_nonce {
+            NONCE_RETRY_ATTEMPTS
+        } else {
+            1
+        };
+        let mut nonce = timestamp_ms.unwrap_or_else(|| self.signer.next_nonce());
+        let mut attempt = 1;
+        loop {
+            // Picked up by the span of the calling operation, if any
+            tracing::Span::current().record("nonce", nonce);
+            let signed_tx = self
+                .signer
+                .generate_alphasec_transaction(Some(nonce), data, wallet)
+                .await?;
+            let (result, resent) = self.submit_signed_tx(ws_method, signed_tx, &rest).await;
+            match result {
+                Err(e) if e.is_nonce_conflict() && !resent && attempt < attempts => {
+                    let fresh = self.signer.next_nonce().max(nonce + 1);
+                    warn!(
+                        "Nonce {} rejected ({}), re-signing with nonce {}",
+                        nonce, e, fresh
+                    );
+                    nonce = fresh;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// One submission of an already signed transaction, see [`Self::submit_signed`]
+    ///
+    /// Also returns whether the transaction went to REST after a failed Trade WebSocket
+    /// request, which the server may have received all the same.
+    async fn submit_signed_tx<'a, F, Fut>(
+        &'a self,
+        #[cfg_attr(not(feature = "websocket"), allow(unused_variables))] ws_method: Option<&str>,
+        signed_tx: String,
+        rest: &F,
+    ) -> (Result<serde_json::Value>, bool)
+    where
+        F: Fn(&'a ApiClient, String) -> Fut,
+        Fut: std::future::Future<Output = Result<ApiResponse<serde_json::Value>>>,
+    {
+        #[cfg_attr(not(feature = "websocket"), allow(unused_mut))]
+        let mut resent = false;
+        // Try Trade WebSocket first, fallback to REST
+        #[cfg(feature = "websocket")]
+        if let Some(method) = ws_method {
+            match self.try_trade_ws(method, &signed_tx).await {
+                TradeWsOutcome::Answered(result) => {
+                    return (result.map(serde_json::Value::String), false)
+                }
+                TradeWsOutcome::NotSent => {}
+                TradeWsOutcome::Failed => resent = true,
             }
         }
+
+        let result = match rest(&self.api, signed_tx).await {
+            Ok(response) if response.success => {
+                Ok(response.result.unwrap_or(serde_json::Value::Null))
+            }
+            Ok(response) => Err(AlphaSecError::from_api(
+                response.code.unwrap(),
+                response.error.unwrap(),
+            )),
+            Err(e) => Err(e),
+        };
+        (result, resent)
     }
 
     // === Trading API Helpers ===
 
+    /// Estimate the notional, fee and debited balance of an order on `market`
+    /// (e.g. "KAIA/USDT") without placing it
+    ///
+    /// Arguments mirror [`Self::order`]; see [`OrderCost`] for how fees are applied. The
+    /// fee rate is that of this account's fee tier (see [`Self::get_account_fee_tier`]),
+    /// or the market's static fee if the tier cannot be fetched.
+    pub async fn estimate_order_cost(
+        &self,
+        market: &str,
+        side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+        order_mode: OrderMode,
+    ) -> Result<OrderCost> {
+        self.estimate_order_cost_for(self.l1_address(), market, side, price, quantity, order_mode)
+            .await
+    }
+
+    /// [`Self::estimate_order_cost`] at the fee tier of `addr`
+    async fn estimate_order_cost_for(
+        &self,
+        addr: &str,
+        market: &str,
+        side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+        order_mode: OrderMode,
+    ) -> Result<OrderCost> {
+        let market = self.get_market(market).await?;
+        let metadata = self.api.metadata().await?;
+        match self
+            .get_account_fee_tier(addr)
+            .await
+            .and_then(|tier| tier.fee_rate())
+        {
+            Ok(fee_rate) => OrderCost::estimate_at_fee_rate(
+                &market, metadata, side, price, quantity, order_mode, fee_rate,
+            ),
+            Err(e) => {
+                warn!(
+                    "Fee tier of {} unavailable, using the market fee: {}",
+                    addr, e
+                );
+                OrderCost::estimate(&market, metadata, side, price, quantity, order_mode)
+            }
+        }
+    }
+
+    /// Estimate an order's cost and check that `addr` holds enough unlocked balance
+    ///
+    /// Returns the estimate, or [`AlphaSecError::InsufficientBalance`] naming the token,
+    /// the amount required and the amount available.
+    pub async fn check_sufficient_balance(
+        &self,
+        addr: &str,
+        market: &str,
+        side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+        order_mode: OrderMode,
+    ) -> Result<OrderCost> {
+        let cost = self
+            .estimate_order_cost_for(addr, market, side, price, quantity, order_mode)
+            .await?;
+        let available = match self
+            .get_balance(addr)
+            .await?
+            .balances
+            .iter()
+            .find(|b| b.token_id == cost.debit_token_id)
+        {
+            Some(balance) => balance.with_symbol(self.api.metadata().await?)?.unlocked,
+            None => Decimal::ZERO,
+        };
+        cost.ensure_covered_by(available)?;
+        Ok(cost)
+    }
+
     /// Place an order
     ///
     /// # Arguments
@@ -373,10 +1048,35 @@ impl Agent {
     /// * `price` - Price in wei
     /// * `quantity` - Quantity in wei
     /// * `order_type` - Order type (Limit or Market)
-    /// * `order_mode` - Order mode (Base or Quote)
+    /// * `order_mode` - Order mode: Base sizes `quantity` in the base token, Quote in the
+    ///   quote token, and `quantity` is truncated to that token's decimals. Quote is only
+    ///   valid for market orders.
     /// * `tp_limit` - Take profit limit price (optional)
     /// * `sl_trigger` - Stop loss trigger price (optional)
     /// * `sl_limit` - Stop loss limit price (optional)
+    /// * `timestamp_ms` - Transaction timestamp (optional, defaults to now)
+    ///
+    /// Price and quantity are truncated to the market's precision, the price to its tick
+    /// size when the market reports one; [`OrderBuilder::rounding`](super::OrderBuilder::rounding)
+    /// picks another [`RoundingMode`].
+    ///
+    /// A non-positive `quantity`, or a non-positive `price` on a limit order, is rejected
+    /// with `InvalidParameter` before anything is fetched or signed. So are take profit
+    /// and stop loss prices that are not positive or, on a limit order, not on the
+    /// profit and loss side of `price` respectively: a buy takes profit above its price
+    /// and stops out below it, a sell the other way round. With
+    /// [`Config::with_order_throttle`] set, orders over the limit fail with `RateLimited`
+    /// without being submitted.
+    ///
+    /// With [`OrderSigning::Eip712`] configured the order is submitted over REST with an
+    /// EIP-712 signature (`timestamp_ms` becomes its nonce) instead of as a signed
+    /// transaction, and the Trade WebSocket is not used.
+    ///
+    /// A transaction whose nonce the server rejects as duplicate or stale is re-signed
+    /// with a fresh one and resubmitted, see [`Config::auto_retry_nonce`].
+    ///
+    /// [`OrderBuilder`](super::OrderBuilder) sets the same options by name, plus a
+    /// client order ID and the reduce-only flag.
     pub async fn order(
         &self,
         market: &str,
@@ -390,97 +1090,325 @@ impl Agent {
         sl_limit: Option<Decimal>,
         timestamp_ms: Option<u64>,
     ) -> Result<String> {
-        // Convert market to base/quote tokens
-        let market_parts: Vec<&str> = market.split('/').collect();
-        if market_parts.len() != 2 {
-            return Err(AlphaSecError::invalid_parameter("Invalid market format"));
-        }
-
-        let base_symbol = market_parts[0];
-        let quote_symbol = market_parts[1];
-
-        // Convert symbols to token_ids using the metadata
-        let token_metadata = self
-            .api
-            .token_metadata()
-            .ok_or_else(|| AlphaSecError::config("Token metadata not initialized"))?;
-        let base_token_id = token_metadata
-            .symbol_token_id_map
-            .get(base_symbol)
-            .ok_or_else(|| {
-                AlphaSecError::config(format!("Unknown base token symbol: {}", base_symbol))
-            })?;
-        let quote_token_id = token_metadata
-            .symbol_token_id_map
-            .get(quote_symbol)
-            .ok_or_else(|| {
-                AlphaSecError::config(format!("Unknown quote token symbol: {}", quote_symbol))
-            })?;
-
-        // Create order data with token_ids
-        let order_data = self.signer.create_order_data(
-            base_token_id,
-            quote_token_id,
-            side as u32,
+        self.submit_order(&super::OrderRequest {
+            market: market.to_string(),
+            side,
             price,
             quantity,
-            order_type as u32,
-            order_mode as u32,
+            order_type,
+            order_mode,
             tp_limit,
             sl_trigger,
             sl_limit,
-        )?;
+            client_order_id: None,
+            reduce_only: false,
+            timestamp_ms,
+            rounding: None,
+            session: None,
+        })
+        .await
+    }
 
-        // Generate and sign transaction
-        let signed_tx = self
-            .signer
-            .generate_alphasec_transaction(timestamp_ms, &order_data, None)
-            .await?;
+    /// Place an order described by an [`OrderRequest`](super::OrderRequest), usually
+    /// built with [`OrderBuilder`](super::OrderBuilder)
+    ///
+    /// Behaves like [`Self::order`]. A `client_order_id` is sent as `clientOrderId` and
+    /// `reduce_only` as `reduceOnly`. Reduce-only is enforced by the exchange: the SDK
+    /// does not know positions and passes the flag through unchecked. Neither is part of
+    /// the EIP-712 order schema, so with [`OrderSigning::Eip712`] they are rejected with
+    /// `InvalidParameter`.
+    pub async fn submit_order(&self, request: &super::OrderRequest) -> Result<String> {
+        let super::OrderRequest {
+            ref market,
+            side,
+            price,
+            quantity,
+            order_type,
+            order_mode,
+            tp_limit,
+            sl_trigger,
+            sl_limit,
+            ref client_order_id,
+            reduce_only,
+            timestamp_ms,
+            rounding,
+            ref session,
+        } = *request;
+        let span = tracing::info_span!(
+            "order",
+            market = %market,
+            side = ?side,
+            order_type = ?order_type,
+            nonce = tracing::field::Empty,
+            outcome = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        );
+        let call = telemetry::order_call("order", async {
+            validate_order_inputs(price, quantity, order_type, order_mode)?;
+            validate_tpsl(side, price, order_type, tp_limit, sl_trigger, sl_limit)?;
+            let wallet = self.session_wallet(session.as_deref())?;
+            let _permit = self.order_permit(1)?;
 
-        // Try Trade WebSocket first, fallback to REST
-        #[cfg(feature = "websocket")]
-        if let Some(result) = self.try_trade_ws("order.place", &signed_tx).await {
-            return result;
-        }
+            // Convert market to base/quote tokens
+            let (base_symbol, quote_symbol) = split_market(market)?;
 
-        let response = self.api.order(&signed_tx).await?;
-        if response.success {
-            Ok(response.result_string())
-        } else {
-            Err(AlphaSecError::api(
-                response.code.unwrap(),
-                response.error.unwrap(),
-            ))
+            // Convert symbols to token_ids using the metadata
+            let token_metadata = self.api.metadata().await?;
+            let base_token_id = token_metadata
+                .symbol_token_id_map
+                .get(base_symbol)
+                .ok_or_else(|| {
+                    AlphaSecError::config(format!("Unknown base token symbol: {}", base_symbol))
+                })?;
+            let quote_token_id = token_metadata
+                .symbol_token_id_map
+                .get(quote_symbol)
+                .ok_or_else(|| {
+                    AlphaSecError::config(format!("Unknown quote token symbol: {}", quote_symbol))
+                })?;
+
+            // Quote-mode quantities are quote-token amounts, so their precision comes
+            // from the quote token
+            let quantity_token_id = match order_mode {
+                OrderMode::Base => base_token_id,
+                OrderMode::Quote => quote_token_id,
+            };
+            let quantity_decimals = token_metadata
+                .token_id_decimal_map
+                .get(quantity_token_id)
+                .map(|decimals| {
+                    decimals.parse::<u32>().map_err(|_| {
+                        AlphaSecError::config(format!(
+                            "Invalid decimals '{}' for token {}",
+                            decimals, quantity_token_id
+                        ))
+                    })
+                })
+                .transpose()?;
+
+            // Check step size and minimums on the values that will be signed
+            let rounding = rounding.unwrap_or_default();
+            let market_id = format!("{}_{}", base_token_id, quote_token_id);
+            let rules = self.market_rules(&market_id).await;
+            let tick_size = match &rules {
+                Some(rules) => rules.tick_size_decimal()?,
+                None => None,
+            };
+            if let Some(rules) = &rules {
+                let (price, normalized_quantity) =
+                    normalize_price_quantity_with(price, quantity, rounding, tick_size)?;
+                let quantity = match order_type {
+                    OrderType::Market => quantity,
+                    OrderType::Limit => normalized_quantity,
+                };
+                rules.validate_order(price, quantity, order_type, order_mode)?;
+            }
+
+            let params = OrderParams {
+                tp_limit,
+                sl_trigger,
+                sl_limit,
+                rounding,
+                tick_size,
+                quantity_decimals,
+                client_order_id: client_order_id.clone(),
+                reduce_only,
+                ..OrderParams::new(
+                    base_token_id,
+                    quote_token_id,
+                    side as u32,
+                    price,
+                    quantity,
+                    order_type as u32,
+                    order_mode as u32,
+                )
+            };
+
+            if self.signer.order_signing() == OrderSigning::Eip712 {
+                if client_order_id.is_some() {
+                    return Err(AlphaSecError::invalid_parameter(
+                        "Client order IDs are not supported with EIP-712 order signing",
+                    ));
+                }
+                if reduce_only {
+                    return Err(AlphaSecError::invalid_parameter(
+                        "Reduce-only orders are not supported with EIP-712 order signing",
+                    ));
+                }
+                let typed_data = self.signer.create_order_typed_data(
+                    &params,
+                    timestamp_ms.unwrap_or_else(|| self.signer.next_nonce()),
+                )?;
+                let signature = self.signer.sign_typed_data(&typed_data, wallet.as_ref())?;
+                let response = self.api.order_typed(&typed_data, &signature).await?;
+                return if response.success {
+                    Ok(response.result_string())
+                } else {
+                    Err(AlphaSecError::from_api(
+                        response.code.unwrap(),
+                        response.error.unwrap(),
+                    ))
+                };
+            }
+
+            if reduce_only {
+                self.require_capability(Capabilities::REDUCE_ONLY, "Reduce-only orders")
+                    .await?;
+            }
+
+            // Create order data with token_ids
+            let order_data = self.signer.create_order_data(&params)?;
+
+            self.submit_signed(
+                Some("order.place"),
+                &order_data,
+                timestamp_ms,
+                wallet.as_ref(),
+                |api, tx| async move { api.order(&tx).await },
+            )
+            .await
+        });
+        telemetry::traced(span, call).await
+    }
+
+    /// Place an entry order with an attached take profit and stop loss
+    ///
+    /// # Arguments
+    ///
+    /// * `market` - Market symbol (e.g., "KAIA/USDT")
+    /// * `side` - Side of the entry order
+    /// * `price` - Entry limit price (ignored for market orders)
+    /// * `quantity` - Entry quantity in the base token
+    /// * `order_type` - Entry order type (Limit or Market)
+    /// * `take_profit` - Take profit limit price
+    /// * `stop_trigger` - Stop loss trigger price
+    /// * `stop_limit` - Stop loss limit price (optional)
+    /// * `timestamp_ms` - Transaction timestamp (optional, defaults to now)
+    ///
+    /// The take profit and stop loss travel in the entry order's `tpsl` block, so the
+    /// three are submitted as one transaction. A buy must take profit above
+    /// `stop_trigger` and a sell below it, otherwise `InvalidParameter` is returned
+    /// without contacting the server; the remaining checks are those of [`Self::order`].
+    ///
+    /// Returns the entry order's ID, plus the IDs of the child orders when the server
+    /// reports them.
+    pub async fn bracket_order(
+        &self,
+        market: &str,
+        side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+        order_type: OrderType,
+        take_profit: Decimal,
+        stop_trigger: Decimal,
+        stop_limit: Option<Decimal>,
+        timestamp_ms: Option<u64>,
+    ) -> Result<BracketOrder> {
+        let inverted = match side {
+            OrderSide::Buy => take_profit <= stop_trigger,
+            OrderSide::Sell => take_profit >= stop_trigger,
+        };
+        if inverted {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "Take profit {} must be {} the stop loss trigger {} for a {} bracket",
+                take_profit,
+                match side {
+                    OrderSide::Buy => "above",
+                    OrderSide::Sell => "below",
+                },
+                stop_trigger,
+                side
+            )));
         }
+        let result = self
+            .order(
+                market,
+                side,
+                price,
+                quantity,
+                order_type,
+                OrderMode::Base,
+                Some(take_profit),
+                Some(stop_trigger),
+                stop_limit,
+                timestamp_ms,
+            )
+            .await?;
+        Ok(BracketOrder::from_result(&result))
     }
 
     /// Cancel an order
+    ///
+    /// Nonce conflicts are retried as in [`Self::order`].
     pub async fn cancel(&self, order_id: &str, timestamp_ms: Option<u64>) -> Result<String> {
-        let cancel_data = self.signer.create_cancel_data(order_id)?;
-        let signed_tx = self
-            .signer
-            .generate_alphasec_transaction(timestamp_ms, &cancel_data, None)
-            .await?;
+        self.cancel_as(order_id, None, timestamp_ms).await
+    }
 
-        // Try Trade WebSocket first, fallback to REST
-        #[cfg(feature = "websocket")]
-        if let Some(result) = self.try_trade_ws("order.cancel", &signed_tx).await {
-            return result;
-        }
+    /// Cancel an order, signed by the named `session` wallet (see
+    /// [`Self::add_session_wallet`]) or, if `None`, the trading wallet
+    ///
+    /// An unknown session name fails with `InvalidParameter` before anything is signed.
+    pub async fn cancel_as(
+        &self,
+        order_id: &str,
+        session: Option<&str>,
+        timestamp_ms: Option<u64>,
+    ) -> Result<String> {
+        let span = tracing::info_span!(
+            "cancel",
+            order_id = %order_id,
+            nonce = tracing::field::Empty,
+            outcome = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        );
+        let call = telemetry::order_call("cancel", async {
+            let wallet = self.session_wallet(session)?;
+            let cancel_data = self.signer.create_cancel_data(order_id)?;
+            self.submit_signed(
+                Some("order.cancel"),
+                &cancel_data,
+                timestamp_ms,
+                wallet.as_ref(),
+                |api, tx| async move { api.cancel(&tx).await },
+            )
+            .await
+        });
+        telemetry::traced(span, call).await
+    }
 
-        let response = self.api.cancel(&signed_tx).await?;
-        if response.success {
-            Ok(response.result_string())
-        } else {
-            Err(AlphaSecError::api(
-                response.code.unwrap(),
-                response.error.unwrap(),
-            ))
+    /// Cancel an order and wait until it reaches a terminal state
+    ///
+    /// Submits the cancel, then polls [`Self::get_order_by_id`] until the order is no
+    /// longer NEW/PARTIALLY_FILLED. If the order filled (or otherwise finished) before the
+    /// cancel landed, that final order is returned with [`CancelOutcome::too_late`] set
+    /// rather than as an error; this also covers the server rejecting the cancel because
+    /// the order is already gone. Errors if the order is still active after `timeout`.
+    pub async fn cancel_and_confirm(
+        &self,
+        order_id: &str,
+        timeout: Duration,
+    ) -> Result<CancelOutcome> {
+        if let Err(e) = self.cancel(order_id, None).await {
+            // Cancelling an order that already finished is rejected; that is a lost race,
+            // not a failure.
+            return match self.get_order_by_id(order_id).await {
+                Ok(Some(order)) if !order.is_active() => Ok(CancelOutcome::from_terminal(order)),
+                _ => Err(e),
+            };
         }
+        poll_until_terminal(
+            || self.get_order_by_id(order_id),
+            timeout,
+            CANCEL_CONFIRM_POLL_INTERVAL,
+        )
+        .await
     }
 
     /// Cancel all orders
-    pub async fn cancel_all(&self, timestamp_ms: Option<u64>) -> Result<String> {
+    ///
+    /// The result carries the number of orders canceled when the server reports it;
+    /// answers over the trade WebSocket only carry the transaction hash.
+    pub async fn cancel_all(&self, timestamp_ms: Option<u64>) -> Result<CancelAllResult> {
         let cancel_all_data = self.signer.create_cancel_all_data()?;
         let signed_tx = self
             .signer
@@ -489,22 +1417,102 @@ impl Agent {
 
         // Try Trade WebSocket first, fallback to REST
         #[cfg(feature = "websocket")]
-        if let Some(result) = self.try_trade_ws("order.cancelAll", &signed_tx).await {
-            return result;
+        if let TradeWsOutcome::Answered(result) =
+            self.try_trade_ws("order.cancelAll", &signed_tx).await
+        {
+            return result.map(|tx_hash| CancelAllResult {
+                canceled_count: None,
+                tx_hash,
+            });
         }
 
         let response = self.api.cancel_all(&signed_tx).await?;
         if response.success {
-            Ok(response.result_string())
+            Ok(CancelAllResult::from_result(response.result.as_ref()))
         } else {
-            Err(AlphaSecError::api(
+            Err(AlphaSecError::from_api(
                 response.code.unwrap(),
                 response.error.unwrap(),
             ))
         }
     }
 
+    /// Cancel all orders, then wait until this agent's account has no open orders left
+    ///
+    /// Submits [`Self::cancel_all`] and polls [`Self::get_open_orders`] for
+    /// [`Self::l1_address`], the account the cancel applies to, until the list is
+    /// empty or `timeout` passes. Returns the orders still open at that point, so an empty
+    /// list means the book is flat. Orders that fill before the cancel lands drop out of
+    /// the open list like cancelled ones.
+    pub async fn cancel_all_and_wait(&self, timeout: Duration) -> Result<Vec<Order>> {
+        self.cancel_all(None).await?;
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let open = self
+                .get_open_orders(self.l1_address(), None, None, None, None)
+                .await?;
+            let now = tokio::time::Instant::now();
+            if open.is_empty() || now >= deadline {
+                return Ok(open);
+            }
+            sleep(CANCEL_CONFIRM_POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    /// Cancel all open orders in one market
+    ///
+    /// Unlike [`Self::cancel_all`], orders in other markets are left untouched. The spot
+    /// DEX has no market-scoped cancel-all command (0x23 is account-wide), so this fetches
+    /// the market's open orders, cancels each one and fetches again, until a fetch holds
+    /// no order it has not already tried to cancel. Each order is tried once: one whose
+    /// cancel failed is not retried in a later round. When `timestamp_ms` is given, the
+    /// n-th cancel is signed with `timestamp_ms + n` so the nonces stay distinct.
+    ///
+    /// Returns one `(order_id, result)` pair per order, in cancel order; a failed cancel
+    /// does not stop the ones after it. Only a failure to list the orders is returned as
+    /// the error.
+    pub async fn cancel_all_by_market(
+        &self,
+        market: &str,
+        timestamp_ms: Option<u64>,
+    ) -> Result<Vec<(String, Result<String>)>> {
+        let market_id = self.api.metadata().await?.market_to_market_id(market)?;
+
+        let mut results: Vec<(String, Result<String>)> = Vec::new();
+        let mut tried = HashSet::new();
+        loop {
+            let open = self
+                .get_open_orders(self.l1_address(), Some(market), None, None, None)
+                .await?;
+            let fresh: Vec<String> = market_order_ids(&open, &market_id)
+                .into_iter()
+                .filter(|id| !tried.contains(*id))
+                .map(str::to_string)
+                .collect();
+            if fresh.is_empty() {
+                break;
+            }
+            for order_id in fresh {
+                let ts = timestamp_ms.map(|t| t + results.len() as u64);
+                let result = self.cancel(&order_id, ts).await;
+                tried.insert(order_id.clone());
+                results.push((order_id, result));
+            }
+        }
+        let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+        info!(
+            "Canceled {} of {} open orders in {} ({})",
+            results.len() - failed,
+            results.len(),
+            market,
+            market_id
+        );
+        Ok(results)
+    }
+
     /// Modify an order
+    ///
+    /// Nonce conflicts are retried as in [`Self::order`].
     pub async fn modify(
         &self,
         order_id: &str,
@@ -513,38 +1521,117 @@ impl Agent {
         order_mode: OrderMode,
         timestamp_ms: Option<u64>,
     ) -> Result<String> {
-        let modify_data =
-            self.signer
-                .create_modify_data(order_id, new_price, new_qty, order_mode as u32)?;
-        let signed_tx = self
-            .signer
-            .generate_alphasec_transaction(timestamp_ms, &modify_data, None)
-            .await?;
+        let span = tracing::info_span!(
+            "modify",
+            order_id = %order_id,
+            nonce = tracing::field::Empty,
+            outcome = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        );
+        telemetry::traced(span, async {
+            let modify_data =
+                self.signer
+                    .create_modify_data(order_id, new_price, new_qty, order_mode as u32)?;
+            self.submit_signed(
+                Some("order.modify"),
+                &modify_data,
+                timestamp_ms,
+                None,
+                |api, tx| async move { api.modify(&tx).await },
+            )
+            .await
+        })
+        .await
+    }
 
-        // Try Trade WebSocket first, fallback to REST
-        #[cfg(feature = "websocket")]
-        if let Some(result) = self.try_trade_ws("order.modify", &signed_tx).await {
-            return result;
+    /// Shrink an open order's remaining quantity by `reduce_by` without changing its price
+    ///
+    /// Looks the order up, then submits a [`Self::modify`] with its current price and its
+    /// original quantity less `reduce_by`; whether the order keeps its queue priority is
+    /// up to the exchange. Reducing by the whole remaining quantity cancels the order
+    /// instead. `reduce_by` must be positive and at most the remaining quantity, and the
+    /// order must still be active.
+    pub async fn reduce_order(
+        &self,
+        order_id: &str,
+        reduce_by: Decimal,
+        timestamp_ms: Option<u64>,
+    ) -> Result<String> {
+        if reduce_by <= Decimal::ZERO {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "Reduction must be greater than 0, got {}",
+                reduce_by
+            )));
         }
-
-        let response = self.api.modify(&signed_tx).await?;
-        if response.success {
-            Ok(response.result_string())
-        } else {
-            Err(AlphaSecError::api(
-                response.code.unwrap(),
-                response.error.unwrap(),
-            ))
+        let order = self
+            .get_order_by_id(order_id)
+            .await?
+            .ok_or_else(|| AlphaSecError::not_found(format!("Order {} not found", order_id)))?;
+        match reduced_order(&order, reduce_by)? {
+            Some((price, new_qty)) => {
+                self.modify(order_id, price, new_qty, OrderMode::Base, timestamp_ms)
+                    .await
+            }
+            None => self.cancel(order_id, timestamp_ms).await,
         }
     }
 
+    /// Change an open order's price, keeping its quantity
+    ///
+    /// The modify command carries both fields, so the order is looked up first and its
+    /// original quantity sent unchanged; see [`Self::modify`]. The order must still be
+    /// active and `new_price` positive.
+    pub async fn modify_price(
+        &self,
+        order_id: &str,
+        new_price: Decimal,
+        timestamp_ms: Option<u64>,
+    ) -> Result<String> {
+        self.modify_fields(order_id, Some(new_price), None, timestamp_ms)
+            .await
+    }
+
+    /// Change an open order's (original) quantity, keeping its price
+    ///
+    /// Like [`Self::modify_price`], the order is looked up first for the field left
+    /// unchanged. `new_qty` is in the base token and must exceed the quantity already
+    /// executed.
+    pub async fn modify_qty(
+        &self,
+        order_id: &str,
+        new_qty: Decimal,
+        timestamp_ms: Option<u64>,
+    ) -> Result<String> {
+        self.modify_fields(order_id, None, Some(new_qty), timestamp_ms)
+            .await
+    }
+
+    async fn modify_fields(
+        &self,
+        order_id: &str,
+        new_price: Option<Decimal>,
+        new_qty: Option<Decimal>,
+        timestamp_ms: Option<u64>,
+    ) -> Result<String> {
+        let order = self
+            .get_order_by_id(order_id)
+            .await?
+            .ok_or_else(|| AlphaSecError::not_found(format!("Order {} not found", order_id)))?;
+        let (price, qty) = modified_order(&order, new_price, new_qty)?;
+        self.modify(order_id, price, qty, OrderMode::Base, timestamp_ms)
+            .await
+    }
+
     /// Transfer value (native token)
+    ///
+    /// `value` is in trading units, as a `Decimal` or a decimal string (see [`IntoAmount`]).
     pub async fn native_transfer(
         &self,
         to: &str,
-        value: Decimal,
+        value: impl IntoAmount,
         timestamp_ms: Option<u64>,
     ) -> Result<String> {
+        let value = value.into_amount()?;
         let transfer_data = self.signer.create_value_transfer_data(to, value)?;
         let signed_tx = self
             .signer
@@ -554,7 +1641,7 @@ impl Agent {
         if response.success {
             Ok(response.result_string())
         } else {
-            Err(AlphaSecError::api(
+            Err(AlphaSecError::from_api(
                 response.code.unwrap(),
                 response.error.unwrap(),
             ))
@@ -562,17 +1649,20 @@ impl Agent {
     }
 
     /// Transfer tokens
+    ///
+    /// `value` is in trading units, as a `Decimal` or a decimal string (see [`IntoAmount`]).
     pub async fn token_transfer(
         &self,
         to: &str,
-        value: f64,
+        value: impl IntoAmount,
         token: &str,
         timestamp_ms: Option<u64>,
     ) -> Result<String> {
+        let value = value.into_amount()?;
         let token_id = self
             .api
-            .token_metadata()
-            .ok_or_else(|| AlphaSecError::config("Token metadata not initialized"))?
+            .metadata()
+            .await?
             .symbol_token_id_map
             .get(token)
             .ok_or_else(|| AlphaSecError::config(format!("Unknown token symbol: {}", token)))?;
@@ -587,7 +1677,7 @@ impl Agent {
         if response.success {
             Ok(response.result_string())
         } else {
-            Err(AlphaSecError::api(
+            Err(AlphaSecError::from_api(
                 response.code.unwrap(),
                 response.error.unwrap(),
             ))
@@ -602,21 +1692,19 @@ impl Agent {
     pub async fn perp_deposit(
         &self,
         token: &str,
-        value: f64,
+        value: impl IntoAmount,
         timestamp_ms: Option<u64>,
     ) -> Result<String> {
         let token_id = self
             .api
-            .token_metadata()
-            .ok_or_else(|| AlphaSecError::config("Token metadata not initialized"))?
+            .metadata()
+            .await?
             .symbol_token_id_map
             .get(token)
             .ok_or_else(|| AlphaSecError::config(format!("Unknown token symbol: {}", token)))?
             .clone();
         // Signer's `perp_scale` applies the ×10^18 on-chain scaling; pass the raw amount.
-        let amount = rust_decimal::Decimal::try_from(value).map_err(|e| {
-            AlphaSecError::invalid_parameter(format!("invalid amount {value}: {e}"))
-        })?;
+        let amount = value.into_amount()?;
         let data = self.signer.create_perp_deposit_data(&token_id, amount)?;
         let signed_tx = self
             .signer
@@ -626,7 +1714,7 @@ impl Agent {
         if response.success {
             Ok(response.result_string())
         } else {
-            Err(AlphaSecError::api(
+            Err(AlphaSecError::from_api(
                 response.code.unwrap(),
                 response.error.unwrap(),
             ))
@@ -637,21 +1725,19 @@ impl Agent {
     pub async fn perp_withdraw(
         &self,
         token: &str,
-        value: f64,
+        value: impl IntoAmount,
         timestamp_ms: Option<u64>,
     ) -> Result<String> {
         let token_id = self
             .api
-            .token_metadata()
-            .ok_or_else(|| AlphaSecError::config("Token metadata not initialized"))?
+            .metadata()
+            .await?
             .symbol_token_id_map
             .get(token)
             .ok_or_else(|| AlphaSecError::config(format!("Unknown token symbol: {}", token)))?
             .clone();
         // Signer's `perp_scale` applies the ×10^18 on-chain scaling; pass the raw amount.
-        let amount = rust_decimal::Decimal::try_from(value).map_err(|e| {
-            AlphaSecError::invalid_parameter(format!("invalid amount {value}: {e}"))
-        })?;
+        let amount = value.into_amount()?;
         let data = self.signer.create_perp_withdraw_data(&token_id, amount)?;
         let signed_tx = self
             .signer
@@ -661,7 +1747,7 @@ impl Agent {
         if response.success {
             Ok(response.result_string())
         } else {
-            Err(AlphaSecError::api(
+            Err(AlphaSecError::from_api(
                 response.code.unwrap(),
                 response.error.unwrap(),
             ))
@@ -669,6 +1755,13 @@ impl Agent {
     }
 
     /// Place a stop order
+    ///
+    /// Once the market reaches `stop_price`, an order of `order_type`/`order_mode` is
+    /// placed at `price` for `quantity`. The triggered order follows the same rules as
+    /// [`Self::order`]: Quote mode is only valid for market orders and sizes `quantity` in
+    /// the quote token. Other combinations are rejected with `InvalidParameter`.
+    ///
+    /// Counts against [`Config::with_order_throttle`] like [`Self::order`].
     pub async fn stop_order(
         &self,
         base_token: &str,
@@ -681,11 +1774,10 @@ impl Agent {
         order_mode: OrderMode,
         timestamp_ms: Option<u64>,
     ) -> Result<String> {
+        let _permit = self.order_permit(1)?;
+
         // Convert symbols to token_ids using the metadata
-        let token_metadata = self
-            .api
-            .token_metadata()
-            .ok_or_else(|| AlphaSecError::config("Token metadata not initialized"))?;
+        let token_metadata = self.api.metadata().await?;
         let base_token_id = token_metadata
             .symbol_token_id_map
             .get(base_token)
@@ -717,14 +1809,109 @@ impl Agent {
         if response.success {
             Ok(response.result_string())
         } else {
-            Err(AlphaSecError::api(
+            Err(AlphaSecError::from_api(
                 response.code.unwrap(),
                 response.error.unwrap(),
             ))
         }
     }
 
+    /// Place a one-cancels-other (OCO) order pair, returning the ids of the limit leg and
+    /// the stop leg in that order
+    ///
+    /// Both legs share `side`, `qty` and `order_mode`. The limit leg rests at
+    /// `limit_price`; the stop leg places a limit order at `stop_limit` once the market
+    /// reaches `stop_trigger`. When either leg fills (even partially) or triggers, the
+    /// exchange cancels the other, and cancelling either leg with [`Self::cancel`] cancels
+    /// the pair. Both legs report `contingency_type == "OCO"` in
+    /// [`Self::get_open_orders`].
+    ///
+    /// `limit_price` must be above `stop_trigger` for a sell (take profit over a stop
+    /// loss) and below it for a buy; otherwise `InvalidParameter` is returned without
+    /// contacting the server, as it is for a quantity or price that is not positive.
+    ///
+    /// Always submitted over REST. The pair takes two slots of
+    /// [`Config::with_order_throttle`], one per leg, and a rejected nonce is retried like
+    /// [`Self::order`] when [`Config::auto_retry_nonce`] is set.
+    pub async fn place_oco(
+        &self,
+        market: &str,
+        side: OrderSide,
+        qty: Decimal,
+        limit_price: Decimal,
+        stop_trigger: Decimal,
+        stop_limit: Decimal,
+        order_mode: OrderMode,
+    ) -> Result<(String, String)> {
+        let span = tracing::info_span!(
+            "place_oco",
+            market = %market,
+            side = ?side,
+            nonce = tracing::field::Empty,
+            outcome = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        );
+        let call = telemetry::order_call("oco", async {
+            validate_order_inputs(limit_price, qty, OrderType::Limit, order_mode)?;
+            validate_order_inputs(stop_limit, qty, OrderType::Limit, order_mode)?;
+            if stop_trigger <= Decimal::ZERO {
+                return Err(AlphaSecError::invalid_parameter(format!(
+                    "OCO stop trigger must be greater than 0, got {}",
+                    stop_trigger
+                )));
+            }
+            // One slot per leg, taken together and held until the pair is submitted
+            let _permit = self.order_permit(2)?;
+
+            let (base_symbol, quote_symbol) = split_market(market)?;
+            let token_metadata = self.api.metadata().await?;
+            let base_token_id = token_metadata
+                .symbol_token_id_map
+                .get(base_symbol)
+                .ok_or_else(|| {
+                    AlphaSecError::config(format!("Unknown base token symbol: {}", base_symbol))
+                })?;
+            let quote_token_id = token_metadata
+                .symbol_token_id_map
+                .get(quote_symbol)
+                .ok_or_else(|| {
+                    AlphaSecError::config(format!("Unknown quote token symbol: {}", quote_symbol))
+                })?;
+
+            let oco_data = self.signer.create_oco_data(
+                base_token_id,
+                quote_token_id,
+                side as u32,
+                qty,
+                limit_price,
+                stop_trigger,
+                stop_limit,
+                order_mode as u32,
+            )?;
+            self.require_capability(Capabilities::OCO, "OCO orders")
+                .await?;
+            let result = self
+                .submit_signed_value(None, &oco_data, None, None, |api, tx| async move {
+                    api.oco_order(&tx).await.map_err(|e| match e.api_code() {
+                        // A server predating OCO that does not report its features
+                        Some(404) => AlphaSecError::unsupported(format!("OCO orders ({})", e)),
+                        _ => e,
+                    })
+                })
+                .await?;
+            oco_leg_ids(&result)
+        });
+        telemetry::traced(span, call).await
+    }
+
     /// Create session
+    ///
+    /// `timestamp_ms` must be within [`SESSION_TIMESTAMP_TOLERANCE_MS`] of the local clock
+    /// and `expires_at` must be later than it; otherwise `InvalidParameter` is returned
+    /// without contacting the server.
+    ///
+    /// Returns the session as the server reports it, falling back to the requested values
+    /// for anything the response leaves out.
     pub async fn create_session(
         &self,
         session_id: &str,
@@ -732,7 +1919,12 @@ impl Agent {
         timestamp_ms: u64,
         expires_at: u64,
         metadata: &[u8],
-    ) -> Result<String> {
+    ) -> Result<SessionAck> {
+        validate_session_window(
+            timestamp_ms,
+            expires_at,
+            chrono::Utc::now().timestamp_millis() as u64,
+        )?;
         let new_session_wallet = match session_wallet {
             Some(wallet) => wallet,
             None => self.config.l2_wallet.as_ref().cloned().ok_or_else(|| {
@@ -761,9 +1953,17 @@ impl Agent {
             .await?;
         let response = self.api.create_session(session_id, &signed_tx).await?;
         if response.success {
-            Ok(response.result_string())
+            SessionAck::from_result(
+                response.result.as_ref(),
+                SessionAck {
+                    session_id: session_id.to_string(),
+                    session_address: format!("0x{:x}", new_session_wallet.address()),
+                    expiry: expires_at,
+                    tx_hash: None,
+                },
+            )
         } else {
-            Err(AlphaSecError::api(
+            Err(AlphaSecError::from_api(
                 response.code.unwrap(),
                 response.error.unwrap(),
             ))
@@ -771,6 +1971,8 @@ impl Agent {
     }
 
     /// Update session
+    ///
+    /// Timestamps are validated as in [`Self::create_session`].
     pub async fn update_session(
         &self,
         session_id: &str,
@@ -778,7 +1980,12 @@ impl Agent {
         timestamp_ms: u64,
         expires_at: u64,
         metadata: &[u8],
-    ) -> Result<String> {
+    ) -> Result<SessionAck> {
+        validate_session_window(
+            timestamp_ms,
+            expires_at,
+            chrono::Utc::now().timestamp_millis() as u64,
+        )?;
         let new_session_wallet = match session_wallet {
             Some(wallet) => wallet,
             None => self.config.l2_wallet.as_ref().cloned().ok_or_else(|| {
@@ -806,9 +2013,17 @@ impl Agent {
             .await?;
         let response = self.api.update_session(session_id, &signed_tx).await?;
         if response.success {
-            Ok(response.result_string())
+            SessionAck::from_result(
+                response.result.as_ref(),
+                SessionAck {
+                    session_id: session_id.to_string(),
+                    session_address: format!("0x{:x}", new_session_wallet.address()),
+                    expiry: expires_at,
+                    tx_hash: None,
+                },
+            )
         } else {
-            Err(AlphaSecError::api(
+            Err(AlphaSecError::from_api(
                 response.code.unwrap(),
                 response.error.unwrap(),
             ))
@@ -819,15 +2034,16 @@ impl Agent {
     ///
     /// # Arguments
     ///
-    /// * `session_id` - Session ID
+    /// * `session_wallet` - Session wallet to remove (defaults to the `Config` L2 wallet)
     /// * `timestamp_ms` - Timestamp in milliseconds
-    /// * `expires_at` - Expiration time in milliseconds
-    /// * `metadata` - Metadata
+    ///
+    /// The returned [`SessionAck`] has an empty `session_id` and zero `expiry` unless the
+    /// server reports them.
     pub async fn delete_session(
         &self,
         session_wallet: Option<LocalWallet>,
         timestamp_ms: u64,
-    ) -> Result<String> {
+    ) -> Result<SessionAck> {
         let new_session_wallet = match session_wallet {
             Some(wallet) => wallet,
             None => self.config.l2_wallet.as_ref().cloned().ok_or_else(|| {
@@ -854,9 +2070,17 @@ impl Agent {
             .await?;
         let response = self.api.delete_session(&signed_tx).await?;
         if response.success {
-            Ok(response.result_string())
+            SessionAck::from_result(
+                response.result.as_ref(),
+                SessionAck {
+                    session_id: String::new(),
+                    session_address: format!("0x{:x}", new_session_wallet.address()),
+                    expiry: 0,
+                    tx_hash: None,
+                },
+            )
         } else {
-            Err(AlphaSecError::api(
+            Err(AlphaSecError::from_api(
                 response.code.unwrap(),
                 response.error.unwrap(),
             ))
@@ -868,34 +2092,23 @@ impl Agent {
     /// # Arguments
     ///
     /// * `token` - Token symbol (e.g., "KAIA")
-    /// * `value` - Amount to deposit in trading units
-    pub async fn deposit_token(&self, token: &str, value: f64) -> Result<String> {
-        let token_id = self
-            .api
-            .token_metadata()
-            .clone()
-            .ok_or_else(|| AlphaSecError::config("Token metadata not initialized"))?
+    /// * `value` - Amount to deposit in trading units, as a `Decimal` or a decimal string
+    pub async fn deposit_token(&self, token: &str, value: impl IntoAmount) -> Result<String> {
+        let value = value.into_amount()?;
+        let token_metadata = self.api.metadata().await?;
+        let token_id = token_metadata
             .symbol_token_id_map
             .get(token)
             .ok_or_else(|| AlphaSecError::config(format!("Unknown token symbol: {}", token)))?;
-        let token_l1_address = self
-            .api
-            .token_metadata()
-            .ok_or_else(|| AlphaSecError::config("Token metadata not initialized"))?
+        let token_l1_address = token_metadata
             .token_id_address_map
             .get(token_id)
             .ok_or_else(|| AlphaSecError::config(format!("Unknown token symbol: {}", token)))?;
-        let token_l1_decimals = self
-            .api
-            .token_metadata()
-            .ok_or_else(|| AlphaSecError::config("Token metadata not initialized"))?
+        let token_l1_decimals = token_metadata
             .token_id_decimal_map
             .get(token_id)
             .ok_or_else(|| AlphaSecError::config(format!("Unknown token symbol: {}", token)))?;
-        let l1_url = match self.config.network {
-            crate::signer::config::Network::Mainnet => endpoints::KAIA_MAINNET_URL,
-            crate::signer::config::Network::Kairos => endpoints::KAIA_KAIROS_URL,
-        };
+        let l1_url = self.config.network_params().l1_rpc_url;
         let l1_url = l1_url
             .parse::<reqwest::Url>()
             .map_err(|e| AlphaSecError::config(format!("Invalid L1 URL: {}", e)))?;
@@ -910,7 +2123,12 @@ impl Agent {
                 token_id,
                 value,
                 Some(token_l1_address),
-                Some(token_l1_decimals.parse::<u8>().unwrap_or(18)),
+                Some(token_l1_decimals.parse::<u8>().map_err(|_| {
+                    AlphaSecError::config(format!(
+                        "Invalid decimals '{}' for token {}",
+                        token_l1_decimals, token
+                    ))
+                })?),
             )
             .await?;
 
@@ -946,55 +2164,54 @@ impl Agent {
     /// # Arguments
     ///
     /// * `token` - Token symbol (e.g., "KAIA")
-    /// * `value` - Amount to withdraw in trading units
+    /// * `value` - Amount to withdraw in trading units, as a `Decimal` or a decimal string
+    ///
+    /// The withdrawal is only initiated on L2; follow it with
+    /// [`get_withdrawal_status`](Self::get_withdrawal_status).
     pub async fn withdraw_token(
         &self,
         token: &str,
-        value: f64,
+        value: impl IntoAmount,
         timestamp_ms: Option<u64>,
     ) -> Result<String> {
-        let token_id = self
-            .api
-            .token_metadata()
-            .clone()
-            .ok_or_else(|| AlphaSecError::config("Token metadata not initialized"))?
+        let value = value.into_amount()?;
+        let token_metadata = self.api.metadata().await?;
+        let token_id = token_metadata
             .symbol_token_id_map
             .get(token)
             .ok_or_else(|| AlphaSecError::config(format!("Unknown token symbol: {}", token)))?;
-        let token_l1_address = self
-            .api
-            .token_metadata()
-            .ok_or_else(|| AlphaSecError::config("Token metadata not initialized"))?
+        let token_l1_address = token_metadata
             .token_id_address_map
             .get(token_id)
             .ok_or_else(|| AlphaSecError::config(format!("Unknown token symbol: {}", token)))?;
-        let token_l1_decimals = self
-            .api
-            .token_metadata()
-            .ok_or_else(|| AlphaSecError::config("Token metadata not initialized"))?
+        let token_l1_decimals = token_metadata
             .token_id_decimal_map
             .get(token_id)
             .ok_or_else(|| AlphaSecError::config(format!("Unknown token symbol: {}", token)))?;
-        let l1_url = match self.config.network {
-            crate::signer::config::Network::Mainnet => endpoints::KAIA_MAINNET_URL,
-            crate::signer::config::Network::Kairos => endpoints::KAIA_KAIROS_URL,
-        };
-
-        let l1_url = l1_url
+        // Withdrawals are L2 transactions, so the provider points at the AlphaSec RPC.
+        let l2_url = self
+            .config
+            .network_params()
+            .l2_rpc_url
             .parse::<reqwest::Url>()
-            .map_err(|e| AlphaSecError::config(format!("Invalid L1 URL: {}", e)))?;
-        let l1_provider = std::sync::Arc::new(ethers::providers::Provider::new(
-            ethers::providers::Http::new(l1_url.clone()),
+            .map_err(|e| AlphaSecError::config(format!("Invalid L2 URL: {}", e)))?;
+        let l2_provider = std::sync::Arc::new(ethers::providers::Provider::new(
+            ethers::providers::Http::new(l2_url),
         ));
 
         let signed_tx = self
             .signer
             .generate_withdraw_transaction(
-                &l1_provider,
+                &l2_provider,
                 token_id,
                 value,
                 Some(token_l1_address),
-                Some(token_l1_decimals.parse::<u8>().unwrap_or(18)),
+                Some(token_l1_decimals.parse::<u8>().map_err(|_| {
+                    AlphaSecError::config(format!(
+                        "Invalid decimals '{}' for token {}",
+                        token_l1_decimals, token
+                    ))
+                })?),
                 timestamp_ms,
             )
             .await?;
@@ -1003,19 +2220,107 @@ impl Agent {
         if response.success {
             Ok(response.result_string())
         } else {
-            Err(AlphaSecError::api(
+            Err(AlphaSecError::from_api(
                 response.code.unwrap(),
                 response.error.unwrap(),
             ))
         }
     }
 
+    /// Read this account's L2 balance of `token` straight from chain
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - Token symbol (e.g., "KAIA")
+    ///
+    /// Unlike [`Self::get_balance`], which reports the DEX's accounting, this asks the
+    /// AlphaSec RPC: the native token's account balance, or `balanceOf` on an ERC20
+    /// token's L2 contract, scaled by the token's decimals. Meant for reconciliation.
+    pub async fn get_onchain_balance(&self, token: &str) -> Result<Decimal> {
+        let l2_url = self
+            .config
+            .network_params()
+            .l2_rpc_url
+            .parse::<reqwest::Url>()
+            .map_err(|e| AlphaSecError::config(format!("Invalid L2 URL: {}", e)))?;
+        let l2_provider = std::sync::Arc::new(ethers::providers::Provider::new(
+            ethers::providers::Http::new(l2_url),
+        ));
+        self.onchain_balance_via(&l2_provider, token).await
+    }
+
+    /// [`Self::get_onchain_balance`] against the given L2 provider
+    async fn onchain_balance_via<M: ethers::providers::Middleware + 'static>(
+        &self,
+        l2_provider: &Arc<M>,
+        token: &str,
+    ) -> Result<Decimal> {
+        let token_metadata = self.api.metadata().await?;
+        let token_id = token_metadata
+            .symbol_token_id_map
+            .get(token)
+            .ok_or_else(|| AlphaSecError::config(format!("Unknown token symbol: {}", token)))?;
+        let token_l1_address = token_metadata.token_id_address_map.get(token_id);
+        // Same decimals as deposits and withdrawals: the metadata, with any
+        // `Config::with_token_decimals` override applied
+        let token_decimals = match token_metadata.token_id_decimal_map.get(token_id) {
+            Some(decimals) => Some(decimals.parse::<u8>().map_err(|_| {
+                AlphaSecError::config(format!(
+                    "Invalid decimals '{}' for token {}",
+                    decimals, token
+                ))
+            })?),
+            None => None,
+        };
+        self.signer
+            .get_onchain_balance(
+                l2_provider,
+                token_id,
+                token_l1_address.map(String::as_str),
+                token_decimals,
+            )
+            .await
+    }
+
+    /// Get the stage of a withdrawal started by [`withdraw_token`](Self::withdraw_token)
+    ///
+    /// `withdrawal_id_or_tx` is the withdrawal ID or the L2 transaction hash. Fails
+    /// with [`AlphaSecError::NotFound`] for an unknown withdrawal.
+    ///
+    /// [`WithdrawalStatus::Claimable`] means the funds can be released on Kaia. The SDK
+    /// does not submit that L1 claim: it needs the deployment's outbox contract and
+    /// withdrawal proof, neither of which the API exposes yet.
+    pub async fn get_withdrawal_status(
+        &self,
+        withdrawal_id_or_tx: &str,
+    ) -> Result<WithdrawalStatus> {
+        self.api.get_withdrawal_status(withdrawal_id_or_tx).await
+    }
+
     // === Market Data Helpers ===
+    /// Check that the REST API is reachable and answering
+    pub async fn health_check(&self) -> Result<()> {
+        self.api.health_check().await
+    }
+
     /// Get depth for specific market
     pub async fn get_depth(&self, market: &str, limit: Option<u32>) -> Result<Depth> {
         self.api.get_depth(market, limit).await
     }
 
+    /// [`Self::get_depth`] with `timeout` in place of `Config::timeout_secs`, e.g. a
+    /// tighter deadline for a snapshot
+    pub async fn get_depth_with_timeout(
+        &self,
+        market: &str,
+        limit: Option<u32>,
+        timeout: Duration,
+    ) -> Result<Depth> {
+        self.api
+            .get_depth_with_timeout(market, limit, timeout)
+            .await
+    }
+
     /// Get ticker for specific market
     pub async fn get_ticker(&self, market: &str) -> Result<Ticker> {
         self.api.get_ticker(market).await
@@ -1028,19 +2333,129 @@ impl Agent {
 
     /// Get market list
     pub async fn get_market_list(&self) -> Result<Vec<Market>> {
-        self.api.get_market_list().await
+        let markets = self.api.get_market_list().await?;
+        *self.spot_markets.write().await = markets
+            .iter()
+            .map(|m| (m.market_id.clone(), m.clone()))
+            .collect();
+        Ok(markets)
     }
 
-    /// Get recent trades
-    pub async fn get_trades(&self, market: &str, limit: Option<u32>) -> Result<Vec<Trade>> {
+    /// Get the full definition of one market (e.g. "KAIA/USDT"), including its
+    /// trading rules
+    pub async fn get_market(&self, market: &str) -> Result<Market> {
+        let market_id = self.api.metadata().await?.market_to_market_id(market)?;
+        self.get_market_list()
+            .await?
+            .into_iter()
+            .find(|m| m.market_id == market_id)
+            .ok_or_else(|| AlphaSecError::not_found(format!("Market not found: {}", market)))
+    }
+
+    /// Definition of `market_id` for client-side order validation.
+    ///
+    /// Served from the cache, refreshing it once on a miss. Returns `None` if the market
+    /// list cannot be fetched, leaving validation to the server.
+    async fn market_rules(&self, market_id: &str) -> Option<Market> {
+        if let Some(market) = self.spot_markets.read().await.get(market_id) {
+            return Some(market.clone());
+        }
+        match self.get_market_list().await {
+            Ok(markets) => markets.into_iter().find(|m| m.market_id == market_id),
+            Err(e) => {
+                warn!(
+                    "Skipping client-side order validation for {}: {}",
+                    market_id, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Get recent trades
+    pub async fn get_trades(&self, market: &str, limit: Option<u32>) -> Result<Vec<Trade>> {
         self.api.get_trades(market, limit).await
     }
 
+    /// [`Self::get_trades`] with `timeout` in place of `Config::timeout_secs`
+    pub async fn get_trades_with_timeout(
+        &self,
+        market: &str,
+        limit: Option<u32>,
+        timeout: Duration,
+    ) -> Result<Vec<Trade>> {
+        self.api
+            .get_trades_with_timeout(market, limit, timeout)
+            .await
+    }
+
+    /// VWAP, volume and price range of the trades in `market` between `from_ms` and
+    /// `to_ms` (inclusive)
+    ///
+    /// Pages through the trade tape (see [`ApiClient::get_trades_between`]). An empty
+    /// window gives zero volumes and `None` for `vwap`, `high` and `low`.
+    pub async fn trade_stats(&self, market: &str, from_ms: u64, to_ms: u64) -> Result<TradeStats> {
+        let trades = self
+            .api
+            .get_trades_between(market, from_ms, to_ms, None, None)
+            .await?;
+        TradeStats::from_trades(&trades)
+    }
+
     /// Get all tokens
     pub async fn get_tokens(&self) -> Result<Vec<Token>> {
         self.api.get_tokens().await
     }
 
+    /// Token metadata, `None` until it has been loaded
+    pub fn token_metadata(&self) -> Option<&TokenMetadata> {
+        self.api.token_metadata()
+    }
+
+    /// Token metadata, fetching it first if it has not been loaded yet
+    ///
+    /// Async symbol-based methods do this on their own; call it before the synchronous
+    /// helpers ([`Self::symbol_to_market_id`], [`Self::market_id_to_symbol`]) on an agent
+    /// created with [`Self::new_lazy`] or whose startup fetch failed.
+    pub async fn load_metadata(&self) -> Result<&TokenMetadata> {
+        self.api.metadata().await
+    }
+
+    /// Convert a wire market ID (e.g. `"5_2"`, as in `marketId` fields) to its symbol
+    /// (e.g. `"KAIA/USDT"`)
+    pub fn market_id_to_symbol(&self, market_id: &str) -> Result<String> {
+        self.loaded_token_metadata()?.market_id_to_market(market_id)
+    }
+
+    /// Convert a market symbol (e.g. `"KAIA/USDT"`) to its wire market ID (e.g. `"5_2"`)
+    pub fn symbol_to_market_id(&self, symbol: &str) -> Result<String> {
+        self.loaded_token_metadata()?.market_to_market_id(symbol)
+    }
+
+    /// Human price of `raw`, a price in the base units of `market`'s quote token
+    ///
+    /// See [`TokenMetadata::humanize`]; the market is a symbol such as `"KAIA/USDT"`.
+    pub fn humanize_price(&self, market: &str, raw: &str) -> Result<Decimal> {
+        let metadata = self.loaded_token_metadata()?;
+        let market_id = metadata.market_id(market)?;
+        metadata.humanize(&market_id.quote_token_id.to_string(), raw)
+    }
+
+    /// Human quantity of `raw`, a quantity in the base units of `market`'s base token
+    ///
+    /// See [`Self::humanize_price`].
+    pub fn humanize_qty(&self, market: &str, raw: &str) -> Result<Decimal> {
+        let metadata = self.loaded_token_metadata()?;
+        let market_id = metadata.market_id(market)?;
+        metadata.humanize(&market_id.base_token_id.to_string(), raw)
+    }
+
+    fn loaded_token_metadata(&self) -> Result<&TokenMetadata> {
+        self.api.token_metadata().ok_or_else(|| {
+            AlphaSecError::config("Token metadata not loaded; call Agent::load_metadata first")
+        })
+    }
+
     // === Order History Helpers ===
 
     /// Get open orders
@@ -1049,18 +2464,56 @@ impl Agent {
         addr: &str,
         market: Option<&str>,
         limit: Option<u32>,
-        _from_msec: Option<i64>,
-        _end_msec: Option<i64>,
+        from_msec: Option<i64>,
+        end_msec: Option<i64>,
+    ) -> Result<Vec<Order>> {
+        let query = orders_query(addr, market, limit, from_msec, end_msec);
+        self.api.get_open_orders(&query).await
+    }
+
+    /// [`Self::get_open_orders`] with `timeout` in place of `Config::timeout_secs`
+    pub async fn get_open_orders_with_timeout(
+        &self,
+        addr: &str,
+        market: Option<&str>,
+        limit: Option<u32>,
+        from_msec: Option<i64>,
+        end_msec: Option<i64>,
+        timeout: Duration,
     ) -> Result<Vec<Order>> {
+        let query = orders_query(addr, market, limit, from_msec, end_msec);
+        self.api.get_open_orders_with_timeout(&query, timeout).await
+    }
+
+    /// Get a page of open orders, newest first
+    ///
+    /// Paged like [`Self::get_order_history_paged`]: pass `None` for the first page and
+    /// the returned cursor for each following one until it comes back `None`.
+    pub async fn get_open_orders_paged(
+        &self,
+        addr: &str,
+        market: Option<&str>,
+        cursor: Option<Cursor>,
+    ) -> Result<(Vec<Order>, Option<Cursor>)> {
         let mut query = OrdersQuery::new(addr);
         if let Some(market) = market {
             query = query.market(market);
         }
-        if let Some(limit) = limit {
-            query = query.limit(limit);
+        self.api.get_open_orders_page(&query, cursor, None).await
+    }
+
+    /// Every open order of `addr`, walking all pages of [`Self::get_open_orders_paged`]
+    async fn all_open_orders(&self, addr: &str, market: Option<&str>) -> Result<Vec<Order>> {
+        let mut orders = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next) = self.get_open_orders_paged(addr, market, cursor).await?;
+            orders.extend(page);
+            match next {
+                Some(next) => cursor = Some(next),
+                None => return Ok(orders),
+            }
         }
-        // TODO: Add time range support
-        self.api.get_open_orders(&query).await
     }
 
     /// Get filled and canceled orders
@@ -1069,25 +2522,64 @@ impl Agent {
         addr: &str,
         market: Option<&str>,
         limit: Option<u32>,
-        _from_msec: Option<i64>,
-        _end_msec: Option<i64>,
+        from_msec: Option<i64>,
+        end_msec: Option<i64>,
+    ) -> Result<Vec<Order>> {
+        let query = orders_query(addr, market, limit, from_msec, end_msec);
+        self.api.get_filled_canceled_orders(&query).await
+    }
+
+    /// [`Self::get_filled_canceled_orders`] with `timeout` in place of
+    /// `Config::timeout_secs`; large history backfills typically want a longer deadline
+    /// than the default
+    pub async fn get_filled_canceled_orders_with_timeout(
+        &self,
+        addr: &str,
+        market: Option<&str>,
+        limit: Option<u32>,
+        from_msec: Option<i64>,
+        end_msec: Option<i64>,
+        timeout: Duration,
     ) -> Result<Vec<Order>> {
+        let query = orders_query(addr, market, limit, from_msec, end_msec);
+        self.api
+            .get_filled_canceled_orders_with_timeout(&query, timeout)
+            .await
+    }
+
+    /// Get a page of filled/canceled orders, newest first
+    ///
+    /// Pass `None` for the first page and the returned cursor for each following one;
+    /// a `None` cursor means there are no more pages. The first page holds the newest
+    /// orders and each following one is requested with the cursor as `endMsec` (see
+    /// [`Cursor`] for how rows at the boundary are de-duplicated). Rows are sorted newest
+    /// first client-side. Pages hold [`ORDER_HISTORY_PAGE_LIMIT`] rows.
+    pub async fn get_order_history_paged(
+        &self,
+        addr: &str,
+        market: Option<&str>,
+        cursor: Option<Cursor>,
+    ) -> Result<(Vec<Order>, Option<Cursor>)> {
         let mut query = OrdersQuery::new(addr);
         if let Some(market) = market {
             query = query.market(market);
         }
-        if let Some(limit) = limit {
-            query = query.limit(limit);
-        }
-        // TODO: Add time range support
-        self.api.get_filled_canceled_orders(&query).await
+        self.api.get_order_history_page(&query, cursor, None).await
     }
 
-    /// Get order by ID
+    /// Get order by ID, `None` if the server does not know it
     pub async fn get_order_by_id(&self, order_id: &str) -> Result<Option<Order>> {
         self.api.get_order_by_id(order_id).await
     }
 
+    /// Get the trades that filled (part of) an order, with price, quantity and fee
+    ///
+    /// Returns an empty list for an unknown order or one with no fills, and an
+    /// [`AlphaSecError::InvalidParameter`] for an ID that is not well-formed.
+    pub async fn get_order_trades(&self, order_id: &str) -> Result<Vec<Trade>> {
+        self.api.get_order_trades(order_id).await
+    }
+
     // === Wallet/Session Helpers ===
 
     /// Get balance
@@ -1095,9 +2587,111 @@ impl Agent {
         self.api.get_balance(addr).await
     }
 
-    /// Get sessions
-    pub async fn get_sessions(&self, addr: &str) -> Result<Vec<Session>> {
-        self.api.get_sessions(addr).await
+    /// Get the balances of several addresses concurrently
+    ///
+    /// Each distinct address gets its own entry, holding its balances or the error its
+    /// request failed with, so one bad address does not hide the others. At most
+    /// [`BALANCE_FETCH_CONCURRENCY`] requests are in flight at once.
+    pub async fn get_balances(&self, addrs: &[&str]) -> HashMap<String, Result<Balances>> {
+        use futures_util::StreamExt;
+
+        let mut unique: Vec<&str> = addrs.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        futures_util::stream::iter(unique)
+            .map(|addr| async move { (addr.to_string(), self.api.get_balance(addr).await) })
+            .buffer_unordered(BALANCE_FETCH_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Get the volume-based fee tier of `addr`
+    ///
+    /// Tiers are cached per address for [`FEE_TIER_CACHE_TTL`](super::FEE_TIER_CACHE_TTL)
+    /// and shared across clones of this agent. A failed lookup is not retried for
+    /// [`FEE_TIER_FAILURE_BACKOFF`](super::FEE_TIER_FAILURE_BACKOFF); until then its
+    /// message comes back as a `Network` error.
+    pub async fn get_account_fee_tier(&self, addr: &str) -> Result<FeeTier> {
+        match self.fee_tiers.get(addr, std::time::Instant::now()) {
+            Some(Ok(tier)) => return Ok(tier),
+            Some(Err(message)) => {
+                return Err(AlphaSecError::network(format!(
+                    "Fee tier lookup for {} failed recently: {}",
+                    addr, message
+                )))
+            }
+            None => {}
+        }
+        let result = self.api.get_fee_tier(addr).await;
+        let entry = match &result {
+            Ok(tier) => Ok(tier.clone()),
+            Err(e) => Err(e.to_string()),
+        };
+        self.fee_tiers
+            .insert(addr, entry, std::time::Instant::now());
+        result
+    }
+
+    /// Get the sessions of `addr`
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Wallet address to query
+    /// * `only_active` - Keep only sessions that are applied and not yet expired
+    /// * `name` - Keep only the session with this name
+    ///
+    /// The filters are applied to the server's full list.
+    pub async fn get_sessions(
+        &self,
+        addr: &str,
+        only_active: bool,
+        name: Option<&str>,
+    ) -> Result<Vec<Session>> {
+        let now_ms = AlphaSecSigner::current_timestamp_ms();
+        let mut sessions = self.api.get_sessions(addr).await?;
+        sessions.retain(|session| {
+            (!only_active || session.is_active(now_ms))
+                && name.is_none_or(|name| session.name == name)
+        });
+        Ok(sessions)
+    }
+
+    /// Keep a session of this account alive by renewing it before it expires
+    ///
+    /// Looks up the session's current expiry, then spawns a task that calls
+    /// [`Self::update_session`] (with the `Config` L2 wallet) `lead_time` before each
+    /// expiry, extending it to [`SESSION_RENEWAL_EXTENSION`](super::SESSION_RENEWAL_EXTENSION)
+    /// from then. Failed renewals are retried until stopped. Every outcome is logged and
+    /// reported through the returned handle; dropping the handle stops the task.
+    ///
+    /// Fails with `NotFound` if the account has no session named `session_id`, and
+    /// with `InvalidParameter` if `lead_time` is zero or not shorter than the extension.
+    pub async fn enable_auto_session_renewal(
+        &self,
+        session_id: &str,
+        lead_time: Duration,
+    ) -> Result<super::SessionRenewal> {
+        if lead_time.is_zero() || lead_time >= super::SESSION_RENEWAL_EXTENSION {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "Session renewal lead time must be between 0 and {:?}, got {:?}",
+                super::SESSION_RENEWAL_EXTENSION,
+                lead_time
+            )));
+        }
+        let session = self
+            .get_sessions(self.l1_address(), false, Some(session_id))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                AlphaSecError::not_found(format!("Session not found: {}", session_id))
+            })?;
+        Ok(super::SessionRenewal::spawn(
+            self.clone(),
+            session_id.to_string(),
+            session.expiry,
+            lead_time,
+        ))
     }
 
     /// Get transfer history for a wallet address
@@ -1127,16 +2721,113 @@ impl Agent {
         self.api.get_transfer_history(&query).await
     }
 
+    /// [`Self::get_transfer_history`] with `timeout` in place of `Config::timeout_secs`
+    pub async fn get_transfer_history_with_timeout(
+        &self,
+        addr: &str,
+        token_id: Option<i64>,
+        from_msec: Option<i64>,
+        to_msec: Option<i64>,
+        limit: Option<u32>,
+        timeout: Duration,
+    ) -> Result<Vec<Transfer>> {
+        let query = TransferHistoryQuery {
+            address: addr.to_string(),
+            token_id,
+            from_msec,
+            to_msec,
+            limit,
+        };
+        self.api
+            .get_transfer_history_with_timeout(&query, timeout)
+            .await
+    }
+
+    // === Own Account Helpers ===
+
+    /// Open orders of this agent's account ([`Self::l1_address`]), see
+    /// [`Self::get_open_orders`]
+    pub async fn my_open_orders(
+        &self,
+        market: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Order>> {
+        self.get_open_orders(self.l1_address(), market, limit, None, None)
+            .await
+    }
+
+    /// Balance of this agent's account, see [`Self::get_balance`]
+    pub async fn my_balance(&self) -> Result<Balances> {
+        self.get_balance(self.l1_address()).await
+    }
+
+    /// Sessions of this agent's account, see [`Self::get_sessions`]
+    pub async fn my_sessions(&self, only_active: bool, name: Option<&str>) -> Result<Vec<Session>> {
+        self.get_sessions(self.l1_address(), only_active, name)
+            .await
+    }
+
+    /// Transfer history of this agent's account, see [`Self::get_transfer_history`]
+    pub async fn my_transfer_history(
+        &self,
+        token_id: Option<i64>,
+        from_msec: Option<i64>,
+        to_msec: Option<i64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Transfer>> {
+        self.get_transfer_history(self.l1_address(), token_id, from_msec, to_msec, limit)
+            .await
+    }
+
     /// Get the signer's L1 address
     pub fn l1_address(&self) -> &str {
         self.signer.l1_address()
     }
 
+    /// Checksummed address of the session (L2) wallet, if an L2 key is configured
+    ///
+    /// Available without signing anything, e.g. to register the session out of band.
+    pub fn session_address(&self) -> Option<String> {
+        self.signer.l2_address()
+    }
+
     /// Check if session is enabled
     pub fn is_session_enabled(&self) -> bool {
         self.signer.is_session_enabled()
     }
 
+    /// Register `wallet` as the session named `name`, replacing any wallet already
+    /// registered under that name
+    ///
+    /// Orders ([`OrderBuilder::session`](super::OrderBuilder::session)) and cancels
+    /// ([`Self::cancel_as`]) can then be signed by it instead of the `Config` wallet,
+    /// e.g. one session per strategy. The session itself must still be registered with
+    /// the exchange, see [`Self::create_session`]. Clones of this agent share the
+    /// registered wallets.
+    pub fn add_session_wallet(&self, name: impl Into<String>, wallet: LocalWallet) {
+        self.session_wallets
+            .write()
+            .unwrap()
+            .insert(name.into(), wallet);
+    }
+
+    /// Wallet of the named `session`, or `None` (the trading wallet) if no session is
+    /// named
+    fn session_wallet(&self, session: Option<&str>) -> Result<Option<LocalWallet>> {
+        let Some(name) = session else {
+            return Ok(None);
+        };
+        self.session_wallets
+            .read()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| {
+                AlphaSecError::invalid_parameter(format!("Unknown session wallet: {}", name))
+            })
+    }
+
     /// Access perpetual futures operations via the PerpAgent sub-facade.
     ///
     /// # Example
@@ -1172,3 +2863,2595 @@ impl Agent {
         )
     }
 }
+
+/// Leg ids from an OCO submission result, `{"limitOrderId", "stopOrderId"}`
+/// [`OrdersQuery`] of the order listing helpers' arguments
+fn orders_query(
+    addr: &str,
+    market: Option<&str>,
+    limit: Option<u32>,
+    from_msec: Option<i64>,
+    end_msec: Option<i64>,
+) -> OrdersQuery {
+    let mut query = OrdersQuery::new(addr);
+    if let Some(market) = market {
+        query = query.market(market);
+    }
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+    query.from_msec = from_msec;
+    query.end_msec = end_msec;
+    query
+}
+
+fn oco_leg_ids(result: &serde_json::Value) -> Result<(String, String)> {
+    let id = |key: &str| result.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    id("limitOrderId")
+        .zip(id("stopOrderId"))
+        .ok_or_else(|| AlphaSecError::api(500, format!("Invalid OCO order response: {}", result)))
+}
+
+/// How a Trade WebSocket submission went, see [`Agent::submit_signed`]
+#[cfg(feature = "websocket")]
+enum TradeWsOutcome {
+    /// The server answered, with the result or an API error
+    Answered(Result<String>),
+    /// No Trade WebSocket was connected, so nothing was sent
+    NotSent,
+    /// The request failed on the connection (e.g. timed out) and may or may not have
+    /// reached the server
+    Failed,
+}
+
+/// Attempts [`Agent::new`] makes at fetching the token metadata
+pub const METADATA_FETCH_ATTEMPTS: u32 = 3;
+
+/// Submissions [`Agent::order`], [`Agent::cancel`] and [`Agent::modify`] make when the
+/// server keeps rejecting the nonce, see [`Config::auto_retry_nonce`]
+pub const NONCE_RETRY_ATTEMPTS: u32 = 3;
+
+/// Delay before the second metadata fetch in [`Agent::new`], doubling after each failure
+const METADATA_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// How long a failed [`Agent::server_capabilities`] fetch is reported again instead of
+/// being retried
+pub const CAPABILITIES_FAILURE_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Delay between order status checks in [`Agent::cancel_and_confirm`].
+const CANCEL_CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Balance requests [`Agent::get_balances`] keeps in flight at once
+pub const BALANCE_FETCH_CONCURRENCY: usize = 4;
+
+/// Poll `fetch` every `interval` until it yields an order that is no longer active.
+///
+/// A missing order (`None`) is treated as not yet visible and polled again.
+async fn poll_until_terminal<F, Fut>(
+    mut fetch: F,
+    timeout: Duration,
+    interval: Duration,
+) -> Result<CancelOutcome>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<Option<Order>>>,
+{
+    let mut last_status = None;
+    let poll = async {
+        loop {
+            if let Some(order) = fetch().await? {
+                if !order.is_active() {
+                    return Ok(CancelOutcome::from_terminal(order));
+                }
+                last_status = Some(order.status);
+            }
+            sleep(interval).await;
+        }
+    };
+    match tokio::time::timeout(timeout, poll).await {
+        Ok(result) => result,
+        Err(_) => Err(AlphaSecError::generic(format!(
+            "Order still not terminal after {:?} (last status: {})",
+            timeout,
+            last_status.as_deref().unwrap_or("not found")
+        ))),
+    }
+}
+
+/// Channel targets standing for every market; the first is the wire form
+#[cfg(feature = "websocket")]
+const ALL_MARKETS_TARGETS: [&str; 2] = ["all", "*"];
+
+/// Whether `channel` is a perp channel, passed to the server verbatim
+#[cfg(feature = "websocket")]
+fn is_perp_channel(channel: &str) -> bool {
+    channel.starts_with("perp_") && channel.contains('@')
+}
+
+/// How far a session `timestamp_ms` may drift from the local clock, in either direction.
+pub const SESSION_TIMESTAMP_TOLERANCE_MS: u64 = 5 * 60 * 1000;
+
+/// Reject session timestamps that are stale, too far ahead of `now_ms`, or that would
+/// create an already-expired session.
+fn validate_session_window(timestamp_ms: u64, expires_at: u64, now_ms: u64) -> Result<()> {
+    if timestamp_ms.abs_diff(now_ms) > SESSION_TIMESTAMP_TOLERANCE_MS {
+        return Err(AlphaSecError::invalid_parameter(format!(
+            "Session timestamp {} is more than {}ms away from now ({})",
+            timestamp_ms, SESSION_TIMESTAMP_TOLERANCE_MS, now_ms
+        )));
+    }
+    if expires_at <= timestamp_ms {
+        return Err(AlphaSecError::invalid_parameter(format!(
+            "Session expiry {} must be after its timestamp {}",
+            expires_at, timestamp_ms
+        )));
+    }
+    Ok(())
+}
+
+/// Reject amounts the server would bounce: every order needs a positive quantity and a
+/// limit order a positive price. Market orders ignore `price`.
+fn validate_order_inputs(
+    price: Decimal,
+    quantity: Decimal,
+    order_type: OrderType,
+    order_mode: OrderMode,
+) -> Result<()> {
+    if quantity <= Decimal::ZERO {
+        let what = match order_mode {
+            OrderMode::Base => "Order quantity",
+            OrderMode::Quote => "Quote-mode order quantity (quote token amount)",
+        };
+        return Err(AlphaSecError::invalid_parameter(format!(
+            "{} must be greater than 0, got {}",
+            what, quantity
+        )));
+    }
+    if order_type == OrderType::Limit && price <= Decimal::ZERO {
+        return Err(AlphaSecError::invalid_parameter(format!(
+            "Limit order price must be greater than 0, got {}",
+            price
+        )));
+    }
+    Ok(())
+}
+
+/// Check that take profit and stop loss prices are positive and, for a limit order, lie
+/// on the profit and loss side of `price`: above and below it for a buy, below and above
+/// it for a sell. A market order has no price to compare against.
+fn validate_tpsl(
+    side: OrderSide,
+    price: Decimal,
+    order_type: OrderType,
+    tp_limit: Option<Decimal>,
+    sl_trigger: Option<Decimal>,
+    sl_limit: Option<Decimal>,
+) -> Result<()> {
+    let legs = [
+        ("Take profit limit", tp_limit, true),
+        ("Stop loss trigger", sl_trigger, false),
+        ("Stop loss limit", sl_limit, false),
+    ];
+    for (what, value, profit_side) in legs {
+        let Some(value) = value else { continue };
+        if value <= Decimal::ZERO {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "{} must be greater than 0, got {}",
+                what, value
+            )));
+        }
+        if order_type == OrderType::Market {
+            continue;
+        }
+        // A buy profits when the price rises, a sell when it falls
+        let above = profit_side == (side == OrderSide::Buy);
+        if (above && value <= price) || (!above && value >= price) {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "{} {} must be {} the order price {} for a {} order",
+                what,
+                value,
+                if above { "above" } else { "below" },
+                price,
+                side
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Price, original quantity and executed quantity of `order`, which must be active for
+/// it to be `action`ed
+fn active_order_terms(order: &Order, action: &str) -> Result<(Decimal, Decimal, Decimal)> {
+    if !order.is_active() {
+        return Err(AlphaSecError::invalid_parameter(format!(
+            "Order {} is {}, only active orders can be {}",
+            order.order_id, order.status, action
+        )));
+    }
+    let invalid = |field: &str, e: rust_decimal::Error| {
+        AlphaSecError::invalid_parameter(format!(
+            "Invalid {} on order {}: {}",
+            field, order.order_id, e
+        ))
+    };
+    let price = order.price_decimal().map_err(|e| invalid("price", e))?;
+    let orig_qty = order
+        .orig_qty_decimal()
+        .map_err(|e| invalid("origQty", e))?;
+    let executed_qty = order
+        .executed_qty_decimal()
+        .map_err(|e| invalid("executedQty", e))?;
+    Ok((price, orig_qty, executed_qty))
+}
+
+/// Price and new quantity of `order` after taking `reduce_by` off its remaining quantity,
+/// or `None` if nothing would remain
+fn reduced_order(order: &Order, reduce_by: Decimal) -> Result<Option<(Decimal, Decimal)>> {
+    let (price, orig_qty, executed_qty) = active_order_terms(order, "reduced")?;
+    let remaining = orig_qty - executed_qty;
+    if reduce_by <= Decimal::ZERO || reduce_by > remaining {
+        return Err(AlphaSecError::invalid_parameter(format!(
+            "Reduction must be greater than 0 and at most the remaining quantity {} of order {}, got {}",
+            remaining, order.order_id, reduce_by
+        )));
+    }
+    Ok((reduce_by < remaining).then_some((price, orig_qty - reduce_by)))
+}
+
+/// Price and quantity of a modify of `order` that changes only the given fields, keeping
+/// its current price or original quantity for the other
+fn modified_order(
+    order: &Order,
+    new_price: Option<Decimal>,
+    new_qty: Option<Decimal>,
+) -> Result<(Decimal, Decimal)> {
+    let (price, orig_qty, executed_qty) = active_order_terms(order, "modified")?;
+    if let Some(new_price) = new_price.filter(|p| *p <= Decimal::ZERO) {
+        return Err(AlphaSecError::invalid_parameter(format!(
+            "New price must be greater than 0, got {}",
+            new_price
+        )));
+    }
+    if let Some(new_qty) = new_qty.filter(|q| *q <= executed_qty) {
+        return Err(AlphaSecError::invalid_parameter(format!(
+            "New quantity must be greater than the executed quantity {} of order {}, got {}",
+            executed_qty, order.order_id, new_qty
+        )));
+    }
+    Ok((new_price.unwrap_or(price), new_qty.unwrap_or(orig_qty)))
+}
+
+/// IDs of the active orders in `market_id`. Re-checks the market locally so a server
+/// that ignores the `marketId` filter cannot widen a market-scoped cancel.
+fn market_order_ids<'a>(orders: &'a [Order], market_id: &str) -> Vec<&'a str> {
+    orders
+        .iter()
+        .filter(|o| o.market_id == market_id && o.is_active())
+        .map(|o| o.order_id.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(order_id: &str, market_id: &str, status: &str) -> Order {
+        serde_json::from_value(order_json(order_id, market_id, status)).unwrap()
+    }
+
+    fn order_json(order_id: &str, market_id: &str, status: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": 1,
+            "orderId": order_id,
+            "accountAddress": "0xabc",
+            "marketId": market_id,
+            "side": "BUY",
+            "orderType": "LIMIT",
+            "price": "1",
+            "origQty": "1",
+            "origQuoteOrderQty": "0",
+            "isTrigger": false,
+            "isTriggered": false,
+            "triggerPrice": "0",
+            "status": status,
+            "contingencyType": "NONE",
+            "otoLegType": "NONE",
+            "txHash": "0x",
+            "createdAt": 1,
+            "updatedAt": 1,
+            "executedQty": "0",
+            "executedQuoteQty": "0"
+        })
+    }
+
+    #[test]
+    fn market_order_ids_keeps_only_active_orders_of_the_market() {
+        let orders = vec![
+            order("a", "1_2", "NEW"),
+            order("b", "3_2", "NEW"),
+            order("c", "1_2", "PARTIALLY_FILLED"),
+            order("d", "1_2", "FILLED"),
+            // "1_20" shares a prefix with "1_2" and must not match.
+            order("e", "1_20", "NEW"),
+        ];
+        assert_eq!(market_order_ids(&orders, "1_2"), vec!["a", "c"]);
+        assert!(market_order_ids(&orders, "9_9").is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancel_all_by_market_refetches_until_the_market_is_clear() {
+        use crate::api::client::tests::{json_response, mock_server_fn};
+        use ethers::types::transaction::eip2718::TypedTransaction;
+
+        // 150 open orders, every third one in 3_2. The server lists at most 100 per
+        // request, ignores the marketId filter, and drops an order once it is canceled.
+        let open: Vec<serde_json::Value> = (0..150u64)
+            .map(|i| {
+                let market = if i % 3 == 0 { "3_2" } else { "5_2" };
+                let mut order = order_json(&format!("o-{}", i), market, "NEW");
+                order["id"] = i.into();
+                order
+            })
+            .collect();
+        let open = Arc::new(std::sync::Mutex::new(open));
+        let canceled = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = canceled.clone();
+        let (url, requests) = mock_server_fn(Duration::ZERO, move |_, head, body| {
+            if head.starts_with("GET /api/v1/order/open?") {
+                let page: Vec<_> = open.lock().unwrap().iter().take(100).cloned().collect();
+                return json_response(
+                    &serde_json::json!({ "code": 200, "result": page }).to_string(),
+                );
+            }
+            let body: serde_json::Value = serde_json::from_str(body).unwrap();
+            let raw = hex::decode(body["tx"].as_str().unwrap().trim_start_matches("0x")).unwrap();
+            let (tx, _) =
+                TypedTransaction::decode_signed(&ethers::core::utils::rlp::Rlp::new(&raw)).unwrap();
+            let data = tx.data().unwrap();
+            assert_eq!(data[0], 0x22, "{}", head);
+            let cancel: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
+            let order_id = cancel["orderId"].as_str().unwrap().to_string();
+            recorded.lock().unwrap().push(order_id.clone());
+            if order_id == "o-10" {
+                // Stays open, and must not be retried in a later round
+                json_response(r#"{"code":400,"errMsg":"order already filled"}"#)
+            } else {
+                open.lock()
+                    .unwrap()
+                    .retain(|o| o["orderId"] != order_id.as_str());
+                json_response(r#"{"code":200,"result":"0xhash"}"#)
+            }
+        })
+        .await;
+        let mut agent = signing_agent_at(&url);
+        agent.api.set_token_metadata(kaia_usdt_metadata());
+
+        let results = agent
+            .cancel_all_by_market("KAIA/USDT", Some(1_700_000_000_000))
+            .await
+            .unwrap();
+
+        let expected: Vec<String> = (0..150)
+            .filter(|i| i % 3 != 0)
+            .map(|i| format!("o-{}", i))
+            .collect();
+        let ids: Vec<String> = results.iter().map(|(id, _)| id.clone()).collect();
+        assert_eq!(ids, expected);
+        assert_eq!(*canceled.lock().unwrap(), expected);
+        // The failed cancel is reported in place and the later ones still went out.
+        for (id, result) in &results {
+            if id == "o-10" {
+                assert!(result.is_err(), "{:?}", result);
+            } else {
+                assert_eq!(result.as_deref().unwrap(), "0xhash", "{}", id);
+            }
+        }
+        // 67 cancels from the first listing, 33 from the second, and a third listing
+        // holding only 3_2 orders and the failed one.
+        let fetches = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|head| head.starts_with("GET "))
+            .count();
+        assert_eq!(fetches, 3);
+    }
+
+    const NOW: u64 = 1_700_000_000_000;
+    const HOUR: u64 = 60 * 60 * 1000;
+
+    fn is_invalid_parameter(r: Result<()>) -> bool {
+        matches!(r, Err(AlphaSecError::InvalidParameter(_)))
+    }
+
+    #[test]
+    fn session_window_accepts_current_timestamp_with_future_expiry() {
+        assert!(validate_session_window(NOW, NOW + HOUR, NOW).is_ok());
+        // Small clock skew either way is tolerated.
+        let skew = SESSION_TIMESTAMP_TOLERANCE_MS;
+        assert!(validate_session_window(NOW - skew, NOW + HOUR, NOW).is_ok());
+        assert!(validate_session_window(NOW + skew, NOW + HOUR, NOW).is_ok());
+    }
+
+    #[test]
+    fn session_window_rejects_expiry_not_after_timestamp() {
+        assert!(is_invalid_parameter(validate_session_window(NOW, NOW, NOW)));
+        assert!(is_invalid_parameter(validate_session_window(
+            NOW,
+            NOW - HOUR,
+            NOW
+        )));
+    }
+
+    #[test]
+    fn session_window_rejects_stale_and_far_future_timestamps() {
+        let skew = SESSION_TIMESTAMP_TOLERANCE_MS + 1;
+        // A stale timestamp is rejected even if its expiry is still ahead.
+        assert!(is_invalid_parameter(validate_session_window(
+            NOW - skew,
+            NOW + HOUR,
+            NOW
+        )));
+        assert!(is_invalid_parameter(validate_session_window(
+            NOW + skew,
+            NOW + 2 * HOUR,
+            NOW
+        )));
+        assert!(is_invalid_parameter(validate_session_window(
+            0,
+            NOW + HOUR,
+            NOW
+        )));
+    }
+
+    #[test]
+    fn order_inputs_require_positive_quantity_and_limit_price() {
+        let one = Decimal::ONE;
+        let zero = Decimal::ZERO;
+        let limit = OrderType::Limit;
+        let market = OrderType::Market;
+
+        assert!(validate_order_inputs(one, one, limit, OrderMode::Base).is_ok());
+        // Market orders carry no meaningful price
+        assert!(validate_order_inputs(zero, one, market, OrderMode::Base).is_ok());
+        assert!(validate_order_inputs(zero, one, market, OrderMode::Quote).is_ok());
+
+        let err = validate_order_inputs(zero, one, limit, OrderMode::Base).unwrap_err();
+        assert!(err.to_string().contains("Limit order price"), "{err}");
+        assert!(is_invalid_parameter(validate_order_inputs(
+            -one,
+            one,
+            limit,
+            OrderMode::Base
+        )));
+
+        let err = validate_order_inputs(one, zero, limit, OrderMode::Base).unwrap_err();
+        assert!(err.to_string().contains("Order quantity"), "{err}");
+        let err = validate_order_inputs(zero, zero, market, OrderMode::Quote).unwrap_err();
+        assert!(err.to_string().contains("Quote-mode"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn order_rejects_zero_amounts_before_any_request() {
+        // The offline agent has no metadata, so reaching the network would fail differently
+        let agent = offline_agent();
+        for (price, quantity) in [(Decimal::ZERO, Decimal::ONE), (Decimal::ONE, Decimal::ZERO)] {
+            let err = agent
+                .order(
+                    "KAIA/USDT",
+                    OrderSide::Buy,
+                    price,
+                    quantity,
+                    OrderType::Limit,
+                    OrderMode::Base,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AlphaSecError::InvalidParameter(_)), "{err}");
+        }
+    }
+
+    #[test]
+    fn tpsl_must_sit_on_the_profit_and_loss_sides_of_the_price() {
+        let price = Decimal::ONE;
+        let (above, below) = (Decimal::new(12, 1), Decimal::new(8, 1));
+        let limit = OrderType::Limit;
+
+        // Buys take profit above the price and stop out below it, sells the other way
+        assert!(validate_tpsl(
+            OrderSide::Buy,
+            price,
+            limit,
+            Some(above),
+            Some(below),
+            Some(below)
+        )
+        .is_ok());
+        assert!(validate_tpsl(
+            OrderSide::Sell,
+            price,
+            limit,
+            Some(below),
+            Some(above),
+            Some(above)
+        )
+        .is_ok());
+
+        let message = |result: Result<()>| match result {
+            Err(AlphaSecError::InvalidParameter(message)) => message,
+            other => panic!("expected InvalidParameter, got {:?}", other),
+        };
+        let inverted_buy = message(validate_tpsl(
+            OrderSide::Buy,
+            price,
+            limit,
+            Some(below),
+            Some(above),
+            None,
+        ));
+        assert_eq!(
+            inverted_buy,
+            "Take profit limit 0.8 must be above the order price 1 for a buy order"
+        );
+        let inverted_buy_stop = message(validate_tpsl(
+            OrderSide::Buy,
+            price,
+            limit,
+            None,
+            Some(above),
+            None,
+        ));
+        assert!(
+            inverted_buy_stop.starts_with("Stop loss trigger 1.2 must be below"),
+            "{}",
+            inverted_buy_stop
+        );
+        let inverted_sell = message(validate_tpsl(
+            OrderSide::Sell,
+            price,
+            limit,
+            Some(above),
+            None,
+            None,
+        ));
+        assert!(
+            inverted_sell.contains("must be below the order price 1 for a sell order"),
+            "{}",
+            inverted_sell
+        );
+        let inverted_sell_stop = message(validate_tpsl(
+            OrderSide::Sell,
+            price,
+            limit,
+            None,
+            Some(above),
+            Some(below),
+        ));
+        assert!(
+            inverted_sell_stop.starts_with("Stop loss limit 0.8 must be above"),
+            "{}",
+            inverted_sell_stop
+        );
+        // A TP/SL at the price itself is on neither side
+        assert!(validate_tpsl(OrderSide::Buy, price, limit, Some(price), None, None).is_err());
+
+        // Market orders have no price to compare with, only positivity is checked
+        let market = OrderType::Market;
+        assert!(validate_tpsl(
+            OrderSide::Buy,
+            Decimal::ZERO,
+            market,
+            Some(below),
+            Some(above),
+            None
+        )
+        .is_ok());
+        assert!(validate_tpsl(
+            OrderSide::Buy,
+            Decimal::ZERO,
+            market,
+            Some(Decimal::ZERO),
+            None,
+            None
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn order_rejects_inverted_tpsl_before_any_request() {
+        let agent = offline_agent();
+        let err = agent
+            .order(
+                "KAIA/USDT",
+                OrderSide::Sell,
+                Decimal::ONE,
+                Decimal::ONE,
+                OrderType::Limit,
+                OrderMode::Base,
+                None,
+                Some(Decimal::new(9, 1)),
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlphaSecError::InvalidParameter(_)), "{err}");
+        assert!(err.to_string().contains("Stop loss trigger 0.9"), "{err}");
+    }
+
+    /// Serve `statuses` in order from a fake `get_order_by_id`; `None` means not found.
+    fn status_feed(
+        statuses: &[Option<&str>],
+    ) -> impl FnMut() -> std::future::Ready<Result<Option<Order>>> {
+        let mut feed: std::collections::VecDeque<Option<Order>> = statuses
+            .iter()
+            .map(|s| s.map(|status| order("x", "1_2", status)))
+            .collect();
+        move || {
+            let next = if feed.len() > 1 {
+                feed.pop_front().unwrap()
+            } else {
+                feed.front().cloned().unwrap()
+            };
+            std::future::ready(Ok(next))
+        }
+    }
+
+    const TICK: Duration = Duration::from_millis(1);
+
+    #[tokio::test]
+    async fn cancel_wins_when_the_order_ends_canceled() {
+        let fetch = status_feed(&[None, Some("NEW"), Some("CANCELED")]);
+        let outcome = poll_until_terminal(fetch, Duration::from_secs(1), TICK)
+            .await
+            .unwrap();
+        assert_eq!(outcome.order.status, "CANCELED");
+        assert!(!outcome.too_late);
+    }
+
+    #[tokio::test]
+    async fn fill_wins_returns_the_filled_order_flagged_too_late() {
+        let fetch = status_feed(&[Some("PARTIALLY_FILLED"), Some("FILLED")]);
+        let outcome = poll_until_terminal(fetch, Duration::from_secs(1), TICK)
+            .await
+            .unwrap();
+        assert_eq!(outcome.order.status, "FILLED");
+        assert!(outcome.too_late);
+    }
+
+    #[tokio::test]
+    async fn order_still_active_at_timeout_is_an_error_naming_its_status() {
+        let fetch = status_feed(&[Some("NEW")]);
+        let err = poll_until_terminal(fetch, Duration::from_millis(20), TICK)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("NEW"), "got: {}", err);
+    }
+
+    /// Agent with no token metadata and an unreachable API, for calls that fail locally.
+    fn offline_agent() -> Agent {
+        agent_at("http://127.0.0.1:1")
+    }
+
+    /// Agent with no token metadata or signing key whose REST API is `base_url`.
+    fn agent_at(base_url: &str) -> Agent {
+        let config = Config::new(
+            base_url,
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+        Agent {
+            api: ApiClient::new(&config, None).unwrap(),
+            signer: AlphaSecSigner::new(config.clone()),
+            #[cfg(feature = "websocket")]
+            ws: None,
+            #[cfg(feature = "websocket")]
+            trade_ws: None,
+            perp_client: PerpApiClient::new(&config).unwrap(),
+            market_cache: MarketCache::new(),
+            spot_markets: Arc::default(),
+            order_throttle: None,
+            fee_tiers: Arc::default(),
+            session_wallets: Arc::default(),
+            capabilities: Arc::default(),
+            capabilities_failure: Arc::default(),
+            config,
+        }
+    }
+
+    /// Config for `base_url` carrying the well-known test signing key.
+    fn signing_config(base_url: &str) -> Config {
+        Config::new(
+            base_url,
+            "kairos",
+            "0x0000000000000000000000000000000000000000",
+            Some("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"),
+            None,
+            false,
+            None,
+        )
+        .unwrap()
+    }
+
+    /// [`agent_at`] signing with `config`'s keys.
+    fn agent_with_signer(base_url: &str, config: &Config) -> Agent {
+        let mut agent = agent_at(base_url);
+        agent.signer = AlphaSecSigner::new(config.clone());
+        agent.api = ApiClient::new(config, Some(agent.signer.clone())).unwrap();
+        agent
+    }
+
+    /// [`agent_at`] with the test signing key of [`signing_config`].
+    fn signing_agent_at(base_url: &str) -> Agent {
+        agent_with_signer(base_url, &signing_config(base_url))
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn order_calls_are_counted_by_outcome() {
+        use metrics::{
+            Counter, Gauge, Histogram, Key, KeyName, Label, Metadata, Recorder, SharedString, Unit,
+        };
+        use std::collections::HashMap;
+        use std::sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        };
+
+        /// Recorder that only keeps counters.
+        #[derive(Default)]
+        struct Counters(Mutex<HashMap<Key, Arc<AtomicU64>>>);
+
+        impl Recorder for Counters {
+            fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+                let mut counters = self.0.lock().unwrap();
+                Counter::from_arc(counters.entry(key.clone()).or_default().clone())
+            }
+            fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+                Gauge::noop()
+            }
+            fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+                Histogram::noop()
+            }
+        }
+
+        let recorder = Counters::default();
+        let agent = offline_agent();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        // The local recorder is per-thread, so drive the calls on this thread.
+        metrics::with_local_recorder(&recorder, || {
+            runtime.block_on(async {
+                for _ in 0..2 {
+                    let result = agent
+                        .order(
+                            "KAIA/USDT",
+                            OrderSide::Buy,
+                            Decimal::ONE,
+                            Decimal::ONE,
+                            OrderType::Limit,
+                            OrderMode::Base,
+                            None,
+                            None,
+                            None,
+                            None,
+                        )
+                        .await;
+                    assert!(result.is_err(), "no token metadata, order must fail");
+                }
+            })
+        });
+
+        let count = |outcome: &'static str| {
+            let key = Key::from_parts(
+                telemetry::ORDER_CALLS_TOTAL,
+                vec![Label::new("op", "order"), Label::new("outcome", outcome)],
+            );
+            recorder
+                .0
+                .lock()
+                .unwrap()
+                .get(&key)
+                .map_or(0, |c| c.load(Ordering::Relaxed))
+        };
+        assert_eq!(count("failure"), 2);
+        assert_eq!(count("success"), 0);
+    }
+
+    /// KAIA (token 5) / USDT (token 2) metadata.
+    fn kaia_usdt_metadata() -> TokenMetadata {
+        let token = |token_id: &str, symbol: &str| Token {
+            token_id: token_id.to_string(),
+            symbol: symbol.to_string(),
+            l2_symbol: symbol.to_string(),
+            l1_address: format!("0x{:0>40}", token_id),
+            decimals: 18,
+            is_active: true,
+        };
+        TokenMetadata::from_tokens(&[token("5", "KAIA"), token("2", "USDT")])
+    }
+
+    #[test]
+    fn market_symbol_helpers_round_trip_through_token_metadata() {
+        let mut agent = offline_agent();
+        assert!(agent.token_metadata().is_none());
+        assert!(matches!(
+            agent.symbol_to_market_id("KAIA/USDT"),
+            Err(AlphaSecError::Config(_))
+        ));
+
+        agent.api.set_token_metadata(kaia_usdt_metadata());
+
+        assert_eq!(agent.symbol_to_market_id("KAIA/USDT").unwrap(), "5_2");
+        assert_eq!(agent.market_id_to_symbol("5_2").unwrap(), "KAIA/USDT");
+        let id = agent.symbol_to_market_id("USDT/KAIA").unwrap();
+        assert_eq!(agent.market_id_to_symbol(&id).unwrap(), "USDT/KAIA");
+        assert!(matches!(
+            agent.market_id_to_symbol("9_2"),
+            Err(AlphaSecError::NotFound(_))
+        ));
+        assert_eq!(agent.token_metadata().unwrap().token_id_symbol_map.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_market_returns_rules_and_order_is_checked_against_them() {
+        use crate::api::client::tests::{json_response, mock_server};
+
+        let body = serde_json::json!({
+            "code": 200,
+            "result": [{
+                "marketId": "5_2",
+                "baseTokenId": "5",
+                "quoteTokenId": "2",
+                "ticker": "KAIA/USDT",
+                "description": "KAIA/USDT",
+                "exchange": "alphasec",
+                "type": "spot",
+                "listed": true,
+                "takerFee": "0.002",
+                "makerFee": "0.001",
+                "minQty": "1",
+                "minNotional": "5",
+                "tickSize": "0.001",
+                "stepSize": "1"
+            }]
+        });
+        let url = mock_server(Duration::ZERO, json_response(&body.to_string())).await;
+        let mut agent = agent_at(&url);
+        agent.api.set_token_metadata(kaia_usdt_metadata());
+
+        let market = agent.get_market("KAIA/USDT").await.unwrap();
+        assert_eq!(market.tick_size.as_deref(), Some("0.001"));
+        assert_eq!(market.min_notional.as_deref(), Some("5"));
+        assert!(matches!(
+            agent.get_market("USDT/KAIA").await,
+            Err(AlphaSecError::NotFound(_))
+        ));
+
+        // The price is rounded onto the tick, but the quantity breaks the step size; this
+        // is rejected before signing, so the missing key never comes into play.
+        let err = agent
+            .order(
+                "KAIA/USDT",
+                OrderSide::Buy,
+                "0.1234".parse().unwrap(),
+                "100.5".parse().unwrap(),
+                OrderType::Limit,
+                OrderMode::Base,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("step size 1"), "got: {}", err);
+    }
+
+    #[tokio::test]
+    async fn get_balances_reports_each_address_separately() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        let empty = r#"{"code":200,"result":{"balances":[],"blockNumber":7}}"#;
+        let funded = r#"{"code":200,"result":{"balances":[{"tokenId":"2","locked":"1","unlocked":"5"}],"blockNumber":7}}"#;
+        let url = mock_server_routed(vec![
+            ("address=0xempty", json_response(empty)),
+            ("address=0xfunded", json_response(funded)),
+        ])
+        .await;
+        let agent = agent_at(&url);
+
+        let balances = agent
+            .get_balances(&["0xfunded", "0xempty", "0xmissing", "0xfunded"])
+            .await;
+        assert_eq!(balances.len(), 3);
+        assert!(balances["0xempty"].as_ref().unwrap().balances.is_empty());
+        let funded = balances["0xfunded"].as_ref().unwrap();
+        assert_eq!(funded.balances[0].token_id, "2");
+        assert_eq!(funded.balances[0].unlocked.as_deref(), Some("5"));
+        // A failing address does not fail the batch
+        assert!(balances["0xmissing"].is_err());
+    }
+
+    #[tokio::test]
+    async fn place_oco_submits_the_pair_and_returns_both_leg_ids() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        let body = r#"{"code":200,"result":{"limitOrderId":"0xlimit","stopOrderId":"0xstop"}}"#;
+        let url = mock_server_routed(vec![("POST /api/v1/order/oco ", json_response(body))]).await;
+        let mut agent = signing_agent_at(&url);
+        agent.api.set_token_metadata(kaia_usdt_metadata());
+
+        let legs = agent
+            .place_oco(
+                "KAIA/USDT",
+                OrderSide::Sell,
+                Decimal::from(100),
+                "0.2".parse().unwrap(),
+                "0.1".parse().unwrap(),
+                "0.09".parse().unwrap(),
+                OrderMode::Base,
+            )
+            .await
+            .unwrap();
+        assert_eq!(legs, ("0xlimit".to_string(), "0xstop".to_string()));
+
+        // A sell whose limit leg sits below the trigger is rejected before any request
+        let err = agent
+            .place_oco(
+                "KAIA/USDT",
+                OrderSide::Sell,
+                Decimal::from(100),
+                "0.05".parse().unwrap(),
+                "0.1".parse().unwrap(),
+                "0.09".parse().unwrap(),
+                OrderMode::Base,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlphaSecError::InvalidParameter(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn place_oco_checks_inputs_and_takes_a_throttle_slot_per_leg() {
+        let mut agent = offline_agent();
+        agent.api.set_token_metadata(kaia_usdt_metadata());
+        let oco = |agent: &Agent, qty: Decimal, stop_limit: &str| {
+            let stop_limit = stop_limit.parse().unwrap();
+            let agent = agent.clone();
+            async move {
+                agent
+                    .place_oco(
+                        "KAIA/USDT",
+                        OrderSide::Sell,
+                        qty,
+                        "0.2".parse().unwrap(),
+                        "0.1".parse().unwrap(),
+                        stop_limit,
+                        OrderMode::Base,
+                    )
+                    .await
+            }
+        };
+        for (qty, stop_limit) in [(Decimal::ZERO, "0.09"), (Decimal::ONE, "0")] {
+            let err = oco(&agent, qty, stop_limit).await.unwrap_err();
+            assert!(matches!(err, AlphaSecError::InvalidParameter(_)), "{err}");
+        }
+
+        agent.order_throttle = Some(super::super::order_throttle::OrderThrottle::new(
+            crate::signer::OrderThrottleConfig {
+                max_per_sec: 3,
+                max_in_flight: 0,
+            },
+        ));
+        // The first pair takes two of the three slots (and fails later, the agent being
+        // offline); the second pair only finds one
+        assert!(!oco(&agent, Decimal::ONE, "0.09")
+            .await
+            .unwrap_err()
+            .is_rate_limited());
+        assert!(oco(&agent, Decimal::ONE, "0.09")
+            .await
+            .unwrap_err()
+            .is_rate_limited());
+        // The rejected pair left the last slot free
+        let throttle = agent.order_throttle.as_ref().unwrap();
+        let now = std::time::Instant::now();
+        let _last = throttle.try_acquire(now, 1).unwrap();
+        assert!(throttle.try_acquire(now, 1).is_err());
+    }
+
+    #[test]
+    fn oco_leg_ids_need_both_named_legs() {
+        let legs = serde_json::json!({"limitOrderId": "0xa", "stopOrderId": "0xb"});
+        assert_eq!(
+            oco_leg_ids(&legs).unwrap(),
+            ("0xa".to_string(), "0xb".to_string())
+        );
+        assert!(oco_leg_ids(&serde_json::json!({"limitOrderId": "0xa"})).is_err());
+        assert!(oco_leg_ids(&serde_json::json!(["0xa", "0xb"])).is_err());
+        // A JSON text of the legs is not parsed back
+        assert!(oco_leg_ids(&serde_json::json!(legs.to_string())).is_err());
+        assert!(oco_leg_ids(&serde_json::Value::Null).is_err());
+    }
+
+    fn ticker_body() -> String {
+        serde_json::json!({"code": 200, "result": [{
+            "marketId": "5_2",
+            "baseTokenId": "5",
+            "quoteTokenId": "2",
+            "price": "0.15",
+            "open24h": "0.14",
+            "high24h": "0.16",
+            "low24h": "0.13",
+            "volume24h": "1000",
+            "quoteVolume24h": "150"
+        }]})
+        .to_string()
+    }
+
+    fn tokens_body() -> String {
+        let token = |id: &str, symbol: &str| {
+            serde_json::json!({
+                "tokenId": id,
+                "l1Symbol": symbol,
+                "l2Symbol": symbol,
+                "l1Address": format!("0x{:0>40}", id),
+                "l1Decimal": 18,
+                "isActive": true
+            })
+        };
+        serde_json::json!({"code": 200, "result": [token("5", "KAIA"), token("2", "USDT")]})
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn new_degrades_when_the_metadata_endpoint_keeps_failing() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        let url = mock_server_routed(vec![("/market/ticker", json_response(&ticker_body()))]).await;
+        let config = Config::new(
+            &url,
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+
+        let agent = Agent::new(config)
+            .await
+            .expect("construction must not fail");
+        assert!(agent.token_metadata().is_none());
+        // Raw IDs need no metadata
+        assert_eq!(agent.get_ticker("5_2").await.unwrap().price, "0.15");
+        // Symbols fail with a clear error instead
+        let err = agent.get_ticker("KAIA/USDT").await.unwrap_err();
+        assert!(
+            matches!(&err, AlphaSecError::Config(m) if m.contains("Token metadata unavailable")),
+            "{err}"
+        );
+        assert!(matches!(
+            agent.symbol_to_market_id("KAIA/USDT"),
+            Err(AlphaSecError::Config(m)) if m.contains("load_metadata")
+        ));
+    }
+
+    #[tokio::test]
+    async fn new_lazy_fetches_metadata_on_first_symbol_use() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        let url = mock_server_routed(vec![
+            ("/market/tokens", json_response(&tokens_body())),
+            (
+                "/market/ticker?marketId=5_2 ",
+                json_response(&ticker_body()),
+            ),
+        ])
+        .await;
+        let config = Config::new(
+            &url,
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+
+        let agent = Agent::new_lazy(config).unwrap();
+        assert!(agent.token_metadata().is_none());
+        assert_eq!(
+            agent.get_ticker("KAIA/USDT").await.unwrap().market_id,
+            "5_2"
+        );
+        assert_eq!(agent.symbol_to_market_id("KAIA/USDT").unwrap(), "5_2");
+        assert_eq!(
+            agent
+                .load_metadata()
+                .await
+                .unwrap()
+                .token_id_symbol_map
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn create_session_returns_the_server_ack() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        let body = r#"{"code":200,"result":{"sessionId":"bot-1","sessionAddress":"0xabc","expiry":1900000000000,"txHash":"0xfeed"}}"#;
+        let url =
+            mock_server_routed(vec![("POST /api/v1/wallet/session ", json_response(body))]).await;
+        let agent = signing_agent_at(&url);
+
+        let session_wallet: LocalWallet =
+            "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d"
+                .parse()
+                .unwrap();
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let ack = agent
+            .create_session("bot-1", Some(session_wallet), now, now + 3_600_000, b"")
+            .await
+            .unwrap();
+        assert_eq!(
+            ack,
+            SessionAck {
+                session_id: "bot-1".to_string(),
+                session_address: "0xabc".to_string(),
+                expiry: 1_900_000_000_000,
+                tx_hash: Some("0xfeed".to_string()),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn agents_built_from_one_http_client_share_it() {
+        use crate::api::client::tests::{header_value, json_response, mock_server_recorded};
+
+        let (url, heads) = mock_server_recorded(
+            std::time::Duration::ZERO,
+            vec![json_response(r#"{"code":200,"result":[]}"#)],
+        )
+        .await;
+        let shared = reqwest::Client::builder()
+            .user_agent("shared-pool/1.0")
+            .build()
+            .unwrap();
+        let config = |l1_address: &str| {
+            Config::new(&url, "kairos", l1_address, None, None, false, None)
+                .unwrap()
+                .with_http_client(shared.clone())
+        };
+        let first = Agent::new_lazy(config("0x1234567890123456789012345678901234567890")).unwrap();
+        let second = Agent::new_lazy(config("0x0000000000000000000000000000000000000001")).unwrap();
+
+        first.health_check().await.unwrap();
+        second.health_check().await.unwrap();
+
+        let heads = heads.lock().unwrap();
+        assert_eq!(heads.len(), 2);
+        for head in heads.iter() {
+            assert_eq!(header_value(head, "user-agent"), Some("shared-pool/1.0"));
+        }
+    }
+
+    #[tokio::test]
+    async fn trade_stats_summarize_the_window() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        let body = r#"{"code":200,"result":[
+            {"tradeId":"t1","marketId":"5_2","price":"0.10","quantity":"300","buyOrderId":"0xb","sellOrderId":"0xs","createdAt":1000,"isBuyerMaker":false},
+            {"tradeId":"t2","marketId":"5_2","price":"0.12","quantity":"100","buyOrderId":"0xb","sellOrderId":"0xs","createdAt":2000,"isBuyerMaker":true}
+        ]}"#;
+        let url = mock_server_routed(vec![("/market/trades?", json_response(body))]).await;
+        let agent = agent_at(&url);
+
+        let stats = agent.trade_stats("5_2", 0, 5000).await.unwrap();
+        // (0.10*300 + 0.12*100) / 400 = 42 / 400
+        assert_eq!(stats.vwap, Some("0.105".parse().unwrap()));
+        assert_eq!(stats.total_base_volume, Decimal::from(400));
+        assert_eq!(stats.total_quote_volume, Decimal::from(42));
+        assert_eq!(stats.trade_count, 2);
+        assert_eq!(stats.high, Some("0.12".parse().unwrap()));
+        assert_eq!(stats.low, Some("0.10".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn auto_session_renewal_renews_before_expiry() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        let expiry = chrono::Utc::now().timestamp_millis() as u64 + 2_000;
+        let sessions = format!(
+            r#"{{"code":200,"result":[{{"name":"bot","sessionAddress":"0xs","ownerAddress":"0xo","expiry":{},"applied":true}}]}}"#,
+            expiry
+        );
+        let url = mock_server_routed(vec![
+            ("GET /api/v1/wallet/session?", json_response(&sessions)),
+            (
+                "POST /api/v1/wallet/session/update ",
+                json_response(r#"{"code":200,"result":"0xrenewed"}"#),
+            ),
+        ])
+        .await;
+        let config = Config::new(
+            &url,
+            "kairos",
+            "0x0000000000000000000000000000000000000000",
+            Some("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"),
+            Some("59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d"),
+            false,
+            None,
+        )
+        .unwrap();
+        let mut agent = agent_with_signer(&url, &config);
+        agent.config = config;
+
+        let lead_time = Duration::from_millis(1_500);
+        let mut renewal = agent
+            .enable_auto_session_renewal("bot", lead_time)
+            .await
+            .unwrap();
+        let ack = tokio::time::timeout(Duration::from_secs(5), renewal.next_renewal())
+            .await
+            .expect("a renewal must be attempted")
+            .unwrap()
+            .unwrap();
+        let renewed_at = chrono::Utc::now().timestamp_millis() as u64;
+        assert!(renewed_at < expiry, "renewed after the session lapsed");
+        assert!(renewed_at + 100 >= expiry - 1_500, "renewed too early");
+        assert_eq!(ack.tx_hash.as_deref(), Some("0xrenewed"));
+        assert!(ack.expiry > expiry);
+        assert!(renewal.is_running());
+        renewal.stop();
+
+        assert!(matches!(
+            agent
+                .enable_auto_session_renewal("missing", lead_time)
+                .await,
+            Err(AlphaSecError::NotFound(_))
+        ));
+        assert!(matches!(
+            agent
+                .enable_auto_session_renewal("bot", Duration::ZERO)
+                .await,
+            Err(AlphaSecError::InvalidParameter(_))
+        ));
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn all_markets_ticker_channel_skips_market_resolution() {
+        // No metadata and an unreachable API: any market lookup would fail
+        let agent = offline_agent();
+        assert_eq!(
+            agent.resolve_channel("ticker@all").await.unwrap(),
+            "ticker@all"
+        );
+        assert_eq!(
+            agent.resolve_channel("ticker@*").await.unwrap(),
+            "ticker@all"
+        );
+        for channel in ["trade@all", "depth@*", "kline@all@1m"] {
+            assert!(
+                matches!(
+                    agent.resolve_channel(channel).await,
+                    Err(AlphaSecError::InvalidParameter(_))
+                ),
+                "{channel}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn token_decimal_override_corrects_fetched_metadata() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        // The backend reports 18 decimals for USDT, which actually has 6
+        let url = mock_server_routed(vec![("/market/tokens", json_response(&tokens_body()))]).await;
+        let config = Config::new(
+            &url,
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap()
+        .with_token_decimals(HashMap::from([("USDT".to_string(), 6)]));
+        let agent = Agent::new_lazy(config).unwrap();
+
+        let metadata = agent.load_metadata().await.unwrap();
+        assert_eq!(metadata.token_id_decimal_map["2"], "6");
+        assert_eq!(metadata.token_id_decimal_map["5"], "18");
+        let balance = Balance {
+            token_id: "2".to_string(),
+            locked: None,
+            unlocked: Some("5000000".to_string()),
+        };
+        assert_eq!(
+            balance.with_symbol(metadata).unwrap().unlocked,
+            Decimal::from(5)
+        );
+    }
+
+    #[tokio::test]
+    async fn cancel_re_signs_after_a_nonce_conflict() {
+        use crate::api::client::tests::{json_response, mock_server_recorded};
+
+        let conflict = json_response(r#"{"code":400,"errMsg":"duplicate nonce"}"#);
+        let ok = json_response(r#"{"code":200,"result":"0xcancel"}"#);
+        for (auto_retry, submissions) in [(true, 2), (false, 1)] {
+            let (url, heads) = mock_server_recorded(
+                std::time::Duration::ZERO,
+                vec![conflict.clone(), ok.clone()],
+            )
+            .await;
+            let config = signing_config(&url).with_auto_retry_nonce(auto_retry);
+            let mut agent = agent_with_signer(&url, &config);
+            agent.config = config;
+
+            let result = agent.cancel("0xorder", Some(1_700_000_000_000)).await;
+            if auto_retry {
+                assert_eq!(result.unwrap(), "0xcancel");
+            } else {
+                assert!(result.unwrap_err().is_nonce_conflict());
+            }
+            assert_eq!(heads.lock().unwrap().len(), submissions);
+        }
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn nonce_conflict_after_a_trade_ws_failure_is_not_re_signed() {
+        use crate::api::client::tests::{json_response, mock_server_recorded};
+        use futures_util::StreamExt;
+
+        // A Trade WebSocket that takes the request and never answers it
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            while ws.next().await.is_some() {}
+        });
+
+        // REST then sees the transaction the server may already hold
+        let conflict = json_response(r#"{"code":400,"errMsg":"duplicate nonce"}"#);
+        let ok = json_response(r#"{"code":200,"result":"0xsecond"}"#);
+        let (url, heads) =
+            mock_server_recorded(std::time::Duration::ZERO, vec![conflict, ok]).await;
+        let mut agent = signing_agent_at(&url);
+        agent.trade_ws = Some(
+            TradeWebSocket::new(&format!("ws://{}", ws_addr))
+                .with_request_timeout(Duration::from_millis(200)),
+        );
+        agent.connect_trade_ws().await.unwrap();
+
+        let err = agent
+            .cancel("0xorder", Some(1_700_000_000_000))
+            .await
+            .unwrap_err();
+        assert!(err.is_nonce_conflict(), "{err}");
+        assert_eq!(heads.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn orders_over_the_throttle_are_rejected_locally() {
+        let mut agent = offline_agent();
+        agent.order_throttle = Some(super::super::order_throttle::OrderThrottle::new(
+            crate::signer::OrderThrottleConfig {
+                max_per_sec: 3,
+                max_in_flight: 0,
+            },
+        ));
+        let mut outcomes = Vec::new();
+        for _ in 0..4 {
+            outcomes.push(
+                agent
+                    .order(
+                        "KAIA/USDT",
+                        OrderSide::Buy,
+                        Decimal::ONE,
+                        Decimal::ONE,
+                        OrderType::Limit,
+                        OrderMode::Base,
+                        None,
+                        None,
+                        None,
+                        None,
+                    )
+                    .await,
+            );
+        }
+        // The first three are admitted (and fail later, the agent being offline)
+        for outcome in &outcomes[..3] {
+            assert!(!outcome.as_ref().unwrap_err().is_rate_limited());
+        }
+        assert!(outcomes[3].as_ref().unwrap_err().is_rate_limited());
+
+        // Clones share the limit
+        let err = agent
+            .clone()
+            .stop_order(
+                "KAIA",
+                "USDT",
+                Decimal::ONE,
+                Decimal::ONE,
+                Decimal::ONE,
+                OrderSide::Buy,
+                OrderType::Limit,
+                OrderMode::Base,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.is_rate_limited());
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn user_events_are_authenticated_before_subscribing() {
+        use futures_util::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (frames_tx, mut frames_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let frame: serde_json::Value = serde_json::from_str(&text).unwrap();
+                let _ = frames_tx.send(frame);
+            }
+        });
+
+        let mut agent = agent_at(&format!("http://{}", addr));
+        agent.ws = Some(WsManager::new(WsConfig {
+            url: format!("ws://{}", addr),
+            ..WsConfig::default()
+        }));
+        agent.start().await.unwrap();
+
+        // A frame that cannot be built fails the call before anything is sent
+        let err = agent
+            .subscribe_user_events(Arc::new(|| Err(AlphaSecError::auth("no key"))))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlphaSecError::Auth(_)), "{err}");
+
+        let auth_frame = r#"{"method":"login","params":{"token":"t"}}"#;
+        agent
+            .subscribe_user_events(Arc::new(move || Ok(auth_frame.to_string())))
+            .await
+            .unwrap();
+
+        let auth = frames_rx.recv().await.unwrap();
+        let subscribe = frames_rx.recv().await.unwrap();
+        assert_eq!(
+            auth,
+            serde_json::from_str::<serde_json::Value>(auth_frame).unwrap()
+        );
+        assert_eq!(subscribe["method"], "subscribe");
+        assert_eq!(
+            subscribe["params"]["channels"][0],
+            format!("userEvent@{}", agent.l1_address())
+        );
+    }
+
+    #[tokio::test]
+    async fn malformed_market_symbols_fail_before_any_request() {
+        let agent = offline_agent();
+        let expect_format_error = |result: Result<String>| match result {
+            Err(AlphaSecError::InvalidParameter(msg)) => {
+                assert!(msg.contains("it has 2 '/' separators"), "{}", msg)
+            }
+            other => panic!("expected InvalidParameter, got {:?}", other),
+        };
+        expect_format_error(
+            agent
+                .order(
+                    "KAIA/USD/T",
+                    OrderSide::Buy,
+                    Decimal::ONE,
+                    Decimal::ONE,
+                    OrderType::Limit,
+                    OrderMode::Base,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await,
+        );
+        expect_format_error(agent.get_ticker("KAIA/USD/T").await.map(|_| String::new()));
+    }
+
+    #[tokio::test]
+    async fn estimate_order_cost_prefers_the_account_fee_tier() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        let markets = serde_json::json!({
+            "code": 200,
+            "result": [{
+                "marketId": "5_2",
+                "baseTokenId": "5",
+                "quoteTokenId": "2",
+                "ticker": "KAIA/USDT",
+                "description": "KAIA/USDT",
+                "exchange": "alphasec",
+                "type": "spot",
+                "listed": true,
+                "takerFee": "0.002",
+                "makerFee": "0.001"
+            }]
+        })
+        .to_string();
+        let fee_tier =
+            r#"{"code":200,"result":{"tier":"VIP 3","makerFeeBps":"1","takerFeeBps":"5"}}"#;
+        let estimate = |url: String| async move {
+            let mut agent = agent_at(&url);
+            agent.api.set_token_metadata(kaia_usdt_metadata());
+            agent
+                .estimate_order_cost(
+                    "KAIA/USDT",
+                    OrderSide::Buy,
+                    Decimal::ONE,
+                    Decimal::ONE_HUNDRED,
+                    OrderMode::Base,
+                )
+                .await
+                .unwrap()
+        };
+
+        let url = mock_server_routed(vec![
+            ("/wallet/fee", json_response(fee_tier)),
+            ("/market", json_response(&markets)),
+        ])
+        .await;
+        let tiered = estimate(url).await;
+        assert_eq!(tiered.fee_rate, Decimal::new(5, 4));
+        assert_eq!(tiered.fee, Decimal::new(5, 2));
+        assert_eq!(tiered.required, Decimal::new(10005, 2));
+
+        // Without a tier the market's static fee applies
+        let url = mock_server_routed(vec![("/market", json_response(&markets))]).await;
+        let fallback = estimate(url).await;
+        assert_eq!(fallback.fee_rate, Decimal::new(2, 3));
+        assert_eq!(fallback.required, Decimal::new(1002, 1));
+    }
+
+    #[tokio::test]
+    async fn failed_fee_tier_lookup_is_not_retried_during_the_backoff() {
+        use crate::api::client::tests::{json_response, mock_server_recorded};
+
+        let (url, heads) = mock_server_recorded(
+            std::time::Duration::ZERO,
+            vec![
+                "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string(),
+                json_response(
+                    r#"{"code":200,"result":{"tier":"VIP 1","makerFeeBps":"2","takerFeeBps":"5"}}"#,
+                ),
+            ],
+        )
+        .await;
+        let agent = agent_at(&url);
+
+        assert!(agent.get_account_fee_tier("0xabc").await.is_err());
+        let err = agent
+            .clone()
+            .get_account_fee_tier("0xABC")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("failed recently"), "{err}");
+        assert_eq!(heads.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn dropping_the_last_agent_clone_closes_the_websocket() {
+        use crate::websocket::{ConnectionObserver, ConnectionState};
+
+        struct States(std::sync::Mutex<Vec<ConnectionState>>);
+        impl ConnectionObserver for States {
+            fn on_state_change(&self, _old: ConnectionState, new: ConnectionState) {
+                self.0.lock().unwrap().push(new);
+            }
+        }
+        impl States {
+            fn last(&self) -> Option<ConnectionState> {
+                self.0.lock().unwrap().last().copied()
+            }
+
+            async fn wait_for(&self, expected: ConnectionState) {
+                for _ in 0..100 {
+                    if self.last() == Some(expected) {
+                        return;
+                    }
+                    sleep(Duration::from_millis(20)).await;
+                }
+                panic!(
+                    "expected {:?}, states were {:?}",
+                    expected,
+                    self.0.lock().unwrap()
+                );
+            }
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            while let Some(Ok(_)) = futures_util::StreamExt::next(&mut ws).await {}
+        });
+
+        let mut agent = offline_agent();
+        agent.ws = Some(WsManager::new(WsConfig {
+            url: format!("ws://{}", addr),
+            ..WsConfig::default()
+        }));
+        let states = Arc::new(States(std::sync::Mutex::default()));
+        agent.ws.as_ref().unwrap().set_observer(states.clone());
+        agent.start().await.unwrap();
+        states.wait_for(ConnectionState::Connected).await;
+
+        // Clones share the connection, so dropping one leaves it open
+        drop(agent.clone());
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(states.last(), Some(ConnectionState::Connected));
+
+        drop(agent);
+        states.wait_for(ConnectionState::Closed).await;
+    }
+
+    #[tokio::test]
+    async fn failed_open_orders_snapshot_unsubscribes_and_leaves_the_feed_usable() {
+        use crate::api::client::tests::mock_server_routed;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (frames_tx, mut frames_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = futures_util::StreamExt::next(&mut ws).await {
+                let frame: serde_json::Value = serde_json::from_str(&text).unwrap();
+                let _ = frames_tx.send(frame);
+            }
+        });
+
+        // No routes: the open orders snapshot fails with a 404
+        let url = mock_server_routed(vec![]).await;
+        let mut agent = agent_at(&url);
+        agent.ws = Some(WsManager::new(WsConfig {
+            url: format!("ws://{}", addr),
+            ..WsConfig::default()
+        }));
+        agent.start().await.unwrap();
+
+        assert!(agent.open_orders_stream("0xabc").await.is_err());
+        assert!(agent.message_receiver().await.is_some());
+        let subscribe = frames_rx.recv().await.unwrap();
+        assert_eq!(subscribe["method"], "subscribe");
+        let unsubscribe = frames_rx.recv().await.unwrap();
+        assert_eq!(unsubscribe["method"], "unsubscribe");
+    }
+
+    #[tokio::test]
+    async fn streams_share_the_websocket_feed() {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            // Answer the trade subscription with one trade
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if !text.contains("trade@5_2") {
+                    continue;
+                }
+                let trade = serde_json::json!({
+                    "method": "subscription",
+                    "params": {
+                        "channel": "trade@5_2",
+                        "result": [{
+                            "tradeId": "t1",
+                            "marketId": "5_2",
+                            "price": "1.5",
+                            "quantity": "10",
+                            "buyOrderId": "b",
+                            "sellOrderId": "s",
+                            "createdAt": 1,
+                            "isBuyerMaker": false
+                        }]
+                    }
+                });
+                ws.send(Message::Text(trade.to_string())).await.unwrap();
+            }
+        });
+
+        let mut agent = offline_agent();
+        agent.api.set_token_metadata(kaia_usdt_metadata());
+        agent.ws = Some(WsManager::new(WsConfig {
+            url: format!("ws://{}", addr),
+            ..WsConfig::default()
+        }));
+        agent.start().await.unwrap();
+
+        let mut messages = agent.message_stream().await.unwrap();
+        let mut trades = agent.trade_stream("KAIA/USDT").await.unwrap();
+        let timeout = Duration::from_secs(5);
+
+        let trade = tokio::time::timeout(timeout, trades.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(trade.trade_id, "t1");
+        loop {
+            let msg = tokio::time::timeout(timeout, messages.next())
+                .await
+                .unwrap()
+                .unwrap();
+            if matches!(msg, crate::types::WebSocketMessage::TradeMsg { .. }) {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn reduced_order_keeps_the_price_and_checks_the_remaining_quantity() {
+        let partly_filled = Order {
+            price: "0.15".to_string(),
+            orig_qty: "10".to_string(),
+            executed_qty: "4".to_string(),
+            ..order("o-1", "5_2", "PARTIALLY_FILLED")
+        };
+        let reduce = |by: &str| reduced_order(&partly_filled, by.parse().unwrap());
+
+        let (price, new_qty) = reduce("2.5").unwrap().unwrap();
+        assert_eq!(price, "0.15".parse().unwrap());
+        assert_eq!(new_qty, "7.5".parse().unwrap());
+        // Taking off everything that is left cancels instead
+        assert_eq!(reduce("6").unwrap(), None);
+        for bad in ["0", "-1", "6.01"] {
+            assert!(
+                matches!(reduce(bad), Err(AlphaSecError::InvalidParameter(_))),
+                "{}",
+                bad
+            );
+        }
+        let filled = order("o-2", "5_2", "FILLED");
+        assert!(matches!(
+            reduced_order(&filled, Decimal::ONE),
+            Err(AlphaSecError::InvalidParameter(_))
+        ));
+
+        // The modify sent for the reduction carries the unchanged price and new quantity
+        let data = offline_agent()
+            .signer
+            .create_modify_data("o-1", price, new_qty, OrderMode::Base as u32)
+            .unwrap();
+        let wire: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
+        assert_eq!(wire["orderId"], "o-1");
+        assert_eq!(wire["newPrice"], "0.15");
+        assert_eq!(wire["newQty"], "7.5");
+    }
+
+    #[tokio::test]
+    async fn reduce_order_validates_before_signing() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        let body = serde_json::json!({
+            "code": 200,
+            "result": serde_json::to_value(Order {
+                orig_qty: "10".to_string(),
+                ..order("o-1", "5_2", "NEW")
+            })
+            .unwrap()
+        })
+        .to_string();
+        let url = mock_server_routed(vec![("GET /api/v1/order/o-1 ", json_response(&body))]).await;
+        let agent = agent_at(&url);
+
+        let err = agent.reduce_order("o-1", Decimal::ZERO, None).await;
+        assert!(
+            matches!(err, Err(AlphaSecError::InvalidParameter(_))),
+            "{:?}",
+            err
+        );
+        let err = agent.reduce_order("o-1", Decimal::new(11, 0), None).await;
+        assert!(
+            matches!(&err, Err(AlphaSecError::InvalidParameter(m)) if m.contains("remaining quantity 10")),
+            "{:?}",
+            err
+        );
+        let err = agent.reduce_order("missing", Decimal::ONE, None).await;
+        assert!(matches!(err, Err(AlphaSecError::NotFound(_))), "{:?}", err);
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn kline_channel_resolves_the_market_and_keeps_the_interval() {
+        let mut agent = offline_agent();
+        agent.api.set_token_metadata(kaia_usdt_metadata());
+        assert_eq!(
+            agent.resolve_channel("kline@KAIA/USDT@1m").await.unwrap(),
+            "kline@5_2@1m"
+        );
+        for channel in ["kline@KAIA/USDT", "kline@KAIA/USDT@7m"] {
+            assert!(
+                matches!(
+                    agent.resolve_channel(channel).await,
+                    Err(AlphaSecError::InvalidParameter(_))
+                ),
+                "{channel}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn cancel_all_and_wait_polls_until_the_book_is_flat() {
+        use crate::api::client::tests::{json_response, mock_server_recorded};
+
+        let open = serde_json::json!({
+            "code": 200,
+            "result": [serde_json::to_value(order("o-1", "5_2", "NEW")).unwrap()]
+        })
+        .to_string();
+        let (url, heads) = mock_server_recorded(
+            std::time::Duration::ZERO,
+            vec![
+                json_response(r#"{"code":200,"result":"0xcancelall"}"#),
+                json_response(&open),
+                json_response(r#"{"code":200,"result":[]}"#),
+            ],
+        )
+        .await;
+        let config = signing_config(&url);
+        let mut agent = agent_with_signer(&url, &config);
+        agent.config = config;
+
+        let survivors = agent
+            .cancel_all_and_wait(Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(survivors.is_empty(), "{:?}", survivors);
+        let heads = heads.lock().unwrap().clone();
+        assert_eq!(heads.len(), 3);
+        assert!(heads[0].starts_with("POST "), "{}", heads[0]);
+        assert!(heads[1].starts_with("GET ") && heads[2].starts_with("GET "));
+        // The poll watches the account the cancel was signed for
+        let addr = format!("address={}", agent.l1_address().to_lowercase());
+        assert!(heads[1].to_lowercase().contains(&addr), "{}", heads[1]);
+    }
+
+    #[tokio::test]
+    async fn cancel_all_and_wait_returns_the_orders_left_at_the_timeout() {
+        use crate::api::client::tests::{json_response, mock_server_recorded};
+
+        let open = serde_json::json!({
+            "code": 200,
+            "result": [serde_json::to_value(order("o-1", "5_2", "NEW")).unwrap()]
+        })
+        .to_string();
+        let (url, _) = mock_server_recorded(
+            std::time::Duration::ZERO,
+            vec![
+                json_response(r#"{"code":200,"result":"0xcancelall"}"#),
+                json_response(&open),
+            ],
+        )
+        .await;
+        let agent = signing_agent_at(&url);
+
+        let survivors = agent
+            .cancel_all_and_wait(Duration::from_millis(600))
+            .await
+            .unwrap();
+        assert_eq!(survivors.len(), 1);
+        assert_eq!(survivors[0].order_id, "o-1");
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn typed_channels_resolve_like_their_string_form() {
+        let mut agent = offline_agent();
+        agent.api.set_token_metadata(kaia_usdt_metadata());
+        let channels = [
+            Channel::Trade("KAIA/USDT".into()),
+            Channel::Ticker("KAIA/USDT".into()),
+            Channel::Ticker("*".into()),
+            Channel::Depth("KAIA/USDT".into()),
+            Channel::UserEvent("0xabc".into()),
+            Channel::Kline("KAIA/USDT".into(), crate::types::KlineInterval::Day1),
+        ];
+        for channel in channels {
+            assert_eq!(
+                agent.resolve(&channel).await.unwrap(),
+                agent.resolve_channel(&channel.to_string()).await.unwrap()
+            );
+        }
+        assert_eq!(
+            agent
+                .resolve(&Channel::Depth("KAIA/USDT".into()))
+                .await
+                .unwrap(),
+            "depth@5_2"
+        );
+        // Perp channels bypass the typed form
+        assert_eq!(
+            agent.resolve_channel("perp_ticker@1").await.unwrap(),
+            "perp_ticker@1"
+        );
+    }
+
+    #[test]
+    fn modified_order_keeps_the_unchanged_field() {
+        let partly_filled = Order {
+            price: "0.15".to_string(),
+            orig_qty: "10".to_string(),
+            executed_qty: "4".to_string(),
+            ..order("o-1", "5_2", "PARTIALLY_FILLED")
+        };
+        let price = |p: &str| modified_order(&partly_filled, Some(p.parse().unwrap()), None);
+        let qty = |q: &str| modified_order(&partly_filled, None, Some(q.parse().unwrap()));
+
+        assert_eq!(
+            price("0.2").unwrap(),
+            ("0.2".parse().unwrap(), Decimal::TEN)
+        );
+        assert_eq!(
+            qty("12").unwrap(),
+            ("0.15".parse().unwrap(), Decimal::new(12, 0))
+        );
+        for result in [price("0"), price("-0.1"), qty("4"), qty("0")] {
+            assert!(
+                matches!(result, Err(AlphaSecError::InvalidParameter(_))),
+                "{:?}",
+                result
+            );
+        }
+        let canceled = order("o-2", "5_2", "CANCELED");
+        assert!(matches!(
+            modified_order(&canceled, Some(Decimal::ONE), None),
+            Err(AlphaSecError::InvalidParameter(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn modify_price_and_qty_look_the_order_up_first() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        let url = mock_server_routed(vec![]).await;
+        let agent = agent_at(&url);
+        let err = agent.modify_price("missing", Decimal::ONE, None).await;
+        assert!(matches!(err, Err(AlphaSecError::NotFound(_))), "{:?}", err);
+
+        let body = serde_json::json!({
+            "code": 200,
+            "result": serde_json::to_value(order("o-1", "5_2", "FILLED")).unwrap()
+        })
+        .to_string();
+        let url = mock_server_routed(vec![("GET /api/v1/order/o-1 ", json_response(&body))]).await;
+        let agent = agent_at(&url);
+        let err = agent.modify_qty("o-1", Decimal::TEN, None).await;
+        assert!(
+            matches!(&err, Err(AlphaSecError::InvalidParameter(m)) if m.contains("FILLED")),
+            "{:?}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn trading_calls_run_inside_spans_with_their_fields() {
+        use std::sync::Mutex;
+        use tracing_subscriber::fmt::format::FmtSpan;
+
+        #[derive(Clone, Default)]
+        struct Capture(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for Capture {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer({
+                let capture = capture.clone();
+                move || capture.clone()
+            })
+            .with_ansi(false)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // Keyless agent: each call gets as far as signing, after the nonce is picked
+        let mut agent = offline_agent();
+        agent.api.set_token_metadata(kaia_usdt_metadata());
+        assert!(agent
+            .order(
+                "KAIA/USDT",
+                OrderSide::Buy,
+                Decimal::ONE,
+                Decimal::TEN,
+                OrderType::Limit,
+                OrderMode::Base,
+                None,
+                None,
+                None,
+                Some(1_700_000_000_001),
+            )
+            .await
+            .is_err());
+        assert!(agent.cancel("o-1", Some(1_700_000_000_002)).await.is_err());
+        assert!(agent
+            .modify(
+                "o-2",
+                Decimal::ONE,
+                Decimal::TEN,
+                OrderMode::Base,
+                Some(1_700_000_000_003)
+            )
+            .await
+            .is_err());
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        for expected in [
+            "order{market=KAIA/USDT side=Buy order_type=Limit nonce=1700000000001 outcome=\"failure\" duration_ms=",
+            "cancel{order_id=o-1 nonce=1700000000002 outcome=\"failure\" duration_ms=",
+            "modify{order_id=o-2 nonce=1700000000003 outcome=\"failure\" duration_ms=",
+        ] {
+            assert!(output.contains(expected), "{} not in:\n{}", expected, output);
+        }
+    }
+
+    #[test]
+    fn humanize_uses_the_quote_token_for_prices_and_the_base_token_for_quantities() {
+        let mut agent = offline_agent();
+        let mut metadata = kaia_usdt_metadata();
+        // KAIA keeps 18 decimals, USDT has 6
+        metadata.apply_decimal_overrides(&HashMap::from([("USDT".to_string(), 6)]));
+        agent.api.set_token_metadata(metadata);
+
+        assert_eq!(
+            agent.humanize_price("KAIA/USDT", "150000").unwrap(),
+            "0.15".parse::<Decimal>().unwrap()
+        );
+        assert_eq!(
+            agent
+                .humanize_qty("KAIA/USDT", "2500000000000000000")
+                .unwrap(),
+            "2.5".parse::<Decimal>().unwrap()
+        );
+        assert!(matches!(
+            agent.humanize_price("BTC/USDT", "1"),
+            Err(AlphaSecError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn named_session_wallets_sign_orders_and_cancels() {
+        use crate::api::client::tests::{json_response, mock_server_bodies};
+        use ethers::types::transaction::eip2718::TypedTransaction;
+
+        let (url, bodies) =
+            mock_server_bodies(json_response(r#"{"code":200,"result":"0xhash"}"#)).await;
+        let mut agent = signing_agent_at(&url);
+        agent.api.set_token_metadata(kaia_usdt_metadata());
+        let default_wallet = agent.signer.get_wallet().unwrap().address();
+        let grid: LocalWallet = "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d"
+            .parse()
+            .unwrap();
+        let grid_address = grid.address();
+        // Clones share the registered wallets
+        agent.clone().add_session_wallet("grid", grid);
+
+        super::super::OrderBuilder::new()
+            .market("KAIA/USDT")
+            .side(OrderSide::Buy)
+            .order_type(OrderType::Limit)
+            .price(Decimal::ONE)
+            .quantity(Decimal::TEN)
+            .session("grid")
+            .submit(&agent)
+            .await
+            .unwrap();
+        agent.cancel_as("o-1", Some("grid"), None).await.unwrap();
+        agent.cancel("o-2", None).await.unwrap();
+
+        let signers: Vec<_> = bodies
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|body| !body.is_empty())
+            .map(|body| {
+                let body: serde_json::Value = serde_json::from_str(body).unwrap();
+                let raw =
+                    hex::decode(body["tx"].as_str().unwrap().trim_start_matches("0x")).unwrap();
+                let rlp = ethers::core::utils::rlp::Rlp::new(&raw);
+                let (tx, signature) = TypedTransaction::decode_signed(&rlp).unwrap();
+                signature.recover(tx.sighash()).unwrap()
+            })
+            .collect();
+        assert_eq!(signers, vec![grid_address, grid_address, default_wallet]);
+
+        match agent.cancel_as("o-1", Some("unknown"), None).await {
+            Err(AlphaSecError::InvalidParameter(message)) => {
+                assert!(message.contains("unknown"), "{}", message)
+            }
+            other => panic!("expected InvalidParameter, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn own_account_helpers_query_the_signer_address() {
+        use crate::api::client::tests::{json_response, mock_server_recorded};
+
+        let (url, heads) = mock_server_recorded(
+            std::time::Duration::ZERO,
+            vec![
+                json_response(r#"{"code":200,"result":[]}"#),
+                json_response(r#"{"code":200,"result":{"balances":[],"blockNumber":1}}"#),
+                json_response(r#"{"code":200,"result":[]}"#),
+                json_response(r#"{"code":200,"result":[]}"#),
+            ],
+        )
+        .await;
+        let agent = agent_at(&url);
+
+        assert!(agent
+            .my_open_orders(None, Some(10))
+            .await
+            .unwrap()
+            .is_empty());
+        agent.my_balance().await.unwrap();
+        assert!(agent.my_sessions(false, None).await.unwrap().is_empty());
+        assert!(agent
+            .my_transfer_history(None, None, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let heads = heads.lock().unwrap();
+        assert_eq!(heads.len(), 4);
+        let address = agent.l1_address();
+        for (head, path) in heads.iter().zip([
+            "/api/v1/order/open",
+            "/api/v1/wallet/balance",
+            "/api/v1/wallet/session",
+            "/api/v1/wallet/transfer",
+        ]) {
+            let line = head.lines().next().unwrap();
+            assert!(line.contains(path), "{}", line);
+            assert!(line.contains(&format!("address={}", address)), "{}", line);
+        }
+    }
+
+    #[tokio::test]
+    async fn server_capabilities_enable_and_disable_optional_features() {
+        use crate::api::client::tests::{json_response, mock_server_routed};
+
+        let meta = |features: &str| {
+            json_response(&format!(
+                r#"{{"code":200,"result":{{"version":"1.2","features":{}}}}}"#,
+                features
+            ))
+        };
+        let keyed_agent = |url: &str| {
+            let mut agent = signing_agent_at(url);
+            agent.api.set_token_metadata(kaia_usdt_metadata());
+            agent
+        };
+        let oco = |agent: Agent| async move {
+            agent
+                .place_oco(
+                    "KAIA/USDT",
+                    OrderSide::Sell,
+                    Decimal::from(100),
+                    "0.2".parse().unwrap(),
+                    "0.1".parse().unwrap(),
+                    "0.09".parse().unwrap(),
+                    OrderMode::Base,
+                )
+                .await
+        };
+        let reduce_only = |agent: Agent| async move {
+            super::super::OrderBuilder::new()
+                .market("KAIA/USDT")
+                .side(OrderSide::Sell)
+                .order_type(OrderType::Limit)
+                .price(Decimal::ONE)
+                .quantity(Decimal::TEN)
+                .reduce_only(true)
+                .submit(&agent)
+                .await
+        };
+        let oco_ok = r#"{"code":200,"result":{"limitOrderId":"0xlimit","stopOrderId":"0xstop"}}"#;
+        let order_ok = r#"{"code":200,"result":"0xorder"}"#;
+
+        // Features the server does not list fail before anything is submitted
+        let url = mock_server_routed(vec![
+            ("GET /api/v1/meta ", meta(r#"["batchOrders"]"#)),
+            ("POST /api/v1/order/oco ", json_response(oco_ok)),
+            ("POST /api/v1/order ", json_response(order_ok)),
+        ])
+        .await;
+        let agent = keyed_agent(&url);
+        let capabilities = agent.server_capabilities().await.unwrap();
+        assert_eq!(capabilities.version.as_deref(), Some("1.2"));
+        assert!(capabilities.supports(Capabilities::BATCH_ORDERS));
+        assert!(!capabilities.supports(Capabilities::OCO));
+        match oco(agent.clone()).await {
+            Err(AlphaSecError::Unsupported(message)) => {
+                assert!(message.contains("`oco`"), "{}", message);
+                assert!(message.contains("version 1.2"), "{}", message);
+            }
+            other => panic!("expected Unsupported, got {:?}", other),
+        }
+        let err = reduce_only(agent.clone()).await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Unsupported, "{err}");
+
+        // Listed features go through
+        let url = mock_server_routed(vec![
+            ("GET /api/v1/meta ", meta(r#"["OCO","reduceOnly"]"#)),
+            ("POST /api/v1/order/oco ", json_response(oco_ok)),
+            ("POST /api/v1/order ", json_response(order_ok)),
+        ])
+        .await;
+        let agent = keyed_agent(&url);
+        assert_eq!(
+            oco(agent.clone()).await.unwrap(),
+            ("0xlimit".to_string(), "0xstop".to_string())
+        );
+        assert_eq!(reduce_only(agent).await.unwrap(), "0xorder");
+
+        // A server without the endpoint reports nothing and allows everything, but an
+        // OCO endpoint it does not have is still reported as unsupported
+        let url = mock_server_routed(vec![("POST /api/v1/order ", json_response(order_ok))]).await;
+        let agent = keyed_agent(&url);
+        assert!(!agent.server_capabilities().await.unwrap().is_reported());
+        assert_eq!(reduce_only(agent.clone()).await.unwrap(), "0xorder");
+        let err = oco(agent).await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Unsupported, "{err}");
+    }
+
+    #[tokio::test]
+    async fn failed_capabilities_fetch_is_not_retried_during_the_backoff() {
+        use crate::api::client::tests::{json_response, mock_server_recorded};
+
+        let (url, heads) = mock_server_recorded(
+            std::time::Duration::ZERO,
+            vec![
+                "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string(),
+                json_response(r#"{"code":200,"result":{"version":"1.2","features":["oco"]}}"#),
+            ],
+        )
+        .await;
+        let agent = agent_at(&url);
+
+        assert!(agent.server_capabilities().await.is_err());
+        let err = agent.clone().server_capabilities().await.unwrap_err();
+        assert!(err.to_string().contains("failed recently"), "{err}");
+        // The capability check still lets the call through
+        agent
+            .require_capability(Capabilities::OCO, "OCO")
+            .await
+            .unwrap();
+        assert_eq!(heads.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn server_capabilities_are_fetched_once_per_agent() {
+        use crate::api::client::tests::{json_response, mock_server_recorded};
+
+        let (url, heads) = mock_server_recorded(
+            std::time::Duration::ZERO,
+            vec![json_response(
+                r#"{"code":200,"result":{"version":"1.2","features":["oco"]}}"#,
+            )],
+        )
+        .await;
+        let agent = agent_at(&url);
+        let first = agent.server_capabilities().await.unwrap();
+        // Clones share the cache
+        assert_eq!(agent.clone().server_capabilities().await.unwrap(), first);
+        assert_eq!(heads.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn cancel_all_reports_the_canceled_count_when_given() {
+        use crate::api::client::tests::{json_response, mock_server};
+
+        for (body, expected) in [
+            (
+                r#"{"code":200,"result":{"txHash":"0xhash","canceledCount":4}}"#,
+                Some(4),
+            ),
+            (r#"{"code":200,"result":"0xhash"}"#, None),
+        ] {
+            let url = mock_server(std::time::Duration::ZERO, json_response(body)).await;
+            let agent = signing_agent_at(&url);
+
+            let result = agent.cancel_all(Some(1_700_000_000_000)).await.unwrap();
+            assert_eq!(result.canceled_count, expected, "{}", body);
+            assert_eq!(result.tx_hash, "0xhash");
+        }
+    }
+
+    #[tokio::test]
+    async fn get_sessions_filters_by_activity_and_name() {
+        use crate::api::client::tests::{json_response, mock_server_recorded};
+
+        let future = AlphaSecSigner::current_timestamp_ms() + 3_600_000;
+        let body = format!(
+            r#"{{"code":200,"result":[
+                {{"name":"live","sessionAddress":"0xa","ownerAddress":"0xo","expiry":{future},"applied":true}},
+                {{"name":"stale","sessionAddress":"0xb","ownerAddress":"0xo","expiry":1,"applied":true}},
+                {{"name":"pending","sessionAddress":"0xc","ownerAddress":"0xo","expiry":{future},"applied":false}}
+            ]}}"#
+        );
+        let (url, _heads) =
+            mock_server_recorded(Duration::ZERO, vec![json_response(&body); 4]).await;
+        let agent = agent_at(&url);
+        let names = |sessions: Vec<Session>| -> Vec<String> {
+            sessions.into_iter().map(|session| session.name).collect()
+        };
+
+        assert_eq!(
+            names(agent.get_sessions("0xo", false, None).await.unwrap()),
+            ["live", "stale", "pending"]
+        );
+        assert_eq!(
+            names(agent.get_sessions("0xo", true, None).await.unwrap()),
+            ["live"]
+        );
+        assert_eq!(
+            names(
+                agent
+                    .get_sessions("0xo", false, Some("stale"))
+                    .await
+                    .unwrap()
+            ),
+            ["stale"]
+        );
+        assert!(agent
+            .get_sessions("0xo", true, Some("pending"))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn bracket_order_sends_the_tpsl_block_and_reads_the_children() {
+        use crate::api::client::tests::{json_response, mock_server_bodies};
+        use ethers::types::transaction::eip2718::TypedTransaction;
+
+        let (url, bodies) = mock_server_bodies(json_response(
+            r#"{"code":200,"result":{"orderId":"0xentry","tpOrderId":"0xtp","slOrderId":"0xsl"}}"#,
+        ))
+        .await;
+        let mut agent = signing_agent_at(&url);
+        agent.api.set_token_metadata(kaia_usdt_metadata());
+
+        let bracket = agent
+            .bracket_order(
+                "KAIA/USDT",
+                OrderSide::Buy,
+                Decimal::new(15, 2),
+                Decimal::TEN,
+                OrderType::Limit,
+                Decimal::new(18, 2),
+                Decimal::new(12, 2),
+                Some(Decimal::new(119, 3)),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            bracket,
+            BracketOrder {
+                order_id: "0xentry".to_string(),
+                take_profit_id: Some("0xtp".to_string()),
+                stop_loss_id: Some("0xsl".to_string()),
+            }
+        );
+
+        let bodies = bodies.lock().unwrap();
+        let body = bodies.iter().find(|body| !body.is_empty()).unwrap();
+        let body: serde_json::Value = serde_json::from_str(body).unwrap();
+        let raw = hex::decode(body["tx"].as_str().unwrap().trim_start_matches("0x")).unwrap();
+        let (tx, _) =
+            TypedTransaction::decode_signed(&ethers::core::utils::rlp::Rlp::new(&raw)).unwrap();
+        let data = tx.data().unwrap();
+        assert_eq!(data[0], crate::types::dex_commands::DEX_COMMAND_ORDER);
+        let payload: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
+        assert_eq!(
+            payload["tpsl"],
+            serde_json::json!({"tpLimit": "0.18", "slTrigger": "0.12", "slLimit": "0.119"})
+        );
+    }
+
+    #[tokio::test]
+    async fn bracket_order_rejects_a_take_profit_past_the_stop() {
+        let agent = offline_agent();
+        for (side, take_profit, stop_trigger, expected) in [
+            (OrderSide::Buy, 9, 10, "above"),
+            (OrderSide::Sell, 11, 10, "below"),
+            (OrderSide::Buy, 10, 10, "above"),
+        ] {
+            // A market entry has no price to check the legs against
+            let err = agent
+                .bracket_order(
+                    "KAIA/USDT",
+                    side,
+                    Decimal::ZERO,
+                    Decimal::ONE,
+                    OrderType::Market,
+                    Decimal::from(take_profit),
+                    Decimal::from(stop_trigger),
+                    None,
+                    None,
+                )
+                .await
+                .unwrap_err();
+            match err {
+                AlphaSecError::InvalidParameter(message) => {
+                    assert!(message.contains(expected), "{}", message)
+                }
+                other => panic!("expected InvalidParameter, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn cloned_agents_sign_unique_nonces_concurrently() {
+        use crate::api::client::tests::{json_response, mock_server_bodies};
+        use ethers::types::transaction::eip2718::TypedTransaction;
+
+        let (url, bodies) =
+            mock_server_bodies(json_response(r#"{"code":200,"result":"0xhash"}"#)).await;
+        let agent = signing_agent_at(&url);
+
+        let tasks: Vec<_> = (0..8)
+            .map(|task| {
+                let agent = agent.clone();
+                tokio::spawn(async move {
+                    for order in 0..5 {
+                        agent
+                            .cancel(&format!("o-{}-{}", task, order), None)
+                            .await
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let nonces: std::collections::HashSet<ethers::types::U256> = bodies
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|body| {
+                let body: serde_json::Value = serde_json::from_str(body).unwrap();
+                let raw =
+                    hex::decode(body["tx"].as_str().unwrap().trim_start_matches("0x")).unwrap();
+                let rlp = ethers::core::utils::rlp::Rlp::new(&raw);
+                let (tx, _) = TypedTransaction::decode_signed(&rlp).unwrap();
+                *tx.nonce().unwrap()
+            })
+            .collect();
+        assert_eq!(nonces.len(), 40);
+    }
+
+    #[tokio::test]
+    async fn onchain_balance_reads_native_and_erc20_balances() {
+        use ethers::{
+            abi::{encode, Token as AbiToken},
+            providers::Provider,
+            types::{Address, Bytes, U256},
+        };
+
+        let mut agent = offline_agent();
+        let token = |token_id: &str, symbol: &str| Token {
+            token_id: token_id.to_string(),
+            symbol: symbol.to_string(),
+            l2_symbol: symbol.to_string(),
+            l1_address: format!("0x{:0>40}", token_id),
+            decimals: 18,
+            is_active: true,
+        };
+        agent.api.set_token_metadata(TokenMetadata::from_tokens(&[
+            token("1", "KAIA"),
+            token("2", "USDT"),
+        ]));
+
+        let (provider, mock) = Provider::mocked();
+        let provider = Arc::new(provider);
+        mock.push(U256::from(1_500_000_000_000_000_000u128))
+            .unwrap();
+        assert_eq!(
+            agent.onchain_balance_via(&provider, "KAIA").await.unwrap(),
+            Decimal::new(15, 1)
+        );
+        let owner: Address = agent.l1_address().parse().unwrap();
+        mock.assert_request("eth_getBalance", (owner, "latest"))
+            .unwrap();
+
+        // The native balance follows the token's configured decimals
+        let mut six_decimals = offline_agent();
+        six_decimals
+            .api
+            .set_token_metadata(TokenMetadata::from_tokens(&[Token {
+                decimals: 6,
+                ..token("1", "KAIA")
+            }]));
+        let (provider, mock) = Provider::mocked();
+        let provider = Arc::new(provider);
+        mock.push(U256::from(1_500_000u64)).unwrap();
+        assert_eq!(
+            six_decimals
+                .onchain_balance_via(&provider, "KAIA")
+                .await
+                .unwrap(),
+            Decimal::new(15, 1)
+        );
+
+        // Responses are served last-pushed first: router lookup, balanceOf, decimals
+        let (provider, mock) = Provider::mocked();
+        let provider = Arc::new(provider);
+        let l2_token = Address::repeat_byte(0x22);
+        mock.push::<Bytes, _>(Bytes::from(encode(&[AbiToken::Uint(6.into())])))
+            .unwrap();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[AbiToken::Uint(12_345_678.into())])))
+            .unwrap();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[AbiToken::Address(l2_token)])))
+            .unwrap();
+        assert_eq!(
+            agent.onchain_balance_via(&provider, "USDT").await.unwrap(),
+            Decimal::new(12_345_678, 6)
+        );
+
+        // An L1 token without an L2 counterpart
+        let (provider, mock) = Provider::mocked();
+        let provider = Arc::new(provider);
+        mock.push::<Bytes, _>(Bytes::from(encode(&[AbiToken::Address(Address::zero())])))
+            .unwrap();
+        assert!(matches!(
+            agent.onchain_balance_via(&provider, "USDT").await,
+            Err(AlphaSecError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,322 @@
+//! REST/WebSocket order book comparison, see [`crate::Agent::verify_depth_consistency`]
+
+use std::{collections::BTreeMap, time::Duration};
+
+use rust_decimal::Decimal;
+use tokio::time::Instant;
+
+use crate::{
+    types::{Depth, DepthResult, WebSocketMessage},
+    websocket::MessageReceiver,
+    AlphaSecError, Result,
+};
+
+/// Depth frames captured after the first snapshot before the reference snapshot is taken
+pub const DEPTH_CHECK_FRAMES: usize = 5;
+
+/// Longest a consistency check waits for depth frames
+pub const DEPTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Best level on one side of a book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookTop {
+    /// Level price
+    pub price: Decimal,
+    /// Quantity resting at that price
+    pub quantity: Decimal,
+}
+
+/// Result of [`Agent::verify_depth_consistency`](crate::Agent::verify_depth_consistency)
+///
+/// The "stream" book is a REST snapshot with the WebSocket depth frames that followed it
+/// applied; the "reference" book is a second REST snapshot taken afterwards. Frames past
+/// the reference's update ID are not applied, so the two books describe the same moment
+/// when [`Self::is_aligned`] holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsistencyReport {
+    /// Wire market ID (e.g. `"5_2"`)
+    pub market_id: String,
+    /// `lastUpdatedId` of the snapshot the frames were applied to
+    pub snapshot_id: i64,
+    /// `lastUpdatedId` of the reference snapshot
+    pub reference_id: i64,
+    /// Update ID the stream book reached
+    pub stream_id: i64,
+    /// Frames applied to the snapshot
+    pub frames_applied: usize,
+    /// Whether an applied frame skipped update IDs, i.e. the stream lost updates
+    pub sequence_gap: bool,
+    /// Best bid of the stream book
+    pub stream_bid: Option<BookTop>,
+    /// Best ask of the stream book
+    pub stream_ask: Option<BookTop>,
+    /// Best bid of the reference snapshot
+    pub reference_bid: Option<BookTop>,
+    /// Best ask of the reference snapshot
+    pub reference_ask: Option<BookTop>,
+}
+
+impl ConsistencyReport {
+    /// Apply the `frames` for `market_id` to `snapshot` and compare the result with
+    /// `reference`
+    ///
+    /// Frames already covered by the snapshot are skipped, and application stops at the
+    /// first frame ending past the reference. A quantity of zero removes a level.
+    pub fn compare(
+        market_id: &str,
+        snapshot: &Depth,
+        frames: &[DepthResult],
+        reference: &Depth,
+    ) -> Result<Self> {
+        let mut bids = rest_levels(snapshot, |depth| &depth.bids)?;
+        let mut asks = rest_levels(snapshot, |depth| &depth.asks)?;
+        let mut stream_id = snapshot.last_updated_id;
+        let mut frames_applied = 0;
+        let mut sequence_gap = false;
+        for frame in frames.iter().filter(|frame| frame.market_id == market_id) {
+            if frame.final_id <= stream_id {
+                continue;
+            }
+            if frame.final_id > reference.last_updated_id {
+                break;
+            }
+            if frame.first_id > stream_id + 1 {
+                sequence_gap = true;
+            }
+            apply_levels(&mut bids, frame.bids.as_deref())?;
+            apply_levels(&mut asks, frame.asks.as_deref())?;
+            stream_id = frame.final_id;
+            frames_applied += 1;
+        }
+
+        let reference_bids = rest_levels(reference, |depth| &depth.bids)?;
+        let reference_asks = rest_levels(reference, |depth| &depth.asks)?;
+        Ok(Self {
+            market_id: market_id.to_string(),
+            snapshot_id: snapshot.last_updated_id,
+            reference_id: reference.last_updated_id,
+            stream_id,
+            frames_applied,
+            sequence_gap,
+            stream_bid: best_bid(&bids),
+            stream_ask: best_ask(&asks),
+            reference_bid: best_bid(&reference_bids),
+            reference_ask: best_ask(&reference_asks),
+        })
+    }
+
+    /// Whether the stream book reached exactly the reference's update ID, so their tops
+    /// are comparable
+    pub fn is_aligned(&self) -> bool {
+        self.stream_id == self.reference_id
+    }
+
+    /// Everything that disagrees, one line each; top-of-book prices and quantities may
+    /// differ by up to `tolerance`
+    pub fn divergences(&self, tolerance: Decimal) -> Vec<String> {
+        let mut divergences = Vec::new();
+        if self.sequence_gap {
+            divergences.push("depth stream skipped update IDs".to_string());
+        }
+        if !self.is_aligned() {
+            divergences.push(format!(
+                "stream book is at update {}, reference snapshot at {}",
+                self.stream_id, self.reference_id
+            ));
+        }
+        for (side, stream, reference) in [
+            ("bid", self.stream_bid, self.reference_bid),
+            ("ask", self.stream_ask, self.reference_ask),
+        ] {
+            let agrees = match (stream, reference) {
+                (Some(stream), Some(reference)) => {
+                    (stream.price - reference.price).abs() <= tolerance
+                        && (stream.quantity - reference.quantity).abs() <= tolerance
+                }
+                (None, None) => true,
+                _ => false,
+            };
+            if !agrees {
+                divergences.push(format!(
+                    "best {}: stream {:?}, reference {:?}",
+                    side, stream, reference
+                ));
+            }
+        }
+        divergences
+    }
+
+    /// Whether [`Self::divergences`] finds nothing
+    pub fn is_consistent(&self, tolerance: Decimal) -> bool {
+        self.divergences(tolerance).is_empty()
+    }
+}
+
+/// Push the depth frames for `market_id` from `ws_rx` onto `frames` until `done` holds,
+/// the receiver closes or `deadline` passes.
+pub(crate) async fn collect_depth_frames(
+    ws_rx: &mut MessageReceiver,
+    market_id: &str,
+    frames: &mut Vec<DepthResult>,
+    deadline: Instant,
+    done: impl Fn(&[DepthResult]) -> bool,
+) {
+    while !done(frames) {
+        match tokio::time::timeout_at(deadline, ws_rx.recv()).await {
+            Ok(Some(WebSocketMessage::DepthMsg { params, .. }))
+                if params.result.market_id == market_id =>
+            {
+                frames.push(params.result)
+            }
+            Ok(Some(_)) => {}
+            Ok(None) | Err(_) => break,
+        }
+    }
+}
+
+type Book = BTreeMap<Decimal, Decimal>;
+
+fn rest_levels(
+    depth: &Depth,
+    side: impl Fn(&Depth) -> &Vec<crate::types::DepthLevel>,
+) -> Result<Book> {
+    side(depth)
+        .iter()
+        .map(|level| Ok((parse(&level.price)?, parse(&level.quantity)?)))
+        .filter(|level| !matches!(level, Ok((_, quantity)) if quantity.is_zero()))
+        .collect()
+}
+
+fn apply_levels(book: &mut Book, levels: Option<&[Vec<String>]>) -> Result<()> {
+    for level in levels.unwrap_or_default() {
+        let [price, quantity] = level.as_slice() else {
+            return Err(AlphaSecError::api(
+                500,
+                format!("Invalid depth level: {:?}", level),
+            ));
+        };
+        let (price, quantity) = (parse(price)?, parse(quantity)?);
+        if quantity.is_zero() {
+            book.remove(&price);
+        } else {
+            book.insert(price, quantity);
+        }
+    }
+    Ok(())
+}
+
+fn best_bid(book: &Book) -> Option<BookTop> {
+    book.iter().next_back().map(|(price, quantity)| BookTop {
+        price: *price,
+        quantity: *quantity,
+    })
+}
+
+fn best_ask(book: &Book) -> Option<BookTop> {
+    book.iter().next().map(|(price, quantity)| BookTop {
+        price: *price,
+        quantity: *quantity,
+    })
+}
+
+fn parse(value: &str) -> Result<Decimal> {
+    value
+        .parse()
+        .map_err(|_| AlphaSecError::api(500, format!("Invalid depth value: {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DepthLevel;
+
+    fn depth(id: i64, bids: &[(&str, &str)], asks: &[(&str, &str)]) -> Depth {
+        let levels = |levels: &[(&str, &str)]| {
+            levels
+                .iter()
+                .map(|(price, quantity)| DepthLevel {
+                    price: price.to_string(),
+                    quantity: quantity.to_string(),
+                })
+                .collect()
+        };
+        Depth {
+            bids: levels(bids),
+            asks: levels(asks),
+            updated_at: 0,
+            last_updated_id: id,
+        }
+    }
+
+    fn frame(first_id: i64, final_id: i64, bids: &[(&str, &str)]) -> DepthResult {
+        DepthResult {
+            market_id: "5_2".to_string(),
+            bids: Some(
+                bids.iter()
+                    .map(|(price, quantity)| vec![price.to_string(), quantity.to_string()])
+                    .collect(),
+            ),
+            asks: None,
+            first_id,
+            final_id,
+            time: 0,
+        }
+    }
+
+    fn top(price: &str, quantity: &str) -> Option<BookTop> {
+        Some(BookTop {
+            price: price.parse().unwrap(),
+            quantity: quantity.parse().unwrap(),
+        })
+    }
+
+    #[test]
+    fn applied_frames_that_match_the_reference_are_consistent() {
+        let snapshot = depth(10, &[("0.10", "50"), ("0.09", "20")], &[("0.11", "30")]);
+        let frames = [
+            // Already covered by the snapshot
+            frame(9, 10, &[("0.10", "999")]),
+            frame(11, 11, &[("0.10", "0")]),
+            frame(12, 12, &[("0.095", "5")]),
+            // Past the reference
+            frame(13, 13, &[("0.12", "1")]),
+        ];
+        let reference = depth(12, &[("0.095", "5"), ("0.09", "20")], &[("0.11", "30")]);
+
+        let report = ConsistencyReport::compare("5_2", &snapshot, &frames, &reference).unwrap();
+        assert_eq!(report.frames_applied, 2);
+        assert_eq!(report.stream_id, 12);
+        assert!(report.is_aligned());
+        assert_eq!(report.stream_bid, top("0.095", "5"));
+        assert_eq!(report.stream_ask, top("0.11", "30"));
+        assert!(report.is_consistent(Decimal::ZERO), "{:?}", report);
+    }
+
+    #[test]
+    fn diverging_stream_is_flagged() {
+        let snapshot = depth(10, &[("0.10", "50")], &[("0.11", "30")]);
+        // The stream misses update 11, which removed the 0.10 bid
+        let frames = [frame(12, 12, &[("0.10", "40")])];
+        let reference = depth(12, &[("0.09", "20")], &[("0.11", "30")]);
+
+        let report = ConsistencyReport::compare("5_2", &snapshot, &frames, &reference).unwrap();
+        assert!(report.sequence_gap);
+        assert!(report.is_aligned());
+        assert_eq!(report.stream_bid, top("0.10", "40"));
+        assert_eq!(report.reference_bid, top("0.09", "20"));
+        let divergences = report.divergences(Decimal::ZERO);
+        assert_eq!(divergences.len(), 2, "{:?}", divergences);
+        assert!(divergences[1].starts_with("best bid"));
+        assert!(!report.is_consistent(Decimal::new(1, 2)));
+    }
+
+    #[test]
+    fn small_differences_within_tolerance_pass() {
+        let snapshot = depth(10, &[("0.10", "50")], &[]);
+        let reference = depth(10, &[("0.10", "50.5")], &[]);
+
+        let report = ConsistencyReport::compare("5_2", &snapshot, &[], &reference).unwrap();
+        assert!(!report.is_consistent(Decimal::ZERO));
+        assert!(report.is_consistent(Decimal::ONE));
+    }
+}
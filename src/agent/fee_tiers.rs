@@ -0,0 +1,115 @@
+//! Per-account fee tier cache, see [`crate::Agent::get_account_fee_tier`]
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::types::FeeTier;
+
+/// How long a fetched fee tier is reused before it is fetched again
+pub const FEE_TIER_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// How long a failed fee tier lookup is reported again instead of being retried
+pub const FEE_TIER_FAILURE_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A fetched tier, or the message of the failed lookup
+type Lookup = std::result::Result<FeeTier, String>;
+
+/// Fee tiers by account address, each valid for a fixed time after it was fetched
+///
+/// Failed lookups are kept too, as their error message, for a shorter time.
+#[derive(Debug)]
+pub(crate) struct FeeTierCache {
+    ttl: Duration,
+    failure_ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Lookup)>>,
+}
+
+impl FeeTierCache {
+    pub(crate) fn new(ttl: Duration, failure_ttl: Duration) -> Self {
+        Self {
+            ttl,
+            failure_ttl,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Tier of `address`, or the message of its failed lookup, if stored less than the
+    /// TTL (or failure backoff) before `now`
+    pub(crate) fn get(&self, address: &str, now: Instant) -> Option<Lookup> {
+        let mut entries = self.entries.lock().unwrap();
+        let key = address.to_ascii_lowercase();
+        let (fetched_at, entry) = entries.get(&key)?;
+        let ttl = if entry.is_ok() {
+            self.ttl
+        } else {
+            self.failure_ttl
+        };
+        if now.duration_since(*fetched_at) < ttl {
+            Some(entry.clone())
+        } else {
+            entries.remove(&key);
+            None
+        }
+    }
+
+    /// Store the outcome of looking up the tier of `address` at `now`
+    pub(crate) fn insert(&self, address: &str, entry: Lookup, now: Instant) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(address.to_ascii_lowercase(), (now, entry));
+    }
+}
+
+impl Default for FeeTierCache {
+    fn default() -> Self {
+        Self::new(FEE_TIER_CACHE_TTL, FEE_TIER_FAILURE_BACKOFF)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tier(name: &str) -> FeeTier {
+        FeeTier {
+            tier: name.to_string(),
+            maker_fee_bps: "2".to_string(),
+            taker_fee_bps: "5".to_string(),
+        }
+    }
+
+    #[test]
+    fn entries_expire_after_the_ttl() {
+        let cache = FeeTierCache::new(Duration::from_secs(60), Duration::from_secs(10));
+        let start = Instant::now();
+        cache.insert("0xABCD", Ok(tier("VIP 1")), start);
+
+        // Addresses match regardless of case
+        let hit = cache.get("0xabcd", start + Duration::from_secs(59));
+        assert_eq!(hit.unwrap().unwrap().tier, "VIP 1");
+        assert_eq!(cache.get("0xabcd", start + Duration::from_secs(60)), None);
+        // Expired entries are dropped rather than served again
+        assert_eq!(cache.get("0xabcd", start), None);
+
+        cache.insert("0xabcd", Ok(tier("VIP 2")), start + Duration::from_secs(60));
+        let refreshed = cache.get("0xABCD", start + Duration::from_secs(61));
+        assert_eq!(refreshed.unwrap().unwrap().tier, "VIP 2");
+    }
+
+    #[test]
+    fn failures_expire_after_the_backoff() {
+        let cache = FeeTierCache::new(Duration::from_secs(60), Duration::from_secs(10));
+        let start = Instant::now();
+        cache.insert("0xabcd", Err("timed out".to_string()), start);
+
+        assert_eq!(
+            cache.get("0xABCD", start + Duration::from_secs(9)),
+            Some(Err("timed out".to_string()))
+        );
+        assert_eq!(cache.get("0xabcd", start + Duration::from_secs(10)), None);
+    }
+}
@@ -1,6 +1,30 @@
 //! Main Agent for AlphaSec SDK
 
 pub mod agent;
+#[cfg(feature = "websocket")]
+mod depth_check;
+mod fee_tiers;
+#[cfg(feature = "websocket")]
+mod open_orders;
+mod order_builder;
+mod order_throttle;
+#[cfg(feature = "websocket")]
+mod price_cache;
+mod session_renewal;
+#[cfg(feature = "websocket")]
+mod streams;
 
 pub use crate::api::utils;
-pub use agent::Agent;
+pub use agent::{
+    Agent, BALANCE_FETCH_CONCURRENCY, CAPABILITIES_FAILURE_BACKOFF, NONCE_RETRY_ATTEMPTS,
+};
+#[cfg(feature = "websocket")]
+pub use depth_check::{BookTop, ConsistencyReport, DEPTH_CHECK_FRAMES, DEPTH_CHECK_TIMEOUT};
+pub use fee_tiers::{FEE_TIER_CACHE_TTL, FEE_TIER_FAILURE_BACKOFF};
+pub use order_builder::{OrderBuilder, OrderRequest};
+#[cfg(feature = "websocket")]
+pub use price_cache::PriceCache;
+pub use session_renewal::{
+    SessionRenewal, SESSION_RENEWAL_EXTENSION, SESSION_RENEWAL_OUTCOMES,
+    SESSION_RENEWAL_RETRY_DELAY,
+};
@@ -0,0 +1,339 @@
+//! Open-order set reconciliation for [`crate::Agent::open_orders_stream`]
+//!
+//! The REST snapshot and the `userEvent` stream are not atomic: events may be
+//! received before the snapshot is fetched, and the snapshot may or may not already
+//! reflect them. Events are buffered until the snapshot lands and then replayed,
+//! skipping any that are older than the snapshot's view of the order.
+
+use std::collections::HashMap;
+
+use crate::types::{Order, OrderUpdate, WebSocketMessage};
+
+/// Capacity of the update channel returned by `open_orders_stream`.
+pub(crate) const ORDER_STREAM_BUFFER: usize = 1000;
+
+/// Extract the ORDER update carried by `msg`, if it belongs to `addr`.
+pub(crate) fn order_update_for(msg: WebSocketMessage, addr: &str) -> Option<OrderUpdate> {
+    match msg {
+        WebSocketMessage::UserEventMsg { params, .. } => params
+            .result
+            .order_update()
+            .filter(|u| u.account_address.eq_ignore_ascii_case(addr)),
+        _ => None,
+    }
+}
+
+/// Keeps a set of open orders consistent with a stream of [`OrderUpdate`]s.
+#[derive(Debug, Default)]
+pub(crate) struct OpenOrders {
+    /// Open orders keyed by order ID; `None` until the snapshot is applied
+    orders: Option<HashMap<String, Order>>,
+    /// Updates received before the snapshot, in arrival order
+    pending: Vec<OrderUpdate>,
+}
+
+impl OpenOrders {
+    /// Create an empty set awaiting its snapshot.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seed the set from a REST snapshot and replay buffered updates on top of it.
+    pub(crate) fn apply_snapshot(&mut self, snapshot: Vec<Order>) {
+        let orders = snapshot
+            .into_iter()
+            .filter(|o| o.is_active())
+            .map(|o| (o.order_id.clone(), o))
+            .collect();
+        self.orders = Some(orders);
+        for update in std::mem::take(&mut self.pending) {
+            self.apply(update);
+        }
+    }
+
+    /// Apply an update, buffering it if the snapshot has not been applied yet.
+    ///
+    /// Returns the update if it changed the set (and so should be forwarded), `None`
+    /// if it was buffered or is stale relative to what the set already holds.
+    pub(crate) fn apply(&mut self, update: OrderUpdate) -> Option<OrderUpdate> {
+        let Some(orders) = self.orders.as_mut() else {
+            self.pending.push(update);
+            return None;
+        };
+
+        if let Some(existing) = orders.get(&update.order_id) {
+            if (update.event_time.max(0) as u64) < existing.updated_at {
+                return None;
+            }
+        }
+
+        if update.is_active() {
+            match orders.get_mut(&update.order_id) {
+                Some(order) => {
+                    order.status = update.status.clone();
+                    order.executed_qty = update.executed_qty.clone();
+                    order.executed_quote_qty = update.executed_quote_qty.clone();
+                    order.updated_at = update.event_time.max(0) as u64;
+                    if update.fee.is_some() {
+                        order.fee = update.fee.clone();
+                        order.fee_token_id = update.fee_token_id.clone();
+                    }
+                }
+                None => {
+                    orders.insert(update.order_id.clone(), order_from_update(&update));
+                }
+            }
+            Some(update)
+        } else {
+            // Terminal updates for orders we never saw (e.g. already gone from the
+            // snapshot) carry no information for the caller.
+            orders.remove(&update.order_id).map(|_| update)
+        }
+    }
+
+    /// Current open orders, oldest first.
+    pub(crate) fn snapshot(&self) -> Vec<Order> {
+        let mut orders: Vec<Order> = self
+            .orders
+            .as_ref()
+            .map(|m| m.values().cloned().collect())
+            .unwrap_or_default();
+        orders.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.order_id.cmp(&b.order_id))
+        });
+        orders
+    }
+}
+
+/// Build an [`Order`] for an order first seen on the event stream. Fields the event
+/// does not carry (database ID, trigger/contingency details) take neutral defaults.
+fn order_from_update(update: &OrderUpdate) -> Order {
+    Order {
+        id: 0,
+        order_id: update.order_id.clone(),
+        account_address: update.account_address.clone(),
+        market_id: update.market_id.clone(),
+        side: update.side.clone(),
+        order_type: update.order_type.clone(),
+        price: update.price.clone(),
+        orig_qty: update.orig_qty.clone(),
+        orig_quote_order_qty: update.orig_quote_order_qty.clone(),
+        is_trigger: false,
+        is_triggered: false,
+        trigger_price: "0".to_string(),
+        status: update.status.clone(),
+        contingency_type: "NONE".to_string(),
+        oto_leg_type: "NONE".to_string(),
+        tx_hash: update.tx_hash.clone(),
+        created_at: update.created_at.max(0) as u64,
+        updated_at: update.event_time.max(0) as u64,
+        executed_qty: update.executed_qty.clone(),
+        executed_quote_qty: update.executed_quote_qty.clone(),
+        fee: update.fee.clone(),
+        fee_token_id: update.fee_token_id.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(order_id: &str, status: &str, updated_at: u64) -> Order {
+        Order {
+            id: 1,
+            order_id: order_id.to_string(),
+            account_address: "0xabc".to_string(),
+            market_id: "1_2".to_string(),
+            side: "BUY".to_string(),
+            order_type: "LIMIT".to_string(),
+            price: "1.5".to_string(),
+            orig_qty: "10".to_string(),
+            orig_quote_order_qty: "0".to_string(),
+            is_trigger: false,
+            is_triggered: false,
+            trigger_price: "0".to_string(),
+            status: status.to_string(),
+            contingency_type: "NONE".to_string(),
+            oto_leg_type: "NONE".to_string(),
+            tx_hash: "0xtx".to_string(),
+            created_at: updated_at,
+            updated_at,
+            executed_qty: "0".to_string(),
+            executed_quote_qty: "0".to_string(),
+            fee: None,
+            fee_token_id: None,
+        }
+    }
+
+    fn update(order_id: &str, status: &str, event_time: i64, executed_qty: &str) -> OrderUpdate {
+        OrderUpdate {
+            order_id: order_id.to_string(),
+            account_address: "0xabc".to_string(),
+            market_id: "1_2".to_string(),
+            side: "BUY".to_string(),
+            order_type: "LIMIT".to_string(),
+            price: "1.5".to_string(),
+            orig_qty: "10".to_string(),
+            orig_quote_order_qty: "0".to_string(),
+            executed_qty: executed_qty.to_string(),
+            executed_quote_qty: "0".to_string(),
+            status: status.to_string(),
+            created_at: 100,
+            event_time,
+            tx_hash: "0xtx".to_string(),
+            fee: None,
+            fee_token_id: None,
+        }
+    }
+
+    #[test]
+    fn order_update_for_keeps_only_order_events_of_the_address() {
+        let frame = |topic: &str, account: &str| {
+            serde_json::from_value::<WebSocketMessage>(serde_json::json!({
+                "method": "subscription",
+                "params": {
+                    "channel": format!("userEvent@{}", account),
+                    "result": {
+                        "topic": topic,
+                        "eventType": "NEW",
+                        "eventTime": 123,
+                        "blockNumber": 1,
+                        "accountAddress": account,
+                        "txHash": "0xtx",
+                        "orderId": "o1",
+                        "marketId": "1_2",
+                        "side": "BUY",
+                        "orderType": "LIMIT",
+                        "orderMode": 0,
+                        "origPrice": "1.5",
+                        "origQty": "10",
+                        "origQuoteOrderQty": "0",
+                        "status": "NEW",
+                        "createdAt": 120,
+                        "executedQty": "0",
+                        "executedQuoteQty": "0",
+                        "lastPrice": "0",
+                        "lastQty": "0",
+                        "fee": "0",
+                        "feeTokenId": null,
+                        "tradeId": "",
+                        "isMaker": false,
+                        "tokenId": "1",
+                        "amount": "1"
+                    }
+                }
+            }))
+            .unwrap()
+        };
+
+        let u = order_update_for(frame("ORDER", "0xABC"), "0xabc").expect("order event");
+        assert_eq!((u.order_id.as_str(), u.event_time), ("o1", 123));
+        assert_eq!(u.price, "1.5");
+        assert_eq!(u.fee.as_deref(), Some("0"));
+        assert!(order_update_for(frame("ORDER", "0xdef"), "0xabc").is_none());
+        assert!(order_update_for(frame("ACCOUNT", "0xabc"), "0xabc").is_none());
+        assert!(order_update_for(WebSocketMessage::Disconnected, "0xabc").is_none());
+    }
+
+    fn ids(set: &OpenOrders) -> Vec<String> {
+        set.snapshot().into_iter().map(|o| o.order_id).collect()
+    }
+
+    #[test]
+    fn updates_before_snapshot_are_buffered_not_forwarded() {
+        let mut set = OpenOrders::new();
+        assert!(set.apply(update("a", "NEW", 100, "0")).is_none());
+        assert!(set.snapshot().is_empty());
+        assert_eq!(set.pending.len(), 1);
+    }
+
+    #[test]
+    fn buffered_new_order_missing_from_snapshot_is_added() {
+        let mut set = OpenOrders::new();
+        set.apply(update("b", "NEW", 300, "0"));
+        set.apply_snapshot(vec![order("a", "NEW", 100)]);
+        assert_eq!(ids(&set), vec!["a", "b"]);
+        assert!(set.pending.is_empty());
+    }
+
+    #[test]
+    fn buffered_fill_removes_order_present_in_stale_snapshot() {
+        let mut set = OpenOrders::new();
+        set.apply(update("a", "FILLED", 200, "10"));
+        set.apply_snapshot(vec![order("a", "NEW", 100), order("c", "NEW", 150)]);
+        assert_eq!(ids(&set), vec!["c"]);
+    }
+
+    #[test]
+    fn buffered_update_older_than_snapshot_is_ignored() {
+        let mut set = OpenOrders::new();
+        // Snapshot already reflects a later partial fill; the earlier NEW event must not
+        // roll executed_qty back.
+        set.apply(update("a", "NEW", 50, "0"));
+        let mut snap = order("a", "PARTIALLY_FILLED", 100);
+        snap.executed_qty = "4".to_string();
+        set.apply_snapshot(vec![snap]);
+        let orders = set.snapshot();
+        assert_eq!(orders[0].status, "PARTIALLY_FILLED");
+        assert_eq!(orders[0].executed_qty, "4");
+    }
+
+    #[test]
+    fn buffered_terminal_event_for_unknown_order_is_a_no_op() {
+        let mut set = OpenOrders::new();
+        // Order placed and filled before the snapshot: NEW then FILLED both buffered.
+        set.apply(update("x", "NEW", 100, "0"));
+        set.apply(update("x", "FILLED", 110, "10"));
+        set.apply_snapshot(vec![]);
+        assert!(set.snapshot().is_empty());
+    }
+
+    #[test]
+    fn live_updates_track_partial_fills_and_remove_terminal_orders() {
+        let mut set = OpenOrders::new();
+        set.apply_snapshot(vec![order("a", "NEW", 100), order("b", "NEW", 100)]);
+
+        let fwd = set.apply(update("a", "PARTIALLY_FILLED", 200, "3"));
+        assert_eq!(fwd.map(|u| u.status), Some("PARTIALLY_FILLED".to_string()));
+        let a = &set.snapshot()[0];
+        assert_eq!((a.executed_qty.as_str(), a.updated_at), ("3", 200));
+
+        assert!(set.apply(update("b", "CANCELED", 210, "0")).is_some());
+        assert_eq!(ids(&set), vec!["a"]);
+
+        // A second terminal event for the same order is not forwarded again.
+        assert!(set.apply(update("b", "CANCELED", 220, "0")).is_none());
+    }
+
+    #[test]
+    fn updates_carry_the_event_fee_into_the_order() {
+        let mut set = OpenOrders::new();
+        set.apply_snapshot(vec![order("a", "NEW", 100)]);
+        let fee = |order_id: &str, status: &str, event_time: i64, fee: &str| OrderUpdate {
+            fee: Some(fee.to_string()),
+            fee_token_id: Some("2".to_string()),
+            ..update(order_id, status, event_time, "3")
+        };
+
+        set.apply(fee("a", "PARTIALLY_FILLED", 200, "0.01"));
+        set.apply(fee("b", "PARTIALLY_FILLED", 210, "0.02"));
+        let orders = set.snapshot();
+        let fees: Vec<_> = orders
+            .iter()
+            .map(|o| (o.fee.as_deref(), o.fee_token_id.as_deref()))
+            .collect();
+        assert_eq!(
+            fees,
+            vec![(Some("0.01"), Some("2")), (Some("0.02"), Some("2"))]
+        );
+    }
+
+    #[test]
+    fn snapshot_drops_non_active_orders() {
+        let mut set = OpenOrders::new();
+        set.apply_snapshot(vec![order("a", "FILLED", 100), order("b", "NEW", 100)]);
+        assert_eq!(ids(&set), vec!["b"]);
+    }
+}
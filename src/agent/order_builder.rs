@@ -0,0 +1,293 @@
+//! Named-field order construction, see [`OrderBuilder`]
+
+use rust_decimal::Decimal;
+
+use crate::{
+    types::{OrderMode, OrderSide, OrderType, RoundingMode},
+    Agent, AlphaSecError, Result,
+};
+
+/// A complete order, ready for [`Agent::submit_order`]
+///
+/// Field meanings are those of the matching [`Agent::order`] arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderRequest {
+    /// Market symbol (e.g. `"KAIA/USDT"`)
+    pub market: String,
+    /// Order side
+    pub side: OrderSide,
+    /// Limit price (ignored for market orders)
+    pub price: Decimal,
+    /// Quantity, in the base token or (quote mode) the quote token
+    pub quantity: Decimal,
+    /// Limit or market
+    pub order_type: OrderType,
+    /// Whether `quantity` is in the base or the quote token
+    pub order_mode: OrderMode,
+    /// Take profit limit price
+    pub tp_limit: Option<Decimal>,
+    /// Stop loss trigger price
+    pub sl_trigger: Option<Decimal>,
+    /// Stop loss limit price
+    pub sl_limit: Option<Decimal>,
+    /// Caller-chosen ID sent as `clientOrderId`
+    pub client_order_id: Option<String>,
+    /// Only reduce an existing position, sent as `reduceOnly` and enforced by the
+    /// exchange
+    pub reduce_only: bool,
+    /// Transaction timestamp (defaults to now)
+    pub timestamp_ms: Option<u64>,
+    /// How price and quantity are cut to the market's precision
+    pub rounding: Option<RoundingMode>,
+    /// Name of the session wallet that signs the order (defaults to the `Config`
+    /// wallet), see [`Agent::add_session_wallet`]
+    pub session: Option<String>,
+}
+
+impl OrderRequest {
+    /// Place this order, see [`Agent::submit_order`]
+    pub async fn submit(&self, agent: &Agent) -> Result<String> {
+        agent.submit_order(self).await
+    }
+}
+
+/// Builder for an [`OrderRequest`], an alternative to the positional [`Agent::order`]
+///
+/// `market`, `side`, `quantity` and `order_type` are required, and so is `price` for a
+/// limit order; [`Self::build`] names the first one missing. `order_mode` defaults to
+/// [`OrderMode::Base`].
+///
+/// ```no_run
+/// # async fn run(agent: &alphasec_rs::Agent) -> alphasec_rs::Result<()> {
+/// use alphasec_rs::{agent::OrderBuilder, OrderSide, OrderType};
+///
+/// let order_id = OrderBuilder::new()
+///     .market("KAIA/USDT")
+///     .side(OrderSide::Buy)
+///     .order_type(OrderType::Limit)
+///     .price("0.15".parse().unwrap())
+///     .quantity("100".parse().unwrap())
+///     .client_order_id("grid-7")
+///     .submit(agent)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OrderBuilder {
+    market: Option<String>,
+    side: Option<OrderSide>,
+    price: Option<Decimal>,
+    quantity: Option<Decimal>,
+    order_type: Option<OrderType>,
+    order_mode: Option<OrderMode>,
+    tp_limit: Option<Decimal>,
+    sl_trigger: Option<Decimal>,
+    sl_limit: Option<Decimal>,
+    client_order_id: Option<String>,
+    reduce_only: bool,
+    timestamp_ms: Option<u64>,
+    rounding: Option<RoundingMode>,
+    session: Option<String>,
+}
+
+impl OrderBuilder {
+    /// Create an empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the market symbol (e.g. `"KAIA/USDT"`)
+    pub fn market(mut self, market: impl Into<String>) -> Self {
+        self.market = Some(market.into());
+        self
+    }
+
+    /// Set the side
+    pub fn side(mut self, side: OrderSide) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    /// Set the limit price
+    pub fn price(mut self, price: Decimal) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    /// Set the quantity
+    pub fn quantity(mut self, quantity: Decimal) -> Self {
+        self.quantity = Some(quantity);
+        self
+    }
+
+    /// Set the order type
+    pub fn order_type(mut self, order_type: OrderType) -> Self {
+        self.order_type = Some(order_type);
+        self
+    }
+
+    /// Set the order mode (default [`OrderMode::Base`])
+    pub fn order_mode(mut self, order_mode: OrderMode) -> Self {
+        self.order_mode = Some(order_mode);
+        self
+    }
+
+    /// Set the take profit limit price
+    pub fn tp_limit(mut self, tp_limit: Decimal) -> Self {
+        self.tp_limit = Some(tp_limit);
+        self
+    }
+
+    /// Set the stop loss trigger price
+    pub fn sl_trigger(mut self, sl_trigger: Decimal) -> Self {
+        self.sl_trigger = Some(sl_trigger);
+        self
+    }
+
+    /// Set the stop loss limit price
+    pub fn sl_limit(mut self, sl_limit: Decimal) -> Self {
+        self.sl_limit = Some(sl_limit);
+        self
+    }
+
+    /// Tag the order with a caller-chosen ID
+    pub fn client_order_id(mut self, client_order_id: impl Into<String>) -> Self {
+        self.client_order_id = Some(client_order_id.into());
+        self
+    }
+
+    /// Make the order reduce-only (default off); enforced by the exchange, not the SDK
+    pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = reduce_only;
+        self
+    }
+
+    /// Set the transaction timestamp (default now)
+    pub fn timestamp_ms(mut self, timestamp_ms: u64) -> Self {
+        self.timestamp_ms = Some(timestamp_ms);
+        self
+    }
+
+    /// Set how price and quantity are rounded (default [`RoundingMode::Truncate`])
+    pub fn rounding(mut self, rounding: RoundingMode) -> Self {
+        self.rounding = Some(rounding);
+        self
+    }
+
+    /// Sign with the session wallet added under `session` by
+    /// [`Agent::add_session_wallet`] (default the `Config` wallet)
+    pub fn session(mut self, session: impl Into<String>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+
+    /// Check the required fields and produce the request
+    pub fn build(self) -> Result<OrderRequest> {
+        fn required<T>(value: Option<T>, field: &str) -> Result<T> {
+            value.ok_or_else(|| {
+                AlphaSecError::invalid_parameter(format!("Order is missing `{}`", field))
+            })
+        }
+
+        let market = required(self.market, "market")?;
+        let side = required(self.side, "side")?;
+        let order_type = required(self.order_type, "order_type")?;
+        let quantity = required(self.quantity, "quantity")?;
+        let price = match order_type {
+            OrderType::Limit => required(self.price, "price")?,
+            OrderType::Market => self.price.unwrap_or(Decimal::ZERO),
+        };
+        Ok(OrderRequest {
+            market,
+            side,
+            price,
+            quantity,
+            order_type,
+            order_mode: self.order_mode.unwrap_or(OrderMode::Base),
+            tp_limit: self.tp_limit,
+            sl_trigger: self.sl_trigger,
+            sl_limit: self.sl_limit,
+            client_order_id: self.client_order_id,
+            reduce_only: self.reduce_only,
+            timestamp_ms: self.timestamp_ms,
+            rounding: self.rounding,
+            session: self.session,
+        })
+    }
+
+    /// Build the request and place it
+    pub async fn submit(self, agent: &Agent) -> Result<String> {
+        self.build()?.submit(agent).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit_order() -> OrderBuilder {
+        OrderBuilder::new()
+            .market("KAIA/USDT")
+            .side(OrderSide::Sell)
+            .order_type(OrderType::Limit)
+            .price(Decimal::ONE)
+            .quantity(Decimal::TEN)
+    }
+
+    fn missing_field(builder: OrderBuilder) -> String {
+        match builder.build() {
+            Err(AlphaSecError::InvalidParameter(message)) => message,
+            other => panic!("expected InvalidParameter, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn build_fills_defaults() {
+        let request = limit_order().client_order_id("c-1").build().unwrap();
+        assert_eq!(request.market, "KAIA/USDT");
+        assert_eq!(request.order_mode, OrderMode::Base);
+        assert_eq!(request.client_order_id.as_deref(), Some("c-1"));
+        assert_eq!(request.tp_limit, None);
+        assert_eq!(request.rounding, None);
+        assert_eq!(request.session, None);
+        assert!(!request.reduce_only);
+        assert!(limit_order().reduce_only(true).build().unwrap().reduce_only);
+
+        let market = OrderBuilder::new()
+            .market("KAIA/USDT")
+            .side(OrderSide::Buy)
+            .order_type(OrderType::Market)
+            .order_mode(OrderMode::Quote)
+            .quantity(Decimal::TEN)
+            .build()
+            .unwrap();
+        assert_eq!(market.price, Decimal::ZERO);
+    }
+
+    #[test]
+    fn build_names_the_missing_required_field() {
+        assert!(missing_field(OrderBuilder::new()).contains("`market`"));
+        let no_side = OrderBuilder {
+            side: None,
+            ..limit_order()
+        };
+        assert!(missing_field(no_side).contains("`side`"));
+        let no_type = OrderBuilder {
+            order_type: None,
+            ..limit_order()
+        };
+        assert!(missing_field(no_type).contains("`order_type`"));
+        let no_quantity = OrderBuilder {
+            quantity: None,
+            ..limit_order()
+        };
+        assert!(missing_field(no_quantity).contains("`quantity`"));
+        // Only limit orders need a price
+        let no_price = OrderBuilder {
+            price: None,
+            ..limit_order()
+        };
+        assert!(missing_field(no_price).contains("`price`"));
+    }
+}
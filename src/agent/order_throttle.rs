@@ -0,0 +1,195 @@
+//! Client-side order submission limits, see [`crate::Config::with_order_throttle`]
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{signer::OrderThrottleConfig, AlphaSecError, Result};
+
+/// Window [`OrderThrottleConfig::max_per_sec`] counts submissions over
+const THROTTLE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Shared submission counters enforcing an [`OrderThrottleConfig`]
+#[derive(Debug)]
+pub(crate) struct OrderThrottle {
+    limits: OrderThrottleConfig,
+    state: Mutex<ThrottleState>,
+}
+
+#[derive(Debug, Default)]
+struct ThrottleState {
+    /// Submission times within the last window, oldest first
+    recent: VecDeque<Instant>,
+    in_flight: usize,
+}
+
+/// Admitted orders; count as in flight until dropped
+#[derive(Debug)]
+pub(crate) struct OrderPermit {
+    throttle: Arc<OrderThrottle>,
+    count: usize,
+}
+
+impl OrderThrottle {
+    pub(crate) fn new(limits: OrderThrottleConfig) -> Arc<Self> {
+        Arc::new(Self {
+            limits,
+            state: Mutex::default(),
+        })
+    }
+
+    /// Admit `n` orders at `now` under one permit, or none of them: fails with
+    /// `RateLimited` if either limit would be exceeded, and with `InvalidParameter` if
+    /// `n` exceeds a limit outright
+    pub(crate) fn try_acquire(self: &Arc<Self>, now: Instant, n: usize) -> Result<OrderPermit> {
+        let max_per_sec = self.limits.max_per_sec as usize;
+        let max_in_flight = self.limits.max_in_flight;
+        for (limit, name) in [
+            (max_per_sec, "max_per_sec"),
+            (max_in_flight, "max_in_flight"),
+        ] {
+            if limit > 0 && n > limit {
+                return Err(AlphaSecError::invalid_parameter(format!(
+                    "{} orders at once exceed the order throttle's {} of {}",
+                    n, name, limit
+                )));
+            }
+        }
+
+        let mut state = self.state.lock().unwrap();
+        while state
+            .recent
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= THROTTLE_WINDOW)
+        {
+            state.recent.pop_front();
+        }
+
+        if max_per_sec > 0 && state.recent.len() + n > max_per_sec {
+            // The submission that has to leave the window to make room for `n` more
+            let blocking = state.recent[state.recent.len() + n - max_per_sec - 1];
+            return Err(AlphaSecError::rate_limited(Some(
+                (blocking + THROTTLE_WINDOW).saturating_duration_since(now),
+            )));
+        }
+        if max_in_flight > 0 && state.in_flight + n > max_in_flight {
+            return Err(AlphaSecError::rate_limited(None));
+        }
+
+        state.recent.extend(std::iter::repeat_n(now, n));
+        state.in_flight += n;
+        Ok(OrderPermit {
+            throttle: Arc::clone(self),
+            count: n,
+        })
+    }
+}
+
+impl Drop for OrderPermit {
+    fn drop(&mut self) {
+        self.throttle.state.lock().unwrap().in_flight -= self.count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle(max_per_sec: u32, max_in_flight: usize) -> Arc<OrderThrottle> {
+        OrderThrottle::new(OrderThrottleConfig {
+            max_per_sec,
+            max_in_flight,
+        })
+    }
+
+    #[test]
+    fn per_second_limit_frees_up_as_the_window_slides() {
+        let throttle = throttle(2, 0);
+        let t0 = Instant::now();
+        drop(throttle.try_acquire(t0, 1).unwrap());
+        drop(
+            throttle
+                .try_acquire(t0 + Duration::from_millis(400), 1)
+                .unwrap(),
+        );
+
+        let err = throttle
+            .try_acquire(t0 + Duration::from_millis(900), 1)
+            .unwrap_err();
+        assert!(
+            matches!(err, AlphaSecError::RateLimited { retry_after: Some(d) } if d == Duration::from_millis(100)),
+            "{err:?}"
+        );
+        // A rejected attempt does not take a slot
+        assert!(throttle.try_acquire(t0 + Duration::from_secs(1), 1).is_ok());
+        assert!(throttle
+            .try_acquire(t0 + Duration::from_millis(1300), 1)
+            .is_err());
+    }
+
+    #[test]
+    fn in_flight_limit_counts_live_permits() {
+        let throttle = throttle(0, 2);
+        let now = Instant::now();
+        let first = throttle.try_acquire(now, 1).unwrap();
+        let _second = throttle.try_acquire(now, 1).unwrap();
+        assert!(matches!(
+            throttle.try_acquire(now, 1),
+            Err(AlphaSecError::RateLimited { retry_after: None })
+        ));
+
+        drop(first);
+        assert!(throttle.try_acquire(now, 1).is_ok());
+    }
+
+    #[test]
+    fn several_orders_are_admitted_together_or_not_at_all() {
+        let t0 = Instant::now();
+        // A pair can never fit a limit of one
+        assert!(matches!(
+            throttle(1, 0).try_acquire(t0, 2),
+            Err(AlphaSecError::InvalidParameter(_))
+        ));
+
+        let throttle = throttle(3, 0);
+        let _first = throttle.try_acquire(t0, 1).unwrap();
+        let _second = throttle
+            .try_acquire(t0 + Duration::from_millis(200), 1)
+            .unwrap();
+
+        // Two more do not fit until the first submission leaves the window, and the
+        // rejected pair takes no slot
+        let err = throttle
+            .try_acquire(t0 + Duration::from_millis(500), 2)
+            .unwrap_err();
+        assert!(
+            matches!(err, AlphaSecError::RateLimited { retry_after: Some(d) } if d == Duration::from_millis(500)),
+            "{err:?}"
+        );
+        let _third = throttle
+            .try_acquire(t0 + Duration::from_millis(500), 1)
+            .unwrap();
+        let pair = throttle
+            .try_acquire(t0 + Duration::from_millis(1000), 2)
+            .unwrap_err();
+        assert!(
+            matches!(pair, AlphaSecError::RateLimited { .. }),
+            "{pair:?}"
+        );
+        assert!(throttle
+            .try_acquire(t0 + Duration::from_millis(1200), 2)
+            .is_ok());
+    }
+
+    #[test]
+    fn a_permit_for_several_orders_releases_them_all() {
+        let throttle = throttle(0, 2);
+        let now = Instant::now();
+        let pair = throttle.try_acquire(now, 2).unwrap();
+        assert!(throttle.try_acquire(now, 1).is_err());
+        drop(pair);
+        assert!(throttle.try_acquire(now, 2).is_ok());
+    }
+}
@@ -0,0 +1,180 @@
+//! Latest-price cache fed by `ticker@` subscriptions, see [`crate::Agent::start_price_cache`]
+
+use std::{collections::HashMap, sync::Arc};
+
+use rust_decimal::Decimal;
+use tokio::{sync::RwLock, task::JoinHandle};
+
+use crate::{
+    types::{TickerData, WebSocketMessage},
+    websocket::MessageReceiver,
+};
+
+/// Latest price per market, kept current by a background task consuming ticker updates.
+///
+/// Markets are keyed by the name they were requested with (e.g. `"KAIA/USDT"`). The
+/// background task stops when the cache is dropped.
+#[derive(Debug)]
+pub struct PriceCache {
+    prices: Arc<RwLock<HashMap<String, Decimal>>>,
+    task: JoinHandle<()>,
+}
+
+impl PriceCache {
+    /// Start consuming `ws_rx`, seeded with `initial` prices.
+    ///
+    /// `markets` maps wire market IDs (e.g. `"1_2"`) to the names prices are stored under;
+    /// ticker entries for other markets are ignored.
+    pub(crate) fn spawn(
+        markets: HashMap<String, String>,
+        initial: HashMap<String, Decimal>,
+        mut ws_rx: MessageReceiver,
+    ) -> Self {
+        let prices = Arc::new(RwLock::new(initial));
+        let task = tokio::spawn({
+            let prices = Arc::clone(&prices);
+            async move {
+                while let Some(msg) = ws_rx.recv().await {
+                    let updates = ticker_prices(&msg, &markets);
+                    if !updates.is_empty() {
+                        prices.write().await.extend(updates);
+                    }
+                }
+            }
+        });
+        Self { prices, task }
+    }
+
+    /// Latest known price for `market`, or `None` if none has been seen yet.
+    pub async fn price(&self, market: &str) -> Option<Decimal> {
+        self.prices.read().await.get(market).copied()
+    }
+
+    /// Snapshot of all known prices.
+    pub async fn prices(&self) -> HashMap<String, Decimal> {
+        self.prices.read().await.clone()
+    }
+}
+
+impl Drop for PriceCache {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Prices carried by `msg` for the tracked markets, keyed by market name.
+///
+/// Entries with an unparseable price are skipped rather than clearing the last good one.
+fn ticker_prices(
+    msg: &WebSocketMessage,
+    markets: &HashMap<String, String>,
+) -> Vec<(String, Decimal)> {
+    let WebSocketMessage::TickerMsg { params, .. } = msg else {
+        return Vec::new();
+    };
+    params
+        .result
+        .iter()
+        .filter_map(|entry| {
+            let name = markets.get(entry.market_id())?;
+            let price = entry.price_decimal().ok()?;
+            Some((name.clone(), price))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::websocket::queue::tests::message_channel;
+    use std::time::Duration;
+
+    fn ticker_msg(entries: &[(&str, &str)]) -> WebSocketMessage {
+        let result: Vec<_> = entries
+            .iter()
+            .map(|(market_id, price)| {
+                serde_json::json!({
+                    "marketId": market_id,
+                    "baseTokenId": "1",
+                    "quoteTokenId": "2",
+                    "price": price,
+                    "open24h": "0",
+                    "high24h": "0",
+                    "low24h": "0",
+                    "volume24h": "0",
+                    "quoteVolume24h": "0"
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "method": "subscription",
+            "params": { "channel": "ticker@1_2", "result": result }
+        }))
+        .unwrap()
+    }
+
+    fn markets() -> HashMap<String, String> {
+        HashMap::from([
+            ("1_2".to_string(), "KAIA/USDT".to_string()),
+            ("3_2".to_string(), "ETH/USDT".to_string()),
+        ])
+    }
+
+    #[test]
+    fn ticker_prices_keeps_tracked_markets_with_valid_prices() {
+        let msg = ticker_msg(&[("1_2", "1.25"), ("9_2", "7"), ("3_2", "bad")]);
+        let updates = ticker_prices(&msg, &markets());
+        assert_eq!(
+            updates,
+            vec![("KAIA/USDT".to_string(), "1.25".parse().unwrap())]
+        );
+        assert!(ticker_prices(&WebSocketMessage::Disconnected, &markets()).is_empty());
+    }
+
+    /// Poll until `market` reaches `expected`; the consumer task runs concurrently.
+    async fn wait_for_price(cache: &PriceCache, market: &str, expected: &str) {
+        let expected: Decimal = expected.parse().unwrap();
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while cache.price(market).await != Some(expected) {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("{} never reached {}", market, expected));
+    }
+
+    #[tokio::test]
+    async fn cache_is_seeded_then_tracks_the_latest_streamed_price() {
+        let (tx, rx) = message_channel();
+        let seed = HashMap::from([
+            ("KAIA/USDT".to_string(), "1.00".parse().unwrap()),
+            ("ETH/USDT".to_string(), "2000".parse().unwrap()),
+        ]);
+        let cache = PriceCache::spawn(markets(), seed, rx);
+        assert_eq!(
+            cache.price("KAIA/USDT").await,
+            Some("1.00".parse().unwrap())
+        );
+        assert_eq!(cache.price("BTC/USDT").await, None);
+
+        tx.send_message(ticker_msg(&[("1_2", "1.10")])).await;
+        tx.send_message(WebSocketMessage::Disconnected).await;
+        tx.send_message(ticker_msg(&[("1_2", "1.20"), ("3_2", "2100")]))
+            .await;
+        wait_for_price(&cache, "ETH/USDT", "2100").await;
+        assert_eq!(
+            cache.price("KAIA/USDT").await,
+            Some("1.20".parse().unwrap())
+        );
+
+        // A malformed price leaves the previous value in place.
+        tx.send_message(ticker_msg(&[("1_2", "oops"), ("3_2", "2200")]))
+            .await;
+        wait_for_price(&cache, "ETH/USDT", "2200").await;
+        assert_eq!(
+            cache.price("KAIA/USDT").await,
+            Some("1.20".parse().unwrap())
+        );
+        assert_eq!(cache.prices().await.len(), 2);
+    }
+}
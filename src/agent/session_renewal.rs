@@ -0,0 +1,104 @@
+//! Background session renewal, see [`crate::Agent::enable_auto_session_renewal`]
+
+use std::time::Duration;
+
+use tokio::{sync::mpsc, task::JoinHandle, time::sleep};
+use tracing::{info, warn};
+
+use crate::{types::SessionAck, Agent, Result};
+
+/// How far past the renewal time each renewal pushes the session's expiry
+pub const SESSION_RENEWAL_EXTENSION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Longest wait before retrying a failed renewal; shorter lead times retry sooner
+pub const SESSION_RENEWAL_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Renewal outcomes a [`SessionRenewal`] holds for [`SessionRenewal::next_renewal`]
+pub const SESSION_RENEWAL_OUTCOMES: usize = 16;
+
+/// Handle to a background task keeping a session alive.
+///
+/// Each renewal attempt is logged and its outcome queued for [`Self::next_renewal`], so
+/// failures can be acted on before the session lapses. Up to
+/// [`SESSION_RENEWAL_OUTCOMES`] outcomes are kept; while that many are unread, newer ones
+/// are only logged. The task stops on [`Self::stop`] or when the handle is dropped.
+#[derive(Debug)]
+pub struct SessionRenewal {
+    outcomes: mpsc::Receiver<Result<SessionAck>>,
+    task: JoinHandle<()>,
+}
+
+impl SessionRenewal {
+    /// Renew `session_id`, currently expiring at `expiry` (milliseconds), `lead_time`
+    /// before each expiry.
+    pub(crate) fn spawn(
+        agent: Agent,
+        session_id: String,
+        expiry: u64,
+        lead_time: Duration,
+    ) -> Self {
+        let (tx, outcomes) = mpsc::channel(SESSION_RENEWAL_OUTCOMES);
+        let lead_ms = lead_time.as_millis() as u64;
+        let retry_delay = SESSION_RENEWAL_RETRY_DELAY.min(lead_time / 4);
+        let task = tokio::spawn(async move {
+            let mut expiry = expiry;
+            loop {
+                let renew_at = expiry.saturating_sub(lead_ms);
+                let now = now_ms();
+                if renew_at > now {
+                    sleep(Duration::from_millis(renew_at - now)).await;
+                }
+
+                let timestamp_ms = now_ms();
+                let expires_at = timestamp_ms + SESSION_RENEWAL_EXTENSION.as_millis() as u64;
+                let result = agent
+                    .update_session(&session_id, None, timestamp_ms, expires_at, &[])
+                    .await;
+                match &result {
+                    Ok(ack) => {
+                        info!("🔁 Renewed session {} until {}", session_id, ack.expiry);
+                        expiry = ack.expiry;
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Failed to renew session {} (expires at {}): {}",
+                            session_id, expiry, e
+                        );
+                    }
+                }
+                let failed = result.is_err();
+                // A handle that is not being read must not stall the renewals
+                if let Err(mpsc::error::TrySendError::Closed(_)) = tx.try_send(result) {
+                    break;
+                }
+                if failed {
+                    sleep(retry_delay).await;
+                }
+            }
+        });
+        Self { outcomes, task }
+    }
+
+    /// Outcome of the next renewal attempt, `None` once the task has stopped
+    pub async fn next_renewal(&mut self) -> Option<Result<SessionAck>> {
+        self.outcomes.recv().await
+    }
+
+    /// Whether the renewal task is still running
+    pub fn is_running(&self) -> bool {
+        !self.task.is_finished()
+    }
+
+    /// Stop renewing; the session keeps its current expiry
+    pub fn stop(self) {}
+}
+
+impl Drop for SessionRenewal {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
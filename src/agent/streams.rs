@@ -0,0 +1,102 @@
+//! `Stream` adapters over the WebSocket message receiver, see
+//! [`crate::Agent::message_stream`] and [`crate::Agent::trade_stream`]
+
+use futures_util::{stream, Stream, StreamExt};
+
+use crate::{
+    types::{TradeResult, WebSocketMessage},
+    websocket::MessageReceiver,
+};
+
+/// Every message received on `rx`, as a [`Stream`].
+pub(crate) fn message_stream(
+    rx: MessageReceiver,
+) -> impl Stream<Item = WebSocketMessage> + Send + Unpin {
+    rx
+}
+
+/// The trades for `market_id` carried by messages on `rx`, one item per trade.
+pub(crate) fn trade_stream(
+    rx: MessageReceiver,
+    market_id: String,
+) -> impl Stream<Item = TradeResult> + Send + Unpin {
+    message_stream(rx).flat_map(move |msg| stream::iter(trades_for(msg, &market_id)))
+}
+
+/// Trades for `market_id` carried by `msg`; empty for any other message.
+fn trades_for(msg: WebSocketMessage, market_id: &str) -> Vec<TradeResult> {
+    match msg {
+        WebSocketMessage::TradeMsg { params, .. } => params
+            .result
+            .into_iter()
+            .filter(|t| t.market_id == market_id)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::websocket::queue::tests::message_channel;
+
+    fn trade_msg(trades: &[(&str, &str)]) -> WebSocketMessage {
+        let result: Vec<_> = trades
+            .iter()
+            .map(|(trade_id, market_id)| {
+                serde_json::json!({
+                    "tradeId": trade_id,
+                    "marketId": market_id,
+                    "price": "1.5",
+                    "quantity": "10",
+                    "buyOrderId": "b",
+                    "sellOrderId": "s",
+                    "createdAt": 1,
+                    "isBuyerMaker": false
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "method": "subscription",
+            "params": { "channel": "trade@1_2", "result": result }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn message_stream_yields_every_message_then_ends() {
+        let (tx, rx) = message_channel();
+        let mut messages = message_stream(rx);
+        tx.send_message(WebSocketMessage::Disconnected).await;
+        tx.send_message(trade_msg(&[("t1", "1_2")])).await;
+        drop(tx);
+
+        assert!(matches!(
+            messages.next().await,
+            Some(WebSocketMessage::Disconnected)
+        ));
+        assert!(matches!(
+            messages.next().await,
+            Some(WebSocketMessage::TradeMsg { .. })
+        ));
+        assert!(messages.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn trade_stream_keeps_only_trades_of_the_market() {
+        let (tx, rx) = message_channel();
+        let mut trades = trade_stream(rx, "1_2".to_string());
+        tx.send_message(trade_msg(&[("t1", "1_2"), ("x", "3_2"), ("t2", "1_2")]))
+            .await;
+        tx.send_message(WebSocketMessage::Disconnected).await;
+        tx.send_message(trade_msg(&[("y", "3_2")])).await;
+        tx.send_message(trade_msg(&[("t3", "1_2")])).await;
+        drop(tx);
+
+        let mut ids = Vec::new();
+        while let Some(trade) = trades.next().await {
+            ids.push(trade.trade_id);
+        }
+        assert_eq!(ids, vec!["t1", "t2", "t3"]);
+    }
+}
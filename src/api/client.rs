@@ -4,72 +4,221 @@ pub use crate::types::account::{Transfer, TransferHistoryQuery};
 use crate::{
     error::{AlphaSecError, Result},
     signer::{AlphaSecSigner, Config},
+    telemetry,
     types::{account::*, api::*, market::*, orders::*},
 };
 use reqwest::Client as HttpClient;
 use serde_json::Value;
-use std::time::Duration;
-use tracing::{debug, info};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::OnceCell;
+use tracing::{debug, info, warn};
+
+/// Header carrying a fresh UUID per request, for correlating with server-side logs
+pub(crate) const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Build the HTTP client shared by the spot and perp REST clients: the configured
+/// timeout, proxy and TLS settings, a JSON `Content-Type` and the configured
+/// `User-Agent`.
+pub(crate) fn build_http_client(config: &Config) -> Result<HttpClient> {
+    if let Some(client) = &config.http_client {
+        return Ok(client.clone());
+    }
+    let user_agent = reqwest::header::HeaderValue::from_str(&config.user_agent)
+        .map_err(|_| AlphaSecError::config("Invalid user agent"))?;
+    let mut builder = HttpClient::builder();
+    // Without an explicit proxy reqwest falls back to the HTTP(S)_PROXY variables
+    if let Some(proxy) = &config.proxy {
+        let proxy = reqwest::Proxy::all(proxy.as_str())
+            .map_err(|e| AlphaSecError::config(format!("Invalid proxy URL: {}", e)))?;
+        builder = builder.proxy(proxy);
+    }
+    for pem in &config.tls.root_certs {
+        let cert = reqwest::Certificate::from_pem(pem)
+            .map_err(|e| AlphaSecError::config(format!("Invalid root certificate: {}", e)))?;
+        builder = builder.add_root_certificate(cert);
+    }
+    if config.tls.accept_invalid_certs {
+        warn!("TLS certificate verification is disabled for REST requests");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    builder
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .default_headers({
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(
+                reqwest::header::CONTENT_TYPE,
+                reqwest::header::HeaderValue::from_static("application/json"),
+            );
+            headers.insert(reqwest::header::USER_AGENT, user_agent);
+            headers
+        })
+        .build()
+        .map_err(|e| AlphaSecError::config(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// Longest server-advertised `Retry-After` delay a rate-limited GET waits out before
+/// retrying; longer delays are returned to the caller as `RateLimited` instead
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
 
 /// AlphaSec API client
 #[derive(Debug, Clone)]
 pub struct ApiClient {
     /// HTTP client
     http_client: HttpClient,
-    /// Base API URL
+    /// Base API URL, without a trailing slash
     base_url: String,
+    /// Path prefix of every route, e.g. `/api/v1`
+    api_prefix: String,
     /// Signer for authenticated requests
     signer: Option<AlphaSecSigner>,
-    /// Token metadata for conversions
-    token_metadata: Option<TokenMetadata>,
+    /// Token metadata for conversions, loaded at most once (shared across clones)
+    token_metadata: Arc<OnceCell<TokenMetadata>>,
+    /// Retries for rate-limited GET requests
+    max_retries: u32,
+    /// Decimals merged over every fetched token list
+    token_decimals: HashMap<String, u8>,
 }
 
 impl ApiClient {
     /// Create a new API client
+    ///
+    /// Fails with a configuration error if `config.api_url` is not an `http(s)` URL
+    /// with a host and without a query or fragment. A path prefix in it (e.g. behind a
+    /// gateway) is kept in front of every route.
     pub fn new(config: &Config, signer: Option<AlphaSecSigner>) -> Result<Self> {
-        let http_client = HttpClient::builder()
-            .timeout(Duration::from_secs(config.timeout_secs))
-            .default_headers({
-                let mut headers = reqwest::header::HeaderMap::new();
-                headers.insert(
-                    reqwest::header::CONTENT_TYPE,
-                    reqwest::header::HeaderValue::from_static("application/json"),
-                );
-                headers
-            })
-            .build()
-            .map_err(|e| AlphaSecError::config(format!("Failed to create HTTP client: {}", e)))?;
+        super::utils::validate_api_url(&config.api_url)?;
+        let http_client = build_http_client(config)?;
+        Ok(Self::with_client(config, signer, http_client))
+    }
 
-        Ok(Self {
+    /// Create a client that sends its requests through `http_client`
+    ///
+    /// Clones of a `reqwest::Client` share one connection pool, so passing the same
+    /// client to several `ApiClient`s pools their connections. Its timeout and default
+    /// headers are used as-is; see [`Config::with_http_client`]. Unlike [`Self::new`],
+    /// this does not validate `config.api_url`.
+    pub fn with_client(
+        config: &Config,
+        signer: Option<AlphaSecSigner>,
+        http_client: HttpClient,
+    ) -> Self {
+        Self {
             http_client,
-            base_url: config.api_url.to_string(),
+            base_url: super::utils::api_base_url(&config.api_url),
+            api_prefix: config.api_prefix.clone(),
             signer,
-            token_metadata: None,
-        })
+            token_metadata: Arc::default(),
+            max_retries: config.max_retries,
+            token_decimals: config.token_decimals.clone(),
+        }
     }
 
     /// Initialize token metadata
+    ///
+    /// Fetches the token list even if metadata is already loaded, replacing it.
     pub async fn initialize_metadata(&mut self) -> Result<()> {
-        let tokens = self.get_tokens().await?;
-        self.token_metadata = Some(TokenMetadata::from_tokens(&tokens));
-        info!("✅ Token metadata initialized with {} tokens", tokens.len());
+        let metadata = self.fetch_metadata().await?;
+        self.token_metadata = Arc::new(OnceCell::new_with(Some(metadata)));
         Ok(())
     }
 
-    /// Get token metadata
+    /// Get token metadata, `None` if it has not been loaded
     pub fn token_metadata(&self) -> Option<&TokenMetadata> {
-        self.token_metadata.as_ref()
+        self.token_metadata.get()
     }
 
-    /// Make a GET request
-    async fn get(&self, path: &str, params: Option<&[(&str, &str)]>) -> Result<Value> {
-        let mut url = if self.base_url.ends_with('/') && path.starts_with('/') {
-            format!("{}{}", self.base_url.trim_end_matches('/'), path)
-        } else if !self.base_url.ends_with('/') && !path.starts_with('/') {
-            format!("{}/{}", self.base_url, path)
+    /// Get token metadata, fetching it first if it has not been loaded
+    ///
+    /// Fails with a configuration error naming the cause if the fetch fails; the next
+    /// call tries again.
+    pub async fn metadata(&self) -> Result<&TokenMetadata> {
+        self.token_metadata
+            .get_or_try_init(|| self.fetch_metadata())
+            .await
+            .map_err(|e| AlphaSecError::config(format!("Token metadata unavailable: {}", e)))
+    }
+
+    async fn fetch_metadata(&self) -> Result<TokenMetadata> {
+        let tokens = self.get_tokens().await?;
+        info!("✅ Token metadata initialized with {} tokens", tokens.len());
+        let mut metadata = TokenMetadata::from_tokens(&tokens);
+        metadata.apply_decimal_overrides(&self.token_decimals);
+        Ok(metadata)
+    }
+
+    /// Wire market ID for `market`: a `BASE/QUOTE` symbol is converted through the
+    /// token metadata (loading it if needed), anything else must be a market ID.
+    /// Malformed symbols and IDs are rejected before the metadata is loaded.
+    pub(crate) async fn market_id(&self, market: &str) -> Result<String> {
+        if market.contains('/') {
+            super::split_market(market)?;
+            self.metadata().await?.market_to_market_id(market)
         } else {
-            format!("{}{}", self.base_url, path)
-        };
+            market.parse::<MarketId>()?;
+            Ok(market.to_string())
+        }
+    }
+
+    /// Install token metadata without fetching it
+    #[cfg(test)]
+    pub(crate) fn set_token_metadata(&mut self, metadata: TokenMetadata) {
+        self.token_metadata = Arc::new(OnceCell::new_with(Some(metadata)));
+    }
+
+    /// Full URL of `route`, a path below the configured API prefix such as `/market`
+    fn url(&self, route: &str) -> String {
+        let route = route.strip_prefix('/').unwrap_or(route);
+        super::utils::join_url(&self.base_url, &format!("{}/{}", self.api_prefix, route))
+    }
+
+    /// Make a GET request
+    ///
+    /// `timeout` overrides the client-wide `timeout_secs` for this request only.
+    /// Rate-limited responses are retried up to `max_retries` times, waiting for the
+    /// server's `Retry-After` delay (or an exponential backoff when it is absent). A
+    /// `Retry-After` above [`MAX_RETRY_AFTER`] is not waited out: the `RateLimited`
+    /// error is returned straight away.
+    async fn get(
+        &self,
+        path: &str,
+        params: Option<&[(&str, &str)]>,
+        timeout: Option<Duration>,
+    ) -> Result<Value> {
+        let mut attempt = 0;
+        loop {
+            match self.get_once(path, params, timeout).await {
+                Err(AlphaSecError::RateLimited { retry_after })
+                    if attempt < self.max_retries
+                        && retry_after.is_none_or(|delay| delay <= MAX_RETRY_AFTER) =>
+                {
+                    let delay = retry_after.unwrap_or(Duration::from_secs(1 << attempt.min(5)));
+                    warn!(
+                        "GET {} rate limited, retrying in {:?} ({}/{})",
+                        path,
+                        delay,
+                        attempt + 1,
+                        self.max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Make a single GET request attempt
+    async fn get_once(
+        &self,
+        path: &str,
+        params: Option<&[(&str, &str)]>,
+        timeout: Option<Duration>,
+    ) -> Result<Value> {
+        let mut url = self.url(path);
 
         if let Some(params) = params {
             if !params.is_empty() {
@@ -87,62 +236,73 @@ impl ApiClient {
             }
         }
 
-        debug!("GET {}", url);
-        let response = self.http_client.get(&url).send().await?;
-
-        if response.status().is_success() {
-            let json: Value = response.json().await?;
-            Ok(json)
-        } else {
-            let status_code = response.status().as_u16() as i32;
-            let error_text = response.text().await.unwrap_or_default();
-            Err(AlphaSecError::api(status_code, error_text))
+        let request_id = uuid::Uuid::new_v4().to_string();
+        debug!("GET {} ({}: {})", url, REQUEST_ID_HEADER, request_id);
+        let mut request = self
+            .http_client
+            .get(&url)
+            .header(REQUEST_ID_HEADER, &request_id);
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
         }
+        let started = telemetry::start();
+        let result = send_request(request).await;
+        telemetry::http_request("GET", started, &result);
+        result
     }
 
     /// Make a POST request
     async fn post(&self, path: &str, params: Option<Value>) -> Result<Value> {
-        let url = if self.base_url.ends_with('/') && path.starts_with('/') {
-            format!("{}{}", self.base_url.trim_end_matches('/'), path)
-        } else if !self.base_url.ends_with('/') && !path.starts_with('/') {
-            format!("{}/{}", self.base_url, path)
-        } else {
-            format!("{}{}", self.base_url, path)
-        };
+        let url = self.url(path);
 
-        debug!("POST {} with params: {:?}", url, params);
+        let request_id = uuid::Uuid::new_v4().to_string();
+        debug!(
+            "POST {} ({}: {}) with params: {:?}",
+            url, REQUEST_ID_HEADER, request_id, params
+        );
         let mut request = self
             .http_client
             .post(&url)
-            .header("Content-Type", "application/json");
+            .header("Content-Type", "application/json")
+            .header(REQUEST_ID_HEADER, &request_id);
 
         if let Some(params) = params {
             request = request.body(params.to_string());
         }
 
-        let response = request.send().await?;
+        let started = telemetry::start();
+        let result = send_request(request).await;
+        telemetry::http_request("POST", started, &result);
+        result
+    }
+
+    // === Server Metadata ===
 
-        if response.status().is_success() {
-            let json: Value = response.json().await?;
-            Ok(json)
-        } else {
-            let status_code = response.status().as_u16() as i32;
-            let error_text = response.text().await.unwrap_or_default();
-            Err(AlphaSecError::api(status_code, error_text))
+    /// Get the protocol version and optional features the server reports
+    ///
+    /// A server without the `/meta` endpoint (HTTP 404) yields
+    /// `Capabilities::default()`, which reports nothing.
+    pub async fn get_capabilities(&self) -> Result<Capabilities> {
+        let response = match self.get("/meta", None, None).await {
+            Ok(response) => response,
+            Err(e) if e.api_code() == Some(404) => return Ok(Capabilities::default()),
+            Err(e) => return Err(e),
+        };
+        let result = &response["result"];
+        if result.is_null() {
+            return Ok(Capabilities::default());
         }
+        serde_json::from_value(result.clone()).map_err(|e| {
+            AlphaSecError::api(500, format!("Invalid server metadata response: {}", e))
+        })
     }
 
     // === Public Market Data API ===
 
     /// Get all markets
     pub async fn get_market_list(&self) -> Result<Vec<Market>> {
-        let response = self.get("/api/v1/market", None).await?;
-        let markets = response["result"]
-            .as_array()
-            .ok_or_else(|| AlphaSecError::api(500, "Invalid market list response format"))?
-            .iter()
-            .map(|market| serde_json::from_value(market.clone()).map_err(AlphaSecError::Json))
-            .collect::<Result<Vec<Market>>>()?;
+        let response = self.get("/market", None, None).await?;
+        let markets = list_result(&response["result"], "market list")?;
         Ok(markets)
     }
 
@@ -152,17 +312,32 @@ impl ApiClient {
         market: &str,
         limit: Option<u32>,
     ) -> Result<crate::types::market::Depth> {
-        let market_id = if let Some(metadata) = &self.token_metadata {
-            metadata.market_to_market_id(market)?
-        } else {
-            market.to_string()
-        };
+        self.depth(market, limit, None).await
+    }
+
+    /// [`Self::get_depth`] with `timeout` in place of the configured request timeout
+    pub async fn get_depth_with_timeout(
+        &self,
+        market: &str,
+        limit: Option<u32>,
+        timeout: Duration,
+    ) -> Result<crate::types::market::Depth> {
+        self.depth(market, limit, Some(timeout)).await
+    }
+
+    async fn depth(
+        &self,
+        market: &str,
+        limit: Option<u32>,
+        timeout: Option<Duration>,
+    ) -> Result<crate::types::market::Depth> {
+        let market_id = self.market_id(market).await?;
         let limit_str = limit.unwrap_or(100).to_string();
         let params = [
             ("marketId", market_id.as_str()),
             ("limit", limit_str.as_str()),
         ];
-        let response = self.get("/api/v1/market/depth", Some(&params)).await?;
+        let response = self.get("/market/depth", Some(&params), timeout).await?;
         let depths: crate::types::market::Depth =
             serde_json::from_value(response["result"].clone()).map_err(AlphaSecError::Json)?;
         Ok(depths)
@@ -170,76 +345,146 @@ impl ApiClient {
 
     /// Get all tickers
     pub async fn get_tickers(&self) -> Result<Vec<Ticker>> {
-        let response = self.get("/api/v1/market/ticker", None).await?;
-        let tickers = response["result"]
-            .as_array()
-            .ok_or_else(|| AlphaSecError::api(500, "Invalid tickers response format"))?
-            .iter()
-            .map(|ticker| serde_json::from_value(ticker.clone()).map_err(AlphaSecError::Json))
-            .collect::<Result<Vec<Ticker>>>()?;
+        let response = self.get("/market/ticker", None, None).await?;
+        let tickers = list_result(&response["result"], "tickers")?;
         Ok(tickers)
     }
 
     /// Get ticker for specific market
     pub async fn get_ticker(&self, market: &str) -> Result<Ticker> {
-        let market_id = if let Some(metadata) = &self.token_metadata {
-            metadata.market_to_market_id(market)?
-        } else {
-            market.to_string()
-        };
+        let market_id = self.market_id(market).await?;
 
         let params = [("marketId", market_id.as_str())];
-        let response = self.get("/api/v1/market/ticker", Some(&params)).await?;
-
-        let ticker_array = response["result"]
-            .as_array()
-            .ok_or_else(|| AlphaSecError::api(500, "Invalid ticker response format"))?;
-
-        if ticker_array.is_empty() {
-            return Err(AlphaSecError::not_found(format!(
-                "Ticker not found for market: {}",
-                market
-            )));
-        }
+        let response = self.get("/market/ticker", Some(&params), None).await?;
 
-        let ticker =
-            serde_json::from_value(ticker_array[0].clone()).map_err(AlphaSecError::Json)?;
-        Ok(ticker)
+        list_result(&response["result"], "ticker")?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                AlphaSecError::not_found(format!("Ticker not found for market: {}", market))
+            })
     }
 
     /// Get all tokens
     pub async fn get_tokens(&self) -> Result<Vec<Token>> {
-        let response = self.get("/api/v1/market/tokens", None).await?;
-        let tokens = response["result"]
-            .as_array()
-            .ok_or_else(|| AlphaSecError::api(500, "Invalid tokens response format"))?
-            .iter()
-            .map(|token| serde_json::from_value(token.clone()).map_err(AlphaSecError::Json))
-            .collect::<Result<Vec<Token>>>()?;
+        let response = self.get("/market/tokens", None, None).await?;
+        let tokens = list_result(&response["result"], "tokens")?;
         Ok(tokens)
     }
 
+    /// Check that the API is reachable and answering
+    ///
+    /// Requests the token list, the smallest public endpoint, and discards it.
+    pub async fn health_check(&self) -> Result<()> {
+        self.get("/market/tokens", None, None).await?;
+        Ok(())
+    }
+
     /// Get recent trades
     pub async fn get_trades(&self, market: &str, limit: Option<u32>) -> Result<Vec<Trade>> {
-        let market_id = if let Some(metadata) = &self.token_metadata {
-            metadata.market_to_market_id(market)?
-        } else {
-            market.to_string()
-        };
+        self.recent_trades(market, limit, None).await
+    }
+
+    /// [`Self::get_trades`] with `timeout` in place of the configured request timeout
+    pub async fn get_trades_with_timeout(
+        &self,
+        market: &str,
+        limit: Option<u32>,
+        timeout: Duration,
+    ) -> Result<Vec<Trade>> {
+        self.recent_trades(market, limit, Some(timeout)).await
+    }
+
+    async fn recent_trades(
+        &self,
+        market: &str,
+        limit: Option<u32>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Trade>> {
+        let market_id = self.market_id(market).await?;
 
         let limit_str = limit.unwrap_or(100).to_string();
         let params = [
             ("marketId", market_id.as_str()),
             ("limit", limit_str.as_str()),
         ];
-        let response = self.get("/api/v1/market/trades", Some(&params)).await?;
+        self.fetch_trades(&params, timeout).await
+    }
 
-        let trades = response["result"]
-            .as_array()
-            .ok_or_else(|| AlphaSecError::api(500, "Invalid trades response format"))?
-            .iter()
-            .map(|trade| serde_json::from_value(trade.clone()).map_err(AlphaSecError::Json))
-            .collect::<Result<Vec<Trade>>>()?;
+    /// Get every trade in `market` created between `from_msec` and `to_msec` (inclusive),
+    /// oldest first
+    ///
+    /// Pages of `limit` trades (default [`TRADES_PAGE_LIMIT`]) are walked newest to oldest,
+    /// relying only on the server answering a `limit` with the newest trades at or before
+    /// `endMsec`: the first page ends at `to_msec` and each following one at the oldest
+    /// trade seen so far. `endMsec` is inclusive, so trades of that millisecond come back
+    /// again and are told apart by `tradeId`. The walk stops at the first page that is
+    /// short or reaches before `from_msec`, which is applied client-side. More than
+    /// `limit` trades in a single millisecond cannot be stepped past and are an error.
+    pub async fn get_trades_between(
+        &self,
+        market: &str,
+        from_msec: u64,
+        to_msec: u64,
+        limit: Option<u32>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Trade>> {
+        if from_msec > to_msec {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "Trade window starts at {} after it ends at {}",
+                from_msec, to_msec
+            )));
+        }
+        let market_id = self.market_id(market).await?;
+        let limit = limit.unwrap_or(TRADES_PAGE_LIMIT);
+        let limit_str = limit.to_string();
+
+        let mut seen = HashSet::new();
+        let mut trades = Vec::new();
+        let mut cursor = to_msec;
+        loop {
+            let end_str = cursor.to_string();
+            let params = [
+                ("marketId", market_id.as_str()),
+                ("limit", limit_str.as_str()),
+                ("endMsec", end_str.as_str()),
+            ];
+            let mut page = self.fetch_trades(&params, timeout).await?;
+            page.sort_by_key(|t| std::cmp::Reverse(t.created_at));
+            let full = page.len() >= limit as usize;
+            let oldest = page.last().map(|t| t.created_at);
+            let before = trades.len();
+            trades.extend(page.into_iter().filter(|t| {
+                (from_msec..=to_msec).contains(&t.created_at) && seen.insert(t.trade_id.clone())
+            }));
+
+            match oldest {
+                Some(oldest) if full && oldest >= from_msec => {
+                    if trades.len() == before {
+                        // A full page of trades already seen, all sharing the cursor's
+                        // millisecond, which endMsec alone cannot step past.
+                        return Err(AlphaSecError::generic(format!(
+                            "More than {} trades at {}ms; retry with a larger limit",
+                            limit, cursor
+                        )));
+                    }
+                    cursor = oldest;
+                }
+                _ => break,
+            }
+        }
+        trades.sort_by_key(|t| t.created_at);
+        Ok(trades)
+    }
+
+    async fn fetch_trades(
+        &self,
+        params: &[(&str, &str)],
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Trade>> {
+        let response = self.get("/market/trades", Some(params), timeout).await?;
+
+        let trades = list_result(&response["result"], "trades")?;
         Ok(trades)
     }
 
@@ -248,33 +493,49 @@ impl ApiClient {
     /// Get account balance
     pub async fn get_balance(&self, address: &str) -> Result<Balances> {
         let params = [("address", address)];
-        let response = self.get("/api/v1/wallet/balance", Some(&params)).await?;
-        let balances: Balances =
-            serde_json::from_value(response["result"].clone()).map_err(AlphaSecError::Json)?;
-        Ok(balances)
+        let response = self.get("/wallet/balance", Some(&params), None).await?;
+        let result = &response["result"];
+        if !(result.is_null() || result.is_object()) {
+            return Err(AlphaSecError::api(
+                500,
+                format!(
+                    "Invalid balance response format: expected an object, got {}",
+                    json_kind(result)
+                ),
+            ));
+        }
+        let block_number = match result.get("blockNumber") {
+            Some(block_number) => {
+                serde_json::from_value(block_number.clone()).map_err(AlphaSecError::Json)?
+            }
+            None => 0,
+        };
+        Ok(Balances {
+            balances: list_result(&result["balances"], "balance")?,
+            block_number,
+        })
+    }
+
+    /// Get the fee tier of an account
+    pub async fn get_fee_tier(&self, address: &str) -> Result<FeeTier> {
+        let params = [("address", address)];
+        let response = self.get("/wallet/fee", Some(&params), None).await?;
+        serde_json::from_value(response["result"].clone()).map_err(AlphaSecError::Json)
     }
 
     /// Get sessions
     pub async fn get_sessions(&self, address: &str) -> Result<Vec<Session>> {
         let params = [("address", address)];
-        let response = self.get("/api/v1/wallet/session", Some(&params)).await?;
-        // info!("🔍 Sessions response: {:?}", response);
-        if response["result"].is_null() {
-            return Ok(vec![]);
-        }
-        let sessions = response["result"]
-            .as_array()
-            .ok_or_else(|| AlphaSecError::api(500, "Invalid sessions response format"))?
-            .iter()
-            .map(|session| serde_json::from_value(session.clone()).map_err(AlphaSecError::Json))
-            .collect::<Result<Vec<Session>>>()?;
+        let response = self.get("/wallet/session", Some(&params), None).await?;
+        let sessions = list_result(&response["result"], "sessions")?;
         Ok(sessions)
     }
 
     /// Get transfer history for a wallet address on the L2 network
     ///
     /// # Arguments
-    /// * `query` - Query parameters including address, optional token_id, time range, and limit
+    /// * `query` - Query parameters including address, optional token_id, time range, and limit;
+    ///   only the fields that are set are sent
     ///
     /// # Returns
     /// * `Result<Vec<Transfer>>` - List of transfer records
@@ -282,56 +543,124 @@ impl ApiClient {
         &self,
         query: &TransferHistoryQuery,
     ) -> Result<Vec<Transfer>> {
-        let address_str = query.address.clone();
-        let token_id_str = query.token_id.map(|id| id.to_string());
-        let from_str = query.from_msec.map(|ts| ts.to_string());
-        let to_str = query.to_msec.map(|ts| ts.to_string());
-        let limit = query.limit.unwrap_or(100).min(500);
-        let limit_str = limit.to_string();
+        self.transfer_history(query, None).await
+    }
+
+    /// [`Self::get_transfer_history`] with `timeout` in place of the configured request timeout
+    pub async fn get_transfer_history_with_timeout(
+        &self,
+        query: &TransferHistoryQuery,
+        timeout: Duration,
+    ) -> Result<Vec<Transfer>> {
+        self.transfer_history(query, Some(timeout)).await
+    }
 
-        let mut params: Vec<(&str, &str)> = vec![("address", address_str.as_str())];
+    async fn transfer_history(
+        &self,
+        query: &TransferHistoryQuery,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Transfer>> {
+        let mut params: Vec<(&str, &str)> = vec![("address", query.address.as_str())];
 
-        if let Some(ref token_id) = token_id_str {
-            params.push(("token_id", token_id.as_str()));
+        let token_id_str;
+        if let Some(token_id) = query.token_id {
+            token_id_str = token_id.to_string();
+            params.push(("tokenId", token_id_str.as_str()));
         }
 
-        if let Some(ref from) = from_str {
-            params.push(("from", from.as_str()));
+        let from_str;
+        if let Some(from_msec) = query.from_msec {
+            from_str = from_msec.to_string();
+            params.push(("fromMsec", from_str.as_str()));
         }
 
-        if let Some(ref to) = to_str {
-            params.push(("to", to.as_str()));
+        let to_str;
+        if let Some(to_msec) = query.to_msec {
+            to_str = to_msec.to_string();
+            params.push(("toMsec", to_str.as_str()));
         }
 
-        params.push(("limit", limit_str.as_str()));
-
-        let response = self.get("/api/v1/wallet/transfer", Some(&params)).await?;
-
-        if response["result"].is_null() {
-            return Ok(vec![]);
+        let limit_str;
+        if let Some(limit) = query.limit {
+            limit_str = limit.min(500).to_string();
+            params.push(("limit", limit_str.as_str()));
         }
 
-        let transfers = response["result"]
-            .as_array()
-            .ok_or_else(|| AlphaSecError::api(500, "Invalid transfer history response format"))?
-            .iter()
-            .map(|transfer| serde_json::from_value(transfer.clone()).map_err(AlphaSecError::Json))
-            .collect::<Result<Vec<Transfer>>>()?;
+        let response = self.get("/wallet/transfer", Some(&params), timeout).await?;
+
+        let transfers = list_result(&response["result"], "transfer history")?;
 
         Ok(transfers)
     }
 
+    /// Get the stage of a withdrawal by its ID or L2 transaction hash
+    ///
+    /// `result` may be the status itself or an object with a `status` field. An
+    /// unknown withdrawal is [`AlphaSecError::NotFound`]; an ID that cannot be a path
+    /// segment is rejected before any request is made.
+    pub async fn get_withdrawal_status(
+        &self,
+        withdrawal_id_or_tx: &str,
+    ) -> Result<WithdrawalStatus> {
+        if withdrawal_id_or_tx.is_empty()
+            || !withdrawal_id_or_tx
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "Invalid withdrawal ID: {:?}",
+                withdrawal_id_or_tx
+            )));
+        }
+
+        let path = format!("/wallet/withdraw/{}", withdrawal_id_or_tx);
+        let response = match self.get(&path, None, None).await {
+            Ok(response) => response,
+            Err(e) if e.api_code() == Some(404) => {
+                return Err(AlphaSecError::not_found(format!(
+                    "Withdrawal {} not found",
+                    withdrawal_id_or_tx
+                )))
+            }
+            Err(e) => return Err(e),
+        };
+        let status = match &response["result"] {
+            Value::Object(result) => result.get("status").cloned().unwrap_or(Value::Null),
+            result => result.clone(),
+        };
+        if status.is_null() {
+            return Err(AlphaSecError::not_found(format!(
+                "Withdrawal {} not found",
+                withdrawal_id_or_tx
+            )));
+        }
+        serde_json::from_value(status).map_err(AlphaSecError::Json)
+    }
+
     /// Get open orders
     pub async fn get_open_orders(&self, query: &OrdersQuery) -> Result<Vec<Order>> {
+        self.open_orders(query, None).await
+    }
+
+    /// [`Self::get_open_orders`] with `timeout` in place of the configured request timeout
+    pub async fn get_open_orders_with_timeout(
+        &self,
+        query: &OrdersQuery,
+        timeout: Duration,
+    ) -> Result<Vec<Order>> {
+        self.open_orders(query, Some(timeout)).await
+    }
+
+    async fn open_orders(
+        &self,
+        query: &OrdersQuery,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Order>> {
         let mut params = vec![("address", query.address.as_str())];
 
         let market_id;
         if let Some(ref market) = query.market {
-            market_id = if let Some(metadata) = &self.token_metadata {
-                metadata.market_to_market_id(market)?
-            } else {
-                market.clone()
-            };
+            market_id = self.market_id(market).await?;
             params.push(("marketId", market_id.as_str()));
         }
 
@@ -341,31 +670,64 @@ impl ApiClient {
             params.push(("limit", limit_str.as_str()));
         }
 
-        let response = self.get("/api/v1/order/open", Some(&params)).await?;
-        if response["result"].is_null() {
-            return Ok(vec![]);
+        let from_str;
+        if let Some(from_msec) = query.from_msec {
+            from_str = from_msec.to_string();
+            params.push(("fromMsec", from_str.as_str()));
         }
 
-        let orders = response["result"]
-            .as_array()
-            .ok_or_else(|| AlphaSecError::api(500, "Invalid open orders response format"))?
-            .iter()
-            .map(|order| serde_json::from_value(order.clone()).map_err(AlphaSecError::Json))
-            .collect::<Result<Vec<Order>>>()?;
+        let end_str;
+        if let Some(end_msec) = query.end_msec {
+            end_str = end_msec.to_string();
+            params.push(("endMsec", end_str.as_str()));
+        }
+
+        let response = self.get("/order/open", Some(&params), timeout).await?;
+        let orders = list_result(&response["result"], "open orders")?;
         Ok(orders)
     }
 
+    /// Get one page of open orders, continuing from `cursor` (see [`Cursor`])
+    ///
+    /// Paged exactly like [`Self::get_order_history_page`]: pages hold `query.limit` rows
+    /// (default [`ORDER_HISTORY_PAGE_LIMIT`]), newest first, and the returned cursor is
+    /// `None` on the last page.
+    pub async fn get_open_orders_page(
+        &self,
+        query: &OrdersQuery,
+        cursor: Option<Cursor>,
+        timeout: Option<Duration>,
+    ) -> Result<(Vec<Order>, Option<Cursor>)> {
+        let page = self
+            .open_orders(&page_query(query, cursor), timeout)
+            .await?;
+        cursor_page(page, query, cursor)
+    }
+
     /// Get order history
     pub async fn get_filled_canceled_orders(&self, query: &OrdersQuery) -> Result<Vec<Order>> {
+        self.filled_canceled_orders(query, None).await
+    }
+
+    /// [`Self::get_filled_canceled_orders`] with `timeout` in place of the configured request timeout
+    pub async fn get_filled_canceled_orders_with_timeout(
+        &self,
+        query: &OrdersQuery,
+        timeout: Duration,
+    ) -> Result<Vec<Order>> {
+        self.filled_canceled_orders(query, Some(timeout)).await
+    }
+
+    async fn filled_canceled_orders(
+        &self,
+        query: &OrdersQuery,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Order>> {
         let mut params = vec![("address", query.address.as_str())];
 
         let market_id;
         if let Some(ref market) = query.market {
-            market_id = if let Some(metadata) = &self.token_metadata {
-                metadata.market_to_market_id(market)?
-            } else {
-                market.clone()
-            };
+            market_id = self.market_id(market).await?;
             params.push(("marketId", market_id.as_str()));
         }
 
@@ -375,33 +737,95 @@ impl ApiClient {
             params.push(("limit", limit_str.as_str()));
         }
 
-        let response = self.get("/api/v1/order/", Some(&params)).await?;
+        let from_str;
+        if let Some(from_msec) = query.from_msec {
+            from_str = from_msec.to_string();
+            params.push(("fromMsec", from_str.as_str()));
+        }
 
-        if response["result"].is_null() {
-            return Ok(vec![]);
+        let end_str;
+        if let Some(end_msec) = query.end_msec {
+            end_str = end_msec.to_string();
+            params.push(("endMsec", end_str.as_str()));
         }
 
-        let orders = response["result"]
-            .as_array()
-            .ok_or_else(|| AlphaSecError::api(500, "Invalid orders response format"))?
-            .iter()
-            .map(|order| serde_json::from_value(order.clone()).map_err(AlphaSecError::Json))
-            .collect::<Result<Vec<Order>>>()?;
+        let response = self.get("/order/", Some(&params), timeout).await?;
+
+        let orders = list_result(&response["result"], "orders")?;
         Ok(orders)
     }
 
+    /// Get one page of order history, continuing from `cursor` (see [`Cursor`])
+    ///
+    /// Pages hold `query.limit` rows (default [`ORDER_HISTORY_PAGE_LIMIT`]) and run
+    /// newest to oldest. This relies only on the server answering a `limit` with the
+    /// newest rows at or before `endMsec` (all rows when it is unset), in any order: the
+    /// first page is requested with `query.end_msec` and each following one with the
+    /// cursor as `endMsec`, ties in that millisecond broken on `id` (see [`Cursor`]).
+    /// `query.from_msec` is applied client-side rather than sent, and ends the walk once
+    /// a page reaches it. Rows are returned newest first whatever order the server sends
+    /// them in. The returned cursor is `None` on the last page.
+    pub async fn get_order_history_page(
+        &self,
+        query: &OrdersQuery,
+        cursor: Option<Cursor>,
+        timeout: Option<Duration>,
+    ) -> Result<(Vec<Order>, Option<Cursor>)> {
+        let page = self
+            .filled_canceled_orders(&page_query(query, cursor), timeout)
+            .await?;
+        cursor_page(page, query, cursor)
+    }
+
     /// Get order by ID
+    ///
+    /// An unknown order, answered with a 404 or with a `null` result, is `None`. A result
+    /// that is not an order fails with an API error naming the order and the problem.
     pub async fn get_order_by_id(&self, order_id: &str) -> Result<Option<Order>> {
-        let path = format!("/api/v1/order/{}", order_id);
-        match self.get(&path, None).await {
-            Ok(response) => {
-                let order = serde_json::from_value(response["result"].clone())
-                    .map_err(AlphaSecError::Json)?;
-                Ok(Some(order))
-            }
-            Err(AlphaSecError::Api { code: 404, .. }) => Ok(None),
-            Err(e) => Err(e),
+        let path = format!("/order/{}", order_id);
+        let response = match self.get(&path, None, None).await {
+            Ok(response) => response,
+            Err(e) if e.api_code() == Some(404) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let result = &response["result"];
+        if result.is_null() {
+            return Ok(None);
+        }
+        serde_json::from_value(result.clone())
+            .map(Some)
+            .map_err(|e| {
+                AlphaSecError::api(
+                    500,
+                    format!("Invalid response for order {}: {}", order_id, e),
+                )
+            })
+    }
+
+    /// Get the trades that filled (part of) an order
+    ///
+    /// An order the server does not know, or one with no fills yet, yields an empty list.
+    /// An ID that cannot be a path segment is rejected before any request is made.
+    pub async fn get_order_trades(&self, order_id: &str) -> Result<Vec<Trade>> {
+        if order_id.is_empty()
+            || !order_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "Invalid order ID: {:?}",
+                order_id
+            )));
         }
+
+        let path = format!("/order/{}/trades", order_id);
+        let response = match self.get(&path, None, None).await {
+            Ok(response) => response,
+            Err(e) if e.api_code() == Some(404) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let trades = list_result(&response["result"], "trades")?;
+        Ok(trades)
     }
 
     // === Trading API ===
@@ -418,7 +842,45 @@ impl ApiClient {
             "tx": signed_tx
         });
 
-        let response = self.post("/api/v1/order", Some(params)).await?;
+        let response = self.post("/order", Some(params)).await?;
+
+        Ok(ApiResponse {
+            success: response["code"] == 200,
+            code: response
+                .get("code")
+                .and_then(|v| v.as_i64())
+                .map(|v| v as i32),
+            result: response.get("result").cloned(),
+            error: response
+                .get("errMsg")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+        })
+    }
+
+    /// Submit an order authorized by an EIP-712 signature instead of a signed transaction
+    ///
+    /// `typed_data` is the output of
+    /// [`AlphaSecSigner::create_order_typed_data`](crate::AlphaSecSigner::create_order_typed_data)
+    /// and `signature` the `0x`-prefixed hex signature over it. Both are posted to the
+    /// same `/order` route as signed transactions.
+    pub async fn order_typed(
+        &self,
+        typed_data: &Value,
+        signature: &str,
+    ) -> Result<ApiResponse<Value>> {
+        if self.signer.is_none() {
+            return Err(AlphaSecError::auth(
+                "Signer required for trading operations",
+            ));
+        }
+
+        let params = serde_json::json!({
+            "typedData": typed_data,
+            "signature": signature
+        });
+
+        let response = self.post("/order", Some(params)).await?;
 
         Ok(ApiResponse {
             success: response["code"] == 200,
@@ -446,7 +908,7 @@ impl ApiClient {
             "tx": signed_tx
         });
 
-        let response = self.post("/api/v1/order/cancel", Some(params)).await?;
+        let response = self.post("/order/cancel", Some(params)).await?;
 
         Ok(ApiResponse {
             success: response["code"] == 200,
@@ -474,7 +936,7 @@ impl ApiClient {
             "tx": signed_tx
         });
 
-        let response = self.post("/api/v1/order/cancel/all", Some(params)).await?;
+        let response = self.post("/order/cancel/all", Some(params)).await?;
 
         Ok(ApiResponse {
             success: response["code"] == 200,
@@ -502,7 +964,35 @@ impl ApiClient {
             "tx": signed_tx
         });
 
-        let response = self.post("/api/v1/order/modify", Some(params)).await?;
+        let response = self.post("/order/modify", Some(params)).await?;
+
+        Ok(ApiResponse {
+            success: response["code"] == 200,
+            code: response
+                .get("code")
+                .and_then(|v| v.as_i64())
+                .map(|v| v as i32),
+            result: response.get("result").cloned(),
+            error: response
+                .get("errMsg")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+        })
+    }
+
+    /// Submit a one-cancels-other order pair
+    pub async fn oco_order(&self, signed_tx: &str) -> Result<ApiResponse<Value>> {
+        if self.signer.is_none() {
+            return Err(AlphaSecError::auth(
+                "Signer required for trading operations",
+            ));
+        }
+
+        let params = serde_json::json!({
+            "tx": signed_tx
+        });
+
+        let response = self.post("/order/oco", Some(params)).await?;
 
         Ok(ApiResponse {
             success: response["code"] == 200,
@@ -530,7 +1020,7 @@ impl ApiClient {
             "tx": signed_tx
         });
 
-        let response = self.post("/api/v1/order/stop", Some(params)).await?;
+        let response = self.post("/order/stop", Some(params)).await?;
 
         Ok(ApiResponse {
             success: response["code"] == 200,
@@ -558,7 +1048,7 @@ impl ApiClient {
             "tx": signed_tx
         });
 
-        let response = self.post("/api/v1/wallet/transfer", Some(params)).await?;
+        let response = self.post("/wallet/transfer", Some(params)).await?;
 
         Ok(ApiResponse {
             success: response["code"] == 200,
@@ -586,7 +1076,7 @@ impl ApiClient {
             "tx": signed_tx
         });
 
-        let response = self.post("/api/v1/wallet/transfer", Some(params)).await?;
+        let response = self.post("/wallet/transfer", Some(params)).await?;
 
         Ok(ApiResponse {
             success: response["code"] == 200,
@@ -619,7 +1109,7 @@ impl ApiClient {
             "tx": signed_tx
         });
 
-        let response = self.post("/api/v1/wallet/session", Some(params)).await?;
+        let response = self.post("/wallet/session", Some(params)).await?;
 
         Ok(ApiResponse {
             success: response["code"] == 200,
@@ -652,9 +1142,7 @@ impl ApiClient {
             "tx": signed_tx
         });
 
-        let response = self
-            .post("/api/v1/wallet/session/update", Some(params))
-            .await?;
+        let response = self.post("/wallet/session/update", Some(params)).await?;
 
         Ok(ApiResponse {
             success: response["code"] == 200,
@@ -682,9 +1170,7 @@ impl ApiClient {
             "tx": signed_tx
         });
 
-        let response = self
-            .post("/api/v1/wallet/session/delete", Some(params))
-            .await?;
+        let response = self.post("/wallet/session/delete", Some(params)).await?;
 
         Ok(ApiResponse {
             success: response["code"] == 200,
@@ -711,7 +1197,7 @@ impl ApiClient {
             "tx": signed_tx
         });
 
-        let response = self.post("/api/v1/wallet/withdraw", Some(params)).await?;
+        let response = self.post("/wallet/withdraw", Some(params)).await?;
 
         Ok(ApiResponse {
             success: response["code"] == 200,
@@ -728,11 +1214,133 @@ impl ApiClient {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// `query` for the page after `cursor`: the limit defaulted to
+/// [`ORDER_HISTORY_PAGE_LIMIT`], `endMsec` set to the cursor (else `query.end_msec`) and
+/// no `fromMsec`, which is applied by [`cursor_page`] instead.
+fn page_query(query: &OrdersQuery, cursor: Option<Cursor>) -> OrdersQuery {
+    let mut query = query.clone();
+    query.limit = Some(query.limit.unwrap_or(ORDER_HISTORY_PAGE_LIMIT));
+    query.from_msec = None;
+    if let Some(cursor) = cursor {
+        query.end_msec = Some(cursor.end_msec as i64);
+    }
+    query
+}
 
-    /// Client whose base URL points at an unreachable endpoint (port 1 on localhost).
+/// Sort a page fetched with [`page_query`] newest first, drop the rows an earlier page
+/// already returned or that are older than `query.from_msec`, and compute the cursor of
+/// the next page.
+fn cursor_page(
+    mut page: Vec<Order>,
+    query: &OrdersQuery,
+    cursor: Option<Cursor>,
+) -> Result<(Vec<Order>, Option<Cursor>)> {
+    let limit = query.limit.unwrap_or(ORDER_HISTORY_PAGE_LIMIT);
+    let from_msec = query.from_msec.unwrap_or(0).max(0) as u64;
+    page.sort_by_key(|o| std::cmp::Reverse((o.created_at, o.id)));
+    let oldest = page.last().map(Cursor::after);
+    let next = oldest.filter(|c| page.len() >= limit as usize && c.end_msec >= from_msec);
+    if next.is_some() && next == cursor {
+        // Every row sits at or after the cursor: a full page of orders sharing one
+        // millisecond, which endMsec alone cannot step past.
+        return Err(AlphaSecError::generic(format!(
+            "More than {} orders created at {}ms; retry with a larger limit",
+            limit,
+            next.map(|c| c.end_msec).unwrap_or_default()
+        )));
+    }
+
+    let orders = page
+        .into_iter()
+        .filter(|o| o.created_at >= from_msec && cursor.is_none_or(|c| c.precedes(o)))
+        .collect();
+    Ok((orders, next))
+}
+
+/// Items of a list `result`
+///
+/// The server is not consistent about empty and one-element lists, so `null` (or an
+/// absent `result`) and `{}` read as no items and any other object as a single item.
+/// Strings, numbers and booleans are rejected, naming `what` was being read.
+fn list_result<T: serde::de::DeserializeOwned>(result: &Value, what: &str) -> Result<Vec<T>> {
+    let items = match result {
+        Value::Null => return Ok(Vec::new()),
+        Value::Object(object) if object.is_empty() => return Ok(Vec::new()),
+        Value::Object(_) => std::slice::from_ref(result),
+        Value::Array(items) => items.as_slice(),
+        other => {
+            return Err(AlphaSecError::api(
+                500,
+                format!(
+                    "Invalid {} response format: expected a list, got {}",
+                    what,
+                    json_kind(other)
+                ),
+            ))
+        }
+    };
+    items
+        .iter()
+        .map(|item| serde_json::from_value(item.clone()).map_err(AlphaSecError::Json))
+        .collect()
+}
+
+/// Name of the JSON type of `value`, for error messages
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Send `request` and decode a JSON body, mapping non-2xx statuses to errors
+async fn send_request(request: reqwest::RequestBuilder) -> Result<Value> {
+    let response = request.send().await?;
+    if response.status().is_success() {
+        Ok(response.json().await?)
+    } else {
+        Err(error_from_response(response).await)
+    }
+}
+
+/// Convert a non-success response into an error. 429 becomes
+/// [`AlphaSecError::RateLimited`] carrying the `Retry-After` delay; everything else is
+/// passed through [`AlphaSecError::from_api`] with the status and body.
+async fn error_from_response(response: reqwest::Response) -> AlphaSecError {
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after);
+        return AlphaSecError::rate_limited(retry_after);
+    }
+    let status_code = response.status().as_u16() as i32;
+    let error_text = response.text().await.unwrap_or_default();
+    AlphaSecError::from_api(status_code, error_text)
+}
+
+/// Parse a `Retry-After` value: either delay-seconds or an HTTP date. Dates in the
+/// past yield a zero delay.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delta = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delta.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Client whose base URL points at an unreachable endpoint (port 1 on localhost).
     /// If a signer guard were missing, the method would attempt HTTP and surface a
     /// transport Err(Http) instead of Err(Auth) — which makes the "guard returns
     /// BEFORE any HTTP attempt" claim falsifiable rather than assumed.
@@ -811,6 +1419,255 @@ mod tests {
         );
     }
 
+    /// Minimal one-route HTTP server: every request is answered with `response` after
+    /// sleeping `delay`. Returns the base URL (`http://127.0.0.1:<port>`).
+    pub(crate) async fn mock_server(delay: Duration, response: String) -> String {
+        mock_server_seq(delay, vec![response]).await
+    }
+
+    /// Like [`mock_server`], but the n-th request gets `responses[n]`; once exhausted
+    /// the last response is repeated.
+    async fn mock_server_seq(delay: Duration, responses: Vec<String>) -> String {
+        mock_server_recorded(delay, responses).await.0
+    }
+
+    /// Like [`mock_server_seq`], also recording each request head: the request line
+    /// (`GET /path?query HTTP/1.1`) followed by the headers.
+    pub(crate) async fn mock_server_recorded(
+        delay: Duration,
+        responses: Vec<String>,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        mock_server_fn(delay, move |n, _, _| {
+            responses[n.min(responses.len() - 1)].clone()
+        })
+        .await
+    }
+
+    /// Answer each request with the response of the first route whose needle occurs
+    /// in the request head, or a 404 if none does.
+    pub(crate) async fn mock_server_routed(routes: Vec<(&'static str, String)>) -> String {
+        mock_server_fn(Duration::ZERO, move |_, head, _| {
+            routes
+                .iter()
+                .find(|(needle, _)| head.contains(needle))
+                .map(|(_, response)| response.clone())
+                .unwrap_or_else(|| {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                })
+        })
+        .await
+        .0
+    }
+
+    /// Like [`mock_server`] without a delay, also recording each request body
+    pub(crate) async fn mock_server_bodies(
+        response: String,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        let bodies = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = bodies.clone();
+        let (url, _) = mock_server_fn(Duration::ZERO, move |_, _, body| {
+            recorded.lock().unwrap().push(body.to_string());
+            response.clone()
+        })
+        .await;
+        (url, bodies)
+    }
+
+    /// Read one request: its head (without the blank line) and its `Content-Length` body
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> (String, String) {
+        use tokio::io::AsyncReadExt;
+
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let text = String::from_utf8_lossy(&request).into_owned();
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = header_value(head, "content-length")
+                    .and_then(|length| length.parse::<usize>().ok())
+                    .unwrap_or(0);
+                if body.len() >= length {
+                    return (head.to_string(), body.to_string());
+                }
+            }
+            match socket.read(&mut buf).await {
+                Ok(0) | Err(_) => {
+                    let text = String::from_utf8_lossy(&request).into_owned();
+                    let (head, body) = text.split_once("\r\n\r\n").unwrap_or((&text, ""));
+                    return (head.to_string(), body.to_string());
+                }
+                Ok(read) => request.extend_from_slice(&buf[..read]),
+            }
+        }
+    }
+
+    /// Server answering the n-th request (0-based) with `respond(n, head, body)`,
+    /// recording each request head.
+    pub(crate) async fn mock_server_fn(
+        delay: Duration,
+        respond: impl Fn(usize, &str, &str) -> String + Send + Sync + 'static,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let respond = std::sync::Arc::new(respond);
+        tokio::spawn(async move {
+            for n in 0.. {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    break;
+                };
+                let recorded = recorded.clone();
+                let respond = respond.clone();
+                tokio::spawn(async move {
+                    let (head, body) = read_request(&mut socket).await;
+                    recorded.lock().unwrap().push(head.clone());
+                    let response = respond(n, &head, &body);
+                    tokio::time::sleep(delay).await;
+                    let _ = socket.write_all(response.as_bytes()).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+        (format!("http://{}", addr), requests)
+    }
+
+    /// Build a 200 OK response carrying `body` as JSON.
+    pub(crate) fn json_response(body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    }
+
+    fn rate_limited_response(retry_after: &str) -> String {
+        let body = r#"{"code":429,"errMsg":"too many requests"}"#;
+        format!(
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            retry_after,
+            body.len(),
+            body
+        )
+    }
+
+    fn client_for(base_url: &str) -> ApiClient {
+        let config = Config::new(
+            base_url,
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .expect("test config must build");
+        ApiClient::new(&config, None).expect("client must build")
+    }
+
+    #[tokio::test]
+    async fn per_call_timeout_overrides_config_timeout() {
+        let body = r#"{"code":200,"result":{"bids":[],"asks":[],"updatedAt":1,"lastUpdatedId":2}}"#;
+        let base = mock_server(Duration::from_millis(500), json_response(body)).await;
+        let client = client_for(&base);
+
+        // Deadline shorter than the server delay: the call must fail even though the
+        // configured timeout (30s) would have allowed it.
+        let err = client
+            .get_depth_with_timeout("1_2", None, Duration::from_millis(100))
+            .await
+            .expect_err("short per-call timeout must fire");
+        match err {
+            AlphaSecError::Http(e) => assert!(e.is_timeout(), "expected timeout, got {:?}", e),
+            other => panic!("expected Http timeout, got {:?}", other),
+        }
+
+        // A longer per-call deadline lets the same delayed response through.
+        let depth = client
+            .get_depth_with_timeout("1_2", None, Duration::from_secs(5))
+            .await
+            .expect("longer per-call timeout must succeed");
+        assert_eq!(depth.last_updated_id, 2);
+
+        // Without an override the configured timeout applies.
+        assert!(client.get_depth("1_2", None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn status_429_maps_to_rate_limited_with_retry_after() {
+        let base = mock_server(Duration::ZERO, rate_limited_response("2")).await;
+        let mut client = client_for(&base);
+        client.max_retries = 0;
+
+        let err = client
+            .get_tickers()
+            .await
+            .expect_err("429 must be an error");
+        assert!(err.is_rate_limited(), "got {:?}", err);
+        match err {
+            AlphaSecError::RateLimited { retry_after } => {
+                assert_eq!(retry_after, Some(Duration::from_secs(2)))
+            }
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limited_get_is_retried_after_the_advertised_delay() {
+        let ok = json_response(r#"{"code":200,"result":[]}"#);
+        let base = mock_server_seq(Duration::ZERO, vec![rate_limited_response("1"), ok]).await;
+        let client = client_for(&base);
+
+        let started = std::time::Instant::now();
+        let tickers = client.get_tickers().await.expect("retry must succeed");
+        assert!(tickers.is_empty());
+        assert!(
+            started.elapsed() >= Duration::from_secs(1),
+            "retry fired before Retry-After elapsed: {:?}",
+            started.elapsed()
+        );
+    }
+
+    #[tokio::test]
+    async fn excessive_retry_after_is_returned_instead_of_waited_out() {
+        let far_future = (chrono::Utc::now() + chrono::Duration::days(365)).to_rfc2822();
+        for retry_after in ["86400".to_string(), far_future] {
+            let ok = json_response(r#"{"code":200,"result":[]}"#);
+            let base = mock_server_seq(
+                Duration::ZERO,
+                vec![rate_limited_response(&retry_after), ok],
+            )
+            .await;
+            let client = client_for(&base);
+
+            let started = std::time::Instant::now();
+            let err = client.get_tickers().await.unwrap_err();
+            assert!(started.elapsed() < Duration::from_secs(5));
+            match err {
+                AlphaSecError::RateLimited {
+                    retry_after: Some(delay),
+                } => assert!(delay > MAX_RETRY_AFTER, "{:?}", delay),
+                other => panic!("expected RateLimited, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn retry_after_parses_seconds_and_http_dates() {
+        assert_eq!(parse_retry_after(" 7 "), Some(Duration::from_secs(7)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO),
+            "past dates clamp to zero"
+        );
+        let future = (chrono::Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        let parsed = parse_retry_after(&future).unwrap();
+        assert!(parsed > Duration::from_secs(25) && parsed <= Duration::from_secs(30));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
     #[tokio::test]
     async fn withdraw_token_returns_auth_error_without_signer_before_http() {
         let client = client_without_signer();
@@ -820,4 +1677,795 @@ mod tests {
             "Signer required for withdraw operations",
         );
     }
+
+    fn history_order(id: u64, created_at: u64) -> Value {
+        serde_json::json!({
+            "id": id,
+            "orderId": format!("o{}", id),
+            "accountAddress": "0xabc",
+            "marketId": "1_2",
+            "side": "BUY",
+            "orderType": "LIMIT",
+            "price": "1",
+            "origQty": "1",
+            "origQuoteOrderQty": "0",
+            "isTrigger": false,
+            "isTriggered": false,
+            "triggerPrice": "0",
+            "status": "FILLED",
+            "contingencyType": "NONE",
+            "otoLegType": "NONE",
+            "txHash": "0x",
+            "createdAt": created_at,
+            "updatedAt": created_at,
+            "executedQty": "1",
+            "executedQuoteQty": "1"
+        })
+    }
+
+    fn history_page(rows: &[(u64, u64)]) -> String {
+        let rows: Vec<Value> = rows.iter().map(|&(id, ts)| history_order(id, ts)).collect();
+        json_response(&serde_json::json!({ "code": 200, "result": rows }).to_string())
+    }
+
+    /// Mock of the paging contract the SDK relies on: a `limit` answered with the newest
+    /// rows (id, createdAt) at or before `endMsec`, listed in the order `sort` leaves them
+    fn history_server_fn(
+        rows: &'static [(u64, u64)],
+        sort: fn(&mut Vec<(u64, u64)>),
+    ) -> impl Fn(usize, &str, &str) -> String + Send + Sync + 'static {
+        move |_, head, _| {
+            let query = head.split_whitespace().nth(1).unwrap_or_default();
+            let param = |name: &str| {
+                query
+                    .split(['?', '&'])
+                    .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+                    .and_then(|value| value.parse::<u64>().ok())
+            };
+            let limit = param("limit").unwrap() as usize;
+            let end = param("endMsec").unwrap_or(u64::MAX);
+            let mut page: Vec<(u64, u64)> = rows
+                .iter()
+                .rev()
+                .copied()
+                .filter(|&(_, ts)| ts <= end)
+                .take(limit)
+                .collect();
+            sort(&mut page);
+            history_page(&page)
+        }
+    }
+
+    /// Rows (id, createdAt), oldest first. Orders 3 and 4 share a millisecond, which
+    /// ends up on a page boundary at limit 3.
+    const HISTORY_ROWS: [(u64, u64); 6] = [(1, 10), (2, 15), (3, 20), (4, 20), (5, 30), (6, 40)];
+
+    async fn walk_order_history(client: &ApiClient, query: &OrdersQuery) -> Vec<u64> {
+        let mut ids = Vec::new();
+        let mut cursor = None;
+        for _ in 0..10 {
+            let (orders, next) = client
+                .get_order_history_page(query, cursor, None)
+                .await
+                .unwrap();
+            ids.extend(orders.iter().map(|o| o.id));
+            cursor = next;
+            if cursor.is_none() {
+                return ids;
+            }
+        }
+        panic!("paging must terminate, got {:?}", ids);
+    }
+
+    #[tokio::test]
+    async fn order_history_pages_backwards_until_a_short_page() {
+        let (base, requests) = mock_server_fn(
+            Duration::ZERO,
+            history_server_fn(&HISTORY_ROWS, |rows| rows.sort()),
+        )
+        .await;
+        let client = client_for(&base);
+        let query = OrdersQuery::new("0xabc").limit(3);
+
+        assert_eq!(
+            walk_order_history(&client, &query).await,
+            vec![6, 5, 4, 3, 2, 1]
+        );
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 3);
+        assert!(!requests[0].contains("endMsec"), "{}", requests[0]);
+        assert!(requests[1].contains("endMsec=20"), "{}", requests[1]);
+        assert!(requests[2].contains("endMsec=15"), "{}", requests[2]);
+        assert!(requests.iter().all(|r| r.contains("limit=3")));
+        assert!(requests.iter().all(|r| !r.contains("fromMsec")));
+    }
+
+    #[tokio::test]
+    async fn order_history_paging_does_not_depend_on_the_server_sort_order() {
+        let (base, _requests) = mock_server_fn(
+            Duration::ZERO,
+            history_server_fn(&HISTORY_ROWS, |rows| {
+                rows.sort_by_key(|&(id, ts)| std::cmp::Reverse((ts, id)))
+            }),
+        )
+        .await;
+        let client = client_for(&base);
+        let query = OrdersQuery::new("0xabc").limit(3);
+
+        assert_eq!(
+            walk_order_history(&client, &query).await,
+            vec![6, 5, 4, 3, 2, 1]
+        );
+    }
+
+    #[tokio::test]
+    async fn order_history_paging_stops_at_from_msec() {
+        let (base, requests) = mock_server_fn(
+            Duration::ZERO,
+            history_server_fn(&HISTORY_ROWS, |rows| rows.sort()),
+        )
+        .await;
+        let client = client_for(&base);
+        let mut query = OrdersQuery::new("0xabc").limit(3);
+        query.from_msec = Some(20);
+
+        assert_eq!(walk_order_history(&client, &query).await, vec![6, 5, 4, 3]);
+        // The second page reaches order 2, older than fromMsec, which ends the walk
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn order_history_page_that_cannot_advance_is_an_error() {
+        // A full page entirely at and after the cursor would repeat forever.
+        let base = mock_server(Duration::ZERO, history_page(&[(1, 20), (2, 20)])).await;
+        let client = client_for(&base);
+        let query = OrdersQuery::new("0xabc").limit(2);
+        let cursor = Some(Cursor {
+            end_msec: 20,
+            last_id: 1,
+        });
+        assert!(client
+            .get_order_history_page(&query, cursor, None)
+            .await
+            .is_err());
+    }
+
+    fn order_trade(trade_id: &str, price: &str, quantity: &str, fee: &str) -> String {
+        format!(
+            r#"{{"tradeId":"{}","marketId":"1_2","price":"{}","quantity":"{}","buyOrderId":"0xb1","sellOrderId":"0xs{}","createdAt":1700000000000,"isBuyerMaker":false,"fee":"{}","feeTokenId":"2"}}"#,
+            trade_id, price, quantity, trade_id, fee
+        )
+    }
+
+    fn tape_page(trades: &[(&str, u64)]) -> String {
+        let rows: Vec<Value> = trades
+            .iter()
+            .map(|&(id, ts)| {
+                serde_json::json!({
+                    "tradeId": id, "marketId": "1_2", "price": "1", "quantity": "1",
+                    "buyOrderId": "0xb", "sellOrderId": "0xs", "createdAt": ts,
+                    "isBuyerMaker": false
+                })
+            })
+            .collect();
+        json_response(&serde_json::json!({ "code": 200, "result": rows }).to_string())
+    }
+
+    /// Mock of the trades paging contract: a `limit` answered with the newest trades
+    /// (tradeId, createdAt) at or before `endMsec`, oldest first
+    fn tape_server_fn(
+        tape: &'static [(&'static str, u64)],
+    ) -> impl Fn(usize, &str, &str) -> String + Send + Sync + 'static {
+        move |_, head, _| {
+            let query = head.split_whitespace().nth(1).unwrap_or_default();
+            let param = |name: &str| {
+                query
+                    .split(['?', '&'])
+                    .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+                    .and_then(|value| value.parse::<u64>().ok())
+            };
+            let limit = param("limit").unwrap() as usize;
+            let end = param("endMsec").unwrap_or(u64::MAX);
+            let mut page: Vec<(&str, u64)> = tape
+                .iter()
+                .rev()
+                .copied()
+                .filter(|&(_, ts)| ts <= end)
+                .take(limit)
+                .collect();
+            page.reverse();
+            tape_page(&page)
+        }
+    }
+
+    #[tokio::test]
+    async fn trades_between_pages_backwards_through_the_window() {
+        // "b" and "c" share a millisecond that becomes a page boundary; "y" and "z" are
+        // outside the window.
+        const TAPE: [(&str, u64); 6] = [
+            ("y", 50),
+            ("a", 100),
+            ("b", 150),
+            ("c", 150),
+            ("d", 200),
+            ("z", 5000),
+        ];
+        let (base, requests) = mock_server_fn(Duration::ZERO, tape_server_fn(&TAPE)).await;
+        let client = client_for(&base);
+
+        let trades = client
+            .get_trades_between("1_2", 100, 1000, Some(3), None)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = trades.iter().map(|t| t.trade_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c", "d"]);
+
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].contains("endMsec=1000"), "{}", requests[0]);
+        assert!(requests[1].contains("endMsec=150"), "{}", requests[1]);
+        assert!(requests[2].contains("endMsec=100"), "{}", requests[2]);
+        assert!(requests.iter().all(|r| !r.contains("fromMsec")));
+    }
+
+    #[tokio::test]
+    async fn trades_between_errors_on_more_trades_in_one_millisecond_than_a_page() {
+        const TAPE: [(&str, u64); 4] = [("a", 100), ("b", 150), ("c", 150), ("d", 150)];
+        let (base, requests) = mock_server_fn(Duration::ZERO, tape_server_fn(&TAPE)).await;
+        let client = client_for(&base);
+
+        let err = client
+            .get_trades_between("1_2", 0, 1000, Some(2), None)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("More than 2 trades at 150ms"),
+            "{err}"
+        );
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn trades_between_rejects_an_inverted_window_without_a_request() {
+        let err = client_without_signer()
+            .get_trades_between("1_2", 2000, 1000, None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlphaSecError::InvalidParameter(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn order_trades_returns_each_partial_fill() {
+        let body = format!(
+            r#"{{"code":200,"result":[{},{},{}]}}"#,
+            order_trade("t1", "1.10", "4", "0.0044"),
+            order_trade("t2", "1.11", "5", "0.00555"),
+            order_trade("t3", "1.12", "1", "0.00112"),
+        );
+        let (base, requests) =
+            mock_server_recorded(Duration::ZERO, vec![json_response(&body)]).await;
+        let client = client_for(&base);
+
+        let trades = client.get_order_trades("0xb1").await.unwrap();
+        assert_eq!(
+            trades
+                .iter()
+                .map(|t| t.trade_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["t1", "t2", "t3"]
+        );
+        let filled: rust_decimal::Decimal =
+            trades.iter().map(|t| t.quantity_decimal().unwrap()).sum();
+        let fees: rust_decimal::Decimal = trades
+            .iter()
+            .map(|t| t.fee_decimal().unwrap().unwrap())
+            .sum();
+        assert_eq!(filled, "10".parse().unwrap());
+        assert_eq!(fees, "0.01107".parse().unwrap());
+        assert_eq!(trades[1].price_decimal().unwrap(), "1.11".parse().unwrap());
+        assert_eq!(trades[0].fee_token_id.as_deref(), Some("2"));
+        assert!(requests.lock().unwrap()[0].starts_with("GET /api/v1/order/0xb1/trades "));
+    }
+
+    #[tokio::test]
+    async fn order_trades_not_found_or_null_is_empty() {
+        let not_found = r#"{"code":404,"errMsg":"order not found"}"#;
+        let response = format!(
+            "HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            not_found.len(),
+            not_found
+        );
+        let client = client_for(&mock_server(Duration::ZERO, response).await);
+        assert!(client
+            .get_order_trades("0xmissing")
+            .await
+            .unwrap()
+            .is_empty());
+
+        let body = r#"{"code":200,"result":null}"#;
+        let client = client_for(&mock_server(Duration::ZERO, json_response(body)).await);
+        assert!(client.get_order_trades("0xb1").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn order_trades_rejects_invalid_id_without_a_request() {
+        // Unreachable base URL: a request would surface a transport error instead.
+        let client = client_without_signer();
+        for id in ["", "../balance", "0xb1?x=1", "a b"] {
+            let err = client.get_order_trades(id).await.unwrap_err();
+            assert!(
+                matches!(err, AlphaSecError::InvalidParameter(_)),
+                "{:?}: got {:?}",
+                id,
+                err
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn withdrawal_status_reads_object_or_bare_status() {
+        let body = r#"{"code":200,"result":{"id":"w1","status":"CLAIMABLE"}}"#;
+        let (base, requests) =
+            mock_server_recorded(Duration::ZERO, vec![json_response(body)]).await;
+        let client = client_for(&base);
+        assert_eq!(
+            client.get_withdrawal_status("w1").await.unwrap(),
+            WithdrawalStatus::Claimable
+        );
+        assert!(requests.lock().unwrap()[0].starts_with("GET /api/v1/wallet/withdraw/w1 "));
+
+        let body = r#"{"code":200,"result":"COMPLETED"}"#;
+        let client = client_for(&mock_server(Duration::ZERO, json_response(body)).await);
+        assert_eq!(
+            client.get_withdrawal_status("0xabc").await.unwrap(),
+            WithdrawalStatus::Completed
+        );
+    }
+
+    #[tokio::test]
+    async fn withdrawal_status_unknown_or_invalid_id_is_an_error() {
+        let not_found = r#"{"code":404,"errMsg":"withdrawal not found"}"#;
+        let response = format!(
+            "HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            not_found.len(),
+            not_found
+        );
+        let client = client_for(&mock_server(Duration::ZERO, response).await);
+        assert!(matches!(
+            client.get_withdrawal_status("missing").await,
+            Err(AlphaSecError::NotFound(_))
+        ));
+
+        let client = client_without_signer();
+        for id in ["", "../balance", "w1?x=1"] {
+            assert!(matches!(
+                client.get_withdrawal_status(id).await,
+                Err(AlphaSecError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    /// Value of header `name` in a recorded request head.
+    #[tokio::test]
+    async fn transfer_history_sends_only_the_query_fields_that_are_set() {
+        let body = r#"{"code":200,"result":[]}"#;
+        let (base, requests) =
+            mock_server_recorded(Duration::ZERO, vec![json_response(body)]).await;
+        let client = client_for(&base);
+        let full = TransferHistoryQuery {
+            address: "0xabc".to_string(),
+            token_id: Some(2),
+            from_msec: Some(1_000),
+            to_msec: Some(2_000),
+            limit: Some(900),
+        };
+        client.get_transfer_history(&full).await.unwrap();
+        let bare = TransferHistoryQuery {
+            address: "0xabc".to_string(),
+            ..Default::default()
+        };
+        client.get_transfer_history(&bare).await.unwrap();
+
+        let requests = requests.lock().unwrap().clone();
+        let request_line = |n: usize| requests[n].lines().next().unwrap().to_string();
+        assert_eq!(
+            request_line(0),
+            "GET /api/v1/wallet/transfer?address=0xabc&tokenId=2&fromMsec=1000&toMsec=2000&limit=500 HTTP/1.1"
+        );
+        assert_eq!(
+            request_line(1),
+            "GET /api/v1/wallet/transfer?address=0xabc HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn configured_proxy_carries_rest_requests() {
+        let body = r#"{"code":200,"result":[]}"#;
+        let (proxy, requests) =
+            mock_server_recorded(Duration::ZERO, vec![json_response(body)]).await;
+        let config = Config::new(
+            "http://alphasec.invalid",
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap()
+        .with_proxy(proxy);
+        let client = ApiClient::new(&config, None).unwrap();
+        client.get_tokens().await.unwrap();
+
+        // A request to the proxy names the absolute target URL
+        let requests = requests.lock().unwrap().clone();
+        assert!(
+            requests[0].starts_with("GET http://alphasec.invalid/api/v1/market/tokens HTTP/1.1"),
+            "{}",
+            requests[0]
+        );
+
+        let bad = config.with_proxy("not a url");
+        assert!(matches!(
+            ApiClient::new(&bad, None),
+            Err(AlphaSecError::Config(_))
+        ));
+    }
+
+    /// Self-signed certificate for `localhost` and `127.0.0.1`, presented by
+    /// [`tls_server`]
+    pub(crate) const SELF_SIGNED_CERT: &[u8] = include_bytes!("testdata/self_signed.pem");
+
+    /// Server presenting [`SELF_SIGNED_CERT`] on a local port, handing every connection
+    /// that completes the TLS handshake to `serve` on a thread of its own. Returns the
+    /// port.
+    pub(crate) fn tls_server(serve: fn(native_tls::TlsStream<std::net::TcpStream>)) -> u16 {
+        let identity = native_tls::Identity::from_pkcs8(
+            SELF_SIGNED_CERT,
+            include_bytes!("testdata/self_signed.key"),
+        )
+        .unwrap();
+        let acceptor = native_tls::TlsAcceptor::new(identity).unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let acceptor = acceptor.clone();
+                std::thread::spawn(move || {
+                    if let Ok(stream) = acceptor.accept(stream) {
+                        serve(stream);
+                    }
+                });
+            }
+        });
+        port
+    }
+
+    /// Answer one HTTP request with an empty result list
+    fn serve_empty_list(mut stream: native_tls::TlsStream<std::net::TcpStream>) {
+        use std::io::{Read, Write};
+
+        let mut head = Vec::new();
+        let mut byte = [0u8];
+        while !head.ends_with(b"\r\n\r\n") && stream.read_exact(&mut byte).is_ok() {
+            head.push(byte[0]);
+        }
+        let response = json_response(r#"{"code":200,"result":[]}"#);
+        let _ = stream.write_all(response.as_bytes());
+    }
+
+    #[tokio::test]
+    #[ignore = "integration: serves TLS on a local port, run with --ignored"]
+    async fn root_cert_admits_a_self_signed_server() {
+        let port = tls_server(serve_empty_list);
+        let config = Config::new(
+            &format!("https://localhost:{}", port),
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+
+        let untrusted = ApiClient::new(&config, None).unwrap();
+        let err = untrusted.get_market_list().await.unwrap_err();
+        assert!(matches!(err, AlphaSecError::Http(_)), "{err}");
+
+        let trusted =
+            ApiClient::new(&config.clone().with_root_cert(SELF_SIGNED_CERT), None).unwrap();
+        assert!(trusted.get_market_list().await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "danger-insecure-tls")]
+    #[tokio::test]
+    #[ignore = "integration: serves TLS on a local port, run with --ignored"]
+    async fn accepting_invalid_certs_admits_any_server() {
+        let port = tls_server(serve_empty_list);
+        let config = Config::new(
+            &format!("https://localhost:{}", port),
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap()
+        .danger_accept_invalid_certs(true);
+        assert!(config.tls.accepts_invalid_certs());
+
+        let client = ApiClient::new(&config, None).unwrap();
+        assert!(client.get_market_list().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_root_certs_fail_the_client_build() {
+        let config = Config::new(
+            "https://api.alphasec.invalid",
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap()
+        .with_root_cert("not a certificate");
+        match ApiClient::new(&config, None) {
+            Err(AlphaSecError::Config(message)) => {
+                assert!(message.contains("root certificate"), "{}", message)
+            }
+            other => panic!("expected a configuration error, got {:?}", other),
+        }
+    }
+
+    pub(crate) fn header_value<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+        head.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+
+    #[tokio::test]
+    async fn requests_carry_user_agent_and_a_unique_request_id() {
+        let body = r#"{"code":200,"result":[]}"#;
+        let (base, requests) =
+            mock_server_recorded(Duration::ZERO, vec![json_response(body)]).await;
+        let client = client_for(&base);
+        client.get_tokens().await.unwrap();
+        client.get_market_list().await.unwrap();
+        client.get_tokens().await.unwrap();
+
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 3);
+        let mut ids = std::collections::HashSet::new();
+        for head in &requests {
+            assert_eq!(
+                header_value(head, "user-agent"),
+                Some(concat!("alphasec-rust-sdk/", env!("CARGO_PKG_VERSION"))),
+                "{}",
+                head
+            );
+            let id = header_value(head, "x-request-id").expect("request id header");
+            assert!(uuid::Uuid::parse_str(id).is_ok(), "{}", id);
+            ids.insert(id.to_string());
+        }
+        assert_eq!(ids.len(), 3, "request ids must be unique per call");
+    }
+
+    #[tokio::test]
+    async fn user_agent_override_is_sent() {
+        let body = r#"{"code":200,"result":[]}"#;
+        let (base, requests) =
+            mock_server_recorded(Duration::ZERO, vec![json_response(body)]).await;
+        let config = Config::new(
+            &base,
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap()
+        .with_user_agent("my-bot/2.1");
+        let client = ApiClient::new(&config, None).unwrap();
+        client.get_tokens().await.unwrap();
+        assert_eq!(
+            header_value(&requests.lock().unwrap()[0], "user-agent"),
+            Some("my-bot/2.1")
+        );
+
+        let bad = config.with_user_agent("line\nbreak");
+        assert!(matches!(
+            ApiClient::new(&bad, None),
+            Err(AlphaSecError::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn health_check_reports_an_unreachable_or_failing_api() {
+        let (base, requests) = mock_server_recorded(
+            Duration::ZERO,
+            vec![json_response(r#"{"code":200,"result":[]}"#)],
+        )
+        .await;
+        client_for(&base).health_check().await.unwrap();
+        assert!(requests.lock().unwrap()[0].starts_with("GET /api/v1/market/tokens"));
+
+        let failing =
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        let base = mock_server(Duration::ZERO, failing.to_string()).await;
+        assert!(client_for(&base).health_check().await.is_err());
+        assert!(client_without_signer().health_check().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn routes_are_built_from_the_configured_prefix() {
+        let body = r#"{"code":200,"result":[]}"#;
+        let (base, requests) =
+            mock_server_recorded(Duration::ZERO, vec![json_response(body)]).await;
+        let config = Config::new(
+            &format!("{}/proxy/", base),
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+        let default = ApiClient::new(&config, None).unwrap();
+        assert_eq!(
+            default.url("/market/tokens"),
+            format!("{}/proxy/api/v1/market/tokens", base)
+        );
+
+        let client = ApiClient::new(&config.with_api_prefix("trade/api/v2/"), None).unwrap();
+        assert_eq!(
+            client.url("/market"),
+            format!("{}/proxy/trade/api/v2/market", base)
+        );
+        client.get_tokens().await.unwrap();
+        client.get_order_trades("0xb1").await.unwrap();
+        let requests = requests.lock().unwrap().clone();
+        assert!(requests[0].starts_with("GET /proxy/trade/api/v2/market/tokens "));
+        assert!(requests[1].starts_with("GET /proxy/trade/api/v2/order/0xb1/trades "));
+    }
+
+    #[tokio::test]
+    async fn list_results_accept_null_empty_and_single_object_shapes() {
+        let trade = r#"{"tradeId":"t-1","marketId":"5_2","price":"1","quantity":"2","buyOrderId":"b","sellOrderId":"s","createdAt":1,"isBuyerMaker":true}"#;
+        let session = r#"{"name":"bot","sessionAddress":"0xs","ownerAddress":"0xo","expiry":9,"applied":true}"#;
+        let balance = r#"{"tokenId":"2","unlocked":"5"}"#;
+        for (result, items) in [
+            ("null".to_string(), 0),
+            ("[]".to_string(), 0),
+            ("{}".to_string(), 0),
+            (format!("[{}]", trade), 1),
+            (trade.to_string(), 1),
+        ] {
+            let body = format!(r#"{{"code":200,"result":{}}}"#, result);
+            let client = client_for(&mock_server(Duration::ZERO, json_response(&body)).await);
+            let trades = client.get_trades("5_2", None).await.unwrap();
+            assert_eq!(trades.len(), items, "result {}", result);
+        }
+        for (result, items) in [
+            ("null".to_string(), 0),
+            ("[]".to_string(), 0),
+            (session.to_string(), 1),
+        ] {
+            let body = format!(r#"{{"code":200,"result":{}}}"#, result);
+            let client = client_for(&mock_server(Duration::ZERO, json_response(&body)).await);
+            let sessions = client.get_sessions("0xo").await.unwrap();
+            assert_eq!(sessions.len(), items, "result {}", result);
+        }
+        for (result, items) in [
+            ("null".to_string(), 0),
+            (r#"{"balances":[],"blockNumber":7}"#.to_string(), 0),
+            (format!(r#"{{"balances":{},"blockNumber":7}}"#, balance), 1),
+        ] {
+            let body = format!(r#"{{"code":200,"result":{}}}"#, result);
+            let client = client_for(&mock_server(Duration::ZERO, json_response(&body)).await);
+            let balances = client.get_balance("0xo").await.unwrap();
+            assert_eq!(balances.balances.len(), items, "result {}", result);
+        }
+        // An absent `result` is an empty list too
+        let body = r#"{"code":200}"#;
+        let client = client_for(&mock_server(Duration::ZERO, json_response(body)).await);
+        assert!(client.get_trades("5_2", None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn malformed_list_results_name_the_list_and_the_shape() {
+        let body = r#"{"code":200,"result":"oops"}"#;
+        let client = client_for(&mock_server(Duration::ZERO, json_response(body)).await);
+        let err = client.get_sessions("0xo").await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Invalid sessions response format: expected a list, got a string"),
+            "{}",
+            err
+        );
+        let err = client.get_balance("0xo").await.unwrap_err();
+        assert!(err.to_string().contains("got a string"), "{}", err);
+        // A single item of the wrong shape is a deserialization error, not an empty list
+        let body = r#"{"code":200,"result":{"tradeId":"t-1"}}"#;
+        let client = client_for(&mock_server(Duration::ZERO, json_response(body)).await);
+        assert!(matches!(
+            client.get_trades("5_2", None).await,
+            Err(AlphaSecError::Json(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn base_url_slashes_and_path_prefixes_reach_the_right_route() {
+        let body = r#"{"code":200,"result":[]}"#;
+        for (suffix, path) in [
+            ("", "/api/v1/market/tokens"),
+            ("/", "/api/v1/market/tokens"),
+            ("/gw", "/gw/api/v1/market/tokens"),
+            ("/gw/", "/gw/api/v1/market/tokens"),
+        ] {
+            let (base, requests) =
+                mock_server_recorded(Duration::ZERO, vec![json_response(body)]).await;
+            let client = client_for(&format!("{}{}", base, suffix));
+            client.get_tokens().await.unwrap();
+            let requests = requests.lock().unwrap().clone();
+            assert!(
+                requests[0].starts_with(&format!("GET {} HTTP/1.1", path)),
+                "{:?}: {}",
+                suffix,
+                requests[0]
+            );
+        }
+
+        let mut config = Config::new(
+            "https://api.alphasec.invalid",
+            "kairos",
+            "0x1234567890123456789012345678901234567890",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+        config.api_url = url::Url::parse("ftp://api.alphasec.invalid").unwrap();
+        assert!(matches!(
+            ApiClient::new(&config, None),
+            Err(AlphaSecError::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn order_by_id_reads_null_and_404_as_missing() {
+        let order = serde_json::json!({ "code": 200, "result": history_order(1, 1) });
+        let base = mock_server_routed(vec![
+            ("GET /api/v1/order/o1 ", json_response(&order.to_string())),
+            (
+                "GET /api/v1/order/o-null ",
+                json_response(r#"{"code":200,"result":null}"#),
+            ),
+            (
+                "GET /api/v1/order/o-bad ",
+                json_response(r#"{"code":200,"result":{"orderId":7}}"#),
+            ),
+        ])
+        .await;
+        let client = client_for(&base);
+
+        let found = client.get_order_by_id("o1").await.unwrap().unwrap();
+        assert_eq!(found.order_id, "o1");
+        assert!(client.get_order_by_id("o-null").await.unwrap().is_none());
+        // Unrouted, so the mock answers 404
+        assert!(client.get_order_by_id("o-404").await.unwrap().is_none());
+        match client.get_order_by_id("o-bad").await {
+            Err(AlphaSecError::Api { code: 500, message }) => {
+                assert!(message.contains("order o-bad"), "{}", message)
+            }
+            other => panic!("expected an API error, got {:?}", other),
+        }
+    }
 }
@@ -2,22 +2,36 @@
 
 use crate::error::{AlphaSecError, Result};
 use std::collections::HashMap;
+use url::Url;
+
+/// Split a `BASE/QUOTE` market symbol into its base and quote token symbols
+///
+/// Fails with `InvalidParameter` naming the problem unless `market` has exactly one `/`
+/// with a symbol on each side of it.
+pub fn split_market(market: &str) -> Result<(&str, &str)> {
+    let problem = match market.split_once('/') {
+        Some((base, quote)) if !quote.contains('/') => match (base.is_empty(), quote.is_empty()) {
+            (false, false) => return Ok((base, quote)),
+            (true, true) => "both the base and the quote symbol are empty".to_string(),
+            (true, false) => "the base symbol is empty".to_string(),
+            (false, true) => "the quote symbol is empty".to_string(),
+        },
+        _ if market.is_empty() => "the symbol is empty".to_string(),
+        None => "it has no '/'".to_string(),
+        Some(_) => format!("it has {} '/' separators", market.matches('/').count()),
+    };
+    Err(AlphaSecError::invalid_parameter(format!(
+        "Invalid market format: {:?}: {}. Expected format: BASE/QUOTE",
+        market, problem
+    )))
+}
 
 /// Convert market symbol to market ID
 pub fn market_to_market_id(
     market: &str,
     symbol_token_id_map: &HashMap<String, u32>,
 ) -> Result<String> {
-    let parts: Vec<&str> = market.split('/').collect();
-    if parts.len() != 2 {
-        return Err(AlphaSecError::invalid_parameter(format!(
-            "Invalid market format: {}. Expected format: BASE/QUOTE",
-            market
-        )));
-    }
-
-    let base_symbol = parts[0];
-    let quote_symbol = parts[1];
+    let (base_symbol, quote_symbol) = split_market(market)?;
 
     let base_token_id = symbol_token_id_map.get(base_symbol).ok_or_else(|| {
         AlphaSecError::not_found(format!("Base token not found: {}", base_symbol))
@@ -35,20 +49,10 @@ pub fn market_id_to_market(
     market_id: &str,
     token_id_symbol_map: &HashMap<u32, String>,
 ) -> Result<String> {
-    let parts: Vec<&str> = market_id.split('_').collect();
-    if parts.len() != 2 {
-        return Err(AlphaSecError::invalid_parameter(format!(
-            "Invalid market ID format: {}. Expected format: BASE_QUOTE",
-            market_id
-        )));
-    }
-
-    let base_token_id = parts[0].parse::<u32>().map_err(|e| {
-        AlphaSecError::invalid_parameter(format!("Invalid base token ID: {}: {}", parts[0], e))
-    })?;
-    let quote_token_id = parts[1].parse::<u32>().map_err(|e| {
-        AlphaSecError::invalid_parameter(format!("Invalid quote token ID: {}: {}", parts[1], e))
-    })?;
+    let crate::types::MarketId {
+        base_token_id,
+        quote_token_id,
+    } = market_id.parse()?;
 
     let base_symbol = token_id_symbol_map.get(&base_token_id).ok_or_else(|| {
         AlphaSecError::not_found(format!("Base token not found: {}", base_token_id))
@@ -81,6 +85,42 @@ pub fn token_id_to_symbol(
     Ok(symbol.clone())
 }
 
+/// Check that `url` can serve as the REST API base URL: `http(s)` with a host, and
+/// without a query or fragment that joined routes would end up inside
+pub(crate) fn validate_api_url(url: &Url) -> Result<()> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(AlphaSecError::config(format!(
+            "API URL {} must be http:// or https://",
+            url
+        )));
+    }
+    if url.host_str().is_none() {
+        return Err(AlphaSecError::config(format!(
+            "API URL {} has no host",
+            url
+        )));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(AlphaSecError::config(format!(
+            "API URL {} must not have a query or fragment",
+            url
+        )));
+    }
+    Ok(())
+}
+
+/// `url` as a base for [`join_url`]: its path prefix, if any, is kept and a single
+/// trailing slash is dropped
+pub(crate) fn api_base_url(url: &Url) -> String {
+    let url = url.as_str();
+    url.strip_suffix('/').unwrap_or(url).to_string()
+}
+
+/// `base` (see [`api_base_url`]) and `path` joined by exactly one slash
+pub(crate) fn join_url(base: &str, path: &str) -> String {
+    format!("{}/{}", base, path.strip_prefix('/').unwrap_or(path))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,34 +159,57 @@ mod tests {
     }
 
     #[test]
-    fn market_to_market_id_empty_base_reports_base_not_found() {
+    fn split_market_explains_malformed_symbols() {
+        assert_eq!(split_market("KAIA/USDT").unwrap(), ("KAIA", "USDT"));
+        for (input, problem) in [
+            ("", "the symbol is empty"),
+            ("/USDT", "the base symbol is empty"),
+            ("KAIA/", "the quote symbol is empty"),
+            ("/", "both the base and the quote symbol are empty"),
+            ("KAIA", "it has no '/'"),
+            ("KAIA/USD/T", "it has 2 '/' separators"),
+        ] {
+            match split_market(input).unwrap_err() {
+                AlphaSecError::InvalidParameter(msg) => {
+                    assert!(msg.contains(problem), "input {:?}: {}", input, msg)
+                }
+                other => panic!(
+                    "input {:?}: expected InvalidParameter, got {:?}",
+                    input, other
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn market_to_market_id_empty_base_reports_base_invalid() {
         let err = market_to_market_id("/USDT", &symbol_map()).unwrap_err();
         match err {
-            AlphaSecError::NotFound(msg) => {
-                assert!(msg.contains("Base token not found"), "got: {}", msg);
+            AlphaSecError::InvalidParameter(msg) => {
+                assert!(msg.contains("the base symbol is empty"), "got: {}", msg);
                 assert!(
-                    !msg.contains("Quote"),
+                    !msg.contains("quote"),
                     "base failure must not blame quote: {}",
                     msg
                 );
             }
-            other => panic!("expected NotFound, got {:?}", other),
+            other => panic!("expected InvalidParameter, got {:?}", other),
         }
     }
 
     #[test]
-    fn market_to_market_id_empty_quote_reports_quote_not_found() {
+    fn market_to_market_id_empty_quote_reports_quote_invalid() {
         let err = market_to_market_id("BTC/", &symbol_map()).unwrap_err();
         match err {
-            AlphaSecError::NotFound(msg) => {
-                assert!(msg.contains("Quote token not found"), "got: {}", msg);
+            AlphaSecError::InvalidParameter(msg) => {
+                assert!(msg.contains("the quote symbol is empty"), "got: {}", msg);
                 assert!(
-                    !msg.contains("Base"),
+                    !msg.contains("base"),
                     "quote failure must not blame base: {}",
                     msg
                 );
             }
-            other => panic!("expected NotFound, got {:?}", other),
+            other => panic!("expected InvalidParameter, got {:?}", other),
         }
     }
 
@@ -204,4 +267,49 @@ mod tests {
             err
         );
     }
+
+    #[test]
+    fn base_urls_join_routes_with_one_slash() {
+        for (api_url, expected) in [
+            (
+                "https://api.alphasec.trade",
+                "https://api.alphasec.trade/api/v1/market",
+            ),
+            (
+                "https://api.alphasec.trade/",
+                "https://api.alphasec.trade/api/v1/market",
+            ),
+            (
+                "http://127.0.0.1:8080/gw",
+                "http://127.0.0.1:8080/gw/api/v1/market",
+            ),
+            (
+                "http://127.0.0.1:8080/gw/",
+                "http://127.0.0.1:8080/gw/api/v1/market",
+            ),
+        ] {
+            let url = Url::parse(api_url).unwrap();
+            validate_api_url(&url).unwrap();
+            let base = api_base_url(&url);
+            assert_eq!(join_url(&base, "/api/v1/market"), expected, "{}", api_url);
+            assert_eq!(join_url(&base, "api/v1/market"), expected, "{}", api_url);
+        }
+    }
+
+    #[test]
+    fn unusable_base_urls_are_rejected() {
+        for api_url in [
+            "ftp://api.alphasec.trade",
+            "https://api.alphasec.trade/?key=1",
+            "https://api.alphasec.trade/#top",
+            "unix:/run/alphasec.sock",
+        ] {
+            let url = Url::parse(api_url).unwrap();
+            assert!(
+                matches!(validate_api_url(&url), Err(AlphaSecError::Config(_))),
+                "{}",
+                api_url
+            );
+        }
+    }
 }
@@ -1,5 +1,6 @@
 //! Error types for the AlphaSec SDK
 
+use std::time::Duration;
 use thiserror::Error;
 
 /// Result type alias for AlphaSec operations
@@ -42,6 +43,13 @@ pub enum AlphaSecError {
         message: String,
     },
 
+    /// Rate limit exceeded (HTTP 429)
+    #[error("Rate limited{}", retry_after.map(|d| format!(", retry after {}s", d.as_secs())).unwrap_or_default())]
+    RateLimited {
+        /// Delay requested by the server's `Retry-After` header, if any
+        retry_after: Option<Duration>,
+    },
+
     /// Authentication/Session errors
     #[error("Authentication failed: {0}")]
     Auth(String),
@@ -58,6 +66,56 @@ pub enum AlphaSecError {
     #[error("Invalid address: {0}")]
     InvalidAddress(String),
 
+    /// Unlocked balance does not cover an order
+    #[error("Insufficient {token} balance: {required} required, {available} available")]
+    InsufficientBalance {
+        /// Symbol of the token the order would debit
+        token: String,
+        /// Amount the order needs
+        required: rust_decimal::Decimal,
+        /// Unlocked amount held
+        available: rust_decimal::Decimal,
+    },
+
+    /// The server rejected a request for lack of balance
+    ///
+    /// The client-side pre-trade check raises [`AlphaSecError::InsufficientBalance`]
+    /// instead; [`AlphaSecError::kind`] reports both as [`ErrorKind::InsufficientBalance`].
+    #[error("Insufficient balance (API error {code}): {message}")]
+    InsufficientFunds {
+        /// Error code from the API
+        code: i32,
+        /// Error message from the API
+        message: String,
+    },
+
+    /// The market is not open for trading
+    #[error("Market closed (API error {code}): {message}")]
+    MarketClosed {
+        /// Error code from the API
+        code: i32,
+        /// Error message from the API
+        message: String,
+    },
+
+    /// The order does not exist or is no longer open
+    #[error("Order not found (API error {code}): {message}")]
+    OrderNotFound {
+        /// Error code from the API
+        code: i32,
+        /// Error message from the API
+        message: String,
+    },
+
+    /// The order price is outside the market's allowed range
+    #[error("Price out of bounds (API error {code}): {message}")]
+    PriceOutOfBounds {
+        /// Error code from the API
+        code: i32,
+        /// Error message from the API
+        message: String,
+    },
+
     /// Token/Market not found errors
     #[error("Not found: {0}")]
     NotFound(String),
@@ -74,11 +132,90 @@ pub enum AlphaSecError {
     #[error("Nonce generation error: {0}")]
     Nonce(String),
 
+    /// The server does not offer a feature, see `Agent::server_capabilities`
+    #[error("Unsupported by this server: {0}")]
+    Unsupported(String),
+
     /// Generic errors
     #[error("AlphaSec error: {0}")]
     Generic(String),
 }
 
+/// Category of an [`AlphaSecError`], see [`AlphaSecError::kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// [`AlphaSecError::Http`]
+    Http,
+    /// [`AlphaSecError::Json`]
+    Json,
+    /// `AlphaSecError::WebSocket`
+    WebSocket,
+    /// [`AlphaSecError::Ethereum`]
+    Ethereum,
+    /// [`AlphaSecError::Eip712`]
+    Eip712,
+    /// [`AlphaSecError::Config`]
+    Config,
+    /// [`AlphaSecError::Api`] not recognized as one of the kinds below
+    Api,
+    /// [`AlphaSecError::RateLimited`]
+    RateLimited,
+    /// [`AlphaSecError::Auth`]
+    Auth,
+    /// [`AlphaSecError::InvalidParameter`]
+    InvalidParameter,
+    /// [`AlphaSecError::Network`]
+    Network,
+    /// [`AlphaSecError::InvalidAddress`]
+    InvalidAddress,
+    /// [`AlphaSecError::InsufficientBalance`] or [`AlphaSecError::InsufficientFunds`]
+    InsufficientBalance,
+    /// [`AlphaSecError::MarketClosed`]
+    MarketClosed,
+    /// [`AlphaSecError::OrderNotFound`]
+    OrderNotFound,
+    /// [`AlphaSecError::PriceOutOfBounds`]
+    PriceOutOfBounds,
+    /// [`AlphaSecError::NotFound`]
+    NotFound,
+    /// [`AlphaSecError::Signer`]
+    Signer,
+    /// [`AlphaSecError::TransactionEncoding`]
+    TransactionEncoding,
+    /// [`AlphaSecError::Nonce`]
+    Nonce,
+    /// [`AlphaSecError::Unsupported`]
+    Unsupported,
+    /// [`AlphaSecError::Generic`]
+    Generic,
+}
+
+/// Lower-case `errMsg` fragments recognized by [`AlphaSecError::from_api`]
+const API_MESSAGE_KINDS: &[(&str, ErrorKind)] = &[
+    ("insufficient balance", ErrorKind::InsufficientBalance),
+    ("insufficient fund", ErrorKind::InsufficientBalance),
+    ("not enough balance", ErrorKind::InsufficientBalance),
+    ("market closed", ErrorKind::MarketClosed),
+    ("market is closed", ErrorKind::MarketClosed),
+    ("market not open", ErrorKind::MarketClosed),
+    ("market is not open", ErrorKind::MarketClosed),
+    ("trading halted", ErrorKind::MarketClosed),
+    ("order not found", ErrorKind::OrderNotFound),
+    ("order does not exist", ErrorKind::OrderNotFound),
+    ("unknown order", ErrorKind::OrderNotFound),
+    ("price out of", ErrorKind::PriceOutOfBounds),
+    ("price band", ErrorKind::PriceOutOfBounds),
+    ("price exceeds", ErrorKind::PriceOutOfBounds),
+];
+
+/// Lower-case `errMsg` phrases marking a nonce conflict
+const NONCE_CONFLICT_MESSAGES: &[&str] = &[
+    "duplicate nonce",
+    "invalid nonce",
+    "nonce too low",
+    "nonce already used",
+];
+
 impl AlphaSecError {
     /// Create a new API error
     pub fn api(code: i32, message: impl Into<String>) -> Self {
@@ -88,6 +225,124 @@ impl AlphaSecError {
         }
     }
 
+    /// Create an error from a server `code`/`errMsg` pair
+    ///
+    /// Well-known rejection messages become [`InsufficientFunds`](Self::InsufficientFunds),
+    /// [`MarketClosed`](Self::MarketClosed), [`OrderNotFound`](Self::OrderNotFound) or
+    /// [`PriceOutOfBounds`](Self::PriceOutOfBounds); anything else is
+    /// [`Api`](Self::Api). The code and message are kept either way, see
+    /// [`api_code`](Self::api_code) and [`api_message`](Self::api_message).
+    pub fn from_api(code: i32, message: impl Into<String>) -> Self {
+        let message = message.into();
+        let lower = message.to_lowercase();
+        let kind = API_MESSAGE_KINDS
+            .iter()
+            .find(|(fragment, _)| lower.contains(fragment))
+            .map(|(_, kind)| *kind);
+        match kind {
+            Some(ErrorKind::InsufficientBalance) => Self::InsufficientFunds { code, message },
+            Some(ErrorKind::MarketClosed) => Self::MarketClosed { code, message },
+            Some(ErrorKind::OrderNotFound) => Self::OrderNotFound { code, message },
+            Some(ErrorKind::PriceOutOfBounds) => Self::PriceOutOfBounds { code, message },
+            _ => Self::Api { code, message },
+        }
+    }
+
+    /// Server error code, for [`Api`](Self::Api) and the variants
+    /// [`from_api`](Self::from_api) maps to
+    pub fn api_code(&self) -> Option<i32> {
+        match self {
+            Self::Api { code, .. }
+            | Self::InsufficientFunds { code, .. }
+            | Self::MarketClosed { code, .. }
+            | Self::OrderNotFound { code, .. }
+            | Self::PriceOutOfBounds { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Raw server error message, for the same variants as [`api_code`](Self::api_code)
+    pub fn api_message(&self) -> Option<&str> {
+        match self {
+            Self::Api { message, .. }
+            | Self::InsufficientFunds { message, .. }
+            | Self::MarketClosed { message, .. }
+            | Self::OrderNotFound { message, .. }
+            | Self::PriceOutOfBounds { message, .. } => Some(message),
+            _ => None,
+        }
+    }
+
+    /// Category of this error, for matching without destructuring
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Http(_) => ErrorKind::Http,
+            Self::Json(_) => ErrorKind::Json,
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(_) => ErrorKind::WebSocket,
+            Self::Ethereum(_) => ErrorKind::Ethereum,
+            Self::Eip712(_) => ErrorKind::Eip712,
+            Self::Config(_) => ErrorKind::Config,
+            Self::Api { .. } => ErrorKind::Api,
+            Self::RateLimited { .. } => ErrorKind::RateLimited,
+            Self::Auth(_) => ErrorKind::Auth,
+            Self::InvalidParameter(_) => ErrorKind::InvalidParameter,
+            Self::Network(_) => ErrorKind::Network,
+            Self::InvalidAddress(_) => ErrorKind::InvalidAddress,
+            Self::InsufficientBalance { .. } | Self::InsufficientFunds { .. } => {
+                ErrorKind::InsufficientBalance
+            }
+            Self::MarketClosed { .. } => ErrorKind::MarketClosed,
+            Self::OrderNotFound { .. } => ErrorKind::OrderNotFound,
+            Self::PriceOutOfBounds { .. } => ErrorKind::PriceOutOfBounds,
+            Self::NotFound(_) => ErrorKind::NotFound,
+            Self::Signer(_) => ErrorKind::Signer,
+            Self::TransactionEncoding(_) => ErrorKind::TransactionEncoding,
+            Self::Nonce(_) => ErrorKind::Nonce,
+            Self::Unsupported(_) => ErrorKind::Unsupported,
+            Self::Generic(_) => ErrorKind::Generic,
+        }
+    }
+
+    /// Create a new rate limit error
+    pub fn rate_limited(retry_after: Option<Duration>) -> Self {
+        Self::RateLimited { retry_after }
+    }
+
+    /// Check if this error is a rate limit rejection
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, Self::RateLimited { .. })
+    }
+
+    /// Check if this is a server rejection of the transaction nonce: a message
+    /// containing "duplicate nonce", "invalid nonce", "nonce too low" or "nonce already
+    /// used"
+    ///
+    /// Such a transaction can be signed again with a fresh nonce and resubmitted, as
+    /// long as no earlier copy of it may have been accepted.
+    pub fn is_nonce_conflict(&self) -> bool {
+        let Self::Api { message, .. } = self else {
+            return false;
+        };
+        let lower = message.to_lowercase();
+        NONCE_CONFLICT_MESSAGES
+            .iter()
+            .any(|phrase| lower.contains(phrase))
+    }
+
+    /// Create a new insufficient balance error
+    pub fn insufficient_balance(
+        token: impl Into<String>,
+        required: rust_decimal::Decimal,
+        available: rust_decimal::Decimal,
+    ) -> Self {
+        Self::InsufficientBalance {
+            token: token.into(),
+            required,
+            available,
+        }
+    }
+
     /// Create a new configuration error
     pub fn config(message: impl Into<String>) -> Self {
         Self::Config(message.into())
@@ -133,6 +388,11 @@ impl AlphaSecError {
         Self::Nonce(message.into())
     }
 
+    /// Create a new unsupported feature error
+    pub fn unsupported(message: impl Into<String>) -> Self {
+        Self::Unsupported(message.into())
+    }
+
     /// Create a new EIP-712 signing error
     pub fn eip712(message: impl Into<String>) -> Self {
         Self::Eip712(message.into())
@@ -173,4 +433,99 @@ mod tests {
             "API error 502: a: b: c"
         );
     }
+
+    #[test]
+    fn rate_limited_display_includes_retry_after_only_when_known() {
+        let err = AlphaSecError::rate_limited(Some(Duration::from_secs(2)));
+        assert!(err.is_rate_limited());
+        assert_eq!(err.to_string(), "Rate limited, retry after 2s");
+        assert_eq!(
+            AlphaSecError::rate_limited(None).to_string(),
+            "Rate limited"
+        );
+        // A plain 429 Api error is not the dedicated variant.
+        assert!(!AlphaSecError::api(429, "slow down").is_rate_limited());
+    }
+
+    #[test]
+    fn nonce_conflicts_are_recognized() {
+        for message in [
+            "duplicate nonce",
+            "Invalid nonce: 1700000000000",
+            "nonce too low",
+            "Nonce already used",
+        ] {
+            assert!(
+                AlphaSecError::from_api(400, message).is_nonce_conflict(),
+                "{message:?}"
+            );
+        }
+        assert!(!AlphaSecError::from_api(400, "invalid signature").is_nonce_conflict());
+        assert!(!AlphaSecError::from_api(400, "nonce").is_nonce_conflict());
+        // "nonce" next to a generic word is not enough
+        for message in [
+            "nonce expired",
+            "session nonce invalid",
+            "used nonce window",
+        ] {
+            assert!(
+                !AlphaSecError::from_api(400, message).is_nonce_conflict(),
+                "{message:?}"
+            );
+        }
+        assert!(!AlphaSecError::nonce("duplicate nonce").is_nonce_conflict());
+    }
+
+    #[test]
+    fn from_api_maps_well_known_server_rejections() {
+        let cases = [
+            (
+                400,
+                "Insufficient balance for order",
+                ErrorKind::InsufficientBalance,
+            ),
+            (
+                400,
+                "insufficient funds: 2 USDT required",
+                ErrorKind::InsufficientBalance,
+            ),
+            (403, "Market is closed", ErrorKind::MarketClosed),
+            (403, "trading halted for KAIA/USDT", ErrorKind::MarketClosed),
+            (404, "order not found", ErrorKind::OrderNotFound),
+            (400, "Order does not exist: 0xabc", ErrorKind::OrderNotFound),
+            (400, "price out of range", ErrorKind::PriceOutOfBounds),
+            (400, "Price exceeds price band", ErrorKind::PriceOutOfBounds),
+            (500, "internal error", ErrorKind::Api),
+        ];
+        for (code, message, kind) in cases {
+            let err = AlphaSecError::from_api(code, message);
+            assert_eq!(err.kind(), kind, "{message:?}");
+            assert_eq!(err.api_code(), Some(code));
+            assert_eq!(err.api_message(), Some(message));
+        }
+
+        let err = AlphaSecError::from_api(400, "Market is closed");
+        assert!(matches!(err, AlphaSecError::MarketClosed { code: 400, .. }));
+        assert_eq!(
+            err.to_string(),
+            "Market closed (API error 400): Market is closed"
+        );
+    }
+
+    #[test]
+    fn kind_groups_client_and_server_balance_errors() {
+        let client = AlphaSecError::insufficient_balance(
+            "USDT",
+            rust_decimal::Decimal::TEN,
+            rust_decimal::Decimal::ONE,
+        );
+        assert_eq!(client.kind(), ErrorKind::InsufficientBalance);
+        assert_eq!(client.api_code(), None);
+        assert_eq!(AlphaSecError::config("x").kind(), ErrorKind::Config);
+        // `api` never re-maps; only `from_api` does.
+        assert_eq!(
+            AlphaSecError::api(400, "insufficient balance").kind(),
+            ErrorKind::Api
+        );
+    }
 }
@@ -60,6 +60,7 @@
 pub mod agent;
 pub mod api;
 pub mod error;
+pub mod offline;
 pub mod perp;
 pub mod signer;
 pub mod types;
@@ -67,8 +68,10 @@ pub mod types;
 #[cfg(feature = "websocket")]
 pub mod websocket;
 
+mod telemetry;
+
 // Re-exports for convenience
 pub use agent::Agent;
-pub use error::{AlphaSecError, Result};
-pub use signer::{AlphaSecSigner, Config};
+pub use error::{AlphaSecError, ErrorKind, Result};
+pub use signer::{AlphaSecSigner, Config, OrderParams, OrderSigning};
 pub use types::*;
@@ -0,0 +1,243 @@
+//! Signing without a network connection
+//!
+//! Everything here needs only an [`AlphaSecSigner`] built from a [`Config`](crate::Config)
+//! holding a private key, plus token IDs known in advance. No token metadata is fetched
+//! and no request is made, so an air-gapped machine can produce the signed transaction
+//! and another one can broadcast it with [`ApiClient::order`](crate::api::ApiClient::order)
+//! or [`ApiClient::cancel`](crate::api::ApiClient::cancel).
+//!
+//! ```rust,no_run
+//! use alphasec_rs::{offline, AlphaSecSigner, Config, OrderMode, OrderSide, OrderType};
+//!
+//! # async fn run() -> alphasec_rs::Result<()> {
+//! let config = Config::new(
+//!     "https://api-testnet.alphasec.trade",
+//!     "kairos",
+//!     "0x0000000000000000000000000000000000000000",
+//!     Some("your_l1_private_key"),
+//!     None,
+//!     false,
+//!     None,
+//! )?;
+//! let signer = AlphaSecSigner::new(config);
+//! // KAIA (token 1) / USDT (token 2)
+//! let signed_tx = offline::order(
+//!     &signer,
+//!     "1",
+//!     "2",
+//!     OrderSide::Buy,
+//!     "0.15".parse().unwrap(),
+//!     "100".parse().unwrap(),
+//!     OrderType::Limit,
+//!     OrderMode::Base,
+//!     None,
+//! )
+//! .await?;
+//! println!("{signed_tx}");
+//! # Ok(())
+//! # }
+//! ```
+
+use rust_decimal::Decimal;
+
+use crate::{
+    error::Result,
+    signer::{AlphaSecSigner, OrderParams},
+    types::{OrderMode, OrderSide, OrderType},
+};
+
+/// Signed order transaction for `base_token_id`/`quote_token_id`
+///
+/// Prices and quantities are truncated
+/// ([`RoundingMode::Truncate`](crate::types::RoundingMode::Truncate)) with no tick size
+/// or token decimals applied; use [`AlphaSecSigner::build_signed_order`] with an
+/// [`OrderParams`] to set those or TP/SL prices. `timestamp_ms` is the transaction
+/// nonce and defaults to now.
+pub async fn order(
+    signer: &AlphaSecSigner,
+    base_token_id: &str,
+    quote_token_id: &str,
+    side: OrderSide,
+    price: Decimal,
+    quantity: Decimal,
+    order_type: OrderType,
+    order_mode: OrderMode,
+    timestamp_ms: Option<u64>,
+) -> Result<String> {
+    let params = OrderParams::new(
+        base_token_id,
+        quote_token_id,
+        side as u32,
+        price,
+        quantity,
+        order_type as u32,
+        order_mode as u32,
+    );
+    signer.build_signed_order(&params, timestamp_ms).await
+}
+
+/// Signed cancel transaction for `order_id`
+pub async fn cancel(
+    signer: &AlphaSecSigner,
+    order_id: &str,
+    timestamp_ms: Option<u64>,
+) -> Result<String> {
+    let data = signer.create_cancel_data(order_id)?;
+    signer
+        .generate_alphasec_transaction(timestamp_ms, &data, None)
+        .await
+}
+
+/// Signed transaction cancelling every open order of the account
+pub async fn cancel_all(signer: &AlphaSecSigner, timestamp_ms: Option<u64>) -> Result<String> {
+    let data = signer.create_cancel_all_data()?;
+    signer
+        .generate_alphasec_transaction(timestamp_ms, &data, None)
+        .await
+}
+
+/// Signed transaction amending the price and quantity of `order_id`
+pub async fn modify(
+    signer: &AlphaSecSigner,
+    order_id: &str,
+    new_price: Decimal,
+    new_qty: Decimal,
+    order_mode: OrderMode,
+    timestamp_ms: Option<u64>,
+) -> Result<String> {
+    let data = signer.create_modify_data(order_id, new_price, new_qty, order_mode as u32)?;
+    signer
+        .generate_alphasec_transaction(timestamp_ms, &data, None)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ethers::{
+        signers::Signer,
+        types::{transaction::eip2718::TypedTransaction, Address},
+    };
+
+    use super::*;
+    use crate::{
+        chain_ids::ALPHASEC_TESTNET_CHAIN_ID,
+        dex_commands::{DEX_COMMAND_CANCEL, DEX_COMMAND_ORDER},
+        l2_contracts::ALPHASEC_ORDER_CONTRACT_ADDR,
+        Config,
+    };
+
+    /// Signer holding only a key; the API URL is unreachable, so any request would fail.
+    fn keyed_signer() -> AlphaSecSigner {
+        let config = Config::new(
+            "http://127.0.0.1:1",
+            "kairos",
+            "0x0000000000000000000000000000000000000000",
+            Some("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"),
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+        AlphaSecSigner::new(config)
+    }
+
+    /// Decode a signed tx, checking its signature recovers to the signer's wallet.
+    fn decode(signer: &AlphaSecSigner, tx_hex: &str) -> TypedTransaction {
+        let raw = hex::decode(tx_hex.trim_start_matches("0x")).unwrap();
+        let rlp = ethers::core::utils::rlp::Rlp::new(&raw);
+        let (tx, signature) = TypedTransaction::decode_signed(&rlp).unwrap();
+        assert_eq!(
+            signature.recover(tx.sighash()).unwrap(),
+            signer.get_wallet().unwrap().address()
+        );
+        tx
+    }
+
+    #[tokio::test]
+    async fn order_is_signed_from_a_key_and_token_ids_alone() {
+        let signer = keyed_signer();
+        let signed = order(
+            &signer,
+            "5",
+            "2",
+            OrderSide::Buy,
+            Decimal::from_str("0.15").unwrap(),
+            Decimal::from(100),
+            OrderType::Limit,
+            OrderMode::Base,
+            Some(1_700_000_000_000),
+        )
+        .await
+        .unwrap();
+
+        let tx = decode(&signer, &signed);
+        assert_eq!(
+            tx.to_addr(),
+            Some(&Address::from_str(ALPHASEC_ORDER_CONTRACT_ADDR).unwrap())
+        );
+        assert_eq!(tx.nonce().unwrap().as_u64(), 1_700_000_000_000);
+        assert_eq!(tx.chain_id().unwrap().as_u64(), ALPHASEC_TESTNET_CHAIN_ID);
+        let data = tx.data().unwrap();
+        assert_eq!(data[0], DEX_COMMAND_ORDER);
+        let json: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
+        assert_eq!(json["baseToken"], "5");
+        assert_eq!(json["quoteToken"], "2");
+        assert_eq!(json["price"], "0.15");
+
+        // Same inputs, same transaction
+        let again = order(
+            &signer,
+            "5",
+            "2",
+            OrderSide::Buy,
+            Decimal::from_str("0.15").unwrap(),
+            Decimal::from(100),
+            OrderType::Limit,
+            OrderMode::Base,
+            Some(1_700_000_000_000),
+        )
+        .await
+        .unwrap();
+        assert_eq!(signed, again);
+    }
+
+    #[tokio::test]
+    async fn cancel_and_modify_are_signed_offline() {
+        let signer = keyed_signer();
+        let tx = decode(&signer, &cancel(&signer, "0xabc", Some(1)).await.unwrap());
+        assert_eq!(tx.data().unwrap()[0], DEX_COMMAND_CANCEL);
+
+        let tx = decode(&signer, &cancel_all(&signer, Some(2)).await.unwrap());
+        assert_eq!(tx.nonce().unwrap().as_u64(), 2);
+
+        let signed = modify(
+            &signer,
+            "0xabc",
+            Decimal::from_str("0.2").unwrap(),
+            Decimal::from(5),
+            OrderMode::Base,
+            Some(3),
+        )
+        .await
+        .unwrap();
+        decode(&signer, &signed);
+    }
+
+    #[tokio::test]
+    async fn signing_without_a_key_fails() {
+        let config = Config::new(
+            "http://127.0.0.1:1",
+            "kairos",
+            "0x0000000000000000000000000000000000000000",
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+        let signer = AlphaSecSigner::new(config);
+        assert!(cancel_all(&signer, Some(1)).await.is_err());
+    }
+}
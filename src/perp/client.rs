@@ -8,6 +8,10 @@
 //! does in `src/api/client.rs`.
 
 use crate::{
+    api::{
+        client::{build_http_client, REQUEST_ID_HEADER},
+        utils::{api_base_url, join_url, validate_api_url},
+    },
     error::{AlphaSecError, Result},
     perp::types::{
         FundingItem, MarketsResponse, PerpAccount, PerpCandle, PerpDepth, PerpFill,
@@ -15,11 +19,11 @@ use crate::{
         PerpTrade, Position, PositionHistory, PositionSetting,
     },
     signer::Config,
+    telemetry,
 };
 use reqwest::Client as HttpClient;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
-use std::time::Duration;
 use tracing::debug;
 
 /// HTTP client for all `/fapi/v1` REST endpoints.
@@ -30,34 +34,21 @@ use tracing::debug;
 pub struct PerpApiClient {
     /// Shared reqwest HTTP client
     http_client: HttpClient,
-    /// Base URL (scheme + host, no trailing slash)
+    /// Base URL (scheme, host and any path prefix, no trailing slash)
     base_url: String,
 }
 
 impl PerpApiClient {
     /// Create a new `PerpApiClient` from a `Config`.
     ///
-    /// Mirrors `ApiClient::new` — same timeout, same Content-Type default header.
+    /// Mirrors `ApiClient::new` — same timeout, Content-Type and User-Agent headers.
     pub fn new(config: &Config) -> Result<Self> {
-        let http_client = HttpClient::builder()
-            .timeout(Duration::from_secs(config.timeout_secs))
-            .default_headers({
-                let mut headers = reqwest::header::HeaderMap::new();
-                headers.insert(
-                    reqwest::header::CONTENT_TYPE,
-                    reqwest::header::HeaderValue::from_static("application/json"),
-                );
-                headers
-            })
-            .build()
-            .map_err(|e| AlphaSecError::config(format!("Failed to create HTTP client: {}", e)))?;
-
-        // Strip trailing slash so all path concatenations are uniform.
-        let base_url = config.api_url.to_string().trim_end_matches('/').to_string();
+        validate_api_url(&config.api_url)?;
+        let http_client = build_http_client(config)?;
 
         Ok(Self {
             http_client,
-            base_url,
+            base_url: api_base_url(&config.api_url),
         })
     }
 
@@ -67,41 +58,31 @@ impl PerpApiClient {
 
     /// Build a full URL from a `/fapi/v1/...` path.
     fn url(&self, path: &str) -> String {
-        if path.starts_with('/') {
-            format!("{}{}", self.base_url, path)
-        } else {
-            format!("{}/{}", self.base_url, path)
-        }
+        join_url(&self.base_url, path)
     }
 
     /// POST `{"tx": signed_tx}` to `path`; return the `result` field as a String
     /// (tx hash on success).  Uses the `{code, errMsg, result}` envelope.
     async fn submit(&self, path: &str, signed_tx: &str) -> Result<String> {
         let url = self.url(path);
-        debug!("POST {}", url);
+        let request_id = uuid::Uuid::new_v4().to_string();
+        debug!("POST {} ({}: {})", url, REQUEST_ID_HEADER, request_id);
 
         let body = serde_json::json!({ "tx": signed_tx });
-        let response = self
+        let request = self
             .http_client
             .post(&url)
-            .body(body.to_string())
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let code = response.status().as_u16() as i32;
-            let text = response.text().await.unwrap_or_default();
-            return Err(AlphaSecError::api(code, text));
-        }
-
-        let json: Value = response.json().await?;
+            .header("Content-Type", "application/json")
+            .header(REQUEST_ID_HEADER, &request_id)
+            .body(body.to_string());
+        let json = Self::send("POST", request).await?;
         let code = json["code"].as_i64().unwrap_or(0);
         if code != 200 {
             let msg = json["errMsg"]
                 .as_str()
                 .unwrap_or("unknown error")
                 .to_string();
-            return Err(AlphaSecError::api(code as i32, msg));
+            return Err(AlphaSecError::from_api(code as i32, msg));
         }
 
         // result is a tx-hash string
@@ -134,17 +115,31 @@ impl PerpApiClient {
             }
         }
 
-        debug!("GET {}", url);
-        let response = self.http_client.get(&url).send().await?;
-
-        if !response.status().is_success() {
-            let code = response.status().as_u16() as i32;
-            let text = response.text().await.unwrap_or_default();
-            return Err(AlphaSecError::api(code, text));
+        let request_id = uuid::Uuid::new_v4().to_string();
+        debug!("GET {} ({}: {})", url, REQUEST_ID_HEADER, request_id);
+        let request = self
+            .http_client
+            .get(&url)
+            .header(REQUEST_ID_HEADER, &request_id);
+        decode_envelope(Self::send("GET", request).await?)
+    }
+
+    /// Send `request` and decode the JSON body; a non-2xx status is an API error
+    /// carrying the status code and raw body.
+    async fn send(method: &'static str, request: reqwest::RequestBuilder) -> Result<Value> {
+        let started = telemetry::start();
+        let result = async {
+            let response = request.send().await?;
+            if !response.status().is_success() {
+                let code = response.status().as_u16() as i32;
+                let text = response.text().await.unwrap_or_default();
+                return Err(AlphaSecError::from_api(code, text));
+            }
+            Ok(response.json().await?)
         }
-
-        let json: Value = response.json().await?;
-        decode_envelope(json)
+        .await;
+        telemetry::http_request(method, started, &result);
+        result
     }
 
     // -------------------------------------------------------------------------
@@ -453,7 +448,7 @@ fn build_candle_params<'a>(
 
 /// Decode the `{code, errMsg, result}` envelope into `T`.
 ///
-/// `code != 200` maps to `AlphaSecError::Api`; otherwise `result` is deserialized into `T`.
+/// `code != 200` maps through `AlphaSecError::from_api`; otherwise `result` is deserialized into `T`.
 /// Pure (no transport) so the envelope contract can be unit-tested offline.
 fn decode_envelope<T: DeserializeOwned>(json: Value) -> Result<T> {
     let code = json["code"].as_i64().unwrap_or(0);
@@ -462,7 +457,7 @@ fn decode_envelope<T: DeserializeOwned>(json: Value) -> Result<T> {
             .as_str()
             .unwrap_or("unknown error")
             .to_string();
-        return Err(AlphaSecError::api(code as i32, msg));
+        return Err(AlphaSecError::from_api(code as i32, msg));
     }
     let result = serde_json::from_value(json["result"].clone()).map_err(AlphaSecError::Json)?;
     Ok(result)
@@ -658,6 +653,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn envelope_maps_well_known_rejections_keeping_code_and_msg() {
+        let json =
+            serde_json::json!({ "code": 400, "errMsg": "Insufficient balance", "result": null });
+        let err = decode_envelope::<PerpAccount>(json).unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::InsufficientBalance);
+        assert_eq!(err.api_code(), Some(400));
+        assert_eq!(err.api_message(), Some("Insufficient balance"));
+    }
+
     #[test]
     fn envelope_missing_code_is_treated_as_error_not_success() {
         let json = serde_json::json!({ "result": { "anything": 1 } });
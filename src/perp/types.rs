@@ -98,7 +98,11 @@ pub enum PositionSide {
 }
 
 /// Perp order status.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Parsed ignoring case and `_` separators; statuses this SDK does not know become
+/// [`PerpOrderStatus::Other`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 pub enum PerpOrderStatus {
     /// Accepted open order
     New,
@@ -116,6 +120,53 @@ pub enum PerpOrderStatus {
     PendingTrigger,
     /// Waiting for trigger condition
     WaitingTrigger,
+    /// Any other status, as received
+    Other(String),
+}
+
+impl PerpOrderStatus {
+    /// Parse a status string (`"PARTIALLY_FILLED"`, `"PartiallyFilled"`)
+    pub fn parse(status: &str) -> Self {
+        match crate::types::orders::enum_key(status).as_str() {
+            "NEW" => Self::New,
+            "PARTIALLYFILLED" => Self::PartiallyFilled,
+            "FILLED" => Self::Filled,
+            "CANCELED" => Self::Canceled,
+            "EXPIRED" => Self::Expired,
+            "REJECTED" => Self::Rejected,
+            "PENDINGTRIGGER" => Self::PendingTrigger,
+            "WAITINGTRIGGER" => Self::WaitingTrigger,
+            _ => Self::Other(status.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for PerpOrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PerpOrderStatus::New => write!(f, "NEW"),
+            PerpOrderStatus::PartiallyFilled => write!(f, "PARTIALLY_FILLED"),
+            PerpOrderStatus::Filled => write!(f, "FILLED"),
+            PerpOrderStatus::Canceled => write!(f, "CANCELED"),
+            PerpOrderStatus::Expired => write!(f, "EXPIRED"),
+            PerpOrderStatus::Rejected => write!(f, "REJECTED"),
+            PerpOrderStatus::PendingTrigger => write!(f, "PENDING_TRIGGER"),
+            PerpOrderStatus::WaitingTrigger => write!(f, "WAITING_TRIGGER"),
+            PerpOrderStatus::Other(status) => write!(f, "{}", status),
+        }
+    }
+}
+
+impl From<String> for PerpOrderStatus {
+    fn from(status: String) -> Self {
+        Self::parse(&status)
+    }
+}
+
+impl From<PerpOrderStatus> for String {
+    fn from(status: PerpOrderStatus) -> Self {
+        status.to_string()
+    }
 }
 
 // ---------------------------------------------------------------------------
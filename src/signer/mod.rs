@@ -6,8 +6,8 @@ pub mod signer;
 pub mod transaction;
 pub mod utils;
 
-pub use config::Config;
+pub use config::{Config, ConfigFile, L1GasConfig, OrderSigning, OrderThrottleConfig, TlsConfig};
 pub use perp_transaction::*;
-pub use signer::AlphaSecSigner;
+pub use signer::{AlphaSecSigner, OrderParams};
 pub use transaction::*;
 pub use utils::*;
@@ -2,16 +2,21 @@
 
 use crate::{
     error::{AlphaSecError, Result},
-    signer::{config::Config, normalize_price_quantity, transaction::*},
+    signer::{
+        config::{Config, OrderSigning},
+        normalize_price_quantity, normalize_price_quantity_with,
+        transaction::*,
+        utils::{get_price_precision, get_quantity_precision},
+    },
     types::{
-        chain_ids::{ALPHASEC_MAINNET_CHAIN_ID, ALPHASEC_TESTNET_CHAIN_ID},
-        constants::{abi::*, l1_contracts::*, ALPHASEC_NATIVE_TOKEN_ID},
+        constants::{abi::*, ALPHASEC_NATIVE_TOKEN_ID},
         dex_commands::*,
         eip712::*,
         gas::*,
         l2_contracts::ALPHASEC_ORDER_CONTRACT_ADDR,
+        RoundingMode,
     },
-    OrderType,
+    OrderMode, OrderSide, OrderType,
 };
 use base64::{self, Engine};
 use ethers::{
@@ -21,11 +26,11 @@ use ethers::{
         transaction::eip2718::TypedTransaction, Address, Bytes, Eip1559TransactionRequest, U256,
         U64,
     },
-    providers::{Middleware, Provider},
+    providers::{JsonRpcClient, Middleware, Provider},
     signers::{LocalWallet, Signer},
     types::transaction::eip712::{Eip712, TypedData as Eip712TypedData},
 };
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde_json;
 use std::str::FromStr;
 use std::{
@@ -36,26 +41,120 @@ use std::{
     time::{SystemTime, UNIX_EPOCH},
 };
 
+/// Largest token decimals accepted by [`AlphaSecSigner::decimal_to_onchain_units`].
+pub const MAX_TOKEN_DECIMALS: u32 = 36;
+
+/// `value * multiplier` (to a thousandth), rounded up; multipliers below 1.0 count as 1.0
+fn scale_by(value: U256, multiplier: f64) -> U256 {
+    let per_mille = (multiplier.max(1.0) * 1000.0).round() as u64;
+    (value * U256::from(per_mille) + 999) / 1000
+}
+
+/// An order for [`AlphaSecSigner::create_order_data`] and the other order builders
+///
+/// Tokens are token IDs and `side`/`order_type`/`order_mode` their wire codes
+/// ([`OrderSide`], [`OrderType`], [`OrderMode`] `as u32`). [`Self::new`] sets the
+/// required fields; set the others with struct update syntax:
+///
+/// ```rust
+/// use alphasec_rs::{OrderMode, OrderParams, OrderSide, OrderType, RoundingMode};
+///
+/// let params = OrderParams {
+///     rounding: RoundingMode::NearestEven,
+///     tp_limit: Some("0.2".parse().unwrap()),
+///     ..OrderParams::new(
+///         "1",
+///         "2",
+///         OrderSide::Buy as u32,
+///         "0.15".parse().unwrap(),
+///         "100".parse().unwrap(),
+///         OrderType::Limit as u32,
+///         OrderMode::Base as u32,
+///     )
+/// };
+/// # let _ = params;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderParams {
+    /// Base token ID
+    pub base_token: String,
+    /// Quote token ID
+    pub quote_token: String,
+    /// Side code
+    pub side: u32,
+    /// Limit price
+    pub price: Decimal,
+    /// Quantity, in the base token or (quote mode) the quote token
+    pub quantity: Decimal,
+    /// Order type code
+    pub order_type: u32,
+    /// Order mode code
+    pub order_mode: u32,
+    /// Take profit limit price
+    pub tp_limit: Option<Decimal>,
+    /// Stop loss trigger price
+    pub sl_trigger: Option<Decimal>,
+    /// Stop loss limit price
+    pub sl_limit: Option<Decimal>,
+    /// How price and quantity are cut to the market's precision
+    pub rounding: RoundingMode,
+    /// Price increment the price is rounded to, if the market has one
+    pub tick_size: Option<Decimal>,
+    /// Decimals of the token `quantity` is denominated in: the base token for
+    /// [`OrderMode::Base`], the quote token for [`OrderMode::Quote`]
+    pub quantity_decimals: Option<u32>,
+    /// Caller-chosen ID sent as `clientOrderId`
+    pub client_order_id: Option<String>,
+    /// Sent as `reduceOnly`
+    pub reduce_only: bool,
+}
+
+impl OrderParams {
+    /// Order with no TP/SL, truncating rounding, no tick size or token decimals, no
+    /// client order ID and not reduce-only
+    pub fn new(
+        base_token: impl Into<String>,
+        quote_token: impl Into<String>,
+        side: u32,
+        price: Decimal,
+        quantity: Decimal,
+        order_type: u32,
+        order_mode: u32,
+    ) -> Self {
+        Self {
+            base_token: base_token.into(),
+            quote_token: quote_token.into(),
+            side,
+            price,
+            quantity,
+            order_type,
+            order_mode,
+            tp_limit: None,
+            sl_trigger: None,
+            sl_limit: None,
+            rounding: RoundingMode::default(),
+            tick_size: None,
+            quantity_decimals: None,
+            client_order_id: None,
+            reduce_only: false,
+        }
+    }
+}
+
 /// AlphaSec transaction signer
 ///
 /// This struct handles all transaction signing operations for AlphaSec,
 /// including EIP-712 typed data signing for session management and
 /// transaction encoding for trading operations.
-#[derive(Debug)]
+///
+/// Clones share the nonce counter, so an agent cloned across tasks still issues a
+/// single increasing nonce sequence, see [`Self::next_nonce`].
+#[derive(Debug, Clone)]
 pub struct AlphaSecSigner {
     /// Configuration
     config: Config,
-    /// Nonce counter for alphasec-style nonce generation
-    nonce_counter: AtomicU64,
-}
-
-impl Clone for AlphaSecSigner {
-    fn clone(&self) -> Self {
-        Self {
-            config: self.config.clone(),
-            nonce_counter: AtomicU64::new(self.nonce_counter.load(Ordering::SeqCst)),
-        }
-    }
+    /// Last nonce handed out by [`Self::next_nonce`] (shared across clone)
+    nonce_counter: Arc<AtomicU64>,
 }
 
 impl AlphaSecSigner {
@@ -63,7 +162,7 @@ impl AlphaSecSigner {
     pub fn new(config: Config) -> Self {
         Self {
             config,
-            nonce_counter: AtomicU64::new(0),
+            nonce_counter: Arc::default(),
         }
     }
 
@@ -77,26 +176,55 @@ impl AlphaSecSigner {
         &self.config.l1_address
     }
 
+    /// Checksummed L2 (session) wallet address, if an L2 key is configured
+    pub fn l2_address(&self) -> Option<String> {
+        self.config.l2_address()
+    }
+
     /// Check if session is enabled
     pub fn is_session_enabled(&self) -> bool {
         self.config.session_enabled
     }
 
+    /// How orders are signed, see [`Config::with_order_signing`]
+    pub fn order_signing(&self) -> OrderSigning {
+        self.config.order_signing
+    }
+
     /// Generate current timestamp in milliseconds
-    fn current_timestamp_ms() -> u64 {
+    pub(crate) fn current_timestamp_ms() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_millis() as u64
     }
 
+    /// Nonce for a transaction whose caller gave no timestamp
+    ///
+    /// The current time in milliseconds, bumped past the last nonce this signer (or any
+    /// clone of it) handed out, so calls landing in the same millisecond still get
+    /// distinct, increasing nonces.
+    pub(crate) fn next_nonce(&self) -> u64 {
+        let now = Self::current_timestamp_ms();
+        let previous = self
+            .nonce_counter
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(now.max(last + 1))
+            })
+            .expect("the update always yields a value");
+        now.max(previous + 1)
+    }
+
     /// Convert a human `value` (float) to on-chain base units.
     ///
     /// Notes:
     /// - Rejects NaN/inf and negative values.
-    /// - Floors/truncates towards zero (matching previous `as u64` behavior for non-negative values).
-    /// - Returns an error instead of silently saturating on overflow.
+    /// - Otherwise behaves as [`Self::decimal_to_onchain_units`] on the float's
+    ///   `Decimal` approximation; prefer that function when the amount is not already
+    ///   an `f64`.
     pub fn to_onchain_units(value: f64, decimals: u32) -> Result<U256> {
+        use rust_decimal::prelude::FromPrimitive;
+
         if !value.is_finite() {
             return Err(AlphaSecError::invalid_parameter(
                 "value must be a finite number",
@@ -107,31 +235,71 @@ impl AlphaSecSigner {
                 "value must be non-negative",
             ));
         }
+        let value = Decimal::from_f64(value).ok_or_else(|| {
+            AlphaSecError::invalid_parameter("value is too large (exceeds supported range)")
+        })?;
+        Self::decimal_to_onchain_units(value, decimals)
+    }
 
-        // Use float math (input is f64 anyway), but validate bounds before converting.
-        let scale = 10_f64.powi(decimals as i32);
-        if !scale.is_finite() {
+    /// Convert a human `value` to on-chain base units without going through floats.
+    ///
+    /// Notes:
+    /// - Rejects negative values and `decimals` above [`MAX_TOKEN_DECIMALS`].
+    /// - Truncates towards zero digits beyond the token's precision.
+    /// - Scales with `Decimal`/`U256` integer math, so 0-decimal tokens are not routed
+    ///   through float powers and large decimals (e.g. 24) do not lose precision or overflow.
+    /// - Returns an error instead of silently saturating on overflow.
+    pub fn decimal_to_onchain_units(value: Decimal, decimals: u32) -> Result<U256> {
+        if value.is_sign_negative() && !value.is_zero() {
             return Err(AlphaSecError::invalid_parameter(
-                "invalid decimals (scale overflow)",
+                "value must be non-negative",
             ));
         }
-
-        let scaled = value * scale;
-        if !scaled.is_finite() {
-            return Err(AlphaSecError::invalid_parameter(
-                "value is too large (scaled overflow)",
-            ));
+        if decimals > MAX_TOKEN_DECIMALS {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "unsupported token decimals {} (maximum is {})",
+                decimals, MAX_TOKEN_DECIMALS
+            )));
         }
 
-        // We only support values that fit into u128 because the input is `f64` and cannot
-        // precisely represent larger integers anyway.
-        if scaled > (u128::MAX as f64) {
-            return Err(AlphaSecError::invalid_parameter(
-                "value is too large (exceeds supported range)",
-            ));
+        // value = mantissa / 10^scale; mantissa is non-negative here.
+        let mantissa = U256::from(value.mantissa().unsigned_abs());
+        let scale = value.scale();
+        if scale >= decimals {
+            // Includes decimals == 0: digits beyond the token's precision (the whole
+            // fraction for 0-decimal tokens) are dropped, truncating toward zero.
+            Ok(mantissa / U256::exp10((scale - decimals) as usize))
+        } else {
+            mantissa
+                .checked_mul(U256::exp10((decimals - scale) as usize))
+                .ok_or_else(|| {
+                    AlphaSecError::invalid_parameter("value is too large (scaled overflow)")
+                })
         }
+    }
 
-        Ok(U256::from(scaled.trunc() as u128))
+    /// Convert on-chain base units to a human amount; the inverse of
+    /// [`Self::decimal_to_onchain_units`]
+    ///
+    /// Digits beyond the 28 a `Decimal` can hold after the point are truncated. Fails
+    /// with `InvalidParameter` for `decimals` above [`MAX_TOKEN_DECIMALS`] or a value too
+    /// large for a `Decimal`.
+    pub fn onchain_units_to_decimal(value: U256, decimals: u32) -> Result<Decimal> {
+        if decimals > MAX_TOKEN_DECIMALS {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "unsupported token decimals {} (maximum is {})",
+                decimals, MAX_TOKEN_DECIMALS
+            )));
+        }
+        let scale = decimals.min(Decimal::MAX_SCALE);
+        let value = value / U256::exp10((decimals - scale) as usize);
+        let too_large = || AlphaSecError::invalid_parameter("value is too large for a Decimal");
+        if value.bits() > 127 {
+            return Err(too_large());
+        }
+        Decimal::try_from_i128_with_scale(value.as_u128() as i128, scale)
+            .map(|amount| amount.normalize())
+            .map_err(|_| too_large())
     }
 
     /// Create EIP-712 typed data for session registration
@@ -145,7 +313,7 @@ impl AlphaSecSigner {
             "domain": {
                 "name": DOMAIN_NAME,
                 "version": DOMAIN_VERSION,
-                "chainId": self.config.get_chain_id(),
+                "chainId": self.config.l1_chain_id(),
                 "verifyingContract": VERIFYING_CONTRACT
             },
             "types": {
@@ -194,11 +362,7 @@ impl AlphaSecSigner {
             AlphaSecError::invalid_parameter("L1 wallet is required for session registration")
         })?;
 
-        let typed_data: Eip712TypedData = serde_json::from_value(typed_json)
-            .map_err(|e| AlphaSecError::generic(&format!("Invalid EIP-712 typed data: {}", e)))?;
-        let digest = typed_data
-            .encode_eip712()
-            .map_err(|e| AlphaSecError::generic(&format!("Failed to encode EIP-712: {}", e)))?;
+        let digest = Self::eip712_digest(&typed_json)?;
         let signature_placeholder = l1_wallet
             .sign_hash(ethers::types::TxHash(digest))
             .map_err(|e| AlphaSecError::signer(&format!("Failed to sign EIP-712 digest: {}", e)))?;
@@ -230,85 +394,255 @@ impl AlphaSecSigner {
         let model = ValueTransferModel {
             l1owner: self.l1_address().to_string(), // Use l1_address
             to: to.to_string(),
-            value: value.to_string(),
+            value: value.normalize().to_string(),
         };
 
-        let payload_json = serde_json::to_string(&model.to_wire())?;
-        let payload_bytes = payload_json.replace(" ", ""); // Remove all spaces
         let mut result = vec![DEX_COMMAND_TRANSFER];
-        result.extend_from_slice(payload_bytes.as_bytes());
+        result.extend_from_slice(&serde_json::to_vec(&model)?);
         Ok(result)
     }
 
     /// Create token transfer data
-    pub fn create_token_transfer_data(&self, to: &str, value: f64, token: &str) -> Result<Vec<u8>> {
+    pub fn create_token_transfer_data(
+        &self,
+        to: &str,
+        value: Decimal,
+        token: &str,
+    ) -> Result<Vec<u8>> {
         let model = TokenTransferModel {
             l1owner: self.l1_address().to_string(), // Use l1_address
             to: to.to_string(),
-            value: value.to_string(),
             token: token.to_string(),
+            value: value.normalize().to_string(),
         };
 
-        let payload_json = serde_json::to_string(&model.to_wire())?;
-        let payload_bytes = payload_json.replace(" ", ""); // Remove all spaces
         let mut result = vec![DEX_COMMAND_TOKEN_TRANSFER];
-        result.extend_from_slice(payload_bytes.as_bytes());
+        result.extend_from_slice(&serde_json::to_vec(&model)?);
         Ok(result)
     }
 
     /// Create order data
-    pub fn create_order_data(
+    ///
+    /// `order_type`/`order_mode` are validated by [`validate_order_mode`]. Price and
+    /// quantity are normalized by [`normalize_price_quantity_with`] using `rounding` and
+    /// `tick_size`. Market orders keep `quantity` as given, since in quote mode it is a
+    /// quote-token amount.
+    ///
+    /// Quantity digits beyond `quantity_decimals` are truncated, so the amount never
+    /// exceeds what its token can represent. A quantity, or a limit order's price, that
+    /// ends up as 0 is rejected with `InvalidParameter`. A `client_order_id` is sent as
+    /// `clientOrderId` and `reduce_only` as `reduceOnly`.
+    pub fn create_order_data(&self, params: &OrderParams) -> Result<Vec<u8>> {
+        let mut model = self.order_model(params)?;
+        model.client_order_id = params.client_order_id.clone();
+        model.reduce_only = params.reduce_only;
+
+        // Use model's to_wire method for alphasec-style encoding
+        let final_tx_bytes = model.to_wire()?;
+        tracing::debug!(
+            "🔍 Order payload bytes: {:?}",
+            String::from_utf8_lossy(&final_tx_bytes[1..])
+        );
+
+        Ok(final_tx_bytes)
+    }
+
+    /// Build and sign an order transaction without touching the network
+    ///
+    /// [`create_order_data`](Self::create_order_data) followed by
+    /// [`generate_alphasec_transaction`](Self::generate_alphasec_transaction). Token IDs
+    /// are used as given, so no token metadata is needed. Returns the signed transaction
+    /// as `0x`-prefixed hex, ready for [`ApiClient::order`](crate::api::ApiClient::order).
+    pub async fn build_signed_order(
         &self,
-        base_token: &str,
-        quote_token: &str,
-        side: u32,
-        price: Decimal,
-        quantity: Decimal,
-        order_type: u32,
-        order_mode: u32,
-        tp_limit: Option<Decimal>,
-        sl_trigger: Option<Decimal>,
-        sl_limit: Option<Decimal>,
-    ) -> Result<Vec<u8>> {
+        params: &OrderParams,
+        timestamp_ms: Option<u64>,
+    ) -> Result<String> {
+        let order_data = self.create_order_data(params)?;
+        self.generate_alphasec_transaction(timestamp_ms, &order_data, None)
+            .await
+    }
+
+    /// Create EIP-712 typed data for an order
+    ///
+    /// The order fields are validated and normalized exactly as in
+    /// [`create_order_data`](Self::create_order_data). The domain is the session
+    /// registration domain on the AlphaSec (L2) chain ID, and `nonce` plays the role
+    /// of the transaction nonce (a millisecond timestamp). Absent TP/SL prices are
+    /// encoded as empty strings. The schema has no client order ID or reduce-only flag,
+    /// so params setting either are rejected with `InvalidParameter`.
+    pub fn create_order_typed_data(
+        &self,
+        params: &OrderParams,
+        nonce: u64,
+    ) -> Result<serde_json::Value> {
+        if params.client_order_id.is_some() || params.reduce_only {
+            return Err(AlphaSecError::invalid_parameter(
+                "Client order IDs and reduce-only are not part of the EIP-712 order schema",
+            ));
+        }
+        let model = self.order_model(params)?;
+        let (tp_limit, sl_trigger, sl_limit) = match model.tpsl {
+            Some(tpsl) => (tpsl.tp_limit, tpsl.sl_trigger, tpsl.sl_limit),
+            None => (None, None, None),
+        };
+
+        Ok(serde_json::json!({
+            "domain": {
+                "name": DOMAIN_NAME,
+                "version": DOMAIN_VERSION,
+                "chainId": self.config.l2_chain_id(),
+                "verifyingContract": VERIFYING_CONTRACT
+            },
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"},
+                    {"name": "verifyingContract", "type": "address"}
+                ],
+                "Order": [
+                    {"name": "l1owner", "type": "address"},
+                    {"name": "baseToken", "type": "string"},
+                    {"name": "quoteToken", "type": "string"},
+                    {"name": "side", "type": "uint32"},
+                    {"name": "price", "type": "string"},
+                    {"name": "quantity", "type": "string"},
+                    {"name": "orderType", "type": "uint32"},
+                    {"name": "orderMode", "type": "uint32"},
+                    {"name": "tpLimit", "type": "string"},
+                    {"name": "slTrigger", "type": "string"},
+                    {"name": "slLimit", "type": "string"},
+                    {"name": "nonce", "type": "uint64"}
+                ]
+            },
+            "primaryType": "Order",
+            "message": {
+                "l1owner": model.l1owner,
+                "baseToken": model.base_token,
+                "quoteToken": model.quote_token,
+                "side": model.side,
+                "price": model.price,
+                "quantity": model.quantity,
+                "orderType": model.order_type,
+                "orderMode": model.order_mode,
+                "tpLimit": tp_limit.unwrap_or_default(),
+                "slTrigger": sl_trigger.unwrap_or_default(),
+                "slLimit": sl_limit.unwrap_or_default(),
+                "nonce": nonce
+            }
+        }))
+    }
+
+    /// Sign EIP-712 typed data with `wallet`, or the trading wallet if `None`
+    ///
+    /// Returns the 65-byte `r || s || v` signature as `0x`-prefixed hex.
+    pub fn sign_typed_data(
+        &self,
+        typed_data: &serde_json::Value,
+        wallet: Option<&LocalWallet>,
+    ) -> Result<String> {
+        let wallet = match wallet {
+            Some(w) => w,
+            None => self.get_wallet()?,
+        };
+        let signature = wallet
+            .sign_hash(ethers::types::TxHash(Self::eip712_digest(typed_data)?))
+            .map_err(|e| AlphaSecError::signer(format!("Failed to sign EIP-712 digest: {}", e)))?;
+        Ok(format!("0x{}", hex::encode(signature.to_vec())))
+    }
+
+    /// EIP-712 digest (`keccak256("\x19\x01" || domainSeparator || structHash)`) of typed data
+    pub fn eip712_digest(typed_data: &serde_json::Value) -> Result<[u8; 32]> {
+        let typed_data: Eip712TypedData = serde_json::from_value(typed_data.clone())
+            .map_err(|e| AlphaSecError::generic(&format!("Invalid EIP-712 typed data: {}", e)))?;
+        typed_data
+            .encode_eip712()
+            .map_err(|e| AlphaSecError::generic(&format!("Failed to encode EIP-712: {}", e)))
+    }
+
+    /// Validated, normalized order model shared by the transaction and EIP-712 paths
+    fn order_model(&self, params: &OrderParams) -> Result<OrderModel> {
+        let OrderParams {
+            ref base_token,
+            ref quote_token,
+            side,
+            price,
+            quantity,
+            order_type,
+            order_mode,
+            tp_limit,
+            sl_trigger,
+            sl_limit,
+            rounding,
+            tick_size,
+            quantity_decimals,
+            ..
+        } = *params;
+        validate_order_mode(order_type, order_mode)?;
         let tpsl_model = if tp_limit.is_some() || sl_trigger.is_some() {
             Some(TpslModel {
-                tp_limit: tp_limit.map(|v| v.to_string()),
-                sl_trigger: sl_trigger.map(|v| v.to_string()),
-                sl_limit: sl_limit.map(|v| v.to_string()),
+                tp_limit: tp_limit.map(|v| v.normalize().to_string()),
+                sl_trigger: sl_trigger.map(|v| v.normalize().to_string()),
+                sl_limit: sl_limit.map(|v| v.normalize().to_string()),
             })
         } else {
             None
         };
 
-        let (normalized_price, normalized_quantity) = normalize_price_quantity(price, quantity)?;
+        let (normalized_price, normalized_quantity) =
+            normalize_price_quantity_with(price, quantity, rounding, tick_size)?;
+        let is_limit = order_type == OrderType::Limit as u32;
+        let signed_quantity = quantity_to_decimals(
+            if is_limit {
+                normalized_quantity
+            } else {
+                quantity
+            },
+            quantity_decimals,
+        );
+
+        // Truncation can take a positive input down to 0, which must not be signed
+        if is_limit && normalized_price <= Decimal::ZERO {
+            let step = match tick_size {
+                Some(tick) if tick > Decimal::ZERO => format!("tick size {}", tick),
+                _ => format!("{} decimals", get_price_precision(price)),
+            };
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "Price {} is 0 once rounded to {}",
+                price, step
+            )));
+        }
+        if signed_quantity <= Decimal::ZERO {
+            let band = is_limit.then(|| get_quantity_precision(price) as u32);
+            let decimals = match (band, quantity_decimals) {
+                (Some(band), Some(token)) => band.min(token),
+                (band, token) => band.or(token).unwrap_or_default(),
+            };
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "Quantity {} is 0 once truncated to {} decimals",
+                quantity, decimals
+            )));
+        }
 
         let model = OrderModel {
             l1owner: self.l1_address().to_string(), // Use l1_address instead of wallet.address
             base_token: base_token.to_string(),
             quote_token: quote_token.to_string(),
             side,
-            price: normalized_price.to_string(),
-            quantity: if order_type == OrderType::Market as u32 {
-                quantity.to_string()
-            } else {
-                normalized_quantity.to_string()
-            },
+            price: normalized_price.normalize().to_string(),
+            quantity: signed_quantity.normalize().to_string(),
             order_type,
             order_mode,
             tpsl: tpsl_model,
+            client_order_id: None,
+            reduce_only: false,
         };
 
         // Debug: Log the order data
         tracing::debug!("🔍 Order model: {:?}", model);
 
-        // Use model's to_wire method for alphasec-style encoding
-        let final_tx_bytes = model.to_wire()?;
-        tracing::debug!(
-            "🔍 Order payload bytes: {:?}",
-            String::from_utf8_lossy(&final_tx_bytes[1..])
-        );
-
-        Ok(final_tx_bytes)
+        Ok(model)
     }
 
     /// Create cancel data
@@ -348,8 +682,8 @@ impl AlphaSecSigner {
         let model = ModifyModel {
             l1owner: self.l1_address().to_string(), // Use l1_address
             order_id: order_id.to_string(),
-            new_price: normalized_price.to_string(),
-            new_qty: normalized_qty.to_string(),
+            new_price: normalized_price.normalize().to_string(),
+            new_qty: normalized_qty.normalize().to_string(),
             order_mode: order_mode as u32,
         };
 
@@ -360,6 +694,9 @@ impl AlphaSecSigner {
     }
 
     /// Create stop order data
+    ///
+    /// `order_type`/`order_mode` describe the order placed once `stop_price` is hit and
+    /// are validated and sized exactly as in [`Self::create_order_data`].
     pub fn create_stop_order_data(
         &self,
         base_token: &str,
@@ -371,15 +708,20 @@ impl AlphaSecSigner {
         order_type: u32,
         order_mode: u32,
     ) -> Result<Vec<u8>> {
+        validate_order_mode(order_type, order_mode)?;
         let (normalized_price, normalized_quantity) = normalize_price_quantity(price, quantity)?;
         let (normalized_stop_price, _) = normalize_price_quantity(stop_price, quantity)?;
         let model = StopOrderModel {
             l1owner: self.l1_address().to_string(), // Use l1_address
             base_token: base_token.to_string(),
             quote_token: quote_token.to_string(),
-            stop_price: normalized_stop_price.to_string(),
-            price: normalized_price.to_string(),
-            quantity: normalized_quantity.to_string(),
+            stop_price: normalized_stop_price.normalize().to_string(),
+            price: normalized_price.normalize().to_string(),
+            quantity: if order_type == OrderType::Market as u32 {
+                quantity.normalize().to_string()
+            } else {
+                normalized_quantity.normalize().to_string()
+            },
             side,
             order_type,
             order_mode,
@@ -391,6 +733,62 @@ impl AlphaSecSigner {
             .map_err(|e| AlphaSecError::signer(e.to_string()))
     }
 
+    /// Create one-cancels-other order data
+    ///
+    /// Both legs share `side`, `quantity` and `order_mode`: a limit order at
+    /// `limit_price`, and a stop-limit order placing `stop_limit` once `stop_trigger` is
+    /// hit. The limit leg must sit on the profitable side of the trigger (above it for a
+    /// sell, below it for a buy), otherwise the pair is rejected with `InvalidParameter`,
+    /// as is a `side` other than [`OrderSide::Buy`] or [`OrderSide::Sell`].
+    pub fn create_oco_data(
+        &self,
+        base_token: &str,
+        quote_token: &str,
+        side: u32,
+        quantity: Decimal,
+        limit_price: Decimal,
+        stop_trigger: Decimal,
+        stop_limit: Decimal,
+        order_mode: u32,
+    ) -> Result<Vec<u8>> {
+        let order_side = OrderSide::try_from(side)?;
+        validate_order_mode(OrderType::Limit as u32, order_mode)?;
+        let (limit_price, quantity) = normalize_price_quantity(limit_price, quantity)?;
+        let (stop_trigger, _) = normalize_price_quantity(stop_trigger, quantity)?;
+        let (stop_limit, _) = normalize_price_quantity(stop_limit, quantity)?;
+        if quantity.is_zero() {
+            return Err(AlphaSecError::invalid_parameter(
+                "OCO quantity must be positive",
+            ));
+        }
+        let (limit_on_profit_side, profit_side) = match order_side {
+            OrderSide::Sell => (limit_price > stop_trigger, "above"),
+            OrderSide::Buy => (limit_price < stop_trigger, "below"),
+        };
+        if !limit_on_profit_side {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "OCO limit price {} must be {} the stop trigger {}",
+                limit_price, profit_side, stop_trigger
+            )));
+        }
+
+        let model = OcoOrderModel {
+            l1owner: self.l1_address().to_string(),
+            base_token: base_token.to_string(),
+            quote_token: quote_token.to_string(),
+            side,
+            quantity: quantity.normalize().to_string(),
+            price: limit_price.normalize().to_string(),
+            stop_price: stop_trigger.normalize().to_string(),
+            stop_limit_price: stop_limit.normalize().to_string(),
+            order_mode,
+            contingency_type: "OCO".to_string(),
+        };
+        model
+            .to_wire()
+            .map_err(|e| AlphaSecError::signer(e.to_string()))
+    }
+
     // =========================================================================
     // Perp commands
     // =========================================================================
@@ -546,16 +944,9 @@ impl AlphaSecSigner {
             Some(w) => w,
             None => self.get_wallet()?,
         };
-        let nonce = timestamp_ms.unwrap_or_else(Self::current_timestamp_ms);
+        let nonce = timestamp_ms.unwrap_or_else(|| self.next_nonce());
 
-        let chain_id = if self.config.chain_id.is_some() {
-            self.config.chain_id.unwrap()
-        } else {
-            match self.config.network {
-                crate::signer::config::Network::Mainnet => ALPHASEC_MAINNET_CHAIN_ID,
-                crate::signer::config::Network::Kairos => ALPHASEC_TESTNET_CHAIN_ID,
-            }
-        };
+        let chain_id = self.config.l2_chain_id();
 
         // Create EIP-1559 transaction
         let tx = Eip1559TransactionRequest {
@@ -594,16 +985,26 @@ impl AlphaSecSigner {
     /// * `token_id` - Token ID to deposit (0 for native token)
     /// * `value` - Amount to deposit in trading units
     /// * `token_l1_address` - L1 token contract address (required for ERC20 tokens)
-    /// * `token_l1_decimals` - L1 token decimals (default: 18)
+    /// * `token_l1_decimals` - L1 token decimals (default: 18, at most [`MAX_TOKEN_DECIMALS`])
+    ///
+    /// Gas limits come from `eth_estimateGas` scaled by [`Config::gas_limit_multiplier`],
+    /// and an ERC20 deposit's max submission cost is derived from the current L1 gas
+    /// price (see [`Self::deposit_max_submission_cost`]). [`Config::l1_gas`] can fix the
+    /// fees and the gas limit instead.
+    ///
+    /// An ERC20 deposit beyond the current allowance first sends an `approve` and waits
+    /// for it to be mined, polling every `l1_provider` interval for up to
+    /// [`APPROVE_RECEIPT_TIMEOUT_SECS`], since the deposit's gas estimate reverts until
+    /// the allowance is in place. A reverted or dropped approve fails the deposit.
     ///
     /// # Returns
     /// * `Ok(String)` - Signed transaction hex string
     /// * `Err(AlphaSecError)` - If operation fails
-    pub async fn generate_deposit_transaction(
+    pub async fn generate_deposit_transaction<P: JsonRpcClient>(
         &self,
-        l1_provider: &Arc<Provider<ethers::providers::Http>>,
+        l1_provider: &Arc<Provider<P>>,
         token_id: &str,
-        value: f64,
+        value: Decimal,
         token_l1_address: Option<&str>,
         token_l1_decimals: Option<u8>,
     ) -> Result<String> {
@@ -612,14 +1013,11 @@ impl AlphaSecSigner {
         })?;
 
         let decimals = token_l1_decimals.unwrap_or(18);
-        let value_onchain_unit = Self::to_onchain_units(value, decimals as u32)?;
+        let value_onchain_unit = Self::decimal_to_onchain_units(value, decimals as u32)?;
 
         if token_id == ALPHASEC_NATIVE_TOKEN_ID.to_string() {
             // Native token deposit
-            let inbox_addr = match self.config.network {
-                crate::signer::config::Network::Mainnet => MAINNET_INBOX_CONTRACT_ADDR,
-                crate::signer::config::Network::Kairos => KAIROS_INBOX_CONTRACT_ADDR,
-            };
+            let inbox_addr = self.config.network_params().l1_inbox_addr;
 
             // Parse ABI and create contract instance
             let abi: Abi = serde_json::from_str(NATIVE_L1_ABI)
@@ -638,18 +1036,20 @@ impl AlphaSecSigner {
                 .await
                 .map_err(|e| AlphaSecError::generic(&format!("Failed to get nonce: {}", e)))?;
 
+            let gas_price = Self::l1_gas_price(l1_provider).await?;
+
             // Build transaction
             let tx = contract
                 .method::<_, ()>("depositEth", ())
                 .map_err(|e| AlphaSecError::generic(&format!("Failed to create method: {}", e)))?
                 .value(value_onchain_unit)
-                .gas(1_000_000)
                 .nonce(nonce)
-                .gas_price(U256::from(l1_provider.get_gas_price().await.unwrap()))
+                .gas_price(gas_price)
                 .from(l1_address);
 
             let mut tx = tx.tx;
-            tx.set_chain_id(self.config.get_chain_id());
+            tx.set_chain_id(self.config.l1_chain_id());
+            let tx = self.finish_l1_tx(l1_provider, tx, gas_price).await?;
 
             // Sign and return transaction (ensure correct L1 chain ID)
             let l1_wallet_chain = l1_wallet.clone();
@@ -672,15 +1072,9 @@ impl AlphaSecSigner {
                 ));
             }
 
-            let erc20_gateway_addr = match self.config.network {
-                crate::signer::config::Network::Mainnet => MAINNET_ERC20_GATEWAY_CONTRACT_ADDR,
-                crate::signer::config::Network::Kairos => KAIROS_ERC20_GATEWAY_CONTRACT_ADDR,
-            };
-
-            let erc20_router_addr = match self.config.network {
-                crate::signer::config::Network::Mainnet => MAINNET_ERC20_ROUTER_CONTRACT_ADDR,
-                crate::signer::config::Network::Kairos => KAIROS_ERC20_ROUTER_CONTRACT_ADDR,
-            };
+            let params = self.config.network_params();
+            let erc20_gateway_addr = params.l1_erc20_gateway_addr;
+            let erc20_router_addr = params.l1_erc20_router_addr;
 
             // Parse ERC20 ABI and create contract instance
             let erc20_abi: Abi = serde_json::from_str(ERC20_ABI).map_err(|e| {
@@ -723,18 +1117,22 @@ impl AlphaSecSigner {
                     .await
                     .map_err(|e| AlphaSecError::generic(&format!("Failed to get nonce: {}", e)))?;
 
+                let gas_price = Self::l1_gas_price(l1_provider).await?;
+
                 let approve_tx = erc20_contract
                     .method::<_, ()>("approve", (gateway_address, value_onchain_unit))
                     .map_err(|e| {
                         AlphaSecError::generic(&format!("Failed to create approve method: {}", e))
                     })?
-                    .gas(1_000_000)
                     .nonce(nonce)
-                    .gas_price(U256::from(l1_provider.get_gas_price().await.unwrap()))
+                    .gas_price(gas_price)
                     .from(l1_address);
 
                 let mut approve_tx = approve_tx.tx;
-                approve_tx.set_chain_id(self.config.get_chain_id());
+                approve_tx.set_chain_id(self.config.l1_chain_id());
+                let approve_tx = self
+                    .finish_l1_tx(l1_provider, approve_tx, gas_price)
+                    .await?;
 
                 // Sign approve transaction with proper L1 chain ID
                 let l1_wallet_chain = l1_wallet.clone();
@@ -748,7 +1146,7 @@ impl AlphaSecSigner {
                 let raw_approve_tx = approve_tx.rlp_signed(&signed_approve_tx);
 
                 // Send approve transaction
-                let _tx_hash = l1_provider
+                let approve_hash = l1_provider
                     .send_raw_transaction(Bytes::from(raw_approve_tx))
                     .await
                     .map_err(|e| {
@@ -756,10 +1154,11 @@ impl AlphaSecSigner {
                             "Failed to send approve transaction: {}",
                             e
                         ))
-                    })?;
+                    })?
+                    .tx_hash();
 
-                // Wait for approval (simplified - in production you might want to poll)
-                tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+                // The deposit's gas estimate reverts until the allowance is mined
+                Self::wait_for_l1_receipt(l1_provider, approve_hash, "Approve").await?;
             }
 
             // Parse ERC20 Router ABI and create contract instance
@@ -774,16 +1173,18 @@ impl AlphaSecSigner {
             let router_contract = Contract::new(router_address, router_abi, l1_provider.clone());
 
             // Prepare data for outbound transfer: abi.encode(uint256, bytes)
-            let max_submission_cost = ((0.01f64) * 1e18) as u64;
-            let encoded = ethers::abi::encode(&[
-                Token::Uint(U256::from(max_submission_cost)),
-                Token::Bytes(vec![]),
-            ]);
+            let gas_price = Self::l1_gas_price(l1_provider).await?;
+            let max_submission_cost =
+                Self::deposit_max_submission_cost(gas_price, self.config.gas_limit_multiplier);
+            let encoded =
+                ethers::abi::encode(&[Token::Uint(max_submission_cost), Token::Bytes(vec![])]);
             let data = Bytes::from(encoded);
 
-            let l2_gas_limit = 1_000_000u64;
-            let l2_gas_price = 1_000_000u64;
-            let value_eth = (0.02 * 1e18) as u64;
+            // The ticket's L2 execution is prepaid on top of the submission cost
+            let l2_gas_limit = DEPOSIT_L2_GAS_LIMIT;
+            let l2_gas_price = DEPOSIT_L2_GAS_PRICE;
+            let value_eth =
+                max_submission_cost + U256::from(l2_gas_limit) * U256::from(l2_gas_price);
 
             // Get nonce for main transaction
             let l1_address: Address = self.l1_address().parse().unwrap();
@@ -812,13 +1213,13 @@ impl AlphaSecSigner {
                     ))
                 })?
                 .value(value_eth)
-                .gas(1_000_000)
                 .nonce(nonce)
-                .gas_price(l1_provider.get_gas_price().await.unwrap())
+                .gas_price(gas_price)
                 .from(l1_address);
 
             let mut tx = tx.tx;
-            tx.set_chain_id(self.config.get_chain_id());
+            tx.set_chain_id(self.config.l1_chain_id());
+            let tx = self.finish_l1_tx(l1_provider, tx, gas_price).await?;
 
             // Sign and return transaction with proper L1 chain ID
             let l1_wallet_chain = l1_wallet.clone();
@@ -831,6 +1232,109 @@ impl AlphaSecSigner {
         }
     }
 
+    /// Max submission cost of the retryable ticket an ERC20 deposit creates, for an L1
+    /// gas price of `l1_gas_price` wei
+    ///
+    /// Uses the retryable fee formula `(1400 + 6 * calldata bytes) * gas price` over
+    /// [`DEPOSIT_RETRYABLE_DATA_BYTES`] of calldata, scaled by `multiplier` as a buffer
+    /// against the gas price rising before the ticket is created.
+    pub fn deposit_max_submission_cost(l1_gas_price: U256, multiplier: f64) -> U256 {
+        let bytes = RETRYABLE_SUBMISSION_BASE_BYTES
+            + RETRYABLE_SUBMISSION_BYTE_WEIGHT * DEPOSIT_RETRYABLE_DATA_BYTES;
+        scale_by(U256::from(bytes) * l1_gas_price, multiplier)
+    }
+
+    /// Poll for the receipt of the L1 transaction `hash` (`what`, for errors) until it
+    /// is mined, failing if it reverted or is not mined within
+    /// [`APPROVE_RECEIPT_TIMEOUT_SECS`]
+    async fn wait_for_l1_receipt<P: JsonRpcClient>(
+        l1_provider: &Provider<P>,
+        hash: ethers::types::TxHash,
+        what: &str,
+    ) -> Result<ethers::types::TransactionReceipt> {
+        let poll = async {
+            loop {
+                let receipt = l1_provider
+                    .get_transaction_receipt(hash)
+                    .await
+                    .map_err(|e| {
+                        AlphaSecError::generic(format!("Failed to get {} receipt: {}", what, e))
+                    })?;
+                if let Some(receipt) = receipt {
+                    return Ok::<_, AlphaSecError>(receipt);
+                }
+                tokio::time::sleep(l1_provider.get_interval()).await;
+            }
+        };
+        let timeout = std::time::Duration::from_secs(APPROVE_RECEIPT_TIMEOUT_SECS);
+        let receipt = tokio::time::timeout(timeout, poll).await.map_err(|_| {
+            AlphaSecError::generic(format!(
+                "{} transaction {:?} not mined within {:?}",
+                what, hash, timeout
+            ))
+        })??;
+        if receipt.status != Some(U64::from(1)) {
+            return Err(AlphaSecError::generic(format!(
+                "{} transaction {:?} reverted",
+                what, hash
+            )));
+        }
+        Ok(receipt)
+    }
+
+    /// Current L1 gas price
+    async fn l1_gas_price<P: JsonRpcClient>(l1_provider: &Provider<P>) -> Result<U256> {
+        l1_provider
+            .get_gas_price()
+            .await
+            .map_err(|e| AlphaSecError::generic(format!("Failed to get gas price: {}", e)))
+    }
+
+    /// Set the gas limit of an L1 transaction built at `gas_price`, and its fees if
+    /// [`Config::l1_gas`] configures them
+    async fn finish_l1_tx<P: JsonRpcClient>(
+        &self,
+        l1_provider: &Provider<P>,
+        tx: TypedTransaction,
+        gas_price: U256,
+    ) -> Result<TypedTransaction> {
+        let l1_gas = self.config.l1_gas;
+        let gas = match l1_gas.gas_limit_override {
+            Some(gas) => gas,
+            None => self.estimate_l1_gas(l1_provider, &tx).await?,
+        };
+        let mut tx = match l1_gas.fees(gas_price) {
+            Some((max_fee, priority_fee)) => TypedTransaction::Eip1559(Eip1559TransactionRequest {
+                from: tx.from().copied(),
+                to: tx.to().cloned(),
+                gas: None,
+                value: tx.value().copied(),
+                data: tx.data().cloned(),
+                nonce: tx.nonce().copied(),
+                access_list: Default::default(),
+                max_priority_fee_per_gas: Some(priority_fee),
+                max_fee_per_gas: Some(max_fee),
+                chain_id: tx.chain_id(),
+            }),
+            None => tx,
+        };
+        tx.set_gas(gas);
+        Ok(tx)
+    }
+
+    /// Gas limit for an L1 transaction: the node's estimate with the configured buffer
+    async fn estimate_l1_gas<P: JsonRpcClient>(
+        &self,
+        l1_provider: &Provider<P>,
+        tx: &TypedTransaction,
+    ) -> Result<U256> {
+        let estimate = l1_provider
+            .estimate_gas(tx, None)
+            .await
+            .map_err(|e| AlphaSecError::generic(format!("Failed to estimate gas: {}", e)))?;
+        Ok(scale_by(estimate, self.config.gas_limit_multiplier))
+    }
+
     /// Generate withdraw transaction for L2 to L1 transfer
     ///
     /// # Arguments
@@ -846,7 +1350,7 @@ impl AlphaSecSigner {
         &self,
         l2_provider: &Arc<Provider<ethers::providers::Http>>,
         token_id: &str,
-        value: f64,
+        value: Decimal,
         token_l1_address: Option<&str>,
         token_l1_decimals: Option<u8>,
         timestamp_ms: Option<u64>,
@@ -855,14 +1359,7 @@ impl AlphaSecSigner {
             AlphaSecError::invalid_parameter("L1 wallet is required for withdraw operations")
         })?;
 
-        let chain_id = if self.config.chain_id.is_some() {
-            self.config.chain_id.unwrap()
-        } else {
-            match self.config.network {
-                crate::signer::config::Network::Mainnet => ALPHASEC_MAINNET_CHAIN_ID,
-                crate::signer::config::Network::Kairos => ALPHASEC_TESTNET_CHAIN_ID,
-            }
-        };
+        let chain_id = self.config.l2_chain_id();
         // For ERC20 tokens, use L1 decimals if provided (e.g., USDT has 6 decimals on L1)
         // For native tokens, always use 18 decimals
         let decimals = if token_id == ALPHASEC_NATIVE_TOKEN_ID.to_string() {
@@ -870,7 +1367,7 @@ impl AlphaSecSigner {
         } else {
             token_l1_decimals.unwrap_or(18) as u32
         };
-        let value_onchain_unit = Self::to_onchain_units(value, decimals)?;
+        let value_onchain_unit = Self::decimal_to_onchain_units(value, decimals)?;
 
         if token_id == ALPHASEC_NATIVE_TOKEN_ID.to_string() {
             // Native token withdrawal
@@ -888,7 +1385,7 @@ impl AlphaSecSigner {
             let contract = Contract::new(system_address, abi, l2_provider.clone());
 
             // Generate nonce using current timestamp
-            let nonce = timestamp_ms.unwrap_or_else(Self::current_timestamp_ms);
+            let nonce = timestamp_ms.unwrap_or_else(|| self.next_nonce());
 
             // Build transaction
             let l1_address: Address = self.l1_address().parse().unwrap();
@@ -902,22 +1399,7 @@ impl AlphaSecSigner {
                 .nonce(nonce)
                 .from(l1_address);
 
-            let tx = call.tx;
-            let tx = match tx {
-                TypedTransaction::Eip1559(mut inner) => {
-                    inner.max_fee_per_gas = Some(U256::from(DEFAULT_MAX_FEE_PER_GAS));
-                    inner.max_priority_fee_per_gas =
-                        Some(U256::from(DEFAULT_MAX_PRIORITY_FEE_PER_GAS));
-                    inner.chain_id = Some(U64::from(chain_id));
-                    TypedTransaction::Eip1559(inner)
-                }
-                TypedTransaction::Legacy(mut inner) => {
-                    inner.gas_price = Some(U256::from(DEFAULT_GAS_PRICE));
-                    inner.chain_id = Some(U64::from(chain_id));
-                    TypedTransaction::Legacy(inner)
-                }
-                TypedTransaction::Eip2930(inner) => TypedTransaction::Eip2930(inner),
-            };
+            let tx = Self::into_l2_transaction(call.tx, chain_id);
 
             tracing::info!("tx: {:?}", tx);
 
@@ -941,14 +1423,7 @@ impl AlphaSecSigner {
                 ));
             }
 
-            let erc20_router_addr = match self.config.network {
-                crate::signer::config::Network::Mainnet => {
-                    crate::types::l2_contracts::ALPHASEC_MAINNET_GATEWAY_ROUTER_CONTRACT_ADDR
-                }
-                crate::signer::config::Network::Kairos => {
-                    crate::types::l2_contracts::ALPHASEC_KAIROS_GATEWAY_ROUTER_CONTRACT_ADDR
-                }
-            };
+            let erc20_router_addr = self.config.network_params().l2_gateway_router_addr;
 
             // Parse L2 ERC20 Router ABI and create contract instance
             let abi: Abi = serde_json::from_str(L2_ERC20_ROUTER_ABI).map_err(|e| {
@@ -962,7 +1437,7 @@ impl AlphaSecSigner {
             let contract = Contract::new(router_address, abi, l2_provider.clone());
 
             // Generate nonce using current timestamp
-            let nonce = timestamp_ms.unwrap_or_else(Self::current_timestamp_ms);
+            let nonce = timestamp_ms.unwrap_or_else(|| self.next_nonce());
 
             // Prepare data for outbound transfer
             // Must be empty bytes (NOT ASCII "0x", which becomes 0x3078 in calldata).
@@ -988,33 +1463,175 @@ impl AlphaSecSigner {
                 .nonce(nonce)
                 .from(l1_address);
 
-            let mut tx = call.tx;
-            tx.set_chain_id(chain_id);
+            // Finalise the L2 fields before signing so the signature covers the
+            // transaction that is actually serialised.
+            let tx = Self::into_l2_transaction(call.tx, chain_id);
 
             // Sign and return transaction (ensure correct L2 AlphaSec chain ID)
             let signed_tx = l1_wallet.clone().sign_transaction(&tx).await.map_err(|e| {
                 AlphaSecError::signer(&format!("Failed to sign transaction: {}", e))
             })?;
 
-            let tx = match tx {
-                TypedTransaction::Eip1559(mut inner) => {
-                    inner.max_fee_per_gas = Some(U256::from(DEFAULT_MAX_FEE_PER_GAS));
-                    inner.max_priority_fee_per_gas =
-                        Some(U256::from(DEFAULT_MAX_PRIORITY_FEE_PER_GAS));
-                    inner.chain_id = Some(U64::from(chain_id));
-                    TypedTransaction::Eip1559(inner)
-                }
-                TypedTransaction::Legacy(mut inner) => {
-                    inner.gas_price = Some(U256::from(DEFAULT_GAS_PRICE));
-                    inner.chain_id = Some(U64::from(chain_id));
-                    TypedTransaction::Legacy(inner)
-                }
-                TypedTransaction::Eip2930(inner) => TypedTransaction::Eip2930(inner),
-            };
             let raw_tx = tx.rlp_signed(&signed_tx);
             Ok(format!("0x{}", hex::encode(raw_tx)))
         }
     }
+
+    /// Read the L2 balance of the L1 address straight from chain
+    ///
+    /// # Arguments
+    /// * `l2_provider` - L2 provider to query
+    /// * `token_id` - Token ID ([`ALPHASEC_NATIVE_TOKEN_ID`] for the native token)
+    /// * `token_l1_address` - L1 token contract address (required for ERC20 tokens)
+    /// * `token_decimals` - Native token decimals (default: 18, as for deposits); ERC20
+    ///   balances use their contract's
+    ///
+    /// The native balance is read with `eth_getBalance`. For an ERC20 token the L2
+    /// gateway router maps the L1 contract to its L2 counterpart, whose `balanceOf` is
+    /// scaled by its own `decimals`.
+    pub async fn get_onchain_balance<M: Middleware + 'static>(
+        &self,
+        l2_provider: &Arc<M>,
+        token_id: &str,
+        token_l1_address: Option<&str>,
+        token_decimals: Option<u8>,
+    ) -> Result<Decimal> {
+        let owner: Address = self
+            .l1_address()
+            .parse()
+            .map_err(|e| AlphaSecError::invalid_address(format!("Invalid L1 address: {}", e)))?;
+
+        if token_id == ALPHASEC_NATIVE_TOKEN_ID.to_string() {
+            let balance = l2_provider
+                .get_balance(owner, None)
+                .await
+                .map_err(|e| AlphaSecError::generic(format!("Failed to get balance: {}", e)))?;
+            return Self::onchain_units_to_decimal(balance, token_decimals.unwrap_or(18) as u32);
+        }
+
+        let token_l1_addr = token_l1_address.ok_or_else(|| {
+            AlphaSecError::invalid_parameter("token_l1_address is required for ERC20 tokens")
+        })?;
+        let token_address: Address = token_l1_addr
+            .parse()
+            .map_err(|e| AlphaSecError::invalid_address(format!("Invalid token address: {}", e)))?;
+        let router_address: Address = self
+            .config
+            .network_params()
+            .l2_gateway_router_addr
+            .parse()
+            .map_err(|e| {
+                AlphaSecError::invalid_address(format!("Invalid router address: {}", e))
+            })?;
+
+        let router_abi: Abi = serde_json::from_str(L2_ERC20_ROUTER_ABI).map_err(|e| {
+            AlphaSecError::generic(format!("Failed to parse L2 ERC20 Router ABI: {}", e))
+        })?;
+        let router = Contract::new(router_address, router_abi, l2_provider.clone());
+        let l2_token: Address = router
+            .method::<_, Address>("calculateL2TokenAddress", token_address)
+            .map_err(|e| {
+                AlphaSecError::generic(format!(
+                    "Failed to create calculateL2TokenAddress method: {}",
+                    e
+                ))
+            })?
+            .call()
+            .await
+            .map_err(|e| {
+                AlphaSecError::generic(format!("Failed to look up the L2 token: {}", e))
+            })?;
+        if l2_token.is_zero() {
+            return Err(AlphaSecError::not_found(format!(
+                "No L2 token for {}",
+                token_l1_addr
+            )));
+        }
+
+        let erc20_abi: Abi = serde_json::from_str(ERC20_ABI)
+            .map_err(|e| AlphaSecError::generic(format!("Failed to parse ERC20 ABI: {}", e)))?;
+        let token = Contract::new(l2_token, erc20_abi, l2_provider.clone());
+        let balance: U256 = token
+            .method::<_, U256>("balanceOf", owner)
+            .map_err(|e| {
+                AlphaSecError::generic(format!("Failed to create balanceOf method: {}", e))
+            })?
+            .call()
+            .await
+            .map_err(|e| AlphaSecError::generic(format!("Failed to get balance: {}", e)))?;
+        let decimals: u8 = token
+            .method::<_, u8>("decimals", ())
+            .map_err(|e| {
+                AlphaSecError::generic(format!("Failed to create decimals method: {}", e))
+            })?
+            .call()
+            .await
+            .map_err(|e| AlphaSecError::generic(format!("Failed to get token decimals: {}", e)))?;
+        Self::onchain_units_to_decimal(balance, decimals as u32)
+    }
+
+    /// Set the AlphaSec L2 chain ID and zero gas pricing on a contract-call transaction
+    fn into_l2_transaction(tx: TypedTransaction, chain_id: u64) -> TypedTransaction {
+        match tx {
+            TypedTransaction::Eip1559(mut inner) => {
+                inner.max_fee_per_gas = Some(U256::from(DEFAULT_MAX_FEE_PER_GAS));
+                inner.max_priority_fee_per_gas = Some(U256::from(DEFAULT_MAX_PRIORITY_FEE_PER_GAS));
+                inner.chain_id = Some(U64::from(chain_id));
+                TypedTransaction::Eip1559(inner)
+            }
+            TypedTransaction::Legacy(mut inner) => {
+                inner.gas_price = Some(U256::from(DEFAULT_GAS_PRICE));
+                inner.chain_id = Some(U64::from(chain_id));
+                TypedTransaction::Legacy(inner)
+            }
+            TypedTransaction::Eip2930(mut inner) => {
+                inner.tx.chain_id = Some(U64::from(chain_id));
+                TypedTransaction::Eip2930(inner)
+            }
+        }
+    }
+}
+
+/// Check that `order_type` and `order_mode` are known values and make sense together
+///
+/// [`OrderMode::Quote`] sizes an order by the quote amount to spend or receive, which
+/// only applies to market orders; a limit order's quantity is always in the base token.
+/// The same rule applies to the order a stop order places once triggered.
+pub fn validate_order_mode(order_type: u32, order_mode: u32) -> Result<()> {
+    let order_type = match order_type {
+        0 => OrderType::Limit,
+        1 => OrderType::Market,
+        other => {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "Unknown order type: {}",
+                other
+            )))
+        }
+    };
+    let order_mode = match order_mode {
+        0 => OrderMode::Base,
+        1 => OrderMode::Quote,
+        other => {
+            return Err(AlphaSecError::invalid_parameter(format!(
+                "Unknown order mode: {}",
+                other
+            )))
+        }
+    };
+    if order_type == OrderType::Limit && order_mode == OrderMode::Quote {
+        return Err(AlphaSecError::invalid_parameter(
+            "Quote order mode is only valid for market orders",
+        ));
+    }
+    Ok(())
+}
+
+/// Truncate `quantity` to the `decimals` of the token it is denominated in
+fn quantity_to_decimals(quantity: Decimal, decimals: Option<u32>) -> Decimal {
+    match decimals {
+        Some(decimals) => quantity.round_dp_with_strategy(decimals, RoundingStrategy::ToZero),
+        None => quantity,
+    }
 }
 
 #[cfg(test)]
@@ -1022,7 +1639,15 @@ mod tests {
     use std::sync::Arc;
 
     use super::*;
-    use crate::{endpoints, signer::Config};
+    use crate::{
+        chain_ids::*,
+        endpoints,
+        signer::{Config, L1GasConfig},
+    };
+    use ethers::{
+        providers::MockProvider,
+        types::{TransactionReceipt, TxHash},
+    };
 
     fn create_test_config() -> Config {
         // These are well-known test keys from Hardhat/Anvil - DO NOT USE IN PRODUCTION
@@ -1061,6 +1686,18 @@ mod tests {
         tx
     }
 
+    /// Decode a signed raw transaction and recover the address that signed it.
+    fn decode_signed_tx_with_sender(tx_hex: &str) -> (TypedTransaction, Address) {
+        let raw = hex::decode(tx_hex.trim_start_matches("0x")).expect("signed tx must be hex");
+        let rlp = ethers::core::utils::rlp::Rlp::new(&raw);
+        let (tx, signature) =
+            TypedTransaction::decode_signed(&rlp).expect("signed tx must RLP-decode");
+        let sender = signature
+            .recover(tx.sighash())
+            .expect("signature must recover");
+        (tx, sender)
+    }
+
     #[test]
     fn test_signer_creation() {
         let config = create_test_config();
@@ -1083,6 +1720,26 @@ mod tests {
         assert!(ts2 > ts1);
     }
 
+    #[test]
+    fn clones_share_one_increasing_nonce_sequence() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let clone = signer.clone();
+        let start = AlphaSecSigner::current_timestamp_ms();
+
+        // Far more calls than milliseconds pass, so most land in the same millisecond
+        let nonces: Vec<u64> = (0..1_000)
+            .map(|i| {
+                if i % 2 == 0 {
+                    signer.next_nonce()
+                } else {
+                    clone.next_nonce()
+                }
+            })
+            .collect();
+        assert!(nonces[0] >= start);
+        assert!(nonces.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
     #[test]
     fn test_session_register_typed_data() {
         let config = create_test_config();
@@ -1122,6 +1779,105 @@ mod tests {
         assert_eq!(typed_data["primaryType"], "RegisterSessionWallet");
     }
 
+    #[test]
+    fn test_order_typed_data_encodes_deterministically_and_recovers_signer() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let typed_data = |price: &str| {
+            signer
+                .create_order_typed_data(
+                    &OrderParams {
+                        tp_limit: Some(Decimal::from_str("1.5").unwrap()),
+                        ..OrderParams::new(
+                            "5",
+                            "2",
+                            0,
+                            Decimal::from_str(price).unwrap(),
+                            Decimal::from_str("10.5").unwrap(),
+                            OrderType::Limit as u32,
+                            OrderMode::Base as u32,
+                        )
+                    },
+                    1_700_000_000_000,
+                )
+                .unwrap()
+        };
+
+        let order = typed_data("1.2345");
+        assert_eq!(order["primaryType"], "Order");
+        assert_eq!(order["domain"]["chainId"], signer.config.l2_chain_id());
+        assert_eq!(order["message"]["l1owner"], signer.l1_address());
+        assert_eq!(order["message"]["price"], "1.2345");
+        assert_eq!(order["message"]["quantity"], "10.5");
+        assert_eq!(order["message"]["tpLimit"], "1.5");
+        assert_eq!(order["message"]["slTrigger"], "");
+        assert_eq!(order["message"]["nonce"], 1_700_000_000_000u64);
+
+        // Same inputs, same digest and (RFC 6979) the same signature
+        let digest = AlphaSecSigner::eip712_digest(&order).unwrap();
+        assert_eq!(
+            digest,
+            AlphaSecSigner::eip712_digest(&typed_data("1.2345")).unwrap()
+        );
+        assert_ne!(
+            digest,
+            AlphaSecSigner::eip712_digest(&typed_data("1.2346")).unwrap()
+        );
+        let signature = signer.sign_typed_data(&order, None).unwrap();
+        assert_eq!(signature, signer.sign_typed_data(&order, None).unwrap());
+
+        let signature = ethers::types::Signature::from_str(&signature).unwrap();
+        let recovered = signature
+            .recover(ethers::types::RecoveryMessage::Hash(digest.into()))
+            .unwrap();
+        assert_eq!(recovered, signer.get_wallet().unwrap().address());
+    }
+
+    #[test]
+    fn test_order_typed_data_rejects_invalid_order_mode() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let result = signer.create_order_typed_data(
+            &OrderParams::new(
+                "5",
+                "2",
+                0,
+                Decimal::ONE,
+                Decimal::ONE,
+                OrderType::Limit as u32,
+                OrderMode::Quote as u32,
+            ),
+            1,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_order_typed_data_rejects_fields_outside_the_schema() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let base = OrderParams::new(
+            "5",
+            "2",
+            0,
+            Decimal::ONE,
+            Decimal::ONE,
+            OrderType::Limit as u32,
+            OrderMode::Base as u32,
+        );
+        for params in [
+            OrderParams {
+                reduce_only: true,
+                ..base.clone()
+            },
+            OrderParams {
+                client_order_id: Some("cid-1".to_string()),
+                ..base.clone()
+            },
+        ] {
+            let result = signer.create_order_typed_data(&params, 1);
+            assert!(matches!(result, Err(AlphaSecError::InvalidParameter(_))));
+        }
+        assert!(signer.create_order_typed_data(&base, 1).is_ok());
+    }
+
     #[test]
     fn test_create_value_transfer_data() {
         let config = create_test_config();
@@ -1144,7 +1900,7 @@ mod tests {
         let signer = AlphaSecSigner::new(config);
 
         let to = "0xrecipientaddressrecipientaddressrecipient";
-        let value = 100f64; // 100 USDT
+        let value = Decimal::from(100); // 100 USDT
         let token = "USDT";
 
         let result = signer.create_token_transfer_data(to, value, token);
@@ -1168,7 +1924,7 @@ mod tests {
         let order_type = 0; // Limit
         let order_mode = 0; // GTC
 
-        let result = signer.create_order_data(
+        let result = signer.create_order_data(&OrderParams::new(
             base_token,
             quote_token,
             side,
@@ -1176,10 +1932,7 @@ mod tests {
             quantity,
             order_type,
             order_mode,
-            None, // tp_limit
-            None, // sl_trigger
-            None, // sl_limit
-        );
+        ));
         assert!(result.is_ok());
 
         let data = result.unwrap();
@@ -1259,9 +2012,87 @@ mod tests {
         );
         assert!(result.is_ok());
 
-        let data = result.unwrap();
-        assert!(!data.is_empty());
-        assert_eq!(data[0], DEX_COMMAND_STOP_ORDER); // First byte should be the command
+        let data = result.unwrap();
+        assert!(!data.is_empty());
+        assert_eq!(data[0], DEX_COMMAND_STOP_ORDER); // First byte should be the command
+    }
+
+    #[test]
+    fn oco_data_carries_both_legs_and_the_oco_contingency_type() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let data = signer
+            .create_oco_data(
+                "5",
+                "2",
+                OrderSide::Buy as u32,
+                Decimal::from_str("10").unwrap(),
+                Decimal::from_str("0.14").unwrap(),
+                Decimal::from_str("0.16").unwrap(),
+                Decimal::from_str("0.17").unwrap(),
+                OrderMode::Base as u32,
+            )
+            .unwrap();
+        assert_eq!(data[0], DEX_COMMAND_OCO_ORDER);
+        let body: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
+        assert_eq!(body["contingencyType"], "OCO");
+        assert_eq!(body["side"], 0);
+        assert_eq!(body["quantity"], "10");
+        assert_eq!(body["price"], "0.14");
+        assert_eq!(body["stopPrice"], "0.16");
+        assert_eq!(body["stopLimitPrice"], "0.17");
+        assert_eq!(body["orderMode"], 0);
+        assert_eq!(body["baseToken"], "5");
+    }
+
+    #[test]
+    fn oco_data_rejects_a_limit_leg_on_the_wrong_side_of_the_trigger() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let oco = |side: OrderSide, limit: &str, trigger: &str, mode: OrderMode| {
+            signer.create_oco_data(
+                "5",
+                "2",
+                side as u32,
+                Decimal::from(10),
+                Decimal::from_str(limit).unwrap(),
+                Decimal::from_str(trigger).unwrap(),
+                Decimal::from_str(trigger).unwrap(),
+                mode as u32,
+            )
+        };
+        assert!(oco(OrderSide::Sell, "0.2", "0.1", OrderMode::Base).is_ok());
+        for result in [
+            oco(OrderSide::Sell, "0.1", "0.2", OrderMode::Base),
+            oco(OrderSide::Buy, "0.2", "0.1", OrderMode::Base),
+            oco(OrderSide::Buy, "0.1", "0.1", OrderMode::Base),
+            // Both legs are limit orders, which cannot be sized in the quote token
+            oco(OrderSide::Sell, "0.2", "0.1", OrderMode::Quote),
+        ] {
+            assert!(matches!(result, Err(AlphaSecError::InvalidParameter(_))));
+        }
+    }
+
+    #[test]
+    fn oco_data_rejects_an_unknown_side() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let err = signer
+            .create_oco_data(
+                "5",
+                "2",
+                2,
+                Decimal::from(10),
+                Decimal::from_str("0.2").unwrap(),
+                Decimal::from_str("0.1").unwrap(),
+                Decimal::from_str("0.1").unwrap(),
+                OrderMode::Base as u32,
+            )
+            .unwrap_err();
+        // Rejected for the side itself, not as a buy with its legs the wrong way round
+        match err {
+            AlphaSecError::InvalidParameter(message) => {
+                assert_eq!(message, "Unknown order side: 2")
+            }
+            other => panic!("expected InvalidParameter, got {:?}", other),
+        }
     }
 
     #[tokio::test]
@@ -1292,12 +2123,172 @@ mod tests {
         let provider = Arc::new(ethers::providers::Provider::new(http));
 
         let result = signer
-            .generate_deposit_transaction(&provider, "1", 1f64, None, None)
+            .generate_deposit_transaction(&provider, "1", Decimal::ONE, None, None)
             .await;
 
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn native_deposit_uses_the_buffered_gas_estimate() {
+        let signer = AlphaSecSigner::new(create_test_config().with_gas_limit_multiplier(1.5));
+        let (provider, mock) = Provider::mocked();
+        // Responses are served last-pushed first
+        mock.push(U256::from(40_000)).unwrap(); // eth_estimateGas
+        mock.push(U256::from(25_000_000_000u64)).unwrap(); // eth_gasPrice
+        mock.push(U256::from(7)).unwrap(); // eth_getTransactionCount
+
+        let raw = signer
+            .generate_deposit_transaction(&Arc::new(provider), "1", Decimal::ONE, None, None)
+            .await
+            .unwrap();
+
+        let tx = decode_signed_tx(&raw);
+        assert_eq!(tx.gas(), Some(&U256::from(60_000)));
+        assert_eq!(tx.gas_price(), Some(U256::from(25_000_000_000u64)));
+        assert_eq!(tx.nonce(), Some(&U256::from(7)));
+    }
+
+    #[tokio::test]
+    async fn native_deposit_uses_the_configured_l1_gas() {
+        let gwei = |n: u64| U256::from(n * 1_000_000_000);
+        let signer = AlphaSecSigner::new(create_test_config().with_l1_gas(L1GasConfig {
+            max_fee_per_gas: Some(gwei(30)),
+            max_priority_fee_per_gas: Some(gwei(2)),
+            gas_limit_override: Some(U256::from(90_000)),
+        }));
+        let (provider, mock) = Provider::mocked();
+        // No eth_estimateGas: the limit is overridden
+        mock.push(gwei(25)).unwrap(); // eth_gasPrice
+        mock.push(U256::from(7)).unwrap(); // eth_getTransactionCount
+
+        let raw = signer
+            .generate_deposit_transaction(&Arc::new(provider), "1", Decimal::ONE, None, None)
+            .await
+            .unwrap();
+
+        let (tx, sender) = decode_signed_tx_with_sender(&raw);
+        let TypedTransaction::Eip1559(tx) = tx else {
+            panic!("expected an EIP-1559 transaction, got {:?}", tx);
+        };
+        assert_eq!(tx.max_fee_per_gas, Some(gwei(30)));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(gwei(2)));
+        assert_eq!(tx.gas, Some(U256::from(90_000)));
+        assert_eq!(tx.nonce, Some(U256::from(7)));
+        assert_eq!(tx.chain_id, Some(U64::from(signer.config.l1_chain_id())));
+        assert_eq!(Some(sender), tx.from);
+    }
+
+    #[tokio::test]
+    async fn erc20_deposit_prices_the_submission_from_the_l1_gas_price() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let gas_price = U256::from(25_000_000_000u64);
+        let (provider, mock) = Provider::mocked();
+        mock.push(U256::from(100_000)).unwrap(); // eth_estimateGas
+        mock.push(U256::from(3)).unwrap(); // eth_getTransactionCount
+        mock.push(gas_price).unwrap(); // eth_gasPrice
+                                       // eth_call: the allowance already covers the deposit, so no approve is sent
+        mock.push::<Bytes, _>(Bytes::from(ethers::abi::encode(&[Token::Uint(U256::MAX)])))
+            .unwrap();
+
+        let raw = signer
+            .generate_deposit_transaction(
+                &Arc::new(provider),
+                "2",
+                Decimal::ONE,
+                Some("0x5FbDB2315678afecb367f032d93F642f64180aa3"),
+                Some(6),
+            )
+            .await
+            .unwrap();
+
+        let tx = decode_signed_tx(&raw);
+        assert_eq!(tx.gas(), Some(&U256::from(120_000)));
+        let submission_cost =
+            AlphaSecSigner::deposit_max_submission_cost(gas_price, DEFAULT_GAS_LIMIT_MULTIPLIER);
+        // (1400 + 6 * 1024) bytes at 25 gwei, plus the 20% buffer
+        assert_eq!(submission_cost, U256::from(226_320_000_000_000u64));
+        assert_eq!(
+            tx.value(),
+            Some(&(submission_cost + U256::from(DEPOSIT_L2_GAS_LIMIT * DEPOSIT_L2_GAS_PRICE)))
+        );
+    }
+
+    /// Mock L1 responses for an ERC20 deposit that has to approve first, with the
+    /// approve mined on the second receipt poll with `approve_status`
+    fn approve_then_deposit_provider(approve_status: u64) -> Provider<MockProvider> {
+        let (provider, mock) = Provider::mocked();
+        // Pushed in reverse: the mock serves the last response first
+        mock.push(U256::from(100_000)).unwrap(); // eth_estimateGas (deposit)
+        mock.push(U256::from(4)).unwrap(); // eth_getTransactionCount (deposit)
+        mock.push(U256::from(25_000_000_000u64)).unwrap(); // eth_gasPrice (deposit)
+        let mined = TransactionReceipt {
+            status: Some(approve_status.into()),
+            ..Default::default()
+        };
+        mock.push(Some(mined)).unwrap(); // eth_getTransactionReceipt: mined
+        mock.push(None::<TransactionReceipt>).unwrap(); // eth_getTransactionReceipt: pending
+        mock.push(TxHash::repeat_byte(0xaa)).unwrap(); // eth_sendRawTransaction (approve)
+        mock.push(U256::from(50_000)).unwrap(); // eth_estimateGas (approve)
+        mock.push(U256::from(25_000_000_000u64)).unwrap(); // eth_gasPrice (approve)
+        mock.push(U256::from(3)).unwrap(); // eth_getTransactionCount (approve)
+                                           // eth_call: no allowance yet
+        mock.push::<Bytes, _>(Bytes::from(ethers::abi::encode(&[Token::Uint(
+            U256::zero(),
+        )])))
+        .unwrap();
+        provider.interval(std::time::Duration::from_millis(10))
+    }
+
+    #[tokio::test]
+    async fn erc20_deposit_waits_for_the_approve_to_be_mined() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let provider = Arc::new(approve_then_deposit_provider(1));
+
+        let raw = signer
+            .generate_deposit_transaction(
+                &provider,
+                "2",
+                Decimal::ONE,
+                Some("0x5FbDB2315678afecb367f032d93F642f64180aa3"),
+                Some(6),
+            )
+            .await
+            .unwrap();
+
+        // The deposit is estimated and signed only after the approve's receipt
+        let tx = decode_signed_tx(&raw);
+        assert_eq!(tx.nonce(), Some(&U256::from(4)));
+        assert_eq!(tx.gas(), Some(&U256::from(120_000)));
+    }
+
+    #[tokio::test]
+    async fn erc20_deposit_fails_when_the_approve_reverts() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let provider = Arc::new(approve_then_deposit_provider(0));
+
+        let err = signer
+            .generate_deposit_transaction(
+                &provider,
+                "2",
+                Decimal::ONE,
+                Some("0x5FbDB2315678afecb367f032d93F642f64180aa3"),
+                Some(6),
+            )
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("Approve transaction"), "{}", err);
+        assert!(err.to_string().contains("reverted"), "{}", err);
+    }
+
+    #[test]
+    fn gas_multiplier_rounds_up_and_never_shrinks() {
+        assert_eq!(scale_by(U256::from(3), 1.2), U256::from(4));
+        assert_eq!(scale_by(U256::from(50_000), 1.2), U256::from(60_000));
+        assert_eq!(scale_by(U256::from(50_000), 0.5), U256::from(50_000));
+    }
+
     #[tokio::test]
     async fn test_generate_withdraw_transaction() {
         let config = create_test_config();
@@ -1310,7 +2301,7 @@ mod tests {
 
         // Test that the function compiles and runs (will fail on network call, but that's expected)
         let result = signer
-            .generate_withdraw_transaction(&provider, "1", 1f64, None, Some(18), None)
+            .generate_withdraw_transaction(&provider, "1", Decimal::ONE, None, Some(18), None)
             .await;
 
         // We expect this to fail due to network connection, but the function should be callable
@@ -2087,6 +3078,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn to_onchain_units_scales_six_decimal_token_exactly() {
+        // USDT-like: float artifacts (0.1 * 1e6 = 100000.00000000001) must not leak in.
+        assert_eq!(
+            AlphaSecSigner::to_onchain_units(12.5, 6).unwrap(),
+            U256::from(12_500_000u64)
+        );
+        assert_eq!(
+            AlphaSecSigner::to_onchain_units(0.1, 6).unwrap(),
+            U256::from(100_000u64)
+        );
+        // Digits beyond the token precision are truncated, not rounded.
+        assert_eq!(
+            AlphaSecSigner::to_onchain_units(1.2345679, 6).unwrap(),
+            U256::from(1_234_567u64)
+        );
+    }
+
+    #[test]
+    fn to_onchain_units_zero_decimal_token_keeps_integer_part() {
+        assert_eq!(
+            AlphaSecSigner::to_onchain_units(42.0, 0).unwrap(),
+            U256::from(42u8)
+        );
+        assert_eq!(
+            AlphaSecSigner::to_onchain_units(42.99, 0).unwrap(),
+            U256::from(42u8)
+        );
+        assert_eq!(
+            AlphaSecSigner::to_onchain_units(0.0, 0).unwrap(),
+            U256::zero()
+        );
+    }
+
+    #[test]
+    fn to_onchain_units_supports_large_decimals_up_to_limit() {
+        // 24 decimals: 1.5e6 * 1e24 = 1.5e30 no longer fits u128 but fits U256.
+        assert_eq!(
+            AlphaSecSigner::to_onchain_units(1_500_000.0, 24).unwrap(),
+            U256::from(15u8) * U256::exp10(29)
+        );
+        assert_eq!(
+            AlphaSecSigner::to_onchain_units(1.0, MAX_TOKEN_DECIMALS).unwrap(),
+            U256::exp10(36)
+        );
+        let err = AlphaSecSigner::to_onchain_units(1.0, MAX_TOKEN_DECIMALS + 1)
+            .expect_err("decimals above the limit must error")
+            .to_string();
+        assert!(err.contains("decimals 37"), "got: {}", err);
+    }
+
     // =========================================================================
     // §3.5 session EIP-712 domain.chainId (get_chain_id branch)
     // =========================================================================
@@ -2203,6 +3245,78 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn generated_tx_signature_recovers_over_the_l2_chain_id() {
+        for (config, l2_id) in [
+            (create_test_config(), ALPHASEC_TESTNET_CHAIN_ID),
+            (create_test_config_mainnet(), ALPHASEC_MAINNET_CHAIN_ID),
+        ] {
+            let signer = AlphaSecSigner::new(config);
+            let tx_hex = signer
+                .generate_alphasec_transaction(Some(1), &[0x23], None)
+                .await
+                .unwrap();
+            // The sighash commits to the chain id, so recovering the wallet address
+            // proves the signature was made for this chain id.
+            let (tx, sender) = decode_signed_tx_with_sender(&tx_hex);
+            assert_eq!(tx.chain_id().map(|c| c.as_u64()), Some(l2_id));
+            assert_eq!(format!("{:?}", sender), signer.l1_address());
+        }
+    }
+
+    #[tokio::test]
+    async fn withdraw_txs_are_signed_for_the_l2_chain_per_network() {
+        // Building withdraw transactions makes no RPC calls, so an unreachable
+        // provider is fine.
+        let url = url::Url::parse("http://127.0.0.1:1").unwrap();
+        let provider = Arc::new(ethers::providers::Provider::new(
+            ethers::providers::Http::new(url),
+        ));
+        let token = "0x1111111111111111111111111111111111111111";
+
+        for (config, l1_id, l2_id) in [
+            (
+                create_test_config(),
+                KAIA_KAIROS_CHAIN_ID,
+                ALPHASEC_TESTNET_CHAIN_ID,
+            ),
+            (
+                create_test_config_mainnet(),
+                KAIA_MAINNET_CHAIN_ID,
+                ALPHASEC_MAINNET_CHAIN_ID,
+            ),
+        ] {
+            let signer = AlphaSecSigner::new(config);
+            for (token_id, token_l1_address) in [("1", None), ("2", Some(token))] {
+                let tx_hex = signer
+                    .generate_withdraw_transaction(
+                        &provider,
+                        token_id,
+                        Decimal::ONE,
+                        token_l1_address,
+                        Some(18),
+                        Some(1),
+                    )
+                    .await
+                    .unwrap();
+                let (tx, sender) = decode_signed_tx_with_sender(&tx_hex);
+                let chain_id = tx.chain_id().expect("chain id must be set").as_u64();
+                assert_eq!(chain_id, l2_id, "token {}: L2 chain id", token_id);
+                assert_ne!(
+                    chain_id, l1_id,
+                    "token {}: must not use the Kaia id",
+                    token_id
+                );
+                assert_eq!(
+                    format!("{:?}", sender),
+                    signer.l1_address(),
+                    "token {}: signature must cover the serialised tx",
+                    token_id
+                );
+            }
+        }
+    }
+
     #[tokio::test]
     async fn generated_tx_pins_to_value_data_and_nonce() {
         let signer = AlphaSecSigner::new(create_test_config());
@@ -2283,23 +3397,16 @@ mod tests {
     // =========================================================================
 
     #[test]
-    fn token_transfer_space_stripping_corrupts_values_inside_strings() {
+    fn token_transfer_keeps_spaces_inside_values() {
         let signer = AlphaSecSigner::new(create_test_config());
         let data = signer
-            .create_token_transfer_data("0xrecipient", 1.0, "US DT")
+            .create_token_transfer_data("0xrecipient", Decimal::ONE, "US DT")
             .unwrap();
 
-        // Not a single space byte survives anywhere in the payload.
-        assert!(
-            !data[1..].contains(&b' '),
-            "payload must contain no space bytes at all"
-        );
-
-        // The space inside the token VALUE was destroyed: "US DT" -> "USDT".
-        let json: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
+        // Compact JSON has no whitespace between tokens, and values are left intact.
         assert_eq!(
-            json["token"], "USDT",
-            "replace(\" \", \"\") must strip spaces inside values too"
+            std::str::from_utf8(&data[1..]).unwrap(),
+            r#"{"l1owner":"0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266","to":"0xrecipient","token":"US DT","value":"1"}"#
         );
     }
 
@@ -2323,29 +3430,83 @@ mod tests {
     }
 
     #[test]
-    fn transfer_builders_do_not_share_numeric_formatting() {
+    fn transfer_builders_share_decimal_formatting() {
         let signer = AlphaSecSigner::new(create_test_config());
+        let amount = Decimal::from_str("100.0").unwrap();
 
-        // Decimal path: trailing zero is preserved.
         let value_tx = signer
-            .create_value_transfer_data("0xrecipient", Decimal::from_str("100.0").unwrap())
+            .create_value_transfer_data("0xrecipient", amount)
             .unwrap();
         let value_json: serde_json::Value = serde_json::from_slice(&value_tx[1..]).unwrap();
-        assert_eq!(
-            value_json["value"], "100.0",
-            "Decimal path must keep the scale"
-        );
-
-        // f64 path: Display drops the trailing zero for the same human amount.
         let token_tx = signer
-            .create_token_transfer_data("0xrecipient", 100.0, "USDT")
+            .create_token_transfer_data("0xrecipient", amount, "USDT")
             .unwrap();
         let token_json: serde_json::Value = serde_json::from_slice(&token_tx[1..]).unwrap();
-        assert_eq!(token_json["value"], "100", "f64 path must drop the scale");
 
-        assert_ne!(
-            value_json["value"], token_json["value"],
-            "same human amount must format differently on the two paths"
+        // Both paths emit the canonical form, without the trailing zero.
+        assert_eq!(value_json["value"], "100");
+        assert_eq!(token_json["value"], "100");
+    }
+
+    #[test]
+    fn token_transfer_value_keeps_sub_f64_precision() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let amount = Decimal::from_str("1234.000000000000000001").unwrap();
+        let data = signer
+            .create_token_transfer_data("0xrecipient", amount, "USDT")
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
+        assert_eq!(json["value"], "1234.000000000000000001");
+    }
+
+    #[test]
+    fn decimal_to_onchain_units_is_exact_where_f64_rounds() {
+        let amount = Decimal::from_str("1234.000000000000000001").unwrap();
+        assert_eq!(
+            AlphaSecSigner::decimal_to_onchain_units(amount, 18).unwrap(),
+            U256::from_dec_str("1234000000000000000001").unwrap()
+        );
+        // The same amount as an f64 has already lost the last digit.
+        let lossy: f64 = "1234.000000000000000001".parse().unwrap();
+        assert_eq!(
+            AlphaSecSigner::to_onchain_units(lossy, 18).unwrap(),
+            U256::from_dec_str("1234000000000000000000").unwrap()
+        );
+        // Digits beyond the token's precision are truncated, not rounded.
+        assert_eq!(
+            AlphaSecSigner::decimal_to_onchain_units(amount, 6).unwrap(),
+            U256::from(1_234_000_000u64)
+        );
+        assert!(AlphaSecSigner::decimal_to_onchain_units(Decimal::NEGATIVE_ONE, 18).is_err());
+        assert!(
+            AlphaSecSigner::decimal_to_onchain_units(Decimal::ONE, MAX_TOKEN_DECIMALS + 1).is_err()
+        );
+    }
+
+    #[test]
+    fn onchain_units_to_decimal_inverts_the_scaling() {
+        let amount = Decimal::from_str("1234.000000000000000001").unwrap();
+        let units = AlphaSecSigner::decimal_to_onchain_units(amount, 18).unwrap();
+        assert_eq!(
+            AlphaSecSigner::onchain_units_to_decimal(units, 18).unwrap(),
+            amount
+        );
+        assert_eq!(
+            AlphaSecSigner::onchain_units_to_decimal(U256::from(1_500_000u64), 6).unwrap(),
+            Decimal::new(15, 1)
+        );
+        assert_eq!(
+            AlphaSecSigner::onchain_units_to_decimal(U256::from(7u64), 0).unwrap(),
+            Decimal::from(7)
+        );
+        // Past a Decimal's 28 fractional digits the rest is truncated
+        assert_eq!(
+            AlphaSecSigner::onchain_units_to_decimal(U256::exp10(36) + 1, 36).unwrap(),
+            Decimal::ONE
+        );
+        assert!(AlphaSecSigner::onchain_units_to_decimal(U256::MAX, 18).is_err());
+        assert!(
+            AlphaSecSigner::onchain_units_to_decimal(U256::one(), MAX_TOKEN_DECIMALS + 1).is_err()
         );
     }
 
@@ -2358,7 +3519,7 @@ mod tests {
             .create_value_transfer_data("0xrecipient", Decimal::from_str("1").unwrap())
             .unwrap();
         let token_tx = signer
-            .create_token_transfer_data("0xrecipient", 1.0, "USDT")
+            .create_token_transfer_data("0xrecipient", Decimal::ONE, "USDT")
             .unwrap();
 
         for (name, payload) in [("value", value_tx), ("token", token_tx)] {
@@ -2386,18 +3547,18 @@ mod tests {
     fn market_order_keeps_quantity_raw_but_normalizes_price() {
         let signer = AlphaSecSigner::new(create_test_config());
         let data = signer
-            .create_order_data(
-                "KAIA",
-                "USDT",
-                0,
-                Decimal::from_str("0.123456789").unwrap(),
-                Decimal::from_str("123.456789").unwrap(),
-                OrderType::Market as u32, // 1
-                0,
-                None,
-                None,
-                None,
-            )
+            .create_order_data(&OrderParams {
+                rounding: RoundingMode::NearestEven,
+                ..OrderParams::new(
+                    "KAIA",
+                    "USDT",
+                    0,
+                    Decimal::from_str("0.123456789").unwrap(),
+                    Decimal::from_str("123.456789").unwrap(),
+                    OrderType::Market as u32,
+                    0,
+                )
+            })
             .unwrap();
         let json: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
 
@@ -2413,22 +3574,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn order_quantity_precision_follows_the_token_it_is_denominated_in() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let try_market_order = |quantity: &str, order_mode: OrderMode, decimals: u32| {
+            signer
+                .create_order_data(&OrderParams {
+                    quantity_decimals: Some(decimals),
+                    ..OrderParams::new(
+                        "5",
+                        "2",
+                        0,
+                        Decimal::from_str("0.15").unwrap(),
+                        Decimal::from_str(quantity).unwrap(),
+                        OrderType::Market as u32,
+                        order_mode as u32,
+                    )
+                })
+                .map_err(Box::new)
+        };
+        let market_order = |quantity: &str, order_mode: OrderMode, decimals: u32| {
+            let data = try_market_order(quantity, order_mode, decimals).unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
+            (json["quantity"].clone(), json["orderMode"].clone())
+        };
+
+        // 1.0 KAIA (18 decimals) and 1.0 USDT (6 decimals) both go out as 1 ...
+        assert_eq!(
+            market_order("1.0", OrderMode::Base, 18),
+            ("1".into(), 0.into())
+        );
+        assert_eq!(
+            market_order("1.0", OrderMode::Quote, 6),
+            ("1".into(), 1.into())
+        );
+        // ... but a quote amount is cut to the quote token's precision, not the base's.
+        assert_eq!(
+            market_order("1.123456789", OrderMode::Quote, 6).0,
+            "1.123456"
+        );
+        assert_eq!(
+            market_order("1.123456789", OrderMode::Base, 18).0,
+            "1.123456789"
+        );
+        // ... and one that truncates to nothing is rejected rather than sent as 0
+        match try_market_order("0.0000009", OrderMode::Quote, 6).map_err(|e| *e) {
+            Err(AlphaSecError::InvalidParameter(message)) => assert_eq!(
+                message,
+                "Quantity 0.0000009 is 0 once truncated to 6 decimals"
+            ),
+            other => panic!("expected InvalidParameter, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn order_values_rounded_to_zero_are_not_signed() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        // The error message, if the order is rejected as invalid
+        let rejection = |price: &str, quantity: &str, tick_size: Option<&str>| {
+            let result = signer.create_order_data(&OrderParams {
+                tick_size: tick_size.map(|tick| Decimal::from_str(tick).unwrap()),
+                ..OrderParams::new(
+                    "5",
+                    "2",
+                    0,
+                    Decimal::from_str(price).unwrap(),
+                    Decimal::from_str(quantity).unwrap(),
+                    OrderType::Limit as u32,
+                    OrderMode::Base as u32,
+                )
+            });
+            match result {
+                Ok(_) => None,
+                Err(AlphaSecError::InvalidParameter(message)) => Some(message),
+                Err(other) => panic!("expected InvalidParameter, got {:?}", other),
+            }
+        };
+
+        // Quantities at prices below 1 keep one decimal
+        assert_eq!(
+            rejection("0.15", "0.05", None).unwrap(),
+            "Quantity 0.05 is 0 once truncated to 1 decimals"
+        );
+        assert_eq!(
+            rejection("0.000000001", "10", None).unwrap(),
+            "Price 0.000000001 is 0 once rounded to 8 decimals"
+        );
+        assert_eq!(
+            rejection("0.004", "10", Some("0.01")).unwrap(),
+            "Price 0.004 is 0 once rounded to tick size 0.01"
+        );
+        assert_eq!(rejection("0.15", "0.1", None), None);
+    }
+
     #[test]
     fn limit_order_normalizes_both_price_and_quantity() {
         let signer = AlphaSecSigner::new(create_test_config());
         let data = signer
-            .create_order_data(
-                "KAIA",
-                "USDT",
-                0,
-                Decimal::from_str("0.123456789").unwrap(),
-                Decimal::from_str("123.456789").unwrap(),
-                OrderType::Limit as u32, // 0
-                0,
-                None,
-                None,
-                None,
-            )
+            .create_order_data(&OrderParams {
+                rounding: RoundingMode::NearestEven,
+                ..OrderParams::new(
+                    "KAIA",
+                    "USDT",
+                    0,
+                    Decimal::from_str("0.123456789").unwrap(),
+                    Decimal::from_str("123.456789").unwrap(),
+                    OrderType::Limit as u32,
+                    0,
+                )
+            })
             .unwrap();
         let json: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
 
@@ -2442,6 +3696,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn conservative_buy_price_is_rounded_down_to_the_tick_size() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let data = signer
+            .create_order_data(&OrderParams {
+                rounding: RoundingMode::Conservative {
+                    side: crate::OrderSide::Buy,
+                },
+                tick_size: Some(Decimal::from_str("0.001").unwrap()),
+                ..OrderParams::new(
+                    "KAIA",
+                    "USDT",
+                    crate::OrderSide::Buy as u32,
+                    Decimal::from_str("1.23456").unwrap(),
+                    Decimal::from_str("10").unwrap(),
+                    OrderType::Limit as u32,
+                    0,
+                )
+            })
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
+        assert_eq!(json["price"], "1.234");
+    }
+
+    #[test]
+    fn client_order_id_is_on_the_wire_only_when_set() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let encode = |client_order_id: Option<&str>, reduce_only| {
+            let data = signer
+                .create_order_data(&OrderParams {
+                    client_order_id: client_order_id.map(str::to_string),
+                    reduce_only,
+                    ..OrderParams::new(
+                        "KAIA",
+                        "USDT",
+                        crate::OrderSide::Buy as u32,
+                        Decimal::ONE,
+                        Decimal::TEN,
+                        OrderType::Limit as u32,
+                        0,
+                    )
+                })
+                .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&data[1..]).unwrap()
+        };
+        assert_eq!(encode(Some("grid-7"), false)["clientOrderId"], "grid-7");
+        assert!(encode(None, false).get("clientOrderId").is_none());
+    }
+
+    #[test]
+    fn reduce_only_is_on_the_wire_only_when_set() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let encode = |reduce_only| {
+            let data = signer
+                .create_order_data(&OrderParams {
+                    reduce_only,
+                    ..OrderParams::new(
+                        "KAIA",
+                        "USDT",
+                        crate::OrderSide::Sell as u32,
+                        Decimal::ONE,
+                        Decimal::TEN,
+                        OrderType::Limit as u32,
+                        0,
+                    )
+                })
+                .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&data[1..]).unwrap()
+        };
+        assert_eq!(encode(true)["reduceOnly"], true);
+        assert!(encode(false).get("reduceOnly").is_none());
+
+        // Off by default when decoding a model that does not carry it
+        let plain = signer
+            .order_model(&OrderParams::new(
+                "KAIA",
+                "USDT",
+                0,
+                Decimal::ONE,
+                Decimal::TEN,
+                0,
+                0,
+            ))
+            .unwrap();
+        let decoded: OrderModel =
+            serde_json::from_slice(&serde_json::to_vec(&plain).unwrap()).unwrap();
+        assert!(!decoded.reduce_only);
+    }
+
     #[test]
     fn sl_limit_alone_is_silently_dropped_from_tpsl() {
         let signer = AlphaSecSigner::new(create_test_config());
@@ -2451,7 +3794,11 @@ mod tests {
 
         // sl_limit ALONE -> no tpsl key at all (silently dropped).
         let sl_limit_only = signer
-            .create_order_data("KAIA", "USDT", 0, price, qty, 0, 0, None, None, Some(aux))
+            .create_order_data(&OrderParams {
+                sl_limit: Some(aux),
+                rounding: RoundingMode::NearestEven,
+                ..OrderParams::new("KAIA", "USDT", 0, price, qty, 0, 0)
+            })
             .unwrap();
         let json: serde_json::Value = serde_json::from_slice(&sl_limit_only[1..]).unwrap();
         assert!(
@@ -2462,7 +3809,11 @@ mod tests {
 
         // sl_trigger alone DOES open the gate.
         let sl_trigger_only = signer
-            .create_order_data("KAIA", "USDT", 0, price, qty, 0, 0, None, Some(aux), None)
+            .create_order_data(&OrderParams {
+                sl_trigger: Some(aux),
+                rounding: RoundingMode::NearestEven,
+                ..OrderParams::new("KAIA", "USDT", 0, price, qty, 0, 0)
+            })
             .unwrap();
         let json: serde_json::Value = serde_json::from_slice(&sl_trigger_only[1..]).unwrap();
         assert!(
@@ -2473,7 +3824,11 @@ mod tests {
 
         // tp_limit alone DOES open the gate.
         let tp_limit_only = signer
-            .create_order_data("KAIA", "USDT", 0, price, qty, 0, 0, Some(aux), None, None)
+            .create_order_data(&OrderParams {
+                tp_limit: Some(aux),
+                rounding: RoundingMode::NearestEven,
+                ..OrderParams::new("KAIA", "USDT", 0, price, qty, 0, 0)
+            })
             .unwrap();
         let json: serde_json::Value = serde_json::from_slice(&tp_limit_only[1..]).unwrap();
         assert!(
@@ -2482,4 +3837,230 @@ mod tests {
         );
         assert_eq!(json["tpsl"]["tpLimit"], "1.1");
     }
+
+    // =========================================================================
+    // create_stop_order_data — side/type/mode matrix
+    // =========================================================================
+
+    #[test]
+    fn stop_order_wire_json_for_every_side_type_mode_combination() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let stop = Decimal::from_str("1.234567").unwrap();
+        let price = Decimal::from_str("1.200049").unwrap();
+        let qty = Decimal::from_str("12.345").unwrap();
+
+        for side in [crate::OrderSide::Buy, crate::OrderSide::Sell] {
+            for order_type in [OrderType::Limit, OrderType::Market] {
+                for mode in [OrderMode::Base, OrderMode::Quote] {
+                    let result = signer.create_stop_order_data(
+                        "KAIA",
+                        "USDT",
+                        stop,
+                        price,
+                        qty,
+                        side as u32,
+                        order_type as u32,
+                        mode as u32,
+                    );
+                    let case = format!("{:?}/{:?}/{:?}", side, order_type, mode);
+
+                    if order_type == OrderType::Limit && mode == OrderMode::Quote {
+                        assert!(
+                            matches!(result, Err(AlphaSecError::InvalidParameter(_))),
+                            "{}: quote mode must be rejected for limit",
+                            case
+                        );
+                        continue;
+                    }
+
+                    let data = result.unwrap_or_else(|e| panic!("{}: {}", case, e));
+                    assert_eq!(data[0], DEX_COMMAND_STOP_ORDER, "{}", case);
+                    let json: serde_json::Value = serde_json::from_slice(&data[1..]).unwrap();
+                    assert_eq!(json["side"], side as u32, "{}", case);
+                    assert_eq!(json["orderType"], order_type as u32, "{}", case);
+                    assert_eq!(json["orderMode"], mode as u32, "{}", case);
+                    assert_eq!(json["stopPrice"], "1.2346", "{}", case);
+                    assert_eq!(json["price"], "1.2", "{}", case);
+                    // Market quantity is passed through (a quote amount in quote mode);
+                    // limit quantity is normalized by the price band like a plain order.
+                    let expected_qty = match order_type {
+                        OrderType::Market => "12.345",
+                        OrderType::Limit => "12.3",
+                    };
+                    assert_eq!(json["quantity"], expected_qty, "{}", case);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn order_and_stop_paths_share_order_mode_validation() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let one = Decimal::ONE;
+        let order = |order_type: u32, mode: u32| {
+            signer.create_order_data(&OrderParams::new(
+                "KAIA", "USDT", 0, one, one, order_type, mode,
+            ))
+        };
+        let stop = |order_type: u32, mode: u32| {
+            signer.create_stop_order_data("KAIA", "USDT", one, one, one, 0, order_type, mode)
+        };
+
+        for (order_type, mode, ok) in [
+            (0, 0, true),
+            (1, 0, true),
+            (1, 1, true),
+            (0, 1, false),
+            (2, 0, false),
+            (1, 2, false),
+        ] {
+            assert_eq!(
+                order(order_type, mode).is_ok(),
+                ok,
+                "order {}/{}",
+                order_type,
+                mode
+            );
+            assert_eq!(
+                stop(order_type, mode).is_ok(),
+                ok,
+                "stop {}/{}",
+                order_type,
+                mode
+            );
+        }
+    }
+
+    /// Regression snapshots of the wire bytes, see the `transaction` module docs. Each
+    /// file holds the JSON after the command byte, with a trailing newline. Inputs carry
+    /// trailing zeros so the canonical decimal formatting is covered.
+    #[test]
+    fn wire_payloads_match_the_snapshots() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let dec = |value: &str| Decimal::from_str(value).unwrap();
+        let recipient = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+        let cases: [(&str, u8, Vec<u8>, &str); 5] = [
+            (
+                "order",
+                DEX_COMMAND_ORDER,
+                signer
+                    .create_order_data(&OrderParams {
+                        tp_limit: Some(dec("0.1800")),
+                        sl_trigger: Some(dec("0.1200")),
+                        sl_limit: Some(dec("0.1190")),
+                        quantity_decimals: Some(18),
+                        client_order_id: Some("grid-7".to_string()),
+                        ..OrderParams::new(
+                            "5",
+                            "2",
+                            crate::OrderSide::Buy as u32,
+                            dec("0.15000"),
+                            dec("100.50"),
+                            OrderType::Limit as u32,
+                            OrderMode::Base as u32,
+                        )
+                    })
+                    .unwrap(),
+                include_str!("testdata/wire/order.json"),
+            ),
+            (
+                "cancel",
+                DEX_COMMAND_CANCEL,
+                signer.create_cancel_data("0x1234abcd").unwrap(),
+                include_str!("testdata/wire/cancel.json"),
+            ),
+            (
+                "modify",
+                DEX_COMMAND_MODIFY,
+                signer
+                    .create_modify_data(
+                        "0x1234abcd",
+                        dec("0.16000"),
+                        dec("50.0"),
+                        OrderMode::Base as u32,
+                    )
+                    .unwrap(),
+                include_str!("testdata/wire/modify.json"),
+            ),
+            (
+                "value transfer",
+                DEX_COMMAND_TRANSFER,
+                signer
+                    .create_value_transfer_data(recipient, dec("1.50"))
+                    .unwrap(),
+                include_str!("testdata/wire/value_transfer.json"),
+            ),
+            (
+                "token transfer",
+                DEX_COMMAND_TOKEN_TRANSFER,
+                signer
+                    .create_token_transfer_data(recipient, dec("5000000.0"), "2")
+                    .unwrap(),
+                include_str!("testdata/wire/token_transfer.json"),
+            ),
+        ];
+        for (name, command, wire, snapshot) in cases {
+            assert_eq!(wire[0], command, "{}", name);
+            assert_eq!(
+                std::str::from_utf8(&wire[1..]).unwrap(),
+                snapshot.trim_end(),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn decimal_prices_and_quantities_serialize_exactly() {
+        let signer = AlphaSecSigner::new(create_test_config());
+        let tenth = Decimal::from_str("0.1").unwrap();
+        let payload =
+            |data: Vec<u8>| serde_json::from_slice::<serde_json::Value>(&data[1..]).unwrap();
+
+        let order = payload(
+            signer
+                .create_order_data(&OrderParams {
+                    tp_limit: Some(tenth),
+                    ..OrderParams::new(
+                        "KAIA",
+                        "USDT",
+                        0,
+                        tenth,
+                        tenth,
+                        OrderType::Limit as u32,
+                        OrderMode::Base as u32,
+                    )
+                })
+                .unwrap(),
+        );
+        assert_eq!(order["price"], "0.1");
+        assert_eq!(order["quantity"], "0.1");
+        assert_eq!(order["tpsl"]["tpLimit"], "0.1");
+
+        let stop = payload(
+            signer
+                .create_stop_order_data(
+                    "KAIA",
+                    "USDT",
+                    tenth,
+                    tenth,
+                    tenth,
+                    0,
+                    OrderType::Limit as u32,
+                    OrderMode::Base as u32,
+                )
+                .unwrap(),
+        );
+        assert_eq!(stop["stopPrice"], "0.1");
+        assert_eq!(stop["price"], "0.1");
+        assert_eq!(stop["quantity"], "0.1");
+
+        let modify = payload(
+            signer
+                .create_modify_data("0x1234abcd", tenth, tenth, OrderMode::Base as u32)
+                .unwrap(),
+        );
+        assert_eq!(modify["newPrice"], "0.1");
+        assert_eq!(modify["newQty"], "0.1");
+    }
 }
@@ -1,4 +1,23 @@
 //! Transaction models for AlphaSec operations
+//!
+//! # Wire format
+//!
+//! A DEX command is its command byte (e.g. `0x21` for an order) followed by the model
+//! as compact JSON: no whitespace, keys in field declaration order, `None` fields left
+//! out. Enums (`side`, `orderType`, `orderMode`) are JSON numbers. Prices, quantities
+//! and amounts are JSON strings in canonical decimal form: no exponent, no trailing
+//! zeros and no trailing `.` (`5000000`, not `5000000.0` or `5e6`; `0.5`, not `0.50`),
+//! as [`rust_decimal::Decimal::normalize`] prints them. No value passes through `f64`.
+//!
+//! The canonical form makes the payload depend on the value alone. A `Decimal` prints
+//! with its scale, so without it the same order signed as `"100"` or `"100.0"`
+//! depending on how its inputs were written or rounded. Only trailing zeros are
+//! dropped, so every value is unchanged, and it is the form the perp builders have
+//! always sent.
+//!
+//! The payloads in `src/signer/testdata/wire` are regression snapshots of this SDK's
+//! own output for fixed inputs. They catch unintended changes to these bytes; they
+//! were not captured from the other AlphaSec SDKs and do not show parity with them.
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -39,12 +58,14 @@ impl ValueTransferModel {
 }
 
 /// Token transfer model
+///
+/// Fields are declared in their wire order, which is alphabetical.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenTransferModel {
     pub l1owner: String,
     pub to: String,
-    pub value: String,
     pub token: String,
+    pub value: String,
 }
 
 impl TokenTransferModel {
@@ -100,6 +121,16 @@ pub struct OrderModel {
     pub order_mode: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tpsl: Option<TpslModel>,
+    /// Caller-chosen order ID, omitted when unset
+    #[serde(rename = "clientOrderId", skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+    /// Only reduce an existing position, omitted unless set
+    #[serde(
+        rename = "reduceOnly",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub reduce_only: bool,
 }
 
 impl OrderModel {
@@ -194,6 +225,47 @@ impl StopOrderModel {
     }
 }
 
+/// One-cancels-other order pair: a limit leg at `price` and a stop-limit leg that
+/// places `stopLimitPrice` once `stopPrice` is hit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcoOrderModel {
+    /// L1 owner address
+    pub l1owner: String,
+    /// Base token ID
+    #[serde(rename = "baseToken")]
+    pub base_token: String,
+    /// Quote token ID
+    #[serde(rename = "quoteToken")]
+    pub quote_token: String,
+    /// Side of both legs
+    pub side: u32,
+    /// Quantity of both legs
+    pub quantity: String,
+    /// Limit leg price
+    pub price: String,
+    /// Stop leg trigger price
+    #[serde(rename = "stopPrice")]
+    pub stop_price: String,
+    /// Stop leg limit price, placed once triggered
+    #[serde(rename = "stopLimitPrice")]
+    pub stop_limit_price: String,
+    /// Order mode of both legs
+    #[serde(rename = "orderMode")]
+    pub order_mode: u32,
+    /// Always `"OCO"`
+    #[serde(rename = "contingencyType")]
+    pub contingency_type: String,
+}
+
+impl OcoOrderModel {
+    /// Create alphasec-style transaction bytes (0x26 + JSON)
+    pub fn to_wire(&self) -> Result<Vec<u8>, serde_json::Error> {
+        let mut final_tx_bytes = vec![0x26]; // DEX_COMMAND_OCO_ORDER
+        final_tx_bytes.extend_from_slice(&serde_json::to_vec(self)?);
+        Ok(final_tx_bytes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +283,8 @@ mod tests {
             order_type: 1,
             order_mode: 2,
             tpsl,
+            client_order_id: None,
+            reduce_only: false,
         }
     }
 
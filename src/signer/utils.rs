@@ -2,7 +2,7 @@ use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use std::str::FromStr;
 
-use crate::AlphaSecError;
+use crate::{types::RoundingMode, AlphaSecError};
 
 /// Scale a Decimal value to an 18-decimal big.Int integer string (perp wire format).
 ///
@@ -27,16 +27,67 @@ pub fn perp_scale(value: Decimal) -> Result<String, AlphaSecError> {
     Ok(scaled.to_string())
 }
 
-/// Truncate a value to the specified number of decimal places (no rounding)
-fn truncate_to_precision(value: Decimal, precision: u64) -> Decimal {
-    value.round_dp(precision as u32)
+/// Round `value` to a multiple of `increment` if one is given, else to `dp` decimal places.
+fn round_to(
+    value: Decimal,
+    dp: u32,
+    increment: Option<Decimal>,
+    strategy: RoundingStrategy,
+) -> Decimal {
+    match increment {
+        Some(step) if step > Decimal::ZERO => {
+            (value / step).round_dp_with_strategy(0, strategy) * step
+        }
+        _ => value.round_dp_with_strategy(dp, strategy),
+    }
+}
+
+/// Decimal places a price is rounded to when the market has no tick size, by magnitude
+pub(crate) fn get_price_precision(price: Decimal) -> u64 {
+    if price >= Decimal::from_str("10000.0").unwrap() {
+        0
+    } else if price >= Decimal::from_str("1000.0").unwrap() {
+        1
+    } else if price >= Decimal::from_str("100.0").unwrap() {
+        2
+    } else if price >= Decimal::from_str("10.0").unwrap() {
+        3
+    } else if price >= Decimal::from_str("1.0").unwrap() {
+        4
+    } else if price >= Decimal::from_str("0.1").unwrap() {
+        5
+    } else if price >= Decimal::from_str("0.01").unwrap() {
+        6
+    } else if price >= Decimal::from_str("0.001").unwrap() {
+        7
+    } else if price >= Decimal::from_str("0.0001").unwrap() {
+        8
+    } else {
+        8
+    }
 }
 
-/// Normalize price and quantity values by truncating them to appropriate precision
+/// Decimal places a limit order quantity is rounded to, by the magnitude of its price
+pub(crate) fn get_quantity_precision(price: Decimal) -> u64 {
+    if price >= Decimal::from_str("10000.0").unwrap() {
+        5
+    } else if price >= Decimal::from_str("1000.0").unwrap() {
+        4
+    } else if price >= Decimal::from_str("100.0").unwrap() {
+        3
+    } else if price >= Decimal::from_str("10.0").unwrap() {
+        2
+    } else if price >= Decimal::from_str("1.0").unwrap() {
+        1
+    } else {
+        1
+    }
+}
+
+/// Normalize price and quantity values by rounding them to appropriate precision
 ///
-/// The precision is determined based on the magnitude of the values:
-/// - Price precision: 0-8 decimal places depending on price range
-/// - Quantity precision: 0-5 decimal places depending on quantity range
+/// Equivalent to [`normalize_price_quantity_with`] using [`RoundingMode::NearestEven`]
+/// and no tick size.
 ///
 /// # Arguments
 /// * `price` - The price value to normalize (must be non-negative)
@@ -49,46 +100,31 @@ pub fn normalize_price_quantity(
     price: Decimal,
     quantity: Decimal,
 ) -> Result<(Decimal, Decimal), AlphaSecError> {
-    fn get_price_precision(price: Decimal) -> u64 {
-        if price >= Decimal::from_str("10000.0").unwrap() {
-            0
-        } else if price >= Decimal::from_str("1000.0").unwrap() {
-            1
-        } else if price >= Decimal::from_str("100.0").unwrap() {
-            2
-        } else if price >= Decimal::from_str("10.0").unwrap() {
-            3
-        } else if price >= Decimal::from_str("1.0").unwrap() {
-            4
-        } else if price >= Decimal::from_str("0.1").unwrap() {
-            5
-        } else if price >= Decimal::from_str("0.01").unwrap() {
-            6
-        } else if price >= Decimal::from_str("0.001").unwrap() {
-            7
-        } else if price >= Decimal::from_str("0.0001").unwrap() {
-            8
-        } else {
-            8
-        }
-    }
-
-    fn get_quantity_precision(price: Decimal) -> u64 {
-        if price >= Decimal::from_str("10000.0").unwrap() {
-            5
-        } else if price >= Decimal::from_str("1000.0").unwrap() {
-            4
-        } else if price >= Decimal::from_str("100.0").unwrap() {
-            3
-        } else if price >= Decimal::from_str("10.0").unwrap() {
-            2
-        } else if price >= Decimal::from_str("1.0").unwrap() {
-            1
-        } else {
-            1
-        }
-    }
+    normalize_price_quantity_with(price, quantity, RoundingMode::NearestEven, None)
+}
 
+/// Normalize price and quantity values to appropriate precision using `rounding`
+///
+/// The precision is determined based on the magnitude of the price:
+/// - Price precision: 0-8 decimal places depending on price range, or a multiple of
+///   `tick_size` when the market reports one
+/// - Quantity precision: 1-5 decimal places depending on price range
+///
+/// # Arguments
+/// * `price` - The price value to normalize (must be non-negative)
+/// * `quantity` - The quantity value to normalize (must be non-negative)
+/// * `rounding` - How excess digits are dropped, see [`RoundingMode`]
+/// * `tick_size` - Market price increment; `None` or zero falls back to the price bands
+///
+/// # Returns
+/// * `Ok((rounded_price, rounded_quantity))` - The normalized values
+/// * `Err(AlphaSecError)` - If price or quantity is negative
+pub fn normalize_price_quantity_with(
+    price: Decimal,
+    quantity: Decimal,
+    rounding: RoundingMode,
+    tick_size: Option<Decimal>,
+) -> Result<(Decimal, Decimal), AlphaSecError> {
     if price < Decimal::from_str("0.0").unwrap() {
         return Err(AlphaSecError::invalid_parameter("Price cannot be negative"));
     }
@@ -101,9 +137,18 @@ pub fn normalize_price_quantity(
     let price_precision = get_price_precision(price);
     let quantity_precision = get_quantity_precision(price);
 
-    // Truncate price and quantity to the calculated precision
-    let rounded_price = truncate_to_precision(price, price_precision);
-    let rounded_quantity = truncate_to_precision(quantity, quantity_precision);
+    let rounded_price = round_to(
+        price,
+        price_precision as u32,
+        tick_size,
+        rounding.price_strategy(),
+    );
+    let rounded_quantity = round_to(
+        quantity,
+        quantity_precision as u32,
+        None,
+        rounding.quantity_strategy(),
+    );
 
     Ok((rounded_price, rounded_quantity))
 }
@@ -346,4 +391,62 @@ mod tests {
         assert_eq!(price, dec("90000"));
         assert_eq!(quantity, dec("1"));
     }
+
+    #[test]
+    fn rounding_modes_at_band_precision() {
+        let with = |mode| normalize_price_quantity_with(dec("2.00006"), dec("0.26"), mode, None);
+        assert_eq!(
+            with(RoundingMode::Truncate).unwrap(),
+            (dec("2.0000"), dec("0.2"))
+        );
+        assert_eq!(
+            with(RoundingMode::NearestEven).unwrap(),
+            (dec("2.0001"), dec("0.3"))
+        );
+        assert_eq!(
+            with(RoundingMode::default()).unwrap(),
+            with(RoundingMode::Truncate).unwrap()
+        );
+    }
+
+    #[test]
+    fn conservative_rounds_buys_down_and_sells_up_at_the_tick_size() {
+        let tick = Some(dec("0.001"));
+        let conservative = |side| {
+            normalize_price_quantity_with(
+                dec("1.23456"),
+                dec("7.89"),
+                RoundingMode::Conservative { side },
+                tick,
+            )
+            .unwrap()
+        };
+        let (buy_price, buy_qty) = conservative(crate::OrderSide::Buy);
+        assert_eq!(buy_price, dec("1.234"));
+        let (sell_price, sell_qty) = conservative(crate::OrderSide::Sell);
+        assert_eq!(sell_price, dec("1.235"));
+        // Quantities never round up, whichever the side.
+        assert_eq!((buy_qty, sell_qty), (dec("7.8"), dec("7.8")));
+
+        // Already on the tick: left alone either way.
+        let (p, _) = normalize_price_quantity_with(
+            dec("1.234"),
+            dec("1"),
+            RoundingMode::Conservative {
+                side: crate::OrderSide::Sell,
+            },
+            tick,
+        )
+        .unwrap();
+        assert_eq!(p, dec("1.234"));
+        // A non-decimal tick is honored too.
+        let (p, _) = normalize_price_quantity_with(
+            dec("1.23"),
+            dec("1"),
+            RoundingMode::Truncate,
+            Some(dec("0.25")),
+        )
+        .unwrap();
+        assert_eq!(p, dec("1.00"));
+    }
 }
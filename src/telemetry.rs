@@ -0,0 +1,142 @@
+//! Optional instrumentation through the [`metrics`](https://docs.rs/metrics) facade
+//!
+//! With the `metrics` feature enabled the SDK records the metrics below to whatever
+//! recorder the application installs (e.g. a Prometheus exporter). Without it every
+//! hook is an empty inline function and nothing is timed.
+//!
+//! | Name                                     | Kind      | Labels              |
+//! | ---------------------------------------- | --------- | ------------------- |
+//! | `alphasec_order_calls_total`             | counter   | `op`, `outcome`     |
+//! | `alphasec_order_call_duration_seconds`   | histogram | `op`                |
+//! | `alphasec_http_request_duration_seconds` | histogram | `method`, `outcome` |
+//! | `alphasec_ws_state_transitions_total`    | counter   | `state`             |
+//!
+//! `op` is `order`, `oco` or `cancel`; `outcome` is `success` or `failure`.
+//!
+//! Independently of the feature, `Agent::order`, `cancel`, `modify` and `place_oco` each
+//! run inside a [`tracing`] span named after the operation, so the signing and HTTP logs they emit
+//! can be correlated. The span carries the market and side (orders) or order ID
+//! (cancels and modifies), the `nonce` of the last signing attempt, and once the call
+//! returns its `outcome` and `duration_ms`. Keys and signed payloads are never recorded.
+
+use std::future::Future;
+
+use crate::error::Result;
+#[cfg(feature = "websocket")]
+use crate::websocket::ConnectionState;
+
+/// Counter of `Agent::order` / `Agent::cancel` calls
+#[cfg(feature = "metrics")]
+pub(crate) const ORDER_CALLS_TOTAL: &str = "alphasec_order_calls_total";
+/// Histogram of `Agent::order` / `Agent::cancel` latency, signing included
+#[cfg(feature = "metrics")]
+pub(crate) const ORDER_CALL_DURATION: &str = "alphasec_order_call_duration_seconds";
+/// Histogram of REST round-trip time
+#[cfg(feature = "metrics")]
+pub(crate) const HTTP_REQUEST_DURATION: &str = "alphasec_http_request_duration_seconds";
+/// Counter of WebSocket connection state changes
+#[cfg(all(feature = "metrics", feature = "websocket"))]
+pub(crate) const WS_STATE_TRANSITIONS_TOTAL: &str = "alphasec_ws_state_transitions_total";
+
+/// Start time of a timed operation; zero-sized when metrics are disabled.
+#[cfg(feature = "metrics")]
+pub(crate) type Started = std::time::Instant;
+/// Start time of a timed operation; zero-sized when metrics are disabled.
+#[cfg(not(feature = "metrics"))]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Started;
+
+/// Mark the start of a timed operation.
+#[cfg(feature = "metrics")]
+#[inline(always)]
+pub(crate) fn start() -> Started {
+    std::time::Instant::now()
+}
+
+/// Mark the start of a timed operation.
+#[cfg(not(feature = "metrics"))]
+#[inline(always)]
+pub(crate) fn start() -> Started {
+    Started
+}
+
+#[cfg(feature = "metrics")]
+fn outcome<T>(result: &Result<T>) -> &'static str {
+    if result.is_ok() {
+        "success"
+    } else {
+        "failure"
+    }
+}
+
+/// Run an order operation (`op` is `"order"`, `"oco"` or `"cancel"`), recording its outcome
+/// and latency.
+#[inline(always)]
+pub(crate) async fn order_call<T>(
+    op: &'static str,
+    call: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let started = start();
+    let result = call.await;
+    #[cfg(feature = "metrics")]
+    {
+        ::metrics::counter!(ORDER_CALLS_TOTAL, "op" => op, "outcome" => outcome(&result))
+            .increment(1);
+        ::metrics::histogram!(ORDER_CALL_DURATION, "op" => op).record(started.elapsed());
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = (op, started);
+    result
+}
+
+/// Run `call` inside `span`, then record its `outcome` and `duration_ms` on the span.
+///
+/// The span must declare both fields (as `tracing::field::Empty`) for them to show up.
+pub(crate) async fn traced<T>(
+    span: tracing::Span,
+    call: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    use tracing::Instrument;
+
+    let started = std::time::Instant::now();
+    let result = call.instrument(span.clone()).await;
+    span.record(
+        "outcome",
+        if result.is_ok() { "success" } else { "failure" },
+    );
+    span.record("duration_ms", started.elapsed().as_millis() as u64);
+    result
+}
+
+/// Record the round-trip time of a REST request started at `started`.
+#[inline(always)]
+pub(crate) fn http_request<T>(method: &'static str, started: Started, result: &Result<T>) {
+    #[cfg(feature = "metrics")]
+    ::metrics::histogram!(
+        HTTP_REQUEST_DURATION,
+        "method" => method,
+        "outcome" => outcome(result)
+    )
+    .record(started.elapsed());
+    #[cfg(not(feature = "metrics"))]
+    let _ = (method, started, result);
+}
+
+/// Record a WebSocket connection entering `state`.
+#[cfg(feature = "websocket")]
+#[inline(always)]
+pub(crate) fn ws_state(state: ConnectionState) {
+    #[cfg(feature = "metrics")]
+    {
+        let state = match state {
+            ConnectionState::Disconnected => "disconnected",
+            ConnectionState::Connecting => "connecting",
+            ConnectionState::Connected => "connected",
+            ConnectionState::Reconnecting => "reconnecting",
+            ConnectionState::Closed => "closed",
+        };
+        ::metrics::counter!(WS_STATE_TRANSITIONS_TOTAL, "state" => state).increment(1);
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = state;
+}
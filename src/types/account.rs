@@ -3,6 +3,8 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::types::TokenMetadata;
+
 /// Account balance information from /api/v1/wallet/balance
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -43,13 +45,93 @@ impl Balance {
         })
     }
 
-    /// Get total balance as Decimal (converted from wei)
+    /// Get total (locked + unlocked) balance as Decimal (converted from wei)
+    ///
+    /// A missing side counts as zero; returns `None` only when both are missing.
     pub fn total_decimal(&self, decimals: u32) -> Option<Result<Decimal, rust_decimal::Error>> {
-        self.locked.as_ref().map(|locked| {
-            let total = locked.parse::<Decimal>()?;
-            Ok(total / Decimal::from(10u64.pow(decimals)))
+        if self.locked.is_none() && self.unlocked.is_none() {
+            return None;
+        }
+        Some(
+            self.locked_or_zero(decimals)
+                .and_then(|locked| Ok(locked + self.unlocked_or_zero(decimals)?)),
+        )
+    }
+
+    /// Attach the token symbol and convert both amounts using the token's decimals
+    ///
+    /// Fails with `NotFound` if the token is missing from `metadata` and with
+    /// `InvalidParameter` if an amount or the token's decimals cannot be parsed.
+    pub fn with_symbol(&self, metadata: &TokenMetadata) -> crate::Result<NamedBalance> {
+        let symbol = metadata
+            .token_id_symbol_map
+            .get(&self.token_id)
+            .ok_or_else(|| {
+                crate::AlphaSecError::not_found(format!("Token ID not found: {}", self.token_id))
+            })?;
+        let decimals = metadata
+            .token_id_decimal_map
+            .get(&self.token_id)
+            .and_then(|d| d.parse::<u32>().ok())
+            .ok_or_else(|| {
+                crate::AlphaSecError::invalid_parameter(format!(
+                    "Missing or invalid decimals for token ID: {}",
+                    self.token_id
+                ))
+            })?;
+        let invalid_amount = |e: rust_decimal::Error| {
+            crate::AlphaSecError::invalid_parameter(format!(
+                "Invalid balance amount for token ID {}: {}",
+                self.token_id, e
+            ))
+        };
+
+        Ok(NamedBalance {
+            symbol: symbol.clone(),
+            token_id: self.token_id.clone(),
+            locked: self.locked_or_zero(decimals).map_err(invalid_amount)?,
+            unlocked: self.unlocked_or_zero(decimals).map_err(invalid_amount)?,
         })
     }
+
+    fn locked_or_zero(&self, decimals: u32) -> Result<Decimal, rust_decimal::Error> {
+        self.locked_decimal(decimals).unwrap_or(Ok(Decimal::ZERO))
+    }
+
+    fn unlocked_or_zero(&self, decimals: u32) -> Result<Decimal, rust_decimal::Error> {
+        self.available_decimal(decimals)
+            .unwrap_or(Ok(Decimal::ZERO))
+    }
+}
+
+impl Balances {
+    /// Resolve every balance to a [`NamedBalance`], see [`Balance::with_symbol`]
+    pub fn named(&self, metadata: &TokenMetadata) -> crate::Result<Vec<NamedBalance>> {
+        self.balances
+            .iter()
+            .map(|balance| balance.with_symbol(metadata))
+            .collect()
+    }
+}
+
+/// Balance resolved against token metadata, with amounts in whole-token units
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedBalance {
+    /// L2 token symbol
+    pub symbol: String,
+    /// Token ID
+    pub token_id: String,
+    /// Locked balance
+    pub locked: Decimal,
+    /// Unlocked balance
+    pub unlocked: Decimal,
+}
+
+impl NamedBalance {
+    /// Locked plus unlocked balance
+    pub fn total(&self) -> Decimal {
+        self.locked + self.unlocked
+    }
 }
 
 /// Session information from /api/v1/wallet/session
@@ -74,6 +156,115 @@ impl Session {
         use chrono::{TimeZone, Utc};
         Utc.timestamp_millis_opt(self.expiry as i64).single()
     }
+
+    /// Whether the session has expired at `now_ms` (milliseconds since Unix epoch)
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        self.expiry <= now_ms
+    }
+
+    /// Whether the session is applied and has not expired at `now_ms`
+    pub fn is_active(&self, now_ms: u64) -> bool {
+        self.applied && !self.is_expired(now_ms)
+    }
+}
+
+/// Volume-based fee tier of an account from /api/v1/wallet/fee
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeTier {
+    /// Tier name (e.g. `"VIP 1"`)
+    pub tier: String,
+    /// Effective maker fee in basis points (as string)
+    pub maker_fee_bps: String,
+    /// Effective taker fee in basis points (as string)
+    pub taker_fee_bps: String,
+}
+
+impl FeeTier {
+    /// Maker fee as a fraction of the notional (10 bps is `0.001`)
+    pub fn maker_rate(&self) -> crate::Result<Decimal> {
+        Self::rate("maker", &self.maker_fee_bps)
+    }
+
+    /// Taker fee as a fraction of the notional
+    pub fn taker_rate(&self) -> crate::Result<Decimal> {
+        Self::rate("taker", &self.taker_fee_bps)
+    }
+
+    /// The higher of the maker and taker rates, as [`crate::types::OrderCost`] applies
+    /// to an order that may or may not rest
+    pub fn fee_rate(&self) -> crate::Result<Decimal> {
+        Ok(self.maker_rate()?.max(self.taker_rate()?))
+    }
+
+    fn rate(name: &str, bps: &str) -> crate::Result<Decimal> {
+        bps.trim()
+            .parse::<Decimal>()
+            .map(|bps| bps / Decimal::from(10_000))
+            .map_err(|_| {
+                crate::AlphaSecError::invalid_parameter(format!(
+                    "Invalid {} fee in basis points: {}",
+                    name, bps
+                ))
+            })
+    }
+}
+
+/// Outcome of a session create/update/delete, see [`crate::Agent::create_session`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAck {
+    /// Session ID (empty after a delete unless the server reports it)
+    pub session_id: String,
+    /// Session wallet address
+    pub session_address: String,
+    /// Session expiry timestamp (milliseconds since Unix epoch), 0 after a delete
+    pub expiry: u64,
+    /// Hash of the session transaction, if the server returned one
+    pub tx_hash: Option<String>,
+}
+
+impl SessionAck {
+    /// Read the `result` of a session operation response
+    ///
+    /// The server answers either with the transaction hash as a bare string or with an
+    /// object (`sessionId`/`name`, `sessionAddress`, `expiry`, `txHash`); fields it does
+    /// not report are taken from `requested`, the values the request was signed with.
+    pub(crate) fn from_result(
+        result: Option<&serde_json::Value>,
+        requested: SessionAck,
+    ) -> crate::Result<Self> {
+        use serde_json::Value;
+
+        let text = |obj: &serde_json::Map<String, Value>, keys: &[&str]| {
+            keys.iter()
+                .find_map(|k| obj.get(*k).and_then(Value::as_str))
+                .map(str::to_string)
+        };
+        match result {
+            None | Some(Value::Null) => Ok(requested),
+            Some(Value::String(tx_hash)) => Ok(Self {
+                tx_hash: Some(tx_hash.clone()),
+                ..requested
+            }),
+            Some(Value::Object(obj)) => Ok(Self {
+                session_id: text(obj, &["sessionId", "name"]).unwrap_or(requested.session_id),
+                session_address: text(obj, &["sessionAddress"])
+                    .unwrap_or(requested.session_address),
+                expiry: match obj.get("expiry") {
+                    Some(Value::Number(n)) => n.as_u64(),
+                    Some(Value::String(s)) => s.parse().ok(),
+                    _ => None,
+                }
+                .unwrap_or(requested.expiry),
+                tx_hash: text(obj, &["txHash", "hash"]).or(requested.tx_hash),
+            }),
+            Some(other) => Err(crate::AlphaSecError::api(
+                500,
+                format!("Invalid session response: {}", other),
+            )),
+        }
+    }
 }
 
 /// Transfer record from /api/v1/wallet/transfer
@@ -112,9 +303,144 @@ impl Transfer {
         let amount = self.amount.parse::<Decimal>()?;
         Ok(amount / Decimal::from(10u64.pow(decimals)))
     }
+
+    /// [`Self::tx_type`] parsed into a [`TransferType`]
+    pub fn tx_type_enum(&self) -> TransferType {
+        TransferType::parse(&self.tx_type)
+    }
+
+    /// Whether this is an L1 → L2 deposit
+    pub fn is_deposit(&self) -> bool {
+        self.tx_type_enum() == TransferType::Deposit
+    }
+
+    /// Whether this is an L2 → L1 withdrawal
+    pub fn is_withdrawal(&self) -> bool {
+        self.tx_type_enum() == TransferType::Withdrawal
+    }
+
+    /// Whether this is a transfer between accounts (native value or token)
+    pub fn is_transfer(&self) -> bool {
+        matches!(
+            self.tx_type_enum(),
+            TransferType::ValueTransfer | TransferType::TokenTransfer
+        )
+    }
+}
+
+/// Kind of a [`Transfer`], parsed from its `txType`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TransferType {
+    /// Native token sent between accounts ("Value Transfer")
+    ValueTransfer,
+    /// Token sent between accounts ("Token Transfer")
+    TokenTransfer,
+    /// Deposit from L1 ("Deposit")
+    Deposit,
+    /// Withdrawal to L1 ("Withdraw" or "Withdrawal")
+    Withdrawal,
+    /// Any other type, as received
+    Unknown(String),
+}
+
+impl TransferType {
+    /// Parse a `txType` string, ignoring case, surrounding whitespace and whether words
+    /// are separated by spaces, `_` or `-`
+    pub fn parse(tx_type: &str) -> Self {
+        let normalized = tx_type
+            .split(|c: char| c.is_whitespace() || c == '_' || c == '-')
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        match normalized.as_str() {
+            "value transfer" => Self::ValueTransfer,
+            "token transfer" => Self::TokenTransfer,
+            "deposit" => Self::Deposit,
+            "withdraw" | "withdrawal" => Self::Withdrawal,
+            _ => Self::Unknown(tx_type.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for TransferType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransferType::ValueTransfer => write!(f, "Value Transfer"),
+            TransferType::TokenTransfer => write!(f, "Token Transfer"),
+            TransferType::Deposit => write!(f, "Deposit"),
+            TransferType::Withdrawal => write!(f, "Withdrawal"),
+            TransferType::Unknown(tx_type) => write!(f, "{}", tx_type),
+        }
+    }
+}
+
+/// Stage of an L2 → L1 withdrawal, from /api/v1/wallet/withdraw/{id}
+///
+/// Withdrawals start on AlphaSec and settle on Kaia once the L2 state containing them
+/// is confirmed there; the stages below follow that order. Case is ignored, and a
+/// stage this SDK does not know becomes [`WithdrawalStatus::Other`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum WithdrawalStatus {
+    /// Withdrawal transaction accepted on L2
+    Initiated,
+    /// L2 state containing the withdrawal has been posted to L1
+    Proven,
+    /// Confirmation period has passed and the funds can be claimed on L1
+    Claimable,
+    /// Funds have been released on L1
+    Completed,
+    /// Any other stage, as received
+    Other(String),
+}
+
+impl WithdrawalStatus {
+    /// Parse a status string, ignoring case
+    pub fn parse(status: &str) -> Self {
+        match crate::types::orders::enum_key(status).as_str() {
+            "INITIATED" => Self::Initiated,
+            "PROVEN" => Self::Proven,
+            "CLAIMABLE" => Self::Claimable,
+            "COMPLETED" => Self::Completed,
+            _ => Self::Other(status.to_string()),
+        }
+    }
+
+    /// Whether the withdrawal has reached its final stage
+    pub fn is_completed(&self) -> bool {
+        *self == WithdrawalStatus::Completed
+    }
+}
+
+impl From<String> for WithdrawalStatus {
+    fn from(status: String) -> Self {
+        Self::parse(&status)
+    }
+}
+
+impl From<WithdrawalStatus> for String {
+    fn from(status: WithdrawalStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl std::fmt::Display for WithdrawalStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WithdrawalStatus::Initiated => write!(f, "INITIATED"),
+            WithdrawalStatus::Proven => write!(f, "PROVEN"),
+            WithdrawalStatus::Claimable => write!(f, "CLAIMABLE"),
+            WithdrawalStatus::Completed => write!(f, "COMPLETED"),
+            WithdrawalStatus::Other(status) => write!(f, "{}", status),
+        }
+    }
 }
 
 /// Query parameters for transfer history
+///
+/// Sent as the `address`, `tokenId`, `fromMsec`, `toMsec` and `limit` query parameters;
+/// unset fields are left out.
 #[derive(Debug, Clone, Default)]
 pub struct TransferHistoryQuery {
     /// Wallet address to query (required)
@@ -125,6 +451,283 @@ pub struct TransferHistoryQuery {
     pub from_msec: Option<i64>,
     /// End timestamp in milliseconds (optional)
     pub to_msec: Option<i64>,
-    /// Maximum records to return (default: 100, max: 500)
+    /// Maximum records to return (server default 100, capped at 500)
     pub limit: Option<u32>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Token;
+    use crate::AlphaSecError;
+
+    fn requested_session() -> SessionAck {
+        SessionAck {
+            session_id: "bot".to_string(),
+            session_address: "0xabc".to_string(),
+            expiry: 1_700_000_000_000,
+            tx_hash: None,
+        }
+    }
+
+    #[test]
+    fn fee_tier_converts_basis_points_to_rates() {
+        let tier: FeeTier = serde_json::from_value(serde_json::json!({
+            "tier": "VIP 2",
+            "makerFeeBps": "2.5",
+            "takerFeeBps": "7",
+        }))
+        .unwrap();
+        assert_eq!(tier.tier, "VIP 2");
+        assert_eq!(tier.maker_rate().unwrap(), Decimal::new(25, 5));
+        assert_eq!(tier.taker_rate().unwrap(), Decimal::new(7, 4));
+        assert_eq!(tier.fee_rate().unwrap(), Decimal::new(7, 4));
+
+        let broken = FeeTier {
+            taker_fee_bps: "seven".to_string(),
+            ..tier
+        };
+        assert!(matches!(
+            broken.fee_rate(),
+            Err(AlphaSecError::InvalidParameter(msg)) if msg.contains("taker")
+        ));
+    }
+
+    #[test]
+    fn transfer_types_parse_regardless_of_spacing_and_case() {
+        let cases = [
+            ("Value Transfer", TransferType::ValueTransfer),
+            ("  value   transfer ", TransferType::ValueTransfer),
+            ("Token Transfer", TransferType::TokenTransfer),
+            ("TOKEN_TRANSFER", TransferType::TokenTransfer),
+            ("Deposit", TransferType::Deposit),
+            ("withdraw", TransferType::Withdrawal),
+            ("Withdrawal", TransferType::Withdrawal),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(TransferType::parse(raw), expected, "{raw:?}");
+        }
+        assert_eq!(
+            TransferType::parse("Contract Call"),
+            TransferType::Unknown("Contract Call".to_string())
+        );
+        assert_eq!(TransferType::TokenTransfer.to_string(), "Token Transfer");
+    }
+
+    #[test]
+    fn transfer_helpers_classify_the_tx_type() {
+        let transfer: Transfer = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "fromAddress": "0xa",
+            "toAddress": "0xb",
+            "txType": "Token Transfer",
+            "tokenId": "2",
+            "amount": "1000000",
+            "status": "Success",
+            "timestamp": 1700000000000i64,
+            "hash": "0xh"
+        }))
+        .unwrap();
+        assert!(transfer.is_transfer());
+        assert!(!transfer.is_deposit());
+        assert!(!transfer.is_withdrawal());
+
+        let deposit = Transfer {
+            tx_type: " deposit".to_string(),
+            ..transfer.clone()
+        };
+        assert!(deposit.is_deposit() && !deposit.is_transfer());
+        let withdrawal = Transfer {
+            tx_type: "Withdraw".to_string(),
+            ..transfer.clone()
+        };
+        assert!(withdrawal.is_withdrawal());
+        let other = Transfer {
+            tx_type: "Fee".to_string(),
+            ..transfer
+        };
+        assert!(!other.is_transfer() && !other.is_deposit() && !other.is_withdrawal());
+    }
+
+    #[test]
+    fn session_ack_reads_a_bare_tx_hash_or_an_object() {
+        let ack =
+            SessionAck::from_result(Some(&serde_json::json!("0xtx")), requested_session()).unwrap();
+        assert_eq!(ack.tx_hash.as_deref(), Some("0xtx"));
+        assert_eq!(ack.session_id, "bot");
+        assert_eq!(ack.session_address, "0xabc");
+        assert_eq!(ack.expiry, 1_700_000_000_000);
+
+        let created = serde_json::json!({
+            "sessionId": "bot-2",
+            "sessionAddress": "0xdef",
+            "expiry": "1700000600000",
+            "txHash": "0xcreated"
+        });
+        let ack = SessionAck::from_result(Some(&created), requested_session()).unwrap();
+        assert_eq!(
+            ack,
+            SessionAck {
+                session_id: "bot-2".to_string(),
+                session_address: "0xdef".to_string(),
+                expiry: 1_700_000_600_000,
+                tx_hash: Some("0xcreated".to_string()),
+            }
+        );
+
+        // Fields the server leaves out come from the request
+        let partial = serde_json::json!({"hash": "0xpartial"});
+        let ack = SessionAck::from_result(Some(&partial), requested_session()).unwrap();
+        assert_eq!(ack.tx_hash.as_deref(), Some("0xpartial"));
+        assert_eq!(ack.session_address, "0xabc");
+
+        assert_eq!(
+            SessionAck::from_result(None, requested_session()).unwrap(),
+            requested_session()
+        );
+        assert!(
+            SessionAck::from_result(Some(&serde_json::json!([1])), requested_session()).is_err()
+        );
+    }
+
+    fn balance(token_id: &str, locked: Option<&str>, unlocked: Option<&str>) -> Balance {
+        Balance {
+            token_id: token_id.to_string(),
+            locked: locked.map(str::to_string),
+            unlocked: unlocked.map(str::to_string),
+        }
+    }
+
+    fn metadata() -> TokenMetadata {
+        let token = |token_id: &str, l2_symbol: &str, decimals: u32| Token {
+            token_id: token_id.to_string(),
+            symbol: l2_symbol.to_string(),
+            l2_symbol: l2_symbol.to_string(),
+            l1_address: format!("0x{:0>40}", token_id),
+            decimals,
+            is_active: true,
+        };
+        TokenMetadata::from_tokens(&[token("1", "KAIA", 18), token("2", "USDT", 6)])
+    }
+
+    #[test]
+    fn total_decimal_adds_locked_and_unlocked() {
+        let b = balance("2", Some("1500000"), Some("2250000"));
+        assert_eq!(
+            b.total_decimal(6).unwrap().unwrap(),
+            "3.75".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn total_decimal_treats_a_missing_side_as_zero() {
+        let only_unlocked = balance("2", None, Some("2000000"));
+        assert_eq!(
+            only_unlocked.total_decimal(6).unwrap().unwrap(),
+            Decimal::from(2)
+        );
+        let only_locked = balance("2", Some("1000000"), None);
+        assert_eq!(
+            only_locked.total_decimal(6).unwrap().unwrap(),
+            Decimal::from(1)
+        );
+        assert!(balance("2", None, None).total_decimal(6).is_none());
+        assert!(balance("2", Some("1"), Some("x"))
+            .total_decimal(6)
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn with_symbol_uses_the_tokens_decimals() {
+        let md = metadata();
+        let kaia = balance("1", Some("500000000000000000"), Some("1000000000000000000"))
+            .with_symbol(&md)
+            .unwrap();
+        assert_eq!(kaia.symbol, "KAIA");
+        assert_eq!(kaia.locked, "0.5".parse().unwrap());
+        assert_eq!(kaia.unlocked, Decimal::from(1));
+        assert_eq!(kaia.total(), "1.5".parse().unwrap());
+
+        let usdt = balance("2", None, Some("1250000"))
+            .with_symbol(&md)
+            .unwrap();
+        assert_eq!(
+            (usdt.locked, usdt.unlocked),
+            (Decimal::ZERO, "1.25".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn with_symbol_rejects_unknown_tokens_and_bad_amounts() {
+        let md = metadata();
+        assert!(matches!(
+            balance("9", None, Some("1")).with_symbol(&md),
+            Err(AlphaSecError::NotFound(_))
+        ));
+        assert!(matches!(
+            balance("1", Some("abc"), None).with_symbol(&md),
+            Err(AlphaSecError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn named_resolves_every_balance_in_order() {
+        let balances = Balances {
+            balances: vec![
+                balance("2", None, Some("1000000")),
+                balance("1", None, None),
+            ],
+            block_number: 1,
+        };
+        let named = balances.named(&metadata()).unwrap();
+        let symbols: Vec<&str> = named.iter().map(|b| b.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["USDT", "KAIA"]);
+        assert_eq!(named[1].total(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn withdrawal_status_deserializes_every_stage() {
+        for (raw, status) in [
+            ("INITIATED", WithdrawalStatus::Initiated),
+            ("PROVEN", WithdrawalStatus::Proven),
+            ("CLAIMABLE", WithdrawalStatus::Claimable),
+            ("COMPLETED", WithdrawalStatus::Completed),
+        ] {
+            let parsed: WithdrawalStatus = serde_json::from_value(serde_json::json!(raw)).unwrap();
+            assert_eq!(parsed, status);
+            assert_eq!(parsed.to_string(), raw);
+            let lower: WithdrawalStatus =
+                serde_json::from_value(serde_json::json!(raw.to_lowercase())).unwrap();
+            assert_eq!(lower, status);
+        }
+        // Stages added later do not break parsing
+        let pending: WithdrawalStatus =
+            serde_json::from_value(serde_json::json!("PENDING")).unwrap();
+        assert_eq!(pending, WithdrawalStatus::Other("PENDING".to_string()));
+        assert_eq!(pending.to_string(), "PENDING");
+        assert!(WithdrawalStatus::Completed.is_completed());
+        assert!(!WithdrawalStatus::Claimable.is_completed());
+    }
+
+    #[test]
+    fn sessions_expire_at_their_expiry() {
+        let session = Session {
+            name: "bot".to_string(),
+            session_address: "0xabc".to_string(),
+            owner_address: "0xdef".to_string(),
+            expiry: 1_000,
+            applied: true,
+        };
+        assert!(!session.is_expired(999));
+        assert!(session.is_active(999));
+        assert!(session.is_expired(1_000));
+        assert!(!session.is_active(1_000));
+
+        let pending = Session {
+            applied: false,
+            ..session
+        };
+        assert!(!pending.is_active(999));
+    }
+}
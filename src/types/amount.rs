@@ -0,0 +1,86 @@
+//! Human-readable token amounts accepted by the transfer, deposit and withdraw calls
+
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+
+use crate::error::{AlphaSecError, Result};
+
+/// A token amount in trading units, as a [`Decimal`] or a decimal string such as
+/// `"1234.000000000000000001"`.
+///
+/// Strings are parsed exactly (no float round-trip) and must be non-negative decimals;
+/// scientific notation is rejected.
+pub trait IntoAmount {
+    /// Convert into a [`Decimal`] amount.
+    fn into_amount(self) -> Result<Decimal>;
+}
+
+impl IntoAmount for Decimal {
+    fn into_amount(self) -> Result<Decimal> {
+        check_non_negative(self)
+    }
+}
+
+impl IntoAmount for &str {
+    fn into_amount(self) -> Result<Decimal> {
+        let value = Decimal::from_str(self.trim()).map_err(|e| {
+            AlphaSecError::invalid_parameter(format!("invalid amount '{}': {}", self, e))
+        })?;
+        check_non_negative(value)
+    }
+}
+
+impl IntoAmount for &String {
+    fn into_amount(self) -> Result<Decimal> {
+        self.as_str().into_amount()
+    }
+}
+
+impl IntoAmount for String {
+    fn into_amount(self) -> Result<Decimal> {
+        self.as_str().into_amount()
+    }
+}
+
+fn check_non_negative(value: Decimal) -> Result<Decimal> {
+    if value.is_sign_negative() && !value.is_zero() {
+        return Err(AlphaSecError::invalid_parameter(format!(
+            "amount must be non-negative, got {}",
+            value
+        )));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_amounts_parse_exactly() {
+        let value = "1234.000000000000000001".into_amount().unwrap();
+        assert_eq!(value.to_string(), "1234.000000000000000001");
+        assert_eq!(value.scale(), 18);
+        assert_eq!(
+            " 1.50 ".to_string().into_amount().unwrap().to_string(),
+            "1.50"
+        );
+        assert_eq!(
+            Decimal::new(15, 1).into_amount().unwrap(),
+            Decimal::new(15, 1)
+        );
+    }
+
+    #[test]
+    fn malformed_or_negative_amounts_are_rejected() {
+        for bad in ["", "abc", "1e3", "-1", "1.2.3"] {
+            assert!(
+                matches!(bad.into_amount(), Err(AlphaSecError::InvalidParameter(_))),
+                "{bad:?} should be rejected"
+            );
+        }
+        assert!(Decimal::NEGATIVE_ONE.into_amount().is_err());
+        assert!("-0".into_amount().is_ok());
+    }
+}
@@ -47,3 +47,44 @@ where
         }
     }
 }
+
+/// Protocol version and optional features a server reports at `/api/v1/meta`, see
+/// `Agent::server_capabilities`
+///
+/// Servers that predate the endpoint report nothing (`features` is `None`), and every
+/// feature is then assumed to be available.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    /// Protocol version (e.g. `"1.4"`), if reported
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Names of the optional features the server offers, if reported
+    #[serde(default)]
+    pub features: Option<Vec<String>>,
+}
+
+impl Capabilities {
+    /// One-cancels-other order pairs, see `Agent::place_oco`
+    pub const OCO: &'static str = "oco";
+    /// Several orders in one request
+    pub const BATCH_ORDERS: &'static str = "batchOrders";
+    /// Reduce-only orders
+    pub const REDUCE_ONLY: &'static str = "reduceOnly";
+
+    /// Whether the server reported its features at all
+    pub fn is_reported(&self) -> bool {
+        self.features.is_some()
+    }
+
+    /// Whether `feature` (e.g. [`Self::OCO`]) is available; case is ignored, and every
+    /// feature is available on a server that does not report them
+    pub fn supports(&self, feature: &str) -> bool {
+        match &self.features {
+            Some(features) => features
+                .iter()
+                .any(|offered| offered.eq_ignore_ascii_case(feature)),
+            None => true,
+        }
+    }
+}
@@ -46,11 +46,14 @@ pub mod l1_contracts {
 }
 
 /// Chain IDs for different networks
+///
+/// L2 transactions (orders, transfers, withdrawals) are signed with the `ALPHASEC_*`
+/// IDs; L1 deposits and the session EIP-712 domain use the `KAIA_*` IDs.
 pub mod chain_ids {
-    /// AlphaSec L2 chain ID
+    /// AlphaSec L2 testnet chain ID (settles to Kaia Kairos)
     pub const ALPHASEC_TESTNET_CHAIN_ID: u64 = 41001;
 
-    /// AlphaSec L2 MAINNET CHAIN ID
+    /// AlphaSec L2 mainnet chain ID (settles to Kaia mainnet)
     pub const ALPHASEC_MAINNET_CHAIN_ID: u64 = 48217;
 
     /// Kaia mainnet chain ID
@@ -96,6 +99,9 @@ pub mod dex_commands {
     /// Stop order command (alphasec style)
     pub const DEX_COMMAND_STOP_ORDER: u8 = 0x25;
 
+    /// One-cancels-other order pair command (alphasec style)
+    pub const DEX_COMMAND_OCO_ORDER: u8 = 0x26;
+
     // Perp commands
     /// Perp deposit command (Spot→Perp)
     pub const DEX_COMMAND_PERP_DEPOSIT: u8 = 0x12;
@@ -146,6 +152,12 @@ pub mod endpoints {
     /// AlphaSec kairos L2 RPC URL
     pub const ALPHASEC_KAIROS_URL: &str = "https://kairos-rpc.alphasec.trade";
 
+    /// AlphaSec WebSocket testnet URL
+    pub const ALPHASEC_WS_TESTNET_URL: &str = "wss://api-testnet.alphasec.trade/ws";
+
+    /// AlphaSec WebSocket mainnet URL
+    pub const ALPHASEC_WS_MAINNET_URL: &str = "wss://api.alphasec.trade/ws";
+
     /// Kaia mainnet RPC URL
     pub const KAIA_MAINNET_URL: &str = "https://public-en.node.kaia.io";
 
@@ -166,6 +178,27 @@ pub mod gas {
 
     /// Default max priority fee per gas (0 for AlphaSec L2)
     pub const DEFAULT_MAX_PRIORITY_FEE_PER_GAS: u64 = 0;
+
+    /// Default factor applied to the node's gas estimate for L1 deposit transactions
+    pub const DEFAULT_GAS_LIMIT_MULTIPLIER: f64 = 1.2;
+
+    /// Fixed size term of the retryable-ticket submission fee, in bytes
+    pub const RETRYABLE_SUBMISSION_BASE_BYTES: u64 = 1400;
+
+    /// Fee weight of each calldata byte of a retryable ticket
+    pub const RETRYABLE_SUBMISSION_BYTE_WEIGHT: u64 = 6;
+
+    /// Calldata size assumed for the L2 side of an ERC20 deposit, in bytes
+    pub const DEPOSIT_RETRYABLE_DATA_BYTES: u64 = 1024;
+
+    /// Longest an ERC20 deposit waits for its approve transaction to be mined, in seconds
+    pub const APPROVE_RECEIPT_TIMEOUT_SECS: u64 = 300;
+
+    /// L2 gas limit of the retryable ticket created by an ERC20 deposit
+    pub const DEPOSIT_L2_GAS_LIMIT: u64 = 1_000_000;
+
+    /// L2 gas price of the retryable ticket created by an ERC20 deposit
+    pub const DEPOSIT_L2_GAS_PRICE: u64 = 1_000_000;
 }
 
 /// EIP-712 domain constants
@@ -193,7 +226,7 @@ pub mod abi {
         }
     ]"#;
 
-    /// ERC20 ABI for token operations
+    /// ERC20 ABI for token operations and balance reads
     pub const ERC20_ABI: &str = r#"[
         {
             "inputs": [
@@ -214,6 +247,20 @@ pub mod abi {
             "outputs": [{"internalType": "uint256", "name": "", "type": "uint256"}],
             "stateMutability": "view",
             "type": "function"
+        },
+        {
+            "inputs": [{"internalType": "address", "name": "account", "type": "address"}],
+            "name": "balanceOf",
+            "outputs": [{"internalType": "uint256", "name": "", "type": "uint256"}],
+            "stateMutability": "view",
+            "type": "function"
+        },
+        {
+            "inputs": [],
+            "name": "decimals",
+            "outputs": [{"internalType": "uint8", "name": "", "type": "uint8"}],
+            "stateMutability": "view",
+            "type": "function"
         }
     ]"#;
 
@@ -246,7 +293,7 @@ pub mod abi {
         }
     ]"#;
 
-    /// L2 ERC20 Router ABI for token withdrawals
+    /// L2 ERC20 Router ABI for token withdrawals and L2 token lookups
     pub const L2_ERC20_ROUTER_ABI: &str = r#"[
         {
             "inputs": [
@@ -259,6 +306,13 @@ pub mod abi {
             "outputs": [{"internalType": "bytes", "name": "", "type": "bytes"}],
             "stateMutability": "nonpayable",
             "type": "function"
+        },
+        {
+            "inputs": [{"internalType": "address", "name": "l1ERC20", "type": "address"}],
+            "name": "calculateL2TokenAddress",
+            "outputs": [{"internalType": "address", "name": "", "type": "address"}],
+            "stateMutability": "view",
+            "type": "function"
         }
     ]"#;
 }
@@ -5,6 +5,8 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::orders::{OrderMode, OrderType};
+
 /// Token information from /api/v1/market/tokens
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -54,6 +56,101 @@ pub struct Market {
     pub taker_fee: String,
     /// Maker fee
     pub maker_fee: String,
+    /// Smallest order quantity in the base token
+    #[serde(default)]
+    pub min_qty: Option<String>,
+    /// Smallest order value in the quote token
+    #[serde(default)]
+    pub min_notional: Option<String>,
+    /// Price increment
+    #[serde(default)]
+    pub tick_size: Option<String>,
+    /// Base quantity increment
+    #[serde(default)]
+    pub step_size: Option<String>,
+}
+
+impl Market {
+    /// Check an order's price and quantity against this market's trading rules.
+    ///
+    /// `quantity` is in the base token for [`OrderMode::Base`] and in the quote token for
+    /// [`OrderMode::Quote`]; market orders skip the price checks. Rules the server did
+    /// not report (absent or zero) are not enforced.
+    pub fn validate_order(
+        &self,
+        price: Decimal,
+        quantity: Decimal,
+        order_type: OrderType,
+        order_mode: OrderMode,
+    ) -> crate::Result<()> {
+        let is_limit = order_type == OrderType::Limit;
+        if let Some(tick) = self.rule("tick size", &self.tick_size)? {
+            if is_limit && !(price % tick).is_zero() {
+                return Err(self.violation(format!(
+                    "price {} is not a multiple of tick size {}",
+                    price, tick
+                )));
+            }
+        }
+        if order_mode == OrderMode::Base {
+            if let Some(step) = self.rule("step size", &self.step_size)? {
+                if !(quantity % step).is_zero() {
+                    return Err(self.violation(format!(
+                        "quantity {} is not a multiple of step size {}",
+                        quantity, step
+                    )));
+                }
+            }
+            if let Some(min) = self.rule("min quantity", &self.min_qty)? {
+                if quantity < min {
+                    return Err(self.violation(format!(
+                        "quantity {} is below the minimum of {}",
+                        quantity, min
+                    )));
+                }
+            }
+        }
+        // A base-mode market order's value is only known once it fills.
+        let notional = match order_mode {
+            OrderMode::Base if is_limit => Some(price * quantity),
+            OrderMode::Base => None,
+            OrderMode::Quote => Some(quantity),
+        };
+        if let (Some(notional), Some(min)) =
+            (notional, self.rule("min notional", &self.min_notional)?)
+        {
+            if notional < min {
+                return Err(self.violation(format!(
+                    "order value {} is below the minimum of {}",
+                    notional, min
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Price increment, or `None` if the server did not report one
+    pub fn tick_size_decimal(&self) -> crate::Result<Option<Decimal>> {
+        self.rule("tick size", &self.tick_size)
+    }
+
+    /// Parse a trading rule, treating an absent or zero value as "not enforced".
+    fn rule(&self, name: &str, value: &Option<String>) -> crate::Result<Option<Decimal>> {
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        let parsed = value.parse::<Decimal>().map_err(|_| {
+            crate::AlphaSecError::invalid_parameter(format!(
+                "Invalid {} for market {}: {}",
+                name, self.market_id, value
+            ))
+        })?;
+        Ok((parsed > Decimal::ZERO).then_some(parsed))
+    }
+
+    fn violation(&self, message: String) -> crate::AlphaSecError {
+        crate::AlphaSecError::invalid_parameter(format!("{}: {}", self.ticker, message))
+    }
 }
 
 /// Single level in orderbook depth (REST)
@@ -113,6 +210,89 @@ pub struct Ticker {
     pub quote_volume_24h: String,
 }
 
+/// Common accessors shared by the REST [`Ticker`] and the WebSocket ticker entry, so a
+/// price cache can accept either source.
+pub trait TickerData {
+    /// Market ID (e.g., "1_2")
+    fn market_id(&self) -> &str;
+    /// Parse the market ID, see [`MarketId`]
+    fn parsed_market_id(&self) -> crate::Result<MarketId> {
+        self.market_id().parse()
+    }
+    /// Current price as received
+    fn price(&self) -> &str;
+    /// 24h open price as received
+    fn open_24h(&self) -> &str;
+    /// 24h high price as received
+    fn high_24h(&self) -> &str;
+    /// 24h low price as received
+    fn low_24h(&self) -> &str;
+    /// 24h base volume as received
+    fn volume_24h(&self) -> &str;
+    /// 24h quote volume as received
+    fn quote_volume_24h(&self) -> &str;
+
+    /// Parse price as Decimal
+    fn price_decimal(&self) -> Result<Decimal, rust_decimal::Error> {
+        use std::str::FromStr;
+        Decimal::from_str(self.price())
+    }
+
+    /// Parse 24h open price as Decimal
+    fn open_24h_decimal(&self) -> Result<Decimal, rust_decimal::Error> {
+        use std::str::FromStr;
+        Decimal::from_str(self.open_24h())
+    }
+
+    /// Parse 24h high price as Decimal
+    fn high_24h_decimal(&self) -> Result<Decimal, rust_decimal::Error> {
+        use std::str::FromStr;
+        Decimal::from_str(self.high_24h())
+    }
+
+    /// Parse 24h low price as Decimal
+    fn low_24h_decimal(&self) -> Result<Decimal, rust_decimal::Error> {
+        use std::str::FromStr;
+        Decimal::from_str(self.low_24h())
+    }
+
+    /// Parse 24h base volume as Decimal
+    fn volume_24h_decimal(&self) -> Result<Decimal, rust_decimal::Error> {
+        use std::str::FromStr;
+        Decimal::from_str(self.volume_24h())
+    }
+
+    /// Parse 24h quote volume as Decimal
+    fn quote_volume_24h_decimal(&self) -> Result<Decimal, rust_decimal::Error> {
+        use std::str::FromStr;
+        Decimal::from_str(self.quote_volume_24h())
+    }
+}
+
+impl TickerData for Ticker {
+    fn market_id(&self) -> &str {
+        &self.market_id
+    }
+    fn price(&self) -> &str {
+        &self.price
+    }
+    fn open_24h(&self) -> &str {
+        &self.open_24h
+    }
+    fn high_24h(&self) -> &str {
+        &self.high_24h
+    }
+    fn low_24h(&self) -> &str {
+        &self.low_24h
+    }
+    fn volume_24h(&self) -> &str {
+        &self.volume_24h
+    }
+    fn quote_volume_24h(&self) -> &str {
+        &self.quote_volume_24h
+    }
+}
+
 /// Trade information from /api/v1/market/trades
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -133,6 +313,106 @@ pub struct Trade {
     pub created_at: u64,
     /// Is buyer maker
     pub is_buyer_maker: bool,
+    /// Fee charged to the queried order (only present on per-order executions)
+    #[serde(default)]
+    pub fee: Option<String>,
+    /// Token ID the fee was charged in (only present on per-order executions)
+    #[serde(default)]
+    pub fee_token_id: Option<String>,
+}
+
+impl Trade {
+    /// Parse price as Decimal
+    pub fn price_decimal(&self) -> Result<Decimal, rust_decimal::Error> {
+        use std::str::FromStr;
+        Decimal::from_str(&self.price)
+    }
+
+    /// Parse quantity as Decimal
+    pub fn quantity_decimal(&self) -> Result<Decimal, rust_decimal::Error> {
+        use std::str::FromStr;
+        Decimal::from_str(&self.quantity)
+    }
+
+    /// Parse fee as Decimal (`Ok(None)` when the response carried no fee)
+    pub fn fee_decimal(&self) -> Result<Option<Decimal>, rust_decimal::Error> {
+        use std::str::FromStr;
+        self.fee.as_deref().map(Decimal::from_str).transpose()
+    }
+
+    /// Side of the aggressor, the order that took liquidity
+    ///
+    /// `is_buyer_maker = true` means the buy order was resting on the book and a sell
+    /// order crossed it, so the taker sold ([`TradeSide::Sell`]); `false` means the
+    /// taker bought ([`TradeSide::Buy`]). A taker buy lifts the ask, so it is the
+    /// upticking side.
+    pub fn taker_side(&self) -> TradeSide {
+        if self.is_buyer_maker {
+            TradeSide::Sell
+        } else {
+            TradeSide::Buy
+        }
+    }
+
+    /// Side of the resting order that provided liquidity, the opposite of
+    /// [`Self::taker_side`]
+    pub fn maker_side(&self) -> TradeSide {
+        match self.taker_side() {
+            TradeSide::Buy => TradeSide::Sell,
+            TradeSide::Sell => TradeSide::Buy,
+        }
+    }
+}
+
+/// Default page size when paging through the trade tape
+pub const TRADES_PAGE_LIMIT: u32 = 100;
+
+/// Price and volume summary of a set of trades, see [`crate::Agent::trade_stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TradeStats {
+    /// Volume-weighted average price (quote volume / base volume), `None` without volume
+    pub vwap: Option<Decimal>,
+    /// Sum of trade quantities, in the base token
+    pub total_base_volume: Decimal,
+    /// Sum of price × quantity, in the quote token
+    pub total_quote_volume: Decimal,
+    /// Number of trades
+    pub trade_count: usize,
+    /// Highest trade price, `None` without trades
+    pub high: Option<Decimal>,
+    /// Lowest trade price, `None` without trades
+    pub low: Option<Decimal>,
+}
+
+impl TradeStats {
+    /// Aggregate `trades`; no trades gives zero volumes and no prices
+    pub fn from_trades(trades: &[Trade]) -> crate::Result<Self> {
+        let mut stats = Self::default();
+        for trade in trades {
+            let invalid = |field: &str, value: &str| {
+                crate::AlphaSecError::api(
+                    500,
+                    format!("Invalid {} '{}' in trade {}", field, value, trade.trade_id),
+                )
+            };
+            let price = trade
+                .price_decimal()
+                .map_err(|_| invalid("price", &trade.price))?;
+            let quantity = trade
+                .quantity_decimal()
+                .map_err(|_| invalid("quantity", &trade.quantity))?;
+
+            stats.total_base_volume += quantity;
+            stats.total_quote_volume += price * quantity;
+            stats.trade_count += 1;
+            stats.high = Some(stats.high.map_or(price, |high| high.max(price)));
+            stats.low = Some(stats.low.map_or(price, |low| low.min(price)));
+        }
+        if !stats.total_base_volume.is_zero() {
+            stats.vwap = Some(stats.total_quote_volume / stats.total_base_volume);
+        }
+        Ok(stats)
+    }
 }
 
 /// Trade side
@@ -153,6 +433,76 @@ impl std::fmt::Display for TradeSide {
     }
 }
 
+/// Spot market ID, written `BASE_QUOTE` in token IDs on the wire (e.g. `"5_2"`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MarketId {
+    /// Token ID of the base token
+    pub base_token_id: u32,
+    /// Token ID of the quote token
+    pub quote_token_id: u32,
+}
+
+impl MarketId {
+    /// Market trading `base_token_id` against `quote_token_id`
+    pub fn new(base_token_id: u32, quote_token_id: u32) -> Self {
+        Self {
+            base_token_id,
+            quote_token_id,
+        }
+    }
+}
+
+impl std::str::FromStr for MarketId {
+    type Err = crate::AlphaSecError;
+
+    /// Parse `BASE_QUOTE`; anything but two unsigned token IDs joined by one `_` fails
+    /// with `InvalidParameter`
+    fn from_str(market_id: &str) -> crate::Result<Self> {
+        let invalid = |problem: String| {
+            crate::AlphaSecError::invalid_parameter(format!(
+                "Invalid market ID {:?}: {}. Expected format: BASE_QUOTE",
+                market_id, problem
+            ))
+        };
+        let parts: Vec<&str> = market_id.split('_').collect();
+        let [base, quote] = parts.as_slice() else {
+            return Err(invalid(format!(
+                "expected 2 token IDs, found {}",
+                parts.len()
+            )));
+        };
+        let token_id = |side: &str, id: &str| {
+            id.parse::<u32>()
+                .map_err(|_| invalid(format!("{} token ID {:?} is not a number", side, id)))
+        };
+        Ok(Self::new(
+            token_id("base", base)?,
+            token_id("quote", quote)?,
+        ))
+    }
+}
+
+impl std::fmt::Display for MarketId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}_{}", self.base_token_id, self.quote_token_id)
+    }
+}
+
+impl TryFrom<String> for MarketId {
+    type Error = crate::AlphaSecError;
+
+    fn try_from(market_id: String) -> crate::Result<Self> {
+        market_id.parse()
+    }
+}
+
+impl From<MarketId> for String {
+    fn from(market_id: MarketId) -> Self {
+        market_id.to_string()
+    }
+}
+
 /// Token metadata mapping helper
 #[derive(Debug, Clone)]
 pub struct TokenMetadata {
@@ -189,18 +539,77 @@ impl TokenMetadata {
         }
     }
 
+    /// Replace the decimals of the tokens in `overrides`, keyed by token ID or symbol
+    ///
+    /// A key that is a loaded token ID wins over a symbol of the same name. Keys matching
+    /// neither are taken as token IDs, so decimals can be supplied for a token the
+    /// fetched list lacks.
+    pub fn apply_decimal_overrides(&mut self, overrides: &HashMap<String, u8>) {
+        for (key, decimals) in overrides {
+            let token_id = if self.token_id_symbol_map.contains_key(key) {
+                key.clone()
+            } else {
+                self.symbol_token_id_map
+                    .get(key)
+                    .cloned()
+                    .unwrap_or_else(|| key.clone())
+            };
+            self.token_id_decimal_map
+                .insert(token_id, decimals.to_string());
+        }
+    }
+
+    /// Decimals of the token with `token_id`
+    pub fn token_decimals(&self, token_id: &str) -> crate::Result<u32> {
+        let decimals = self.token_id_decimal_map.get(token_id).ok_or_else(|| {
+            crate::AlphaSecError::not_found(format!("Token ID not found: {}", token_id))
+        })?;
+        decimals.parse().map_err(|_| {
+            crate::AlphaSecError::config(format!(
+                "Invalid decimals {:?} for token {}",
+                decimals, token_id
+            ))
+        })
+    }
+
+    /// Human amount of `raw`, an integer count of `token_id`'s base units
+    ///
+    /// For a 6-decimal token `"1500000"` is `1.5`. Fails with `InvalidParameter` if `raw`
+    /// is not an integer or the result does not fit a `Decimal` (28 significant digits).
+    pub fn humanize(&self, token_id: &str, raw: &str) -> crate::Result<Decimal> {
+        let decimals = self.token_decimals(token_id)?;
+        let invalid = |reason: &str| {
+            crate::AlphaSecError::invalid_parameter(format!(
+                "Cannot humanize {:?} of token {}: {}",
+                raw, token_id, reason
+            ))
+        };
+        let units: i128 = raw.trim().parse().map_err(|_| invalid("not an integer"))?;
+        Decimal::try_from_i128_with_scale(units, decimals)
+            .map(|amount| amount.normalize())
+            .map_err(|_| invalid("out of range"))
+    }
+
+    /// `human` in `token_id`'s base units, as an integer string; the inverse of
+    /// [`Self::humanize`]
+    ///
+    /// Digits beyond the token's precision are truncated, and negative amounts are
+    /// rejected (see [`AlphaSecSigner::decimal_to_onchain_units`](crate::AlphaSecSigner::decimal_to_onchain_units)).
+    pub fn scale(&self, token_id: &str, human: Decimal) -> crate::Result<String> {
+        let decimals = self.token_decimals(token_id)?;
+        crate::AlphaSecSigner::decimal_to_onchain_units(human, decimals)
+            .map(|units| units.to_string())
+    }
+
     /// Convert market symbol to market ID
     pub fn market_to_market_id(&self, market: &str) -> crate::Result<String> {
-        let parts: Vec<&str> = market.split('/').collect();
-        if parts.len() != 2 {
-            return Err(crate::AlphaSecError::invalid_parameter(format!(
-                "Invalid market format: {}. Expected format: BASE/QUOTE",
-                market
-            )));
-        }
+        self.market_id(market)
+            .map(|market_id| market_id.to_string())
+    }
 
-        let base_symbol = parts[0];
-        let quote_symbol = parts[1];
+    /// Typed market ID of a `BASE/QUOTE` market symbol
+    pub fn market_id(&self, market: &str) -> crate::Result<MarketId> {
+        let (base_symbol, quote_symbol) = crate::api::split_market(market)?;
 
         let base_token_id = self.symbol_token_id_map.get(base_symbol).ok_or_else(|| {
             crate::AlphaSecError::not_found(format!("Base token not found: {}", base_symbol))
@@ -210,29 +619,28 @@ impl TokenMetadata {
             crate::AlphaSecError::not_found(format!("Quote token not found: {}", quote_symbol))
         })?;
 
-        Ok(format!("{}_{}", base_token_id, quote_token_id))
+        format!("{}_{}", base_token_id, quote_token_id).parse()
     }
 
     /// Convert market ID to market symbol
     pub fn market_id_to_market(&self, market_id: &str) -> crate::Result<String> {
-        let parts: Vec<&str> = market_id.split('_').collect();
-        if parts.len() != 2 {
-            return Err(crate::AlphaSecError::invalid_parameter(format!(
-                "Invalid market ID format: {}",
-                market_id
-            )));
-        }
+        let market_id: MarketId = market_id.parse()?;
+        let base_token_id = market_id.base_token_id.to_string();
+        let quote_token_id = market_id.quote_token_id.to_string();
 
-        let base_token_id = parts[0];
-        let quote_token_id = parts[1];
-
-        let base_symbol = self.token_id_symbol_map.get(base_token_id).ok_or_else(|| {
-            crate::AlphaSecError::not_found(format!("Base token ID not found: {}", base_token_id))
-        })?;
+        let base_symbol = self
+            .token_id_symbol_map
+            .get(&base_token_id)
+            .ok_or_else(|| {
+                crate::AlphaSecError::not_found(format!(
+                    "Base token ID not found: {}",
+                    base_token_id
+                ))
+            })?;
 
         let quote_symbol = self
             .token_id_symbol_map
-            .get(quote_token_id)
+            .get(&quote_token_id)
             .ok_or_else(|| {
                 crate::AlphaSecError::not_found(format!(
                     "Quote token ID not found: {}",
@@ -267,6 +675,127 @@ mod tests {
         TokenMetadata::from_tokens(&[make_token("1", "KAIA"), make_token("2", "USDT")])
     }
 
+    fn market_json(rules: serde_json::Value) -> Market {
+        let mut value = serde_json::json!({
+            "marketId": "1_2",
+            "baseTokenId": "1",
+            "quoteTokenId": "2",
+            "ticker": "KAIA/USDT",
+            "description": "KAIA/USDT",
+            "exchange": "alphasec",
+            "type": "spot",
+            "listed": true,
+            "takerFee": "0.002",
+            "makerFee": "0.001"
+        });
+        value
+            .as_object_mut()
+            .unwrap()
+            .extend(rules.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn market_deserializes_trading_rules_and_defaults_them_when_absent() {
+        let market = market_json(serde_json::json!({
+            "minQty": "0.1",
+            "minNotional": "5",
+            "tickSize": "0.0001",
+            "stepSize": "0.1"
+        }));
+        assert_eq!(market.min_qty.as_deref(), Some("0.1"));
+        assert_eq!(market.min_notional.as_deref(), Some("5"));
+        assert_eq!(market.tick_size.as_deref(), Some("0.0001"));
+        assert_eq!(market.step_size.as_deref(), Some("0.1"));
+
+        let legacy = market_json(serde_json::json!({}));
+        assert!(legacy.min_qty.is_none() && legacy.tick_size.is_none());
+        assert!(legacy
+            .validate_order(
+                dec("1.23456789"),
+                dec("0.001"),
+                OrderType::Limit,
+                OrderMode::Base
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn validate_order_enforces_tick_step_and_minimums() {
+        let market = market_json(serde_json::json!({
+            "minQty": "0.1",
+            "minNotional": "5",
+            "tickSize": "0.0001",
+            "stepSize": "0.1",
+        }));
+        let check = |price: &str, qty: &str, order_type, order_mode| {
+            market.validate_order(dec(price), dec(qty), order_type, order_mode)
+        };
+        use OrderMode::{Base, Quote};
+        use OrderType::Limit;
+
+        assert!(check("1.2345", "10.0", Limit, Base).is_ok());
+        let err = check("1.23456", "10", Limit, Base).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid parameter: KAIA/USDT: price 1.23456 is not a multiple of tick size 0.0001"
+        );
+        assert!(check("1", "10.05", Limit, Base)
+            .unwrap_err()
+            .to_string()
+            .contains("step size"));
+        assert!(check("100", "0.05", Limit, Base).is_err());
+        assert!(check("1", "4.9", Limit, Base)
+            .unwrap_err()
+            .to_string()
+            .contains("order value 4.9 is below the minimum of 5"));
+
+        // Market orders: no tick check, and base-mode value is unknown up front.
+        assert!(check("0", "0.1", OrderType::Market, Base).is_ok());
+        // Quote mode: quantity is the order value; base step/min rules do not apply.
+        assert!(check("0", "5.05", OrderType::Market, Quote).is_ok());
+        assert!(check("0", "4", OrderType::Market, Quote).is_err());
+    }
+
+    #[test]
+    fn validate_order_rejects_malformed_rules() {
+        let market = market_json(serde_json::json!({ "tickSize": "tiny" }));
+        let err = market
+            .validate_order(dec("1"), dec("1"), OrderType::Limit, OrderMode::Base)
+            .unwrap_err();
+        assert!(matches!(err, AlphaSecError::InvalidParameter(m) if m.contains("tick size")));
+    }
+
+    #[test]
+    fn market_ids_parse_only_two_numeric_token_ids() {
+        let market_id: MarketId = "5_2".parse().unwrap();
+        assert_eq!(market_id, MarketId::new(5, 2));
+        assert_eq!(market_id.to_string(), "5_2");
+        assert_eq!(
+            serde_json::from_str::<MarketId>(r#""5_2""#).unwrap(),
+            market_id
+        );
+        assert_eq!(serde_json::to_string(&market_id).unwrap(), r#""5_2""#);
+
+        for (input, problem) in [
+            ("5", "expected 2 token IDs, found 1"),
+            ("5_2_1", "expected 2 token IDs, found 3"),
+            ("a_b", "base token ID \"a\" is not a number"),
+            ("5_b", "quote token ID \"b\" is not a number"),
+        ] {
+            match input.parse::<MarketId>() {
+                Err(AlphaSecError::InvalidParameter(msg)) => {
+                    assert!(msg.contains(problem), "{:?}: {}", input, msg)
+                }
+                other => panic!("{:?}: expected InvalidParameter, got {:?}", input, other),
+            }
+        }
+    }
+
     #[test]
     fn market_to_market_id_joins_base_then_quote_token_ids() {
         let md = metadata();
@@ -291,14 +820,18 @@ mod tests {
     }
 
     #[test]
-    fn empty_quote_is_quote_not_found_distinct_from_base_missing() {
+    fn empty_quote_is_invalid_distinct_from_base_missing() {
         let md = metadata();
 
-        // "KAIA/" -> base resolves, empty quote symbol fails the quote lookup.
+        // "KAIA/" -> rejected as malformed before any lookup, naming the empty quote.
         let err = md.market_to_market_id("KAIA/").unwrap_err();
-        assert!(matches!(err, AlphaSecError::NotFound(_)), "got {:?}", err);
+        assert!(
+            matches!(err, AlphaSecError::InvalidParameter(_)),
+            "got {:?}",
+            err
+        );
         let msg = err.to_string();
-        assert!(msg.contains("Quote token not found"), "msg: {}", msg);
+        assert!(msg.contains("the quote symbol is empty"), "msg: {}", msg);
         assert!(!msg.contains("Base token"), "msg: {}", msg);
 
         // Unknown base is attributed to the base side. (Lookup ORDER itself is pinned in
@@ -311,9 +844,13 @@ mod tests {
 
         // Empty base ("/USDT") is also attributed to the base side.
         let err = md.market_to_market_id("/USDT").unwrap_err();
-        assert!(matches!(err, AlphaSecError::NotFound(_)), "got {:?}", err);
+        assert!(
+            matches!(err, AlphaSecError::InvalidParameter(_)),
+            "got {:?}",
+            err
+        );
         let msg = err.to_string();
-        assert!(msg.contains("Base token not found"), "msg: {}", msg);
+        assert!(msg.contains("the base symbol is empty"), "msg: {}", msg);
         assert!(!msg.contains("Quote token"), "msg: {}", msg);
     }
 
@@ -368,4 +905,133 @@ mod tests {
         let err = md.market_id_to_market("1_2").unwrap_err();
         assert!(matches!(err, AlphaSecError::NotFound(_)), "got {:?}", err);
     }
+
+    fn trade(trade_id: &str, price: &str, quantity: &str) -> Trade {
+        Trade {
+            trade_id: trade_id.to_string(),
+            market_id: "1_2".to_string(),
+            price: price.to_string(),
+            quantity: quantity.to_string(),
+            buy_order_id: "0xb".to_string(),
+            sell_order_id: "0xs".to_string(),
+            created_at: 0,
+            is_buyer_maker: false,
+            fee: None,
+            fee_token_id: None,
+        }
+    }
+
+    #[test]
+    fn buyer_maker_trades_were_taken_by_a_seller() {
+        let buyer_maker = Trade {
+            is_buyer_maker: true,
+            ..trade("t1", "0.15", "100")
+        };
+        assert_eq!(buyer_maker.taker_side(), TradeSide::Sell);
+        assert_eq!(buyer_maker.maker_side(), TradeSide::Buy);
+
+        let seller_maker = trade("t2", "0.15", "100");
+        assert_eq!(seller_maker.taker_side(), TradeSide::Buy);
+        assert_eq!(seller_maker.maker_side(), TradeSide::Sell);
+
+        assert_eq!(seller_maker.price_decimal().unwrap(), Decimal::new(15, 2));
+        assert_eq!(seller_maker.quantity_decimal().unwrap(), Decimal::from(100));
+        assert!(trade("t3", "n/a", "1").price_decimal().is_err());
+    }
+
+    #[test]
+    fn trade_stats_weight_the_average_price_by_quantity() {
+        let trades = [
+            trade("t1", "10", "2"),
+            trade("t2", "20", "3"),
+            trade("t3", "12", "5"),
+        ];
+        let stats = TradeStats::from_trades(&trades).unwrap();
+        // (10*2 + 20*3 + 12*5) / (2 + 3 + 5) = 140 / 10
+        assert_eq!(stats.total_base_volume, Decimal::from(10));
+        assert_eq!(stats.total_quote_volume, Decimal::from(140));
+        assert_eq!(stats.vwap, Some(Decimal::from(14)));
+        assert_eq!(stats.trade_count, 3);
+        assert_eq!(stats.high, Some(Decimal::from(20)));
+        assert_eq!(stats.low, Some(Decimal::from(10)));
+    }
+
+    #[test]
+    fn trade_stats_of_an_empty_window_are_zero() {
+        let stats = TradeStats::from_trades(&[]).unwrap();
+        assert_eq!(stats, TradeStats::default());
+        assert_eq!(stats.vwap, None);
+        assert_eq!(stats.total_base_volume, Decimal::ZERO);
+    }
+
+    #[test]
+    fn trade_stats_reject_an_unparseable_price() {
+        let err = TradeStats::from_trades(&[trade("bad", "1.2.3", "1")]).unwrap_err();
+        assert!(err.to_string().contains("trade bad"), "{err}");
+    }
+
+    #[test]
+    fn decimal_overrides_accept_token_ids_and_symbols() {
+        let mut md =
+            TokenMetadata::from_tokens(&[make_token("1", "KAIA"), make_token("2", "USDT")]);
+        md.token_id_decimal_map
+            .insert("2".to_string(), "18".to_string());
+        md.apply_decimal_overrides(&HashMap::from([
+            ("USDT".to_string(), 6),
+            ("1".to_string(), 9),
+            ("99".to_string(), 8),
+        ]));
+        assert_eq!(md.token_id_decimal_map["2"], "6");
+        assert_eq!(md.token_id_decimal_map["1"], "9");
+        // A key matching no loaded token is taken as a token ID
+        assert_eq!(md.token_id_decimal_map["99"], "8");
+        assert!(!md.token_id_decimal_map.contains_key("USDT"));
+    }
+
+    #[test]
+    fn humanize_and_scale_follow_the_token_decimals() {
+        let mut metadata = metadata();
+        // KAIA keeps 18 decimals, USDT has 6
+        metadata.apply_decimal_overrides(&HashMap::from([("USDT".to_string(), 6)]));
+        let dec = |s: &str| s.parse::<Decimal>().unwrap();
+
+        assert_eq!(metadata.humanize("2", "1500000").unwrap(), dec("1.5"));
+        assert_eq!(metadata.humanize("2", "1").unwrap(), dec("0.000001"));
+        assert_eq!(metadata.scale("2", dec("1.5")).unwrap(), "1500000");
+        // Digits past the token's precision are truncated
+        assert_eq!(metadata.scale("2", dec("0.0000019")).unwrap(), "1");
+
+        let raw = "1234567890123456789012";
+        assert_eq!(
+            metadata.humanize("1", raw).unwrap(),
+            dec("1234.567890123456789012")
+        );
+        assert_eq!(
+            metadata.scale("1", dec("1234.567890123456789012")).unwrap(),
+            raw
+        );
+        assert_eq!(
+            metadata.scale("1", Decimal::ONE).unwrap(),
+            "1000000000000000000"
+        );
+
+        for bad in ["1.5", "", "wei", "1e18"] {
+            assert!(
+                matches!(
+                    metadata.humanize("2", bad),
+                    Err(AlphaSecError::InvalidParameter(_))
+                ),
+                "{:?}",
+                bad
+            );
+        }
+        assert!(matches!(
+            metadata.humanize("9", "1"),
+            Err(AlphaSecError::NotFound(_))
+        ));
+        assert!(matches!(
+            metadata.scale("2", dec("-1")),
+            Err(AlphaSecError::InvalidParameter(_))
+        ));
+    }
 }
@@ -1,6 +1,7 @@
 //! Type definitions for AlphaSec API
 
 pub mod account;
+pub mod amount;
 pub mod api;
 pub mod constants;
 pub mod market;
@@ -11,6 +12,7 @@ pub mod websocket;
 
 // Re-export commonly used types
 pub use account::*;
+pub use amount::*;
 pub use api::*;
 pub use constants::*;
 pub use market::*;
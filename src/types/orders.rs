@@ -1,9 +1,16 @@
 //! Order-related types for AlphaSec API
 
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 
+use crate::types::{Market, TokenMetadata};
+
 /// Order side
+///
+/// Serializes as its API integer (see the variant docs) and rejects any other integer;
+/// [`Display`](std::fmt::Display) gives the lowercase name.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "u32", try_from = "u32")]
 pub enum OrderSide {
     /// Buy order (side = 0 in API)
     Buy = 0,
@@ -20,12 +27,19 @@ impl std::fmt::Display for OrderSide {
     }
 }
 
-impl From<u32> for OrderSide {
-    fn from(value: u32) -> Self {
+impl TryFrom<u32> for OrderSide {
+    type Error = crate::AlphaSecError;
+
+    /// Fails with [`crate::AlphaSecError::InvalidParameter`] on a value the API does not
+    /// define
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
         match value {
-            0 => OrderSide::Buy,
-            1 => OrderSide::Sell,
-            _ => OrderSide::Buy, // Default to buy
+            0 => Ok(OrderSide::Buy),
+            1 => Ok(OrderSide::Sell),
+            _ => Err(crate::AlphaSecError::invalid_parameter(format!(
+                "Unknown order side: {}",
+                value
+            ))),
         }
     }
 }
@@ -37,7 +51,11 @@ impl From<OrderSide> for u32 {
 }
 
 /// Order type
+///
+/// Serializes as its API integer (see the variant docs) and rejects any other integer;
+/// [`Display`](std::fmt::Display) gives the lowercase name.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "u32", try_from = "u32")]
 pub enum OrderType {
     /// Limit order (order_type = 0 in API)
     Limit = 0,
@@ -54,12 +72,19 @@ impl std::fmt::Display for OrderType {
     }
 }
 
-impl From<u32> for OrderType {
-    fn from(value: u32) -> Self {
+impl TryFrom<u32> for OrderType {
+    type Error = crate::AlphaSecError;
+
+    /// Fails with [`crate::AlphaSecError::InvalidParameter`] on a value the API does not
+    /// define
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
         match value {
-            0 => OrderType::Limit,
-            1 => OrderType::Market,
-            _ => OrderType::Limit, // Default to limit
+            0 => Ok(OrderType::Limit),
+            1 => Ok(OrderType::Market),
+            _ => Err(crate::AlphaSecError::invalid_parameter(format!(
+                "Unknown order type: {}",
+                value
+            ))),
         }
     }
 }
@@ -71,7 +96,11 @@ impl From<OrderType> for u32 {
 }
 
 /// Order mode (base=0, quote=1)
+///
+/// Serializes as its API integer (see the variant docs) and rejects any other integer;
+/// [`Display`](std::fmt::Display) gives the lowercase name.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "u32", try_from = "u32")]
 pub enum OrderMode {
     /// Base token mode (order_mode = 0 in API)
     Base = 0,
@@ -88,12 +117,19 @@ impl std::fmt::Display for OrderMode {
     }
 }
 
-impl From<u32> for OrderMode {
-    fn from(value: u32) -> Self {
+impl TryFrom<u32> for OrderMode {
+    type Error = crate::AlphaSecError;
+
+    /// Fails with [`crate::AlphaSecError::InvalidParameter`] on a value the API does not
+    /// define
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
         match value {
-            0 => OrderMode::Base,
-            1 => OrderMode::Quote,
-            _ => OrderMode::Base, // Default to base
+            0 => Ok(OrderMode::Base),
+            1 => Ok(OrderMode::Quote),
+            _ => Err(crate::AlphaSecError::invalid_parameter(format!(
+                "Unknown order mode: {}",
+                value
+            ))),
         }
     }
 }
@@ -104,9 +140,52 @@ impl From<OrderMode> for u32 {
     }
 }
 
+/// How order prices and quantities are cut to the precision the exchange accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// Drop excess digits
+    #[default]
+    Truncate,
+    /// Round to the nearest value, ties to even
+    NearestEven,
+    /// Round the price toward the passive side of the book (buys down, sells up) so the
+    /// order is never more aggressive than requested; quantities are truncated
+    Conservative {
+        /// Side of the order being rounded
+        side: OrderSide,
+    },
+}
+
+impl RoundingMode {
+    /// Strategy applied to the order price
+    pub(crate) fn price_strategy(self) -> RoundingStrategy {
+        match self {
+            RoundingMode::Truncate => RoundingStrategy::ToZero,
+            RoundingMode::NearestEven => RoundingStrategy::MidpointNearestEven,
+            RoundingMode::Conservative {
+                side: OrderSide::Buy,
+            } => RoundingStrategy::ToZero,
+            RoundingMode::Conservative {
+                side: OrderSide::Sell,
+            } => RoundingStrategy::AwayFromZero,
+        }
+    }
+
+    /// Strategy applied to the order quantity
+    pub(crate) fn quantity_strategy(self) -> RoundingStrategy {
+        match self {
+            RoundingMode::NearestEven => RoundingStrategy::MidpointNearestEven,
+            RoundingMode::Truncate | RoundingMode::Conservative { .. } => RoundingStrategy::ToZero,
+        }
+    }
+}
+
 /// Order status
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "UPPERCASE")]
+///
+/// Deserializes from any string: statuses this SDK does not know become
+/// [`OrderStatus::Other`] instead of failing the whole response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 pub enum OrderStatus {
     /// Order is new and waiting to be processed
     New,
@@ -120,6 +199,29 @@ pub enum OrderStatus {
     Rejected,
     /// Order expired
     Expired,
+    /// Any other status, as received
+    Other(String),
+}
+
+impl OrderStatus {
+    /// Parse a status string, ignoring case and `_` separators (`"PARTIALLY_FILLED"`,
+    /// `"partiallyFilled"`)
+    pub fn parse(status: &str) -> Self {
+        match enum_key(status).as_str() {
+            "NEW" => Self::New,
+            "PARTIALLYFILLED" => Self::PartiallyFilled,
+            "FILLED" => Self::Filled,
+            "CANCELED" => Self::Canceled,
+            "REJECTED" => Self::Rejected,
+            "EXPIRED" => Self::Expired,
+            _ => Self::Other(status.to_string()),
+        }
+    }
+
+    /// Whether the order can still fill (new or partially filled)
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::New | Self::PartiallyFilled)
+    }
 }
 
 impl std::fmt::Display for OrderStatus {
@@ -131,10 +233,34 @@ impl std::fmt::Display for OrderStatus {
             OrderStatus::Canceled => write!(f, "CANCELED"),
             OrderStatus::Rejected => write!(f, "REJECTED"),
             OrderStatus::Expired => write!(f, "EXPIRED"),
+            OrderStatus::Other(status) => write!(f, "{}", status),
         }
     }
 }
 
+impl From<String> for OrderStatus {
+    fn from(status: String) -> Self {
+        Self::parse(&status)
+    }
+}
+
+impl From<OrderStatus> for String {
+    fn from(status: OrderStatus) -> Self {
+        status.to_string()
+    }
+}
+
+/// Uppercase `value` with surrounding whitespace and `_`, `-` and space separators
+/// removed, so API enum strings match regardless of spelling
+pub(crate) fn enum_key(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
 /// Order information from API responses
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -179,6 +305,12 @@ pub struct Order {
     pub executed_qty: String,
     /// Executed quote quantity as string
     pub executed_quote_qty: String,
+    /// Fee charged as string (absent in older responses)
+    #[serde(default)]
+    pub fee: Option<String>,
+    /// Token ID the fee was charged in (absent in older responses)
+    #[serde(default)]
+    pub fee_token_id: Option<String>,
 }
 
 impl Order {
@@ -218,6 +350,27 @@ impl Order {
         rust_decimal::Decimal::from_str(&self.executed_qty)
     }
 
+    /// Parse fee as Decimal (`Ok(None)` when the response carried no fee)
+    pub fn fee_decimal(&self) -> Result<Option<rust_decimal::Decimal>, rust_decimal::Error> {
+        use std::str::FromStr;
+        self.fee
+            .as_deref()
+            .map(rust_decimal::Decimal::from_str)
+            .transpose()
+    }
+
+    /// Resolve the fee token ID to its symbol
+    pub fn fee_token_symbol<'a>(
+        &self,
+        metadata: &'a crate::types::TokenMetadata,
+    ) -> Option<&'a str> {
+        let token_id = self.fee_token_id.as_ref()?;
+        metadata
+            .token_id_symbol_map
+            .get(token_id)
+            .map(String::as_str)
+    }
+
     /// Convert creation timestamp to DateTime<Utc>
     pub fn created_at_datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
         use chrono::{TimeZone, Utc};
@@ -230,19 +383,117 @@ impl Order {
         Utc.timestamp_millis_opt(self.updated_at as i64).single()
     }
 
+    /// Parsed [`Self::status`]
+    pub fn order_status(&self) -> OrderStatus {
+        OrderStatus::parse(&self.status)
+    }
+
     /// Check if order is filled
     pub fn is_filled(&self) -> bool {
-        self.status == "FILLED"
+        self.order_status() == OrderStatus::Filled
     }
 
     /// Check if order is canceled
     pub fn is_canceled(&self) -> bool {
-        self.status == "CANCELED"
+        self.order_status() == OrderStatus::Canceled
     }
 
     /// Check if order is active (NEW or PARTIALLY_FILLED)
     pub fn is_active(&self) -> bool {
-        matches!(self.status.as_str(), "NEW" | "PARTIALLY_FILLED")
+        self.order_status().is_active()
+    }
+}
+
+/// Final state of an order after [`crate::Agent::cancel_and_confirm`]
+#[derive(Debug, Clone)]
+pub struct CancelOutcome {
+    /// The order in its terminal state
+    pub order: Order,
+    /// `true` if the order reached a terminal state other than CANCELED (typically
+    /// FILLED) before the cancel landed
+    pub too_late: bool,
+}
+
+impl CancelOutcome {
+    /// Build the outcome for an order that is no longer active.
+    pub fn from_terminal(order: Order) -> Self {
+        let too_late = !order.is_canceled();
+        Self { order, too_late }
+    }
+}
+
+/// Outcome of [`crate::Agent::cancel_all`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelAllResult {
+    /// Number of orders canceled, if the server reported it
+    pub canceled_count: Option<u32>,
+    /// Hash of the cancel-all transaction, or the raw result when the server gave no
+    /// hash
+    pub tx_hash: String,
+}
+
+impl CancelAllResult {
+    /// Read the `result` of a cancel-all response
+    ///
+    /// The server answers either with the transaction hash as a bare string or with an
+    /// object of this struct's fields, `{"txHash", "canceledCount"}`. Anything else is
+    /// kept verbatim as `tx_hash`, without a count.
+    pub(crate) fn from_result(result: Option<&serde_json::Value>) -> Self {
+        use serde_json::Value;
+
+        let uncounted = |tx_hash: String| Self {
+            canceled_count: None,
+            tx_hash,
+        };
+        match result {
+            None | Some(Value::Null) => uncounted(String::new()),
+            Some(Value::String(tx_hash)) => uncounted(tx_hash.clone()),
+            Some(other) => serde_json::from_value(other.clone())
+                .unwrap_or_else(|_| uncounted(other.to_string())),
+        }
+    }
+}
+
+/// Outcome of [`crate::Agent::bracket_order`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BracketOrder {
+    /// ID of the entry order
+    pub order_id: String,
+    /// ID of the take profit order, if the server reported it
+    pub take_profit_id: Option<String>,
+    /// ID of the stop loss order, if the server reported it
+    pub stop_loss_id: Option<String>,
+}
+
+impl BracketOrder {
+    /// Read the result of the entry order's placement
+    ///
+    /// A bare result is the entry order's ID. A JSON object carries it as `orderId` and
+    /// the child orders as `tpOrderId` and `slOrderId`, named like the `tpsl` block's
+    /// fields. Anything else is kept verbatim as `order_id`, without children.
+    pub(crate) fn from_result(result: &str) -> Self {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Placement {
+            order_id: String,
+            tp_order_id: Option<String>,
+            sl_order_id: Option<String>,
+        }
+
+        match serde_json::from_str::<Placement>(result) {
+            Ok(placement) => Self {
+                order_id: placement.order_id,
+                take_profit_id: placement.tp_order_id,
+                stop_loss_id: placement.sl_order_id,
+            },
+            Err(_) => Self {
+                order_id: result.to_string(),
+                take_profit_id: None,
+                stop_loss_id: None,
+            },
+        }
     }
 }
 
@@ -305,54 +556,510 @@ impl OrdersQuery {
     }
 }
 
+/// Default page size for order history paging
+pub const ORDER_HISTORY_PAGE_LIMIT: u32 = 100;
+
+/// Continuation point for paging through order history
+///
+/// Identifies the oldest order of the previous page. Pages run newest to oldest: the next
+/// request sends `endMsec` set to that order's `createdAt`. `endMsec` is inclusive, so
+/// orders created in the same millisecond with an `id` at or above `last_id` were
+/// already returned and are dropped client-side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    /// `createdAt` of the oldest order returned (milliseconds), sent as `endMsec`
+    pub end_msec: u64,
+    /// Database ID of the oldest order returned
+    pub last_id: u64,
+}
+
+impl Cursor {
+    /// Cursor positioned just after `order` in the walk, i.e. just before it in time
+    pub fn after(order: &Order) -> Self {
+        Self {
+            end_msec: order.created_at,
+            last_id: order.id,
+        }
+    }
+
+    /// Whether `order` comes after this cursor in the walk, i.e. is older and was not on
+    /// an earlier page
+    pub fn precedes(&self, order: &Order) -> bool {
+        (order.created_at, order.id) < (self.end_msec, self.last_id)
+    }
+}
+
+/// Estimated cost of an order, see [`crate::Agent::estimate_order_cost`]
+///
+/// Fees are taken in the quote token: they are added on top of a buy and come out of
+/// a sell's proceeds, so only buys need extra balance for them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderCost {
+    /// Order value in the quote token
+    pub notional: Decimal,
+    /// Fee rate applied: the higher of the maker and taker rates (the market's, or the
+    /// account's fee tier's), since whether a limit order rests is not known up front
+    pub fee_rate: Decimal,
+    /// Estimated fee in the quote token
+    pub fee: Decimal,
+    /// Symbol of the debited token (quote for buys, base for sells)
+    pub debit_token: String,
+    /// ID of the debited token
+    pub debit_token_id: String,
+    /// Unlocked amount of the debited token the order needs
+    pub required: Decimal,
+}
+
+impl OrderCost {
+    /// Estimate the cost of an order on `market`.
+    ///
+    /// `quantity` is in the base token for [`OrderMode::Base`] and in the quote token
+    /// for [`OrderMode::Quote`]. `price` is needed whenever the other side's amount has
+    /// to be derived from it.
+    pub fn estimate(
+        market: &Market,
+        metadata: &TokenMetadata,
+        side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+        order_mode: OrderMode,
+    ) -> crate::Result<Self> {
+        let rate = |name: &str, value: &str| {
+            value.parse::<Decimal>().map_err(|_| {
+                crate::AlphaSecError::invalid_parameter(format!(
+                    "Invalid {} fee for market {}: {}",
+                    name, market.market_id, value
+                ))
+            })
+        };
+        let fee_rate = rate("maker", &market.maker_fee)?.max(rate("taker", &market.taker_fee)?);
+        Self::estimate_at_fee_rate(
+            market, metadata, side, price, quantity, order_mode, fee_rate,
+        )
+    }
+
+    /// [`Self::estimate`] with `fee_rate` in place of the market's fees, e.g. an
+    /// account's [`FeeTier::fee_rate`](crate::types::FeeTier::fee_rate)
+    pub fn estimate_at_fee_rate(
+        market: &Market,
+        metadata: &TokenMetadata,
+        side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+        order_mode: OrderMode,
+        fee_rate: Decimal,
+    ) -> crate::Result<Self> {
+        if quantity <= Decimal::ZERO {
+            return Err(crate::AlphaSecError::invalid_parameter(
+                "Quantity must be positive",
+            ));
+        }
+        let needs_price = !(order_mode == OrderMode::Quote && side == OrderSide::Buy);
+        if needs_price && price <= Decimal::ZERO {
+            return Err(crate::AlphaSecError::invalid_parameter(
+                "Price must be positive to estimate this order",
+            ));
+        }
+        let (notional, base_quantity) = match order_mode {
+            OrderMode::Base => (price * quantity, quantity),
+            OrderMode::Quote if side == OrderSide::Buy => (quantity, Decimal::ZERO),
+            OrderMode::Quote => (quantity, quantity / price),
+        };
+        let fee = notional * fee_rate;
+        let (debit_token_id, required) = match side {
+            OrderSide::Buy => (&market.quote_token_id, notional + fee),
+            OrderSide::Sell => (&market.base_token_id, base_quantity),
+        };
+        let debit_token = metadata
+            .token_id_symbol_map
+            .get(debit_token_id)
+            .ok_or_else(|| {
+                crate::AlphaSecError::not_found(format!("Token ID not found: {}", debit_token_id))
+            })?;
+
+        Ok(Self {
+            notional,
+            fee_rate,
+            fee,
+            debit_token: debit_token.clone(),
+            debit_token_id: debit_token_id.clone(),
+            required,
+        })
+    }
+
+    /// Check that `available` (unlocked, in the debited token) covers the order.
+    pub fn ensure_covered_by(&self, available: Decimal) -> crate::Result<()> {
+        if available < self.required {
+            return Err(crate::AlphaSecError::insufficient_balance(
+                &self.debit_token,
+                self.required.normalize(),
+                available.normalize(),
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn order_side_u32_discriminants_and_unknown_fallback_to_buy() {
-        assert_eq!(OrderSide::from(0u32), OrderSide::Buy);
-        assert_eq!(OrderSide::from(1u32), OrderSide::Sell);
-        // Documented fallback: any unknown value maps to Buy.
-        assert_eq!(OrderSide::from(2u32), OrderSide::Buy);
-        assert_eq!(OrderSide::from(u32::MAX), OrderSide::Buy);
+    fn order_side_u32_discriminants_and_unknown_values_are_rejected() {
+        assert_eq!(OrderSide::try_from(0u32).unwrap(), OrderSide::Buy);
+        assert_eq!(OrderSide::try_from(1u32).unwrap(), OrderSide::Sell);
+        // Unknown values are an error rather than a silent Buy.
+        for value in [2u32, u32::MAX] {
+            assert!(matches!(
+                OrderSide::try_from(value),
+                Err(crate::AlphaSecError::InvalidParameter(_))
+            ));
+        }
         // Reverse direction pins the `as u32` discriminants.
         assert_eq!(u32::from(OrderSide::Buy), 0);
         assert_eq!(u32::from(OrderSide::Sell), 1);
     }
 
     #[test]
-    fn order_type_u32_mapping_and_unknown_fallback_to_limit() {
-        assert_eq!(OrderType::from(0u32), OrderType::Limit);
-        assert_eq!(OrderType::from(1u32), OrderType::Market);
-        assert_eq!(OrderType::from(99u32), OrderType::Limit);
+    fn unknown_order_statuses_deserialize_as_other() {
+        for (raw, status) in [
+            ("NEW", OrderStatus::New),
+            ("PARTIALLY_FILLED", OrderStatus::PartiallyFilled),
+            ("filled", OrderStatus::Filled),
+            ("CANCELED", OrderStatus::Canceled),
+        ] {
+            let parsed: OrderStatus = serde_json::from_value(serde_json::json!(raw)).unwrap();
+            assert_eq!(parsed, status, "{}", raw);
+        }
+        assert_eq!(
+            serde_json::to_value(OrderStatus::PartiallyFilled).unwrap(),
+            "PARTIALLY_FILLED"
+        );
+
+        let unknown: OrderStatus =
+            serde_json::from_value(serde_json::json!("PENDING_NEW")).unwrap();
+        assert_eq!(unknown, OrderStatus::Other("PENDING_NEW".to_string()));
+        assert!(!unknown.is_active());
+        // Round-trips as received
+        assert_eq!(serde_json::to_value(&unknown).unwrap(), "PENDING_NEW");
+    }
+
+    #[test]
+    fn order_enums_serialize_as_api_integers() {
+        assert_eq!(serde_json::to_value(OrderSide::Sell).unwrap(), 1);
+        assert_eq!(serde_json::to_value(OrderSide::Buy).unwrap(), 0);
+        assert_eq!(serde_json::to_value(OrderType::Market).unwrap(), 1);
+        assert_eq!(serde_json::to_value(OrderMode::Quote).unwrap(), 1);
+
+        for side in [OrderSide::Buy, OrderSide::Sell] {
+            let json = serde_json::to_string(&side).unwrap();
+            assert_eq!(serde_json::from_str::<OrderSide>(&json).unwrap(), side);
+        }
+        for order_type in [OrderType::Limit, OrderType::Market] {
+            let json = serde_json::to_string(&order_type).unwrap();
+            assert_eq!(
+                serde_json::from_str::<OrderType>(&json).unwrap(),
+                order_type
+            );
+        }
+        for mode in [OrderMode::Base, OrderMode::Quote] {
+            let json = serde_json::to_string(&mode).unwrap();
+            assert_eq!(serde_json::from_str::<OrderMode>(&json).unwrap(), mode);
+        }
+
+        // Names are no longer accepted; Display keeps them for humans
+        assert!(serde_json::from_str::<OrderSide>(r#""Sell""#).is_err());
+        // Neither are integers the API does not define
+        let err = serde_json::from_str::<OrderSide>("2").unwrap_err();
+        assert!(err.to_string().contains("Unknown order side: 2"), "{err}");
+        assert!(serde_json::from_str::<OrderType>("7").is_err());
+        assert!(serde_json::from_str::<OrderMode>("3").is_err());
+        assert_eq!(OrderSide::Sell.to_string(), "sell");
+    }
+
+    #[test]
+    fn order_type_u32_mapping_and_unknown_values_are_rejected() {
+        assert_eq!(OrderType::try_from(0u32).unwrap(), OrderType::Limit);
+        assert_eq!(OrderType::try_from(1u32).unwrap(), OrderType::Market);
+        assert!(matches!(
+            OrderType::try_from(99u32),
+            Err(crate::AlphaSecError::InvalidParameter(_))
+        ));
         assert_eq!(u32::from(OrderType::Market), 1);
     }
 
     #[test]
-    fn order_mode_u32_mapping_and_unknown_fallback_to_base() {
-        assert_eq!(OrderMode::from(0u32), OrderMode::Base);
-        assert_eq!(OrderMode::from(1u32), OrderMode::Quote);
-        assert_eq!(OrderMode::from(255u32), OrderMode::Base);
+    fn order_mode_u32_mapping_and_unknown_values_are_rejected() {
+        assert_eq!(OrderMode::try_from(0u32).unwrap(), OrderMode::Base);
+        assert_eq!(OrderMode::try_from(1u32).unwrap(), OrderMode::Quote);
+        assert!(matches!(
+            OrderMode::try_from(255u32),
+            Err(crate::AlphaSecError::InvalidParameter(_))
+        ));
         assert_eq!(u32::from(OrderMode::Quote), 1);
     }
 
+    fn order_json() -> serde_json::Value {
+        serde_json::json!({
+            "id": 42,
+            "orderId": "0xorder",
+            "accountAddress": "0xabc",
+            "marketId": "1_2",
+            "side": "BUY",
+            "orderType": "LIMIT",
+            "price": "1.5",
+            "origQty": "10",
+            "origQuoteOrderQty": "0",
+            "isTrigger": false,
+            "isTriggered": false,
+            "triggerPrice": "0",
+            "status": "FILLED",
+            "contingencyType": "NONE",
+            "otoLegType": "NONE",
+            "txHash": "0xtx",
+            "createdAt": 1,
+            "updatedAt": 2,
+            "executedQty": "10",
+            "executedQuoteQty": "15"
+        })
+    }
+
+    #[test]
+    fn order_with_fee_fields_parses_fee_and_resolves_token_symbol() {
+        use crate::types::{Token, TokenMetadata};
+        use rust_decimal::Decimal;
+        use std::str::FromStr;
+
+        let mut json = order_json();
+        json["fee"] = "0.015".into();
+        json["feeTokenId"] = "2".into();
+        let order: Order = serde_json::from_value(json).unwrap();
+
+        assert_eq!(order.fee.as_deref(), Some("0.015"));
+        assert_eq!(
+            order.fee_decimal().unwrap(),
+            Some(Decimal::from_str("0.015").unwrap())
+        );
+        let metadata = TokenMetadata::from_tokens(&[Token {
+            token_id: "2".to_string(),
+            symbol: "USDT".to_string(),
+            l2_symbol: "USDT".to_string(),
+            l1_address: "0x0".to_string(),
+            decimals: 6,
+            is_active: true,
+        }]);
+        assert_eq!(order.fee_token_symbol(&metadata), Some("USDT"));
+        assert_eq!(
+            order.fee_token_symbol(&TokenMetadata::from_tokens(&[])),
+            None
+        );
+    }
+
+    #[test]
+    fn order_without_fee_fields_still_parses() {
+        let order: Order = serde_json::from_value(order_json()).unwrap();
+        assert_eq!(order.fee, None);
+        assert_eq!(order.fee_token_id, None);
+        assert_eq!(order.fee_decimal().unwrap(), None);
+        // Explicit nulls behave the same as absent keys.
+        let mut json = order_json();
+        json["fee"] = serde_json::Value::Null;
+        json["feeTokenId"] = serde_json::Value::Null;
+        let order: Order = serde_json::from_value(json).unwrap();
+        assert_eq!(order.fee, None);
+    }
+
+    fn cost_market() -> (Market, TokenMetadata) {
+        let token = |token_id: &str, symbol: &str| crate::types::Token {
+            token_id: token_id.to_string(),
+            symbol: symbol.to_string(),
+            l2_symbol: symbol.to_string(),
+            l1_address: format!("0x{:0>40}", token_id),
+            decimals: 18,
+            is_active: true,
+        };
+        let market = Market {
+            market_id: "1_2".to_string(),
+            base_token_id: "1".to_string(),
+            quote_token_id: "2".to_string(),
+            ticker: "KAIA/USDT".to_string(),
+            description: String::new(),
+            exchange: "alphasec".to_string(),
+            market_type: "spot".to_string(),
+            listed: true,
+            taker_fee: "0.002".to_string(),
+            maker_fee: "0.001".to_string(),
+            min_qty: None,
+            min_notional: None,
+            tick_size: None,
+            step_size: None,
+        };
+        let md = TokenMetadata::from_tokens(&[token("1", "KAIA"), token("2", "USDT")]);
+        (market, md)
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
     #[test]
-    fn serde_representation_is_variant_name_string_not_numeric() {
-        assert_eq!(serde_json::to_string(&OrderSide::Buy).unwrap(), "\"Buy\"");
-        assert_eq!(serde_json::to_string(&OrderSide::Sell).unwrap(), "\"Sell\"");
+    fn buy_debits_quote_including_the_fee() {
+        let (market, md) = cost_market();
+        let cost = OrderCost::estimate(
+            &market,
+            &md,
+            OrderSide::Buy,
+            dec("1.5"),
+            dec("100"),
+            OrderMode::Base,
+        )
+        .unwrap();
+        assert_eq!(cost.notional, dec("150"));
+        assert_eq!(cost.fee_rate, dec("0.002"), "higher of maker and taker");
+        assert_eq!(cost.fee, dec("0.3"));
         assert_eq!(
-            serde_json::to_string(&OrderType::Limit).unwrap(),
-            "\"Limit\""
+            (cost.debit_token.as_str(), cost.debit_token_id.as_str()),
+            ("USDT", "2")
         );
-        assert_eq!(serde_json::to_string(&OrderMode::Base).unwrap(), "\"Base\"");
-        // The numeric representation belongs ONLY to From<u32>: serde must reject it.
-        // (From<u32> maps 1 -> Sell, but JSON `1` is not a valid OrderSide.)
-        assert!(serde_json::from_str::<OrderSide>("1").is_err());
-        // Round-trip through the string form stays lossless.
+        assert_eq!(cost.required, dec("150.3"));
+
+        // Quote-mode market buy: quantity is already the notional, no price needed.
+        let cost = OrderCost::estimate(
+            &market,
+            &md,
+            OrderSide::Buy,
+            Decimal::ZERO,
+            dec("50"),
+            OrderMode::Quote,
+        )
+        .unwrap();
+        assert_eq!((cost.notional, cost.required), (dec("50"), dec("50.1")));
+    }
+
+    #[test]
+    fn sell_debits_base_and_pays_the_fee_from_proceeds() {
+        let (market, md) = cost_market();
+        let cost = OrderCost::estimate(
+            &market,
+            &md,
+            OrderSide::Sell,
+            dec("1.5"),
+            dec("100"),
+            OrderMode::Base,
+        )
+        .unwrap();
+        assert_eq!((cost.notional, cost.fee), (dec("150"), dec("0.3")));
+        assert_eq!(cost.debit_token, "KAIA");
+        assert_eq!(cost.required, dec("100"));
+
+        let cost = OrderCost::estimate(
+            &market,
+            &md,
+            OrderSide::Sell,
+            dec("2"),
+            dec("50"),
+            OrderMode::Quote,
+        )
+        .unwrap();
+        assert_eq!(cost.required, dec("25"));
+    }
+
+    #[test]
+    fn estimate_rejects_missing_price_and_non_positive_quantity() {
+        let (market, md) = cost_market();
+        for (side, price, qty, mode) in [
+            (OrderSide::Buy, "0", "1", OrderMode::Base),
+            (OrderSide::Sell, "0", "1", OrderMode::Quote),
+            (OrderSide::Buy, "1", "0", OrderMode::Base),
+        ] {
+            let err =
+                OrderCost::estimate(&market, &md, side, dec(price), dec(qty), mode).unwrap_err();
+            assert!(
+                matches!(err, crate::AlphaSecError::InvalidParameter(_)),
+                "{:?}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn ensure_covered_by_reports_token_required_and_available() {
+        let (market, md) = cost_market();
+        let cost = OrderCost::estimate(
+            &market,
+            &md,
+            OrderSide::Buy,
+            dec("1.5"),
+            dec("100"),
+            OrderMode::Base,
+        )
+        .unwrap();
+        assert!(cost.ensure_covered_by(dec("150.3")).is_ok());
+        let err = cost.ensure_covered_by(dec("150")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Insufficient USDT balance: 150.3 required, 150 available"
+        );
+    }
+
+    #[test]
+    fn cancel_all_result_reads_a_hash_or_an_object_with_a_count() {
+        let bare = CancelAllResult::from_result(Some(&serde_json::json!("0xhash")));
+        assert_eq!(
+            bare,
+            CancelAllResult {
+                canceled_count: None,
+                tx_hash: "0xhash".to_string(),
+            }
+        );
+
+        let counted = CancelAllResult::from_result(Some(
+            &serde_json::json!({"txHash": "0xhash", "canceledCount": 3}),
+        ));
+        assert_eq!(counted.canceled_count, Some(3));
+        assert_eq!(counted.tx_hash, "0xhash");
+        let uncounted =
+            CancelAllResult::from_result(Some(&serde_json::json!({"txHash": "0xhash"})));
+        assert_eq!(uncounted.canceled_count, None);
+        assert_eq!(uncounted.tx_hash, "0xhash");
+
+        // Other field names are not guessed at: the object is kept as text
+        let unknown =
+            CancelAllResult::from_result(Some(&serde_json::json!({"hash": "0xhash", "count": 1})));
+        assert_eq!(unknown.canceled_count, None);
+        assert_eq!(unknown.tx_hash, r#"{"count":1,"hash":"0xhash"}"#);
+        assert_eq!(CancelAllResult::from_result(None).tx_hash, "");
+    }
+
+    #[test]
+    fn bracket_order_reads_an_id_or_an_object_with_children() {
         assert_eq!(
-            serde_json::from_str::<OrderSide>("\"Sell\"").unwrap(),
-            OrderSide::Sell
+            BracketOrder::from_result("0xentry"),
+            BracketOrder {
+                order_id: "0xentry".to_string(),
+                take_profit_id: None,
+                stop_loss_id: None,
+            }
         );
+
+        let children = BracketOrder::from_result(
+            r#"{"orderId":"0xentry","tpOrderId":"0xtp","slOrderId":"0xsl"}"#,
+        );
+        assert_eq!(children.order_id, "0xentry");
+        assert_eq!(children.take_profit_id.as_deref(), Some("0xtp"));
+        assert_eq!(children.stop_loss_id.as_deref(), Some("0xsl"));
+        let entry_only = BracketOrder::from_result(r#"{"orderId":"0xentry"}"#);
+        assert_eq!(entry_only.order_id, "0xentry");
+        assert_eq!(entry_only.stop_loss_id, None);
+
+        // Other field names are not guessed at: the object is kept as text
+        for unknown in [
+            r#"{"status":"ok"}"#,
+            r#"{"id":"0xentry","takeProfitOrderId":"0xtp","stopLossOrderId":"0xsl"}"#,
+        ] {
+            let bracket = BracketOrder::from_result(unknown);
+            assert_eq!(bracket.order_id, unknown);
+            assert_eq!(bracket.take_profit_id, None);
+        }
     }
 }
@@ -1,9 +1,16 @@
 //! WebSocket message types for AlphaSec (JSON-RPC 2.0 format)
 
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
 
 /// WebSocket message from AlphaSec (JSON-RPC 2.0 format)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Deserialization is driven by the envelope rather than the payload shape: a
+/// `{"method": "subscription", "params": {"channel": ..}}` frame is routed by the channel
+/// prefix (`trade@`, `ticker@`, `depth@`, `kline@`, `userEvent@`), an `{"id", "result"}` frame is an
+/// [`Ack`](Self::Ack), and everything else, including unknown channels, is
+/// [`Generic`](Self::Generic). A frame on a known channel whose payload does not fit that
+/// channel's type is an error rather than being matched against another variant.
+#[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum WebSocketMessage {
     /// Subscription acknowledgment
@@ -15,6 +22,30 @@ pub enum WebSocketMessage {
     },
     /// Disconnected notification (SDK-internal, not from server)
     Disconnected,
+    /// The server closed the connection (SDK-internal, built from the close frame
+    /// rather than from JSON)
+    ///
+    /// Arrives just before that connection's [`WebSocketMessage::Disconnected`] and lets
+    /// consumers tell, say, a maintenance window from an authentication failure. After
+    /// 1001 (going away), 1012 (restart) or 1013 (try again later) the manager
+    /// reconnects after its backoff delay; after any other code, or none, it stops.
+    Closed {
+        /// Close code (e.g. 1001 going away, 1008 policy violation), `None` if the
+        /// server sent no status
+        code: Option<u16>,
+        /// Reason text from the close frame, possibly empty
+        reason: String,
+    },
+    /// Messages were dropped because this receiver's queue was full (SDK-internal, see
+    /// [`OverflowPolicy`](crate::websocket::OverflowPolicy))
+    ///
+    /// Arrives in place of the dropped messages, before the next one received, and
+    /// counts the drops since the previous `Lagged`. Only market data is ever dropped,
+    /// so a consumer rebuilding a depth book should re-snapshot it.
+    Lagged {
+        /// Number of messages dropped
+        dropped: u64,
+    },
     /// Trade message
     TradeMsg {
         /// Method (always "subscription")
@@ -36,6 +67,13 @@ pub enum WebSocketMessage {
         /// Ticker parameters
         params: TickerParams,
     },
+    /// Kline (candlestick) message
+    KlineMsg {
+        /// Method (always "subscription")
+        method: String,
+        /// Kline parameters
+        params: KlineParams,
+    },
     /// User event message
     UserEventMsg {
         /// Method (always "subscription")
@@ -44,6 +82,9 @@ pub enum WebSocketMessage {
         params: UserEventParams,
     },
     /// Generic message (fallback for any other format)
+    ///
+    /// Also carries spot frames whose payload failed strict parsing;
+    /// [`Self::try_typed`] recovers the ones that are only slightly off.
     Generic(serde_json::Value),
     /// Pong message
     Pong(Vec<u8>),
@@ -51,6 +92,201 @@ pub enum WebSocketMessage {
     Ping(Vec<u8>),
 }
 
+impl<'de> Deserialize<'de> for WebSocketMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+
+        if value.get("method").and_then(|m| m.as_str()) == Some("subscription") {
+            let channel = value
+                .get("params")
+                .and_then(|p| p.get("channel"))
+                .and_then(|c| c.as_str())
+                .unwrap_or_default();
+            let method = "subscription".to_string();
+            let params = || value["params"].clone();
+            let prefix = channel
+                .split_once('@')
+                .map_or(channel, |(prefix, _)| prefix);
+            return match prefix {
+                "trade" => Ok(Self::TradeMsg {
+                    method,
+                    params: serde_json::from_value(params()).map_err(D::Error::custom)?,
+                }),
+                "ticker" => Ok(Self::TickerMsg {
+                    method,
+                    params: serde_json::from_value(params()).map_err(D::Error::custom)?,
+                }),
+                "depth" => Ok(Self::DepthMsg {
+                    method,
+                    params: serde_json::from_value(params()).map_err(D::Error::custom)?,
+                }),
+                "kline" => Ok(Self::KlineMsg {
+                    method,
+                    params: serde_json::from_value(params()).map_err(D::Error::custom)?,
+                }),
+                "userEvent" => Ok(Self::UserEventMsg {
+                    method,
+                    params: serde_json::from_value(params()).map_err(D::Error::custom)?,
+                }),
+                _ => Ok(Self::Generic(value)),
+            };
+        }
+
+        if let (Some(id), Some(result), None) = (
+            value.get("id").and_then(|v| v.as_i64()),
+            value.get("result").and_then(|v| v.as_str()),
+            value.get("method"),
+        ) {
+            if let Ok(id) = i32::try_from(id) {
+                return Ok(Self::Ack {
+                    id,
+                    result: result.to_string(),
+                });
+            }
+        }
+
+        Ok(Self::Generic(value))
+    }
+}
+
+/// Keys of spot payload fields typed as integers, see [`relax`]
+const INTEGER_FIELDS: [&str; 9] = [
+    "createdAt",
+    "firstId",
+    "finalId",
+    "time",
+    "openTime",
+    "closeTime",
+    "eventTime",
+    "blockNumber",
+    "orderMode",
+];
+
+/// Keys of spot payload fields typed as booleans, see [`relax`]
+const BOOLEAN_FIELDS: [&str; 3] = ["isBuyerMaker", "isClosed", "isMaker"];
+
+/// Payload of a spot subscription frame, see [`WebSocketMessage::try_typed`]
+#[derive(Debug, Clone)]
+pub enum TypedFrame {
+    /// `trade@` payload
+    Trade(TradeParams),
+    /// `depth@` payload
+    Depth(DepthParams),
+    /// `ticker@` payload
+    Ticker(TickerParams),
+    /// `kline@` payload
+    Kline(KlineParams),
+    /// `userEvent@` payload
+    UserEvent(UserEventParams),
+}
+
+impl TypedFrame {
+    /// Channel the frame arrived on
+    pub fn channel(&self) -> &str {
+        match self {
+            Self::Trade(params) => &params.channel,
+            Self::Depth(params) => &params.channel,
+            Self::Ticker(params) => &params.channel,
+            Self::Kline(params) => &params.channel,
+            Self::UserEvent(params) => &params.channel,
+        }
+    }
+
+    /// Parse a subscription frame routed by its channel prefix, coercing the payload
+    /// with [`relax`] first
+    fn from_lenient(value: &serde_json::Value) -> Option<Self> {
+        let params = value.get("params")?;
+        let channel = params.get("channel")?.as_str()?;
+        let prefix = channel.split_once('@')?.0;
+        let mut result = relax(None, params.get("result")?);
+        // Trade and ticker frames carry a list, even of one entry
+        if matches!(prefix, "trade" | "ticker") && result.is_object() {
+            result = serde_json::Value::Array(vec![result]);
+        }
+        let params = serde_json::json!({ "channel": channel, "result": result });
+        match prefix {
+            "trade" => serde_json::from_value(params).ok().map(Self::Trade),
+            "depth" => serde_json::from_value(params).ok().map(Self::Depth),
+            "ticker" => serde_json::from_value(params).ok().map(Self::Ticker),
+            "kline" => serde_json::from_value(params).ok().map(Self::Kline),
+            "userEvent" => serde_json::from_value(params).ok().map(Self::UserEvent),
+            _ => None,
+        }
+    }
+}
+
+impl From<TypedFrame> for WebSocketMessage {
+    fn from(frame: TypedFrame) -> Self {
+        let method = "subscription".to_string();
+        match frame {
+            TypedFrame::Trade(params) => Self::TradeMsg { method, params },
+            TypedFrame::Depth(params) => Self::DepthMsg { method, params },
+            TypedFrame::Ticker(params) => Self::TickerMsg { method, params },
+            TypedFrame::Kline(params) => Self::KlineMsg { method, params },
+            TypedFrame::UserEvent(params) => Self::UserEventMsg { method, params },
+        }
+    }
+}
+
+impl WebSocketMessage {
+    /// Payload of a spot subscription frame, reinterpreting a [`Self::Generic`] one
+    ///
+    /// Typed variants are returned as they are. A `Generic` frame on a spot channel is
+    /// parsed again after coercing numbers to the decimal strings the types expect (and
+    /// numeric or boolean strings back for the integer and flag fields), and wrapping a
+    /// lone trade or ticker entry in a list. `None` if it still does not fit, or for
+    /// acks, pings and other SDK-internal variants.
+    pub fn try_typed(&self) -> Option<TypedFrame> {
+        match self {
+            Self::TradeMsg { params, .. } => Some(TypedFrame::Trade(params.clone())),
+            Self::DepthMsg { params, .. } => Some(TypedFrame::Depth(params.clone())),
+            Self::TickerMsg { params, .. } => Some(TypedFrame::Ticker(params.clone())),
+            Self::KlineMsg { params, .. } => Some(TypedFrame::Kline(params.clone())),
+            Self::UserEventMsg { params, .. } => Some(TypedFrame::UserEvent(params.clone())),
+            Self::Generic(value) => TypedFrame::from_lenient(value),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a market data frame (trade, depth, ticker or kline), the only kind
+    /// an [`OverflowPolicy`](crate::websocket::OverflowPolicy) may drop
+    pub fn is_market_data(&self) -> bool {
+        matches!(
+            self,
+            Self::TradeMsg { .. }
+                | Self::DepthMsg { .. }
+                | Self::TickerMsg { .. }
+                | Self::KlineMsg { .. }
+        )
+    }
+}
+
+/// Copy of `value`, found under `key`, with scalars coerced to the types the spot
+/// payloads use: strings for everything but [`INTEGER_FIELDS`] and [`BOOLEAN_FIELDS`]
+fn relax(key: Option<&str>, value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), relax(Some(key), value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(|item| relax(key, item)).collect()),
+        Value::String(text) if key.is_some_and(|key| INTEGER_FIELDS.contains(&key)) => text
+            .parse::<i64>()
+            .map_or_else(|_| value.clone(), Value::from),
+        Value::String(text) if key.is_some_and(|key| BOOLEAN_FIELDS.contains(&key)) => text
+            .parse::<bool>()
+            .map_or_else(|_| value.clone(), Value::from),
+        Value::Number(number) if !key.is_some_and(|key| INTEGER_FIELDS.contains(&key)) => {
+            Value::String(number.to_string())
+        }
+        _ => value.clone(),
+    }
+}
+
 /// Trade parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeParams {
@@ -157,6 +393,296 @@ pub struct TickerEntry {
     pub quote_volume_24h: String,
 }
 
+impl crate::types::TickerData for TickerEntry {
+    fn market_id(&self) -> &str {
+        &self.market_id
+    }
+    fn price(&self) -> &str {
+        &self.price
+    }
+    fn open_24h(&self) -> &str {
+        &self.open_24h
+    }
+    fn high_24h(&self) -> &str {
+        &self.high_24h
+    }
+    fn low_24h(&self) -> &str {
+        &self.low_24h
+    }
+    fn volume_24h(&self) -> &str {
+        &self.volume_24h
+    }
+    fn quote_volume_24h(&self) -> &str {
+        &self.quote_volume_24h
+    }
+}
+
+impl From<TickerEntry> for crate::types::Ticker {
+    fn from(entry: TickerEntry) -> Self {
+        Self {
+            market_id: entry.market_id,
+            base_token_id: entry.base_token_id,
+            quote_token_id: entry.quote_token_id,
+            price: entry.price,
+            open_24h: entry.open_24h,
+            high_24h: entry.high_24h,
+            low_24h: entry.low_24h,
+            volume_24h: entry.volume_24h,
+            quote_volume_24h: entry.quote_volume_24h,
+        }
+    }
+}
+
+impl From<crate::types::Ticker> for TickerEntry {
+    fn from(ticker: crate::types::Ticker) -> Self {
+        Self {
+            market_id: ticker.market_id,
+            base_token_id: ticker.base_token_id,
+            quote_token_id: ticker.quote_token_id,
+            price: ticker.price,
+            open_24h: ticker.open_24h,
+            high_24h: ticker.high_24h,
+            low_24h: ticker.low_24h,
+            volume_24h: ticker.volume_24h,
+            quote_volume_24h: ticker.quote_volume_24h,
+        }
+    }
+}
+
+/// Candle interval of a `kline@{market}@{interval}` channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KlineInterval {
+    /// `1m`
+    Minute1,
+    /// `3m`
+    Minute3,
+    /// `5m`
+    Minute5,
+    /// `15m`
+    Minute15,
+    /// `30m`
+    Minute30,
+    /// `1h`
+    Hour1,
+    /// `2h`
+    Hour2,
+    /// `4h`
+    Hour4,
+    /// `6h`
+    Hour6,
+    /// `12h`
+    Hour12,
+    /// `1d`
+    Day1,
+    /// `1w`
+    Week1,
+}
+
+impl KlineInterval {
+    /// Every interval, shortest first
+    pub const ALL: [Self; 12] = [
+        Self::Minute1,
+        Self::Minute3,
+        Self::Minute5,
+        Self::Minute15,
+        Self::Minute30,
+        Self::Hour1,
+        Self::Hour2,
+        Self::Hour4,
+        Self::Hour6,
+        Self::Hour12,
+        Self::Day1,
+        Self::Week1,
+    ];
+
+    /// Channel suffix (e.g. `"1m"`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Minute1 => "1m",
+            Self::Minute3 => "3m",
+            Self::Minute5 => "5m",
+            Self::Minute15 => "15m",
+            Self::Minute30 => "30m",
+            Self::Hour1 => "1h",
+            Self::Hour2 => "2h",
+            Self::Hour4 => "4h",
+            Self::Hour6 => "6h",
+            Self::Hour12 => "12h",
+            Self::Day1 => "1d",
+            Self::Week1 => "1w",
+        }
+    }
+}
+
+impl std::str::FromStr for KlineInterval {
+    type Err = crate::AlphaSecError;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|interval| interval.as_str() == s)
+            .ok_or_else(|| {
+                crate::AlphaSecError::invalid_parameter(format!(
+                    "Unsupported kline interval {:?}, expected one of {}",
+                    s,
+                    Self::ALL.map(|interval| interval.as_str()).join(", ")
+                ))
+            })
+    }
+}
+
+impl std::fmt::Display for KlineInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Spot WebSocket channel, see [`crate::Agent::subscribe_channel`]
+///
+/// Markets are named by symbol (e.g. `"KAIA/USDT"`) and resolved to their wire ID on
+/// subscribe. [`Display`](std::fmt::Display) renders the `type@target` form accepted by
+/// [`crate::Agent::subscribe`], and [`FromStr`](std::str::FromStr) parses it back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// `trade@{market}`
+    Trade(String),
+    /// `ticker@{market}`, or `ticker@all` for every market
+    Ticker(String),
+    /// `depth@{market}`
+    Depth(String),
+    /// `userEvent@{address}`
+    UserEvent(String),
+    /// `kline@{market}@{interval}`
+    Kline(String, KlineInterval),
+}
+
+impl Channel {
+    /// Channel type, the part before the first `@`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Trade(_) => "trade",
+            Self::Ticker(_) => "ticker",
+            Self::Depth(_) => "depth",
+            Self::UserEvent(_) => "userEvent",
+            Self::Kline(..) => "kline",
+        }
+    }
+
+    /// Market symbol or, for [`Self::UserEvent`], account address
+    pub fn target(&self) -> &str {
+        match self {
+            Self::Trade(target)
+            | Self::Ticker(target)
+            | Self::Depth(target)
+            | Self::UserEvent(target)
+            | Self::Kline(target, _) => target,
+        }
+    }
+}
+
+impl std::str::FromStr for Channel {
+    type Err = crate::AlphaSecError;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let parts: Vec<&str> = s.split('@').collect();
+        let (kind, target) = match parts.as_slice() {
+            [kind, target, ..] if !target.is_empty() => (*kind, target.to_string()),
+            _ => {
+                return Err(crate::AlphaSecError::invalid_parameter(format!(
+                    "Channel format should be 'type@target', got: {}",
+                    s
+                )))
+            }
+        };
+        let max_parts = match kind {
+            "kline" => 3,
+            _ => 2,
+        };
+        if parts.len() > max_parts {
+            return Err(crate::AlphaSecError::invalid_parameter(format!(
+                "Unexpected '@' in channel: {}",
+                s
+            )));
+        }
+        match kind {
+            "trade" => Ok(Self::Trade(target)),
+            "ticker" => Ok(Self::Ticker(target)),
+            "depth" => Ok(Self::Depth(target)),
+            "userEvent" => Ok(Self::UserEvent(target)),
+            "kline" => match parts.get(2) {
+                Some(interval) => Ok(Self::Kline(target, interval.parse()?)),
+                None => Err(crate::AlphaSecError::invalid_parameter(format!(
+                    "Kline channel format should be 'kline@market@interval', got: {}",
+                    s
+                ))),
+            },
+            _ => Err(crate::AlphaSecError::invalid_parameter(format!(
+                "Unsupported channel type: {}. Use 'trade', 'ticker', 'depth', 'kline' or 'userEvent'",
+                kind
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.kind(), self.target())?;
+        if let Self::Kline(_, interval) = self {
+            write!(f, "@{}", interval)?;
+        }
+        Ok(())
+    }
+}
+
+/// Kline parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KlineParams {
+    /// Channel name
+    pub channel: String,
+    /// Kline result
+    pub result: KlineResult,
+}
+
+/// Candle update, pushed whenever the candle in progress changes and once more when it
+/// closes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KlineResult {
+    /// Market ID
+    #[serde(rename = "marketId")]
+    pub market_id: String,
+    /// Candle interval (e.g. "1m")
+    pub interval: String,
+    /// Candle open time (milliseconds)
+    #[serde(rename = "openTime")]
+    pub open_time: i64,
+    /// Candle close time (milliseconds)
+    #[serde(rename = "closeTime")]
+    pub close_time: i64,
+    /// Open price
+    pub open: String,
+    /// High price
+    pub high: String,
+    /// Low price
+    pub low: String,
+    /// Close (latest) price
+    pub close: String,
+    /// Base token volume
+    pub volume: String,
+    /// Quote token volume
+    #[serde(rename = "quoteVolume")]
+    pub quote_volume: String,
+    /// Whether the candle is final
+    #[serde(rename = "isClosed", default)]
+    pub is_closed: bool,
+}
+
+impl KlineResult {
+    /// Parsed [`Self::interval`]
+    pub fn interval(&self) -> crate::Result<KlineInterval> {
+        self.interval.parse()
+    }
+}
+
 /// User event parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserEventParams {
@@ -300,6 +826,79 @@ impl UserEventResult {
             UserEventResult::Account { base, .. } => base,
         }
     }
+
+    /// Normalize an ORDER event into an [`OrderUpdate`]; `None` for other topics.
+    pub fn order_update(&self) -> Option<OrderUpdate> {
+        match self {
+            UserEventResult::Order { base, order } => Some(OrderUpdate {
+                order_id: order.order_id.clone(),
+                account_address: base.account_address.clone(),
+                market_id: order.market_id.clone(),
+                side: order.side.clone(),
+                order_type: order.order_type.clone(),
+                price: order.orig_price.clone(),
+                orig_qty: order.orig_qty.clone(),
+                orig_quote_order_qty: order.orig_quote_order_qty.clone(),
+                executed_qty: order.executed_qty.clone(),
+                executed_quote_qty: order.executed_quote_qty.clone(),
+                status: order.status.clone(),
+                created_at: order.created_at,
+                event_time: base.event_time,
+                tx_hash: base.tx_hash.clone(),
+                fee: Some(order.fee.clone()),
+                fee_token_id: order.fee_token_id.clone(),
+            }),
+            UserEventResult::Account { .. } => None,
+        }
+    }
+}
+
+/// Order state change derived from a `userEvent` ORDER message, carrying the fields
+/// needed to keep a set of open [`crate::types::Order`]s current.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderUpdate {
+    /// Order ID
+    pub order_id: String,
+    /// Account address
+    pub account_address: String,
+    /// Market ID
+    pub market_id: String,
+    /// Order side (BUY/SELL)
+    pub side: String,
+    /// Order type (LIMIT/MARKET)
+    pub order_type: String,
+    /// Order price
+    pub price: String,
+    /// Original quantity
+    pub orig_qty: String,
+    /// Original quote order quantity
+    pub orig_quote_order_qty: String,
+    /// Executed quantity
+    pub executed_qty: String,
+    /// Executed quote quantity
+    pub executed_quote_qty: String,
+    /// Order status after this event (NEW/PARTIALLY_FILLED/FILLED/CANCELED/etc.)
+    pub status: String,
+    /// Order creation timestamp (milliseconds)
+    pub created_at: i64,
+    /// Event timestamp (milliseconds)
+    pub event_time: i64,
+    /// Transaction hash
+    pub tx_hash: String,
+    /// Fee reported by the event (absent in updates serialized by older versions)
+    #[serde(default)]
+    pub fee: Option<String>,
+    /// Token ID the fee was charged in
+    #[serde(default)]
+    pub fee_token_id: Option<String>,
+}
+
+impl OrderUpdate {
+    /// Check if the order is still resting (NEW or PARTIALLY_FILLED)
+    pub fn is_active(&self) -> bool {
+        matches!(self.status.as_str(), "NEW" | "PARTIALLY_FILLED")
+    }
 }
 /// WebSocket subscription request
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -307,3 +906,426 @@ pub struct SubscriptionRequest {
     /// Channels to subscribe to
     pub channels: Vec<String>,
 }
+
+/// A received message tagged with the connection it arrived on
+///
+/// `epoch` starts at 1 and increases by one with every successful (re)connection, so
+/// frames from a replaced connection carry a lower epoch than those from its successor.
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    /// Connection epoch the message was received on
+    pub epoch: u64,
+    /// The message itself
+    pub msg: WebSocketMessage,
+}
+
+/// Drops messages from connections older than the newest one seen so far
+#[derive(Debug, Clone, Default)]
+pub struct EpochFilter {
+    latest: u64,
+}
+
+impl EpochFilter {
+    /// Create a filter that has not seen any connection yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Newest connection epoch seen so far (0 before the first message)
+    pub fn latest(&self) -> u64 {
+        self.latest
+    }
+
+    /// Unwrap `envelope`, or return `None` if it comes from a stale connection
+    pub fn admit(&mut self, envelope: Envelope) -> Option<WebSocketMessage> {
+        if envelope.epoch < self.latest {
+            return None;
+        }
+        self.latest = envelope.epoch;
+        Some(envelope.msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Ticker, TickerData};
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    /// A ticker frame as pushed on `ticker@1_2`; every field value is distinct so a
+    /// swapped assignment in the conversion shows up as a mismatch.
+    fn entry() -> TickerEntry {
+        let frame = r#"{
+            "marketId": "1_2",
+            "baseTokenId": "1",
+            "quoteTokenId": "2",
+            "price": "0.1523",
+            "open24h": "0.15",
+            "high24h": "0.16",
+            "low24h": "0.14",
+            "volume24h": "12345.6",
+            "quoteVolume24h": "1880.2"
+        }"#;
+        serde_json::from_str(frame).unwrap()
+    }
+
+    #[test]
+    fn ticker_entry_converts_to_equivalent_rest_ticker() {
+        let ticker = Ticker::from(entry());
+        assert_eq!(ticker.market_id, "1_2");
+        assert_eq!(ticker.base_token_id, "1");
+        assert_eq!(ticker.quote_token_id, "2");
+        assert_eq!(ticker.price, "0.1523");
+        assert_eq!(ticker.open_24h, "0.15");
+        assert_eq!(ticker.high_24h, "0.16");
+        assert_eq!(ticker.low_24h, "0.14");
+        assert_eq!(ticker.volume_24h, "12345.6");
+        assert_eq!(ticker.quote_volume_24h, "1880.2");
+        // Both types share the wire format, so the serialized forms must match too.
+        assert_eq!(
+            serde_json::to_value(&ticker).unwrap(),
+            serde_json::to_value(entry()).unwrap()
+        );
+    }
+
+    #[test]
+    fn rest_ticker_round_trips_back_to_ticker_entry() {
+        let back = TickerEntry::from(Ticker::from(entry()));
+        assert_eq!(
+            serde_json::to_value(back).unwrap(),
+            serde_json::to_value(entry()).unwrap()
+        );
+    }
+
+    #[test]
+    fn ticker_data_accessors_agree_across_sources() {
+        fn snapshot(t: &impl TickerData) -> (String, Decimal, Decimal, Decimal) {
+            (
+                t.market_id().to_string(),
+                t.price_decimal().unwrap(),
+                t.volume_24h_decimal().unwrap(),
+                t.quote_volume_24h_decimal().unwrap(),
+            )
+        }
+        let ws = entry();
+        let rest = Ticker::from(entry());
+        assert_eq!(snapshot(&ws), snapshot(&rest));
+        assert_eq!(
+            ws.price_decimal().unwrap(),
+            Decimal::from_str("0.1523").unwrap()
+        );
+        assert_eq!(
+            rest.high_24h_decimal().unwrap(),
+            Decimal::from_str("0.16").unwrap()
+        );
+        assert_eq!(
+            rest.low_24h_decimal().unwrap(),
+            Decimal::from_str("0.14").unwrap()
+        );
+        assert_eq!(
+            rest.open_24h_decimal().unwrap(),
+            Decimal::from_str("0.15").unwrap()
+        );
+    }
+
+    #[test]
+    fn non_numeric_price_surfaces_decimal_error() {
+        let mut ws = entry();
+        ws.price = "n/a".to_string();
+        assert!(ws.price_decimal().is_err());
+    }
+
+    #[test]
+    fn epoch_filter_drops_frames_from_a_replaced_connection() {
+        let envelope = |epoch, trade_id: &str| Envelope {
+            epoch,
+            msg: WebSocketMessage::Generic(serde_json::json!({ "id": trade_id })),
+        };
+        let frames = vec![
+            envelope(1, "a"),
+            envelope(1, "b"),
+            Envelope {
+                epoch: 1,
+                msg: WebSocketMessage::Disconnected,
+            },
+            envelope(2, "c"),
+            // Late frame still in flight from the first connection.
+            envelope(1, "late"),
+            envelope(2, "d"),
+        ];
+
+        let mut filter = EpochFilter::new();
+        let mut kept = Vec::new();
+        for frame in frames {
+            match filter.admit(frame) {
+                Some(WebSocketMessage::Generic(v)) => {
+                    kept.push(v["id"].as_str().unwrap().to_string())
+                }
+                Some(WebSocketMessage::Disconnected) => kept.push("disconnected".to_string()),
+                Some(other) => panic!("unexpected {:?}", other),
+                None => {}
+            }
+        }
+        assert_eq!(kept, vec!["a", "b", "disconnected", "c", "d"]);
+        assert_eq!(filter.latest(), 2);
+    }
+
+    fn subscription(channel: &str, result: serde_json::Value) -> WebSocketMessage {
+        serde_json::from_value(serde_json::json!({
+            "method": "subscription",
+            "params": { "channel": channel, "result": result }
+        }))
+        .unwrap()
+    }
+
+    fn trade_json() -> serde_json::Value {
+        serde_json::json!({
+            "tradeId": "t1",
+            "marketId": "1_2",
+            "price": "0.15",
+            "quantity": "10",
+            "buyOrderId": "b",
+            "sellOrderId": "s",
+            "createdAt": 1,
+            "isBuyerMaker": true
+        })
+    }
+
+    #[test]
+    fn subscription_frames_are_routed_by_channel() {
+        match subscription("trade@1_2", serde_json::json!([trade_json()])) {
+            WebSocketMessage::TradeMsg { method, params } => {
+                assert_eq!(method, "subscription");
+                assert_eq!(params.channel, "trade@1_2");
+                assert_eq!(params.result[0].trade_id, "t1");
+            }
+            other => panic!("expected a trade, got {:?}", other),
+        }
+
+        let ticker = serde_json::to_value(entry()).unwrap();
+        match subscription("ticker@1_2", serde_json::json!([ticker])) {
+            WebSocketMessage::TickerMsg { params, .. } => {
+                assert_eq!(params.result[0].price, "0.1523")
+            }
+            other => panic!("expected a ticker, got {:?}", other),
+        }
+
+        let depth = serde_json::json!({
+            "marketId": "1_2",
+            "bids": [["0.15", "10"]],
+            "asks": null,
+            "firstId": 1,
+            "finalId": 2,
+            "time": 3
+        });
+        match subscription("depth@1_2", depth) {
+            WebSocketMessage::DepthMsg { params, .. } => {
+                assert_eq!(params.result.final_id, 2);
+                assert!(params.result.asks.is_none());
+            }
+            other => panic!("expected depth, got {:?}", other),
+        }
+
+        let kline = serde_json::json!({
+            "marketId": "1_2",
+            "interval": "1m",
+            "openTime": 1_700_000_040_000i64,
+            "closeTime": 1_700_000_099_999i64,
+            "open": "0.15",
+            "high": "0.152",
+            "low": "0.149",
+            "close": "0.151",
+            "volume": "1200",
+            "quoteVolume": "180.6",
+            "isClosed": false
+        });
+        match subscription("kline@1_2@1m", kline) {
+            WebSocketMessage::KlineMsg { params, .. } => {
+                assert_eq!(params.channel, "kline@1_2@1m");
+                assert_eq!(params.result.market_id, "1_2");
+                assert_eq!(params.result.interval().unwrap(), KlineInterval::Minute1);
+                assert_eq!(params.result.open_time, 1_700_000_040_000);
+                assert_eq!(params.result.high, "0.152");
+                assert_eq!(params.result.quote_volume, "180.6");
+                assert!(!params.result.is_closed);
+            }
+            other => panic!("expected a kline, got {:?}", other),
+        }
+
+        let event = serde_json::json!({
+            "topic": "ACCOUNT",
+            "eventType": "DEPOSIT",
+            "eventTime": 1,
+            "blockNumber": 2,
+            "accountAddress": "0xabc",
+            "txHash": "0xdef",
+            "tokenId": "2",
+            "amount": "5"
+        });
+        match subscription("userEvent@0xabc", event) {
+            WebSocketMessage::UserEventMsg { params, .. } => {
+                assert_eq!(params.result.topic(), "ACCOUNT")
+            }
+            other => panic!("expected a user event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn acks_and_unknown_frames_keep_their_variants() {
+        let ack: WebSocketMessage = serde_json::from_str(r#"{"id":7,"result":"success"}"#).unwrap();
+        assert!(matches!(ack, WebSocketMessage::Ack { id: 7, .. }));
+
+        let error: WebSocketMessage =
+            serde_json::from_str(r#"{"id":7,"error":{"message":"bad channel"}}"#).unwrap();
+        assert!(matches!(error, WebSocketMessage::Generic(_)));
+
+        // Trade-shaped payload on a channel the spot types do not cover
+        assert!(matches!(
+            subscription("perp_aggTrade@BTC", serde_json::json!([trade_json()])),
+            WebSocketMessage::Generic(_)
+        ));
+    }
+
+    #[test]
+    fn payload_fitting_several_variants_follows_its_channel() {
+        // Carries every trade and ticker field, so shape matching alone would take it
+        // for a trade (the first variant it fits).
+        let mut both = trade_json();
+        both.as_object_mut().unwrap().extend(
+            serde_json::to_value(entry())
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .clone(),
+        );
+
+        match subscription("ticker@1_2", serde_json::json!([both.clone()])) {
+            WebSocketMessage::TickerMsg { params, .. } => {
+                assert_eq!(params.result[0].market_id, "1_2")
+            }
+            other => panic!("expected a ticker, got {:?}", other),
+        }
+        assert!(matches!(
+            subscription("trade@1_2", serde_json::json!([both])),
+            WebSocketMessage::TradeMsg { .. }
+        ));
+    }
+
+    #[test]
+    fn payload_not_matching_its_channel_is_rejected() {
+        let frame = serde_json::json!({
+            "method": "subscription",
+            "params": { "channel": "trade@1_2", "result": [serde_json::to_value(entry()).unwrap()] }
+        });
+        assert!(serde_json::from_value::<WebSocketMessage>(frame).is_err());
+    }
+
+    #[test]
+    fn kline_intervals_round_trip_through_their_suffix() {
+        for interval in KlineInterval::ALL {
+            assert_eq!(
+                interval.to_string().parse::<KlineInterval>().unwrap(),
+                interval
+            );
+        }
+        for bad in ["", "1M", "2m", "60", "1m "] {
+            assert!(bad.parse::<KlineInterval>().is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn channels_round_trip_through_their_wire_form() {
+        let channels = [
+            (Channel::Trade("KAIA/USDT".into()), "trade@KAIA/USDT"),
+            (Channel::Ticker("all".into()), "ticker@all"),
+            (Channel::Depth("KAIA/USDT".into()), "depth@KAIA/USDT"),
+            (Channel::UserEvent("0xabc".into()), "userEvent@0xabc"),
+            (
+                Channel::Kline("KAIA/USDT".into(), KlineInterval::Hour4),
+                "kline@KAIA/USDT@4h",
+            ),
+        ];
+        for (channel, wire) in channels {
+            assert_eq!(channel.to_string(), wire);
+            assert_eq!(wire.parse::<Channel>().unwrap(), channel);
+        }
+        for bad in [
+            "trade",
+            "trade@",
+            "trades@KAIA/USDT",
+            "trade@KAIA/USDT@1m",
+            "kline@KAIA/USDT",
+            "kline@KAIA/USDT@7m",
+            "perp_ticker@1",
+        ] {
+            assert!(bad.parse::<Channel>().is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn slightly_off_ticker_frame_is_recovered_by_try_typed() {
+        // Numeric prices and volume, and a lone entry instead of a list
+        let frame = serde_json::json!({
+            "method": "subscription",
+            "params": {
+                "channel": "ticker@1_2",
+                "result": {
+                    "marketId": "1_2",
+                    "baseTokenId": "1",
+                    "quoteTokenId": "2",
+                    "price": 0.15,
+                    "open24h": "0.14",
+                    "high24h": 0.16,
+                    "low24h": "0.13",
+                    "volume24h": 1000,
+                    "quoteVolume24h": "150"
+                }
+            }
+        });
+        // Strict parsing rejects it, so the manager forwards it as Generic
+        assert!(serde_json::from_value::<WebSocketMessage>(frame.clone()).is_err());
+        let generic = WebSocketMessage::Generic(frame);
+
+        let typed = generic.try_typed().expect("ticker frame");
+        assert_eq!(typed.channel(), "ticker@1_2");
+        match WebSocketMessage::from(typed) {
+            WebSocketMessage::TickerMsg { params, .. } => {
+                assert_eq!(params.result.len(), 1);
+                assert_eq!(params.result[0].price, "0.15");
+                assert_eq!(params.result[0].high_24h, "0.16");
+                assert_eq!(params.result[0].volume_24h, "1000");
+            }
+            other => panic!("expected a ticker, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn try_typed_coerces_integer_fields_and_leaves_other_frames_alone() {
+        let mut trade = trade_json();
+        trade["createdAt"] = serde_json::json!(trade["createdAt"].to_string());
+        trade["isBuyerMaker"] = serde_json::json!("true");
+        trade["price"] = serde_json::json!(1.5);
+        let generic = WebSocketMessage::Generic(serde_json::json!({
+            "method": "subscription",
+            "params": { "channel": "trade@1_2", "result": [trade] }
+        }));
+        match generic.try_typed() {
+            Some(TypedFrame::Trade(params)) => {
+                assert_eq!(params.result[0].price, "1.5");
+                assert!(params.result[0].is_buyer_maker);
+            }
+            other => panic!("expected a trade, got {:?}", other),
+        }
+
+        let typed = subscription("ticker@1_2", serde_json::json!([entry()]));
+        assert!(matches!(typed.try_typed(), Some(TypedFrame::Ticker(_))));
+        for other in [
+            WebSocketMessage::Generic(serde_json::json!({ "id": 7, "error": "bad channel" })),
+            subscription("perp_aggTrade@BTC", serde_json::json!([trade_json()])),
+            WebSocketMessage::Disconnected,
+        ] {
+            assert!(other.try_typed().is_none(), "{:?}", other);
+        }
+    }
+}
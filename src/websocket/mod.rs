@@ -7,7 +7,24 @@ pub mod manager;
 pub mod trade;
 
 #[cfg(feature = "websocket")]
-pub use manager::{ConnectionState, WsConfig, WsManager};
+mod proxy;
+
+#[cfg(feature = "websocket")]
+pub mod queue;
+
+#[cfg(feature = "replay")]
+pub mod replay;
+
+#[cfg(feature = "websocket")]
+pub use manager::{
+    AuthFrameBuilder, ConnectionObserver, ConnectionState, WsConfig, WsHealth, WsManager,
+};
+
+#[cfg(feature = "websocket")]
+pub use queue::{EnvelopeReceiver, MessageReceiver, OverflowPolicy};
+
+#[cfg(feature = "replay")]
+pub use replay::{Pacing, WsRecorder, WsReplayer};
 
 #[cfg(feature = "websocket")]
 pub use trade::{TradeWebSocket, TradeWsError, TradeWsResponse};
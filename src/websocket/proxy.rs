@@ -0,0 +1,287 @@
+//! HTTP `CONNECT` tunnelling and TLS settings for WebSocket connections, see
+//! [`WsConfig::proxy`] and [`WsConfig::tls`]
+//!
+//! tungstenite opens its own TCP connection and knows nothing about proxies, so when one
+//! is configured the tunnel is opened here and the WebSocket (and, for `wss://`, TLS)
+//! handshake runs inside it.
+//!
+//! [`WsConfig::proxy`]: super::WsConfig::proxy
+//! [`WsConfig::tls`]: super::WsConfig::tls
+
+use std::io;
+
+use base64::Engine;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_tungstenite::{
+    client_async_tls_with_config, connect_async_tls_with_config,
+    tungstenite::{
+        client::IntoClientRequest, handshake::client::Response, protocol::WebSocketConfig,
+        Error as WsError,
+    },
+    Connector, MaybeTlsStream, WebSocketStream,
+};
+use tracing::warn;
+use url::Url;
+
+use crate::signer::TlsConfig;
+
+/// Longest proxy response head read before the tunnel is given up on
+const MAX_RESPONSE_HEAD: usize = 8 * 1024;
+
+/// Connect to `request`, through the HTTP proxy at `proxy` if one is given, with the
+/// TLS settings `tls`
+pub(crate) async fn connect<R>(
+    request: R,
+    proxy: Option<&str>,
+    tls: &TlsConfig,
+    config: Option<WebSocketConfig>,
+) -> Result<(WebSocketStream<MaybeTlsStream<TcpStream>>, Response), WsError>
+where
+    R: IntoClientRequest + Unpin,
+{
+    let connector = tls_connector(tls)?;
+    let Some(proxy) = proxy else {
+        return connect_async_tls_with_config(request, config, false, connector).await;
+    };
+    let request = request.into_client_request()?;
+    let uri = request.uri();
+    let host = uri
+        .host()
+        .ok_or_else(|| invalid("WebSocket URL has no host"))?;
+    let port = uri
+        .port_u16()
+        .or(match uri.scheme_str() {
+            Some("wss") => Some(443),
+            Some("ws") => Some(80),
+            _ => None,
+        })
+        .ok_or_else(|| invalid("WebSocket URL must be ws:// or wss://"))?;
+    let stream = tunnel(proxy, host, port).await?;
+    client_async_tls_with_config(request, stream, config, connector).await
+}
+
+/// TLS connector applying `tls`, or `None` for tungstenite's default one
+fn tls_connector(tls: &TlsConfig) -> Result<Option<Connector>, WsError> {
+    if *tls == TlsConfig::default() {
+        return Ok(None);
+    }
+    let mut builder = native_tls::TlsConnector::builder();
+    for pem in &tls.root_certs {
+        let cert = native_tls::Certificate::from_pem(pem)
+            .map_err(|e| invalid(format!("Invalid root certificate: {}", e)))?;
+        builder.add_root_certificate(cert);
+    }
+    if tls.accept_invalid_certs {
+        warn!("TLS certificate verification is disabled for WebSocket connections");
+        builder.danger_accept_invalid_certs(true);
+    }
+    let connector = builder.build().map_err(|e| WsError::Tls(e.into()))?;
+    Ok(Some(Connector::NativeTls(connector)))
+}
+
+/// Open a TCP connection to `host:port` through the HTTP proxy at `proxy`
+async fn tunnel(proxy: &str, host: &str, port: u16) -> Result<TcpStream, WsError> {
+    let proxy = Url::parse(proxy).map_err(|e| invalid(format!("Invalid proxy URL: {}", e)))?;
+    if proxy.scheme() != "http" {
+        return Err(invalid(format!(
+            "Unsupported WebSocket proxy scheme {:?}, only http:// proxies can tunnel WebSockets",
+            proxy.scheme()
+        )));
+    }
+    let proxy_host = proxy
+        .host_str()
+        .ok_or_else(|| invalid("Proxy URL has no host"))?;
+    let proxy_port = proxy.port_or_known_default().unwrap_or(80);
+    let mut stream = TcpStream::connect((proxy_host, proxy_port)).await?;
+
+    let target = if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+    let mut head = format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n");
+    if !proxy.username().is_empty() {
+        // `Url` keeps the userinfo percent-encoded; the header carries it decoded
+        let mut credentials = urlencoding::decode_binary(proxy.username().as_bytes()).into_owned();
+        credentials.push(b':');
+        credentials.extend_from_slice(&urlencoding::decode_binary(
+            proxy.password().unwrap_or_default().as_bytes(),
+        ));
+        head.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            base64::engine::general_purpose::STANDARD.encode(credentials)
+        ));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await?;
+
+    // Read the response head byte by byte so nothing past it (the start of the tunnelled
+    // stream) is consumed
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_RESPONSE_HEAD {
+            return Err(invalid("Proxy response head is too long"));
+        }
+        let byte = stream.read_u8().await?;
+        response.push(byte);
+    }
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1);
+    if status != Some("200") {
+        return Err(WsError::Io(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("Proxy refused to tunnel to {}: {}", target, status_line),
+        )));
+    }
+    Ok(stream)
+}
+
+fn invalid(message: impl Into<String>) -> WsError {
+    WsError::Io(io::Error::new(io::ErrorKind::InvalidInput, message.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// Proxy that answers the first `CONNECT` with `status` and, on success, serves
+    /// the WebSocket itself, echoing one text frame. Returns its URL and the request
+    /// head it received.
+    async fn proxy(status: &'static str) -> (String, tokio::sync::oneshot::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (head_tx, head_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut head = Vec::new();
+            while !head.ends_with(b"\r\n\r\n") {
+                head.push(socket.read_u8().await.unwrap());
+            }
+            let _ = head_tx.send(String::from_utf8(head).unwrap());
+            socket
+                .write_all(format!("HTTP/1.1 {}\r\n\r\n", status).as_bytes())
+                .await
+                .unwrap();
+            if !status.starts_with("200") {
+                return;
+            }
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            if let Some(Ok(message)) = ws.next().await {
+                ws.send(message).await.unwrap();
+            }
+        });
+        (format!("http://user:secret@{}", addr), head_rx)
+    }
+
+    #[tokio::test]
+    async fn websocket_is_tunnelled_through_the_proxy() {
+        let (proxy_url, head) = proxy("200 Connection established").await;
+        let (mut ws, _) = connect(
+            "ws://alphasec.invalid:8080/ws",
+            Some(&proxy_url),
+            &TlsConfig::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        ws.send(Message::Text("hello".to_string())).await.unwrap();
+        assert_eq!(
+            ws.next().await.unwrap().unwrap(),
+            Message::Text("hello".to_string())
+        );
+
+        let head = head.await.unwrap();
+        assert!(
+            head.starts_with("CONNECT alphasec.invalid:8080 HTTP/1.1\r\n"),
+            "{}",
+            head
+        );
+        // base64("user:secret")
+        assert!(
+            head.contains("Proxy-Authorization: Basic dXNlcjpzZWNyZXQ=\r\n"),
+            "{}",
+            head
+        );
+    }
+
+    #[tokio::test]
+    async fn percent_encoded_proxy_credentials_are_decoded() {
+        let (proxy_url, head) = proxy("407 Proxy Authentication Required").await;
+        let proxy_url = proxy_url.replace("user:secret", "us%40er:p%3As%20s");
+        let _ = connect(
+            "ws://alphasec.invalid/ws",
+            Some(&proxy_url),
+            &TlsConfig::default(),
+            None,
+        )
+        .await;
+
+        let head = head.await.unwrap();
+        // base64("us@er:p:s s")
+        assert!(
+            head.contains("Proxy-Authorization: Basic dXNAZXI6cDpzIHM=\r\n"),
+            "{}",
+            head
+        );
+    }
+
+    #[tokio::test]
+    async fn refused_tunnels_and_unsupported_proxies_fail() {
+        let (proxy_url, _head) = proxy("407 Proxy Authentication Required").await;
+        let err = connect(
+            "ws://alphasec.invalid/ws",
+            Some(&proxy_url),
+            &TlsConfig::default(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("407"), "{}", err);
+
+        let err = connect(
+            "ws://alphasec.invalid/ws",
+            Some("socks5://127.0.0.1:1"),
+            &TlsConfig::default(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("socks5"), "{}", err);
+    }
+
+    #[tokio::test]
+    #[ignore = "integration: serves TLS on a local port, run with --ignored"]
+    async fn root_cert_admits_a_self_signed_websocket_server() {
+        use crate::api::client::tests::{tls_server, SELF_SIGNED_CERT};
+
+        let port = tls_server(|stream| {
+            let mut ws = tokio_tungstenite::tungstenite::accept(stream).unwrap();
+            if let Ok(message) = ws.read() {
+                let _ = ws.send(message);
+            }
+        });
+        let url = format!("wss://localhost:{}/ws", port);
+
+        assert!(connect(url.as_str(), None, &TlsConfig::default(), None)
+            .await
+            .is_err());
+
+        let tls = TlsConfig {
+            root_certs: vec![SELF_SIGNED_CERT.to_vec()],
+            ..TlsConfig::default()
+        };
+        let (mut ws, _) = connect(url.as_str(), None, &tls, None).await.unwrap();
+        ws.send(Message::Text("hello".to_string())).await.unwrap();
+        assert_eq!(
+            ws.next().await.unwrap().unwrap(),
+            Message::Text("hello".to_string())
+        );
+    }
+}
@@ -0,0 +1,451 @@
+//! Bounded queue between the connection task and the message consumer, see
+//! [`OverflowPolicy`]
+
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use futures_util::Stream;
+use tokio::sync::{mpsc::error::TryRecvError, Notify};
+
+use crate::types::{Envelope, WebSocketMessage};
+
+/// What the connection task does with a message when the consumer's queue is full
+///
+/// The queue holds [`WsConfig::message_queue_size`](super::WsConfig::message_queue_size)
+/// messages. Only market data (see [`WebSocketMessage::is_market_data`]) is ever
+/// dropped: it is superseded by the next update, while user events (fills, order and
+/// balance changes), acks and connection notices must not be lost. Under every policy
+/// those wait for room, so the connection stops reading until the consumer catches up
+/// and the server's buffers (and eventually its patience) absorb the backlog. Pings are
+/// not answered while waiting.
+///
+/// - [`Self::DropOldest`] evicts the oldest queued market data, keeping the consumer on
+///   the freshest data at the cost of gaps. Depth books rebuilt from the stream must be
+///   re-snapshotted after a drop.
+/// - [`Self::Block`] drops nothing; market data waits like user events. A receiver
+///   that is never taken blocks the connection for good.
+/// - [`Self::DropNewest`] keeps what is queued and discards arriving market data, which
+///   suits consumers that process a backlog in order and resynchronize from REST
+///   afterwards.
+///
+/// Every dropped message is counted in
+/// [`ConnectionStats::messages_dropped`](super::manager::ConnectionStats::messages_dropped),
+/// and the receiver gets a [`WebSocketMessage::Lagged`] with the number dropped before
+/// its next message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Wait for the consumer to make room
+    Block,
+    /// Evict the oldest queued market data to make room, or discard the arrival if it
+    /// is market data and nothing queued is
+    #[default]
+    DropOldest,
+    /// Discard the arriving message if it is market data
+    DropNewest,
+}
+
+#[derive(Debug)]
+struct State {
+    items: VecDeque<Envelope>,
+    /// Messages dropped since the receiver last got a [`WebSocketMessage::Lagged`], and
+    /// the epoch of the latest of them
+    lagged: Option<(u64, u64)>,
+    senders: usize,
+    receiver_alive: bool,
+    waker: Option<Waker>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    /// Signalled whenever the receiver takes a message or goes away
+    space: Notify,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Create a queue holding up to `capacity` (at least 1) envelopes; overflowing messages
+/// are handled by `policy` and counted in `dropped`
+pub(crate) fn channel(
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: Arc<AtomicU64>,
+) -> (Sender, EnvelopeReceiver) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            items: VecDeque::new(),
+            lagged: None,
+            senders: 1,
+            receiver_alive: true,
+            waker: None,
+        }),
+        space: Notify::new(),
+        capacity: capacity.max(1),
+        policy,
+        dropped,
+    });
+    let receiver = Receiver {
+        handle: ReceiverHandle(Arc::clone(&shared)),
+        open: |envelope| envelope,
+    };
+    (Sender { shared }, receiver)
+}
+
+/// Sending half of a message queue, held by the connection task
+#[derive(Debug)]
+pub(crate) struct Sender {
+    shared: Arc<Shared>,
+}
+
+impl Sender {
+    /// Queue `envelope`, applying the overflow policy if the queue is full
+    ///
+    /// Fails, handing the envelope back, once the receiver has been dropped. A message
+    /// discarded by the policy is not an error.
+    pub(crate) async fn send(&self, envelope: Envelope) -> std::result::Result<(), Envelope> {
+        let mut envelope = Some(envelope);
+        loop {
+            {
+                let mut state = self.shared.state.lock().unwrap();
+                if !state.receiver_alive {
+                    return Err(envelope.take().expect("envelope is only taken once"));
+                }
+                if state.items.len() >= self.shared.capacity {
+                    let arrival = envelope.as_ref().expect("envelope is only taken once");
+                    let oldest_market_data = match self.shared.policy {
+                        OverflowPolicy::DropOldest => state
+                            .items
+                            .iter()
+                            .position(|queued| queued.msg.is_market_data()),
+                        _ => None,
+                    };
+                    if let Some(index) = oldest_market_data {
+                        let evicted = state.items.remove(index).expect("index is in bounds");
+                        self.lag(&mut state, evicted.epoch);
+                    } else if self.shared.policy != OverflowPolicy::Block
+                        && arrival.msg.is_market_data()
+                    {
+                        let epoch = arrival.epoch;
+                        self.lag(&mut state, epoch);
+                        return Ok(());
+                    }
+                }
+                if state.items.len() < self.shared.capacity {
+                    state
+                        .items
+                        .push_back(envelope.take().expect("envelope is only taken once"));
+                    if let Some(waker) = state.waker.take() {
+                        waker.wake();
+                    }
+                    return Ok(());
+                }
+            }
+            // Only reached when nothing could be dropped. A message taken between the check above and
+            // this wait leaves a permit, so the wakeup is not missed.
+            self.shared.space.notified().await;
+        }
+    }
+
+    /// Count a message of connection `epoch` dropped by the policy
+    fn lag(&self, state: &mut State, epoch: u64) {
+        let dropped = state.lagged.map_or(0, |(dropped, _)| dropped);
+        state.lagged = Some((dropped + 1, epoch));
+        self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Whether the receiver is still alive, i.e. [`Self::send`] can still succeed
+    pub(crate) fn is_open(&self) -> bool {
+        self.shared.state.lock().unwrap().receiver_alive
+    }
+}
+
+impl Clone for Sender {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().unwrap().senders += 1;
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.senders -= 1;
+        if state.senders == 0 {
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Receiving half of the manager's message queue, see [`OverflowPolicy`]
+///
+/// Yields `None` once the manager has stopped and every queued message has been
+/// received. Also usable as a [`Stream`].
+#[derive(Debug)]
+pub struct Receiver<T> {
+    handle: ReceiverHandle,
+    open: fn(Envelope) -> T,
+}
+
+/// The queue as seen by its receiver, marking it abandoned when dropped
+#[derive(Debug)]
+struct ReceiverHandle(Arc<Shared>);
+
+impl Drop for ReceiverHandle {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().unwrap();
+        state.receiver_alive = false;
+        state.items.clear();
+        drop(state);
+        // Release a sender blocked on a full queue
+        self.0.space.notify_one();
+    }
+}
+
+/// Receiver of bare messages, see [`super::WsManager::take_message_receiver`]
+pub type MessageReceiver = Receiver<WebSocketMessage>;
+
+/// Receiver of epoch-tagged messages, see [`super::WsManager::take_envelope_receiver`]
+pub type EnvelopeReceiver = Receiver<Envelope>;
+
+impl EnvelopeReceiver {
+    /// Receive the messages without their connection epoch
+    pub fn into_messages(self) -> MessageReceiver {
+        Receiver {
+            handle: self.handle,
+            open: |envelope| envelope.msg,
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Receive the next message, waiting for one to arrive
+    pub async fn recv(&mut self) -> Option<T> {
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Receive the next message if one is queued
+    ///
+    /// After a drop, the next message is a [`WebSocketMessage::Lagged`] counting the
+    /// messages dropped.
+    pub fn try_recv(&mut self) -> std::result::Result<T, TryRecvError> {
+        let mut state = self.handle.0.state.lock().unwrap();
+        if let Some((dropped, epoch)) = state.lagged.take() {
+            return Ok((self.open)(Envelope {
+                epoch,
+                msg: WebSocketMessage::Lagged { dropped },
+            }));
+        }
+        match state.items.pop_front() {
+            Some(envelope) => {
+                drop(state);
+                self.handle.0.space.notify_one();
+                Ok((self.open)(envelope))
+            }
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Poll for the next message, see [`Self::recv`]
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.try_recv() {
+            Ok(message) => Poll::Ready(Some(message)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => {
+                let mut state = self.handle.0.state.lock().unwrap();
+                // A message or the last sender's drop may have slipped in since
+                if !state.items.is_empty() || state.lagged.is_some() || state.senders == 0 {
+                    drop(state);
+                    return self.poll_recv(cx);
+                }
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Number of queued messages
+    pub fn len(&self) -> usize {
+        self.handle.0.state.lock().unwrap().items.len()
+    }
+
+    /// Whether no message is queued
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Queue of bare messages for tests feeding a consumer directly
+    pub(crate) fn message_channel() -> (Sender, MessageReceiver) {
+        let (tx, rx) = channel(64, OverflowPolicy::Block, Arc::default());
+        (tx, rx.into_messages())
+    }
+
+    impl Sender {
+        /// Send `msg` tagged with epoch 1, panicking if the receiver is gone
+        pub(crate) async fn send_message(&self, msg: WebSocketMessage) {
+            self.send(Envelope { epoch: 1, msg }).await.unwrap();
+        }
+    }
+
+    /// Market data envelope tagged with epoch `n`
+    fn envelope(n: u64) -> Envelope {
+        let msg = serde_json::from_value(serde_json::json!({
+            "method": "subscription",
+            "params": {"channel": "ticker@5_2", "result": []}
+        }))
+        .unwrap();
+        Envelope { epoch: n, msg }
+    }
+
+    /// User event envelope tagged with epoch `n`
+    fn user_event(n: u64) -> Envelope {
+        Envelope {
+            epoch: n,
+            msg: WebSocketMessage::Generic(serde_json::json!({ "n": n })),
+        }
+    }
+
+    /// Everything left in the queue once `tx` is gone: the epoch of each message, or
+    /// `lagged N` for a notice of N drops
+    async fn epochs(tx: Sender, mut rx: EnvelopeReceiver) -> Vec<String> {
+        drop(tx);
+        let mut epochs = Vec::new();
+        while let Some(envelope) = rx.recv().await {
+            epochs.push(match envelope.msg {
+                WebSocketMessage::Lagged { dropped } => format!("lagged {}", dropped),
+                _ => envelope.epoch.to_string(),
+            });
+        }
+        epochs
+    }
+
+    #[tokio::test]
+    async fn full_queue_drops_by_policy_and_counts_the_drops() {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let (tx, rx) = channel(2, OverflowPolicy::DropOldest, Arc::clone(&dropped));
+        for n in 1..=3 {
+            tx.send(envelope(n)).await.unwrap();
+        }
+        assert_eq!(rx.len(), 2);
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+        assert_eq!(epochs(tx, rx).await, ["lagged 1", "2", "3"]);
+
+        let dropped = Arc::new(AtomicU64::new(0));
+        let (tx, rx) = channel(2, OverflowPolicy::DropNewest, Arc::clone(&dropped));
+        for n in 1..=4 {
+            tx.send(envelope(n)).await.unwrap();
+        }
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+        assert_eq!(epochs(tx, rx).await, ["lagged 2", "1", "2"]);
+    }
+
+    #[tokio::test]
+    async fn lag_notice_counts_the_drops_since_the_last_one() {
+        let (tx, mut rx) = channel(1, OverflowPolicy::DropOldest, Arc::default());
+        for n in 1..=3 {
+            tx.send(envelope(n)).await.unwrap();
+        }
+        assert!(matches!(
+            rx.recv().await.unwrap().msg,
+            WebSocketMessage::Lagged { dropped: 2 }
+        ));
+        assert_eq!(rx.recv().await.unwrap().epoch, 3);
+        tx.send(envelope(4)).await.unwrap();
+        tx.send(envelope(5)).await.unwrap();
+        assert_eq!(epochs(tx, rx).await, ["lagged 1", "5"]);
+    }
+
+    #[tokio::test]
+    async fn user_events_are_never_dropped() {
+        for policy in [OverflowPolicy::DropOldest, OverflowPolicy::DropNewest] {
+            let dropped = Arc::new(AtomicU64::new(0));
+            let (tx, mut rx) = channel(2, policy, Arc::clone(&dropped));
+            tx.send(envelope(1)).await.unwrap();
+            tx.send(user_event(2)).await.unwrap();
+            if policy == OverflowPolicy::DropOldest {
+                // Evicts the queued market data rather than a user event
+                tx.send(user_event(3)).await.unwrap();
+            }
+            // Market data arriving at a full queue is dropped
+            tx.send(envelope(4)).await.unwrap();
+
+            // With nothing left to drop, a user event waits for room
+            let sender = tokio::spawn(async move {
+                tx.send(user_event(5)).await.unwrap();
+                tx
+            });
+            tokio::task::yield_now().await;
+            assert!(!sender.is_finished(), "{policy:?}");
+            assert!(matches!(
+                rx.recv().await.unwrap().msg,
+                WebSocketMessage::Lagged { .. }
+            ));
+            let first = rx.recv().await.unwrap().epoch;
+            let tx = sender.await.unwrap();
+            let (expected_first, expected_rest, expected_dropped) = match policy {
+                OverflowPolicy::DropOldest => (2, ["3", "5"], 2),
+                _ => (1, ["2", "5"], 1),
+            };
+            assert_eq!(first, expected_first, "{policy:?}");
+            assert_eq!(epochs(tx, rx).await, expected_rest, "{policy:?}");
+            assert_eq!(
+                dropped.load(Ordering::Relaxed),
+                expected_dropped,
+                "{policy:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn blocking_sender_waits_for_the_consumer() {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let (tx, mut rx) = channel(1, OverflowPolicy::Block, Arc::clone(&dropped));
+        tx.send(envelope(1)).await.unwrap();
+        let sender = tokio::spawn(async move {
+            tx.send(envelope(2)).await.unwrap();
+            tx
+        });
+        tokio::task::yield_now().await;
+        assert!(!sender.is_finished());
+
+        assert_eq!(rx.recv().await.unwrap().epoch, 1);
+        let tx = sender.await.unwrap();
+        assert_eq!(dropped.load(Ordering::Relaxed), 0);
+        assert_eq!(epochs(tx, rx).await, ["2"]);
+    }
+
+    #[tokio::test]
+    async fn dropped_receiver_releases_a_blocked_sender() {
+        let (tx, rx) = channel(1, OverflowPolicy::Block, Arc::default());
+        tx.send(envelope(1)).await.unwrap();
+        let sender = tokio::spawn(async move { tx.send(envelope(2)).await });
+        tokio::task::yield_now().await;
+        drop(rx);
+        assert_eq!(sender.await.unwrap().unwrap_err().epoch, 2);
+    }
+}
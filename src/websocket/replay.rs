@@ -0,0 +1,398 @@
+//! Recording a WebSocket feed to a file and replaying it without a connection, for
+//! deterministic tests of code consuming a [`MessageReceiver`]
+//!
+//! A recording holds one JSON object per line: the wall-clock time the message was
+//! received (`atMs`, Unix milliseconds) and either the frame (`frame`) or one of the
+//! SDK's own `closed`/`disconnected` notifications. Pings and pongs are not recorded.
+//!
+//! ```no_run
+//! # async fn run(agent: &alphasec_rs::Agent) -> alphasec_rs::Result<()> {
+//! use alphasec_rs::websocket::{Pacing, WsRecorder, WsReplayer};
+//!
+//! // Record the live feed while consuming it as usual
+//! let rx = agent.take_message_receiver().await.expect("receiver not taken yet");
+//! let mut rx = WsRecorder::create("feed.jsonl")?.tap(rx);
+//! # let _ = rx.recv().await;
+//!
+//! // Later, in a test: the same messages, ten times faster, without a network
+//! let mut rx = WsReplayer::open("feed.jsonl")?
+//!     .pacing(Pacing::Accelerated(10.0))
+//!     .replay();
+//! # let _ = rx.recv().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::queue::{self, MessageReceiver, OverflowPolicy};
+use crate::{
+    signer::AlphaSecSigner,
+    types::{Envelope, WebSocketMessage},
+    AlphaSecError, Result,
+};
+
+/// Capacity of the queue between a tapped receiver and [`WsRecorder::tap`]'s consumer
+const TAP_QUEUE_SIZE: usize = 1000;
+
+/// One line of a recording
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Line {
+    at_ms: u64,
+    #[serde(flatten)]
+    event: Event,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum Event {
+    Frame(serde_json::Value),
+    Closed { code: Option<u16>, reason: String },
+    Disconnected {},
+}
+
+/// Writes the messages of a WebSocket feed, with the time each was received, to a
+/// recording that [`WsReplayer`] plays back
+pub struct WsRecorder {
+    writer: Box<dyn Write + Send>,
+}
+
+impl std::fmt::Debug for WsRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WsRecorder").finish_non_exhaustive()
+    }
+}
+
+impl WsRecorder {
+    /// Record to the file at `path`, replacing it if it exists
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| {
+            AlphaSecError::generic(format!("Cannot create recording {}: {}", path.display(), e))
+        })?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+
+    /// Record to `writer`
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Box::new(writer),
+        }
+    }
+
+    /// Append `msg`, stamped with the current time
+    ///
+    /// Pings, pongs and [`WebSocketMessage::Lagged`] notices are skipped.
+    pub fn record(&mut self, msg: &WebSocketMessage) -> Result<()> {
+        self.record_at(AlphaSecSigner::current_timestamp_ms(), msg)
+    }
+
+    fn record_at(&mut self, at_ms: u64, msg: &WebSocketMessage) -> Result<()> {
+        let event = match msg {
+            WebSocketMessage::Ping(_)
+            | WebSocketMessage::Pong(_)
+            | WebSocketMessage::Lagged { .. } => return Ok(()),
+            WebSocketMessage::Disconnected => Event::Disconnected {},
+            WebSocketMessage::Closed { code, reason } => Event::Closed {
+                code: *code,
+                reason: reason.clone(),
+            },
+            msg => Event::Frame(serde_json::to_value(msg)?),
+        };
+        let mut line = serde_json::to_vec(&Line { at_ms, event })?;
+        line.push(b'\n');
+        self.writer
+            .write_all(&line)
+            .map_err(|e| AlphaSecError::generic(format!("Cannot write recording: {}", e)))
+    }
+
+    /// Write out anything buffered
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer
+            .flush()
+            .map_err(|e| AlphaSecError::generic(format!("Cannot write recording: {}", e)))
+    }
+
+    /// Record every message of `receiver` and pass it on through the returned receiver
+    ///
+    /// Recording runs on a spawned task, so this must be called within a Tokio runtime.
+    /// The returned receiver ends after `receiver` does, once the recording has been
+    /// flushed. A write error is logged and ends the recording, not the feed.
+    pub fn tap(mut self, mut receiver: MessageReceiver) -> MessageReceiver {
+        let (tx, rx) = queue::channel(TAP_QUEUE_SIZE, OverflowPolicy::Block, Arc::default());
+        tokio::spawn(async move {
+            let mut recording = true;
+            while let Some(msg) = receiver.recv().await {
+                if recording {
+                    if let Err(e) = self.record(&msg) {
+                        warn!("Stopping WebSocket recording: {}", e);
+                        recording = false;
+                    }
+                }
+                if tx.send(Envelope { epoch: 1, msg }).await.is_err() {
+                    break;
+                }
+            }
+            if recording {
+                if let Err(e) = self.flush() {
+                    warn!("Stopping WebSocket recording: {}", e);
+                }
+            }
+        });
+        rx.into_messages()
+    }
+}
+
+/// How fast [`WsReplayer`] plays a recording back
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Pacing {
+    /// Keep the recorded gaps between messages
+    #[default]
+    Original,
+    /// Divide the recorded gaps by this factor; one that is not positive and finite
+    /// plays back without pauses
+    Accelerated(f64),
+    /// Deliver every message at once
+    Unpaced,
+}
+
+impl Pacing {
+    fn scale(self, gap: Duration) -> Duration {
+        match self {
+            Self::Original => gap,
+            Self::Accelerated(factor) if factor.is_finite() && factor > 0.0 => {
+                Duration::try_from_secs_f64(gap.as_secs_f64() / factor).unwrap_or_default()
+            }
+            Self::Accelerated(_) | Self::Unpaced => Duration::ZERO,
+        }
+    }
+}
+
+/// Plays a [`WsRecorder`] recording back as a [`MessageReceiver`], the receiver
+/// [`Agent::take_message_receiver`](crate::Agent::take_message_receiver) returns,
+/// without a network connection
+///
+/// Frames are parsed as the live connection parses them, so a recording replays into
+/// the same typed messages.
+#[derive(Debug)]
+pub struct WsReplayer {
+    messages: Vec<(u64, WebSocketMessage)>,
+    pacing: Pacing,
+}
+
+impl WsReplayer {
+    /// Load the recording at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| {
+            AlphaSecError::generic(format!("Cannot open recording {}: {}", path.display(), e))
+        })?;
+        Self::from_reader(BufReader::new(file))
+    }
+
+    /// Load a recording from `reader`
+    ///
+    /// Blank lines are skipped; any other line that is not a recorded message is an
+    /// `InvalidParameter` error naming it.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self> {
+        let mut messages = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line =
+                line.map_err(|e| AlphaSecError::generic(format!("Cannot read recording: {}", e)))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let line: Line = serde_json::from_str(&line).map_err(|e| {
+                AlphaSecError::invalid_parameter(format!(
+                    "Invalid recording line {}: {}",
+                    index + 1,
+                    e
+                ))
+            })?;
+            let msg = match line.event {
+                Event::Frame(frame) => super::manager::message_from_value(frame),
+                Event::Closed { code, reason } => WebSocketMessage::Closed { code, reason },
+                Event::Disconnected {} => WebSocketMessage::Disconnected,
+            };
+            messages.push((line.at_ms, msg));
+        }
+        Ok(Self {
+            messages,
+            pacing: Pacing::default(),
+        })
+    }
+
+    /// Set how fast the recording plays back (default [`Pacing::Original`])
+    pub fn pacing(mut self, pacing: Pacing) -> Self {
+        self.pacing = pacing;
+        self
+    }
+
+    /// Number of recorded messages
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Whether the recording holds no messages
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Start playing the recording back
+    ///
+    /// The first message arrives at once and each later one after its recorded gap,
+    /// scaled by the pacing. The receiver ends after the last message. Playback runs
+    /// on a spawned task, so this must be called within a Tokio runtime.
+    pub fn replay(self) -> MessageReceiver {
+        let (tx, rx) = queue::channel(self.messages.len(), OverflowPolicy::Block, Arc::default());
+        let Self { messages, pacing } = self;
+        tokio::spawn(async move {
+            let started = tokio::time::Instant::now();
+            let first_at = messages.first().map(|(at_ms, _)| *at_ms).unwrap_or(0);
+            // Like a live connection, every disconnect starts a new epoch
+            let mut epoch = 1;
+            for (at_ms, msg) in messages {
+                let gap = Duration::from_millis(at_ms.saturating_sub(first_at));
+                tokio::time::sleep_until(started + pacing.scale(gap)).await;
+                let disconnected = matches!(msg, WebSocketMessage::Disconnected);
+                if tx.send(Envelope { epoch, msg }).await.is_err() {
+                    break;
+                }
+                if disconnected {
+                    epoch += 1;
+                }
+            }
+        });
+        rx.into_messages()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Writer whose contents stay readable after the recorder is dropped
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn trade(id: &str) -> WebSocketMessage {
+        let frame = serde_json::json!({
+            "method": "subscription",
+            "params": {
+                "channel": "trade@5_2",
+                "result": [{
+                    "tradeId": id,
+                    "marketId": "5_2",
+                    "price": "0.15",
+                    "quantity": "100",
+                    "buyOrderId": "b",
+                    "sellOrderId": "s",
+                    "createdAt": 1,
+                    "isBuyerMaker": true
+                }]
+            }
+        });
+        serde_json::from_value(frame).unwrap()
+    }
+
+    fn trade_id(msg: &WebSocketMessage) -> String {
+        match msg {
+            WebSocketMessage::TradeMsg { params, .. } => params.result[0].trade_id.clone(),
+            other => panic!("expected a trade, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn tapped_feed_is_recorded_and_replays_in_order() {
+        let buffer = Shared::default();
+        let (tx, rx) = queue::tests::message_channel();
+        let mut tapped = WsRecorder::new(buffer.clone()).tap(rx);
+        for msg in [
+            trade("t-1"),
+            WebSocketMessage::Ping(vec![1]),
+            trade("t-2"),
+            WebSocketMessage::Disconnected,
+            trade("t-3"),
+        ] {
+            tx.send_message(msg).await;
+        }
+        drop(tx);
+        let mut passed = 0;
+        while tapped.recv().await.is_some() {
+            passed += 1;
+        }
+        assert_eq!(passed, 5);
+
+        let recording = buffer.0.lock().unwrap().clone();
+        let replayer = WsReplayer::from_reader(recording.as_slice()).unwrap();
+        assert_eq!(replayer.len(), 4);
+        let mut replayed = replayer.pacing(Pacing::Unpaced).replay();
+        assert_eq!(trade_id(&replayed.recv().await.unwrap()), "t-1");
+        assert_eq!(trade_id(&replayed.recv().await.unwrap()), "t-2");
+        assert!(matches!(
+            replayed.recv().await,
+            Some(WebSocketMessage::Disconnected)
+        ));
+        assert_eq!(trade_id(&replayed.recv().await.unwrap()), "t-3");
+        assert!(replayed.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn replay_keeps_or_scales_the_recorded_gaps() {
+        let buffer = Shared::default();
+        let mut recorder = WsRecorder::new(buffer.clone());
+        for (at_ms, id) in [(1_000, "t-1"), (1_100, "t-2"), (1_300, "t-3")] {
+            recorder.record_at(at_ms, &trade(id)).unwrap();
+        }
+        let recording = buffer.0.lock().unwrap().clone();
+
+        for (pacing, min, max) in [
+            (Pacing::Original, 300, 2_000),
+            (Pacing::Accelerated(10.0), 30, 250),
+        ] {
+            let replayer = WsReplayer::from_reader(recording.as_slice()).unwrap();
+            let started = std::time::Instant::now();
+            let mut replayed = replayer.pacing(pacing).replay();
+            let mut ids = Vec::new();
+            while let Some(msg) = replayed.recv().await {
+                ids.push(trade_id(&msg));
+            }
+            let elapsed = started.elapsed();
+            assert_eq!(ids, ["t-1", "t-2", "t-3"]);
+            assert!(
+                elapsed >= Duration::from_millis(min) && elapsed < Duration::from_millis(max),
+                "{:?} took {:?}",
+                pacing,
+                elapsed
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_lines_are_named() {
+        let recording = "{\"atMs\":1,\"disconnected\":{}}\n\nnot json\n";
+        let err = WsReplayer::from_reader(recording.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("line 3"), "{}", err);
+    }
+}
@@ -5,11 +5,12 @@
 //! with UUID IDs and oneshot channels.
 
 use crate::error::{AlphaSecError, Result};
+use crate::signer::TlsConfig;
 use futures_util::{SinkExt, StreamExt};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{mpsc, oneshot, Mutex};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::tungstenite::Message;
 use tracing::{debug, error, info, warn};
 
 /// Response from the Trade WebSocket API
@@ -34,6 +35,9 @@ pub struct TradeWsError {
     pub message: String,
 }
 
+/// How long [`TradeWebSocket::send_request`] waits for a response
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
 /// Pending request waiting for response
 struct PendingRequest {
     tx: oneshot::Sender<TradeWsResponse>,
@@ -53,6 +57,9 @@ struct TradeWsState {
 /// The caller (connector) is responsible for reconnection policy.
 pub struct TradeWebSocket {
     ws_api_url: String,
+    proxy: Option<String>,
+    tls: TlsConfig,
+    request_timeout: std::time::Duration,
     state: Arc<Mutex<TradeWsState>>,
 }
 
@@ -60,6 +67,9 @@ impl Clone for TradeWebSocket {
     fn clone(&self) -> Self {
         Self {
             ws_api_url: self.ws_api_url.clone(),
+            proxy: self.proxy.clone(),
+            tls: self.tls.clone(),
+            request_timeout: self.request_timeout,
             state: Arc::clone(&self.state),
         }
     }
@@ -69,6 +79,8 @@ impl std::fmt::Debug for TradeWebSocket {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TradeWebSocket")
             .field("ws_api_url", &self.ws_api_url)
+            .field("proxy", &self.proxy)
+            .field("tls", &self.tls)
             .field("state", &"<Mutex>")
             .finish()
     }
@@ -82,6 +94,9 @@ impl TradeWebSocket {
     pub fn new(ws_api_url: &str) -> Self {
         Self {
             ws_api_url: ws_api_url.to_string(),
+            proxy: None,
+            tls: TlsConfig::default(),
+            request_timeout: REQUEST_TIMEOUT,
             state: Arc::new(Mutex::new(TradeWsState {
                 write_tx: None,
                 pending_requests: HashMap::new(),
@@ -90,6 +105,26 @@ impl TradeWebSocket {
         }
     }
 
+    /// Tunnel the connection through the HTTP proxy at `proxy`, see
+    /// [`WsConfig::proxy`](super::WsConfig::proxy)
+    pub fn with_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Connect with the TLS settings `tls`, see [`WsConfig::tls`](super::WsConfig::tls)
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Wait `timeout` for each response instead of [`REQUEST_TIMEOUT`]
+    #[cfg(test)]
+    pub(crate) fn with_request_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
     /// Connect to the Trade WebSocket (single attempt).
     ///
     /// Establishes connection and spawns background read/write/ping tasks.
@@ -102,9 +137,12 @@ impl TradeWebSocket {
             self.ws_api_url
         );
 
-        let (ws_stream, _) = connect_async(&self.ws_api_url)
-            .await
-            .map_err(|e| AlphaSecError::network(format!("Failed to connect to Trade WS: {}", e)))?;
+        let (ws_stream, _) =
+            super::proxy::connect(&self.ws_api_url, self.proxy.as_deref(), &self.tls, None)
+                .await
+                .map_err(|e| {
+                    AlphaSecError::network(format!("Failed to connect to Trade WS: {}", e))
+                })?;
 
         info!("✅ Connected to AlphaSec Trade WebSocket");
 
@@ -243,7 +281,7 @@ impl TradeWebSocket {
         }
 
         // Wait for response with 10s timeout
-        match tokio::time::timeout(tokio::time::Duration::from_secs(10), rx).await {
+        match tokio::time::timeout(self.request_timeout, rx).await {
             Ok(Ok(response)) => Ok(response),
             Ok(Err(_)) => {
                 let mut state_guard = self.state.lock().await;